@@ -0,0 +1,270 @@
+//! Home timeline read from the materialized `home_feed` against the one assembled from follows at
+//! query time, and the cost of fanning a note out on write.
+//!
+//! Runs the migrations in a scratch schema of the database in the `DATABASE_*` variables and
+//! drops it afterwards:
+//!
+//! ```sh
+//! cargo bench --bench home_feed
+//! ```
+
+use std::{path::Path, sync::LazyLock};
+
+use divan::Bencher;
+use tokio::runtime::Runtime;
+use tokio_postgres::{Client, NoTls};
+
+const LOCAL_ACCOUNTS: i32 = 100;
+const REMOTE_ACTORS: i32 = 1000;
+/// every local account follows one in this many remote actors
+const FOLLOW_EVERY: i32 = 5;
+const NOTES_PER_ACTOR: i32 = 50;
+/// entries kept per feed, as in `HOME_FEED_SIZE`
+const HOME_FEED_SIZE: i64 = 400;
+
+struct Fixture {
+    client: Client,
+    schema: String,
+    /// local account whose home timeline is read
+    account_id: uuid::Uuid,
+    /// remote actor followed by every local account
+    author_id: uuid::Uuid,
+}
+
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().expect("tokio runtime"));
+
+static FIXTURE: LazyLock<Fixture> =
+    LazyLock::new(|| RUNTIME.block_on(setup()).expect("bench database"));
+
+fn main() {
+    if std::env::var("DATABASE_HOST").is_err() {
+        eprintln!("DATABASE_HOST is not set, skipping home feed benchmarks");
+        return;
+    }
+
+    divan::main();
+
+    if LazyLock::get(&FIXTURE).is_some() {
+        let drop_schema = format!("DROP SCHEMA {} CASCADE", FIXTURE.schema);
+        RUNTIME
+            .block_on(FIXTURE.client.batch_execute(&drop_schema))
+            .expect("drop bench schema");
+    }
+}
+
+/// SQL of the query `name` in `queries/ap.sql`
+fn query(name: &str) -> String {
+    let queries = include_str!("../src/infrastructure/postgres/queries/ap.sql");
+    let header = format!("-- name: {name} ");
+    let start = queries
+        .find(&header)
+        .unwrap_or_else(|| panic!("query {name} not found"));
+    let body = &queries[start..];
+    let body = &body[body.find('\n').unwrap_or_default()..];
+    let end = body.find(';').unwrap_or(body.len());
+    body[..end].to_string()
+}
+
+async fn setup() -> Result<Fixture, anyhow::Error> {
+    let var = |name: &str| std::env::var(name).map_err(|e| anyhow::anyhow!("{name}: {e}"));
+    let mut config = tokio_postgres::Config::new();
+    config
+        .host(var("DATABASE_HOST")?)
+        .port(var("DATABASE_PORT")?.parse()?)
+        .user(var("DATABASE_USER")?)
+        .password(var("DATABASE_PASSWORD")?)
+        .dbname(var("DATABASE_NAME")?);
+    let (client, connection) = config.connect(NoTls).await?;
+    tokio::spawn(connection);
+
+    let schema = format!("bench_home_feed_{}", std::process::id());
+    client
+        .batch_execute(&format!(
+            "CREATE SCHEMA {schema}; SET search_path TO {schema}, public"
+        ))
+        .await?;
+
+    let migrations =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/infrastructure/postgres/migrations");
+    let mut ups = std::fs::read_dir(migrations)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    ups.retain(|path| path.to_string_lossy().ends_with(".up.sql"));
+    ups.sort();
+    for up in ups {
+        client.batch_execute(&std::fs::read_to_string(up)?).await?;
+    }
+
+    client
+        .batch_execute(&format!(
+            r#"
+BEGIN;
+
+CREATE TEMPORARY TABLE local_actors AS
+SELECT
+    n,
+    gen_random_uuid() AS account_id,
+    gen_random_uuid() AS actor_id
+FROM generate_series(1, {LOCAL_ACCOUNTS}) AS n;
+
+INSERT INTO accounts (id, name)
+SELECT account_id, 'user' || n FROM local_actors;
+
+INSERT INTO actors (id, type, name, host, actor_url, inbox_url, outbox_url, account_id)
+SELECT
+    actor_id, 'Person', 'user' || n, 'localhost',
+    'http://localhost/users/user' || n,
+    'http://localhost/users/user' || n || '/inbox',
+    'http://localhost/users/user' || n || '/outbox',
+    account_id
+FROM local_actors;
+
+CREATE TEMPORARY TABLE remote_actors AS
+SELECT n, gen_random_uuid() AS actor_id
+FROM generate_series(1, {REMOTE_ACTORS}) AS n;
+
+INSERT INTO actors (id, type, name, host, actor_url, inbox_url, outbox_url)
+SELECT
+    actor_id, 'Person', 'remote' || n, 'remote.example',
+    'https://remote.example/users/remote' || n,
+    'https://remote.example/users/remote' || n || '/inbox',
+    'https://remote.example/users/remote' || n || '/outbox'
+FROM remote_actors;
+
+INSERT INTO follows (id, follower_id, followee_id, follow_url)
+SELECT
+    gen_random_uuid(), local_actors.actor_id, remote_actors.actor_id,
+    'http://localhost/follows/' || local_actors.n || '/' || remote_actors.n
+FROM local_actors
+CROSS JOIN remote_actors
+WHERE (local_actors.n + remote_actors.n) % {FOLLOW_EVERY} = 0 OR remote_actors.n = 1;
+
+CREATE TEMPORARY TABLE seeded_notes AS
+SELECT
+    remote_actors.actor_id,
+    remote_actors.n,
+    seq,
+    gen_random_uuid() AS note_id,
+    gen_random_uuid() AS source_id
+FROM remote_actors
+CROSS JOIN generate_series(1, {NOTES_PER_ACTOR}) AS seq;
+
+INSERT INTO note_sources (id, content, note_id)
+SELECT source_id, 'note ' || seq, note_id FROM seeded_notes;
+
+INSERT INTO notes (id, actor_id, source_id, content, note_url, published)
+SELECT
+    note_id, actor_id, source_id, '<p>note ' || seq || '</p>',
+    'https://remote.example/notes/' || n || '/' || seq, now()
+FROM seeded_notes;
+
+INSERT INTO reblogs (id, actor_id, note_id, announce_url)
+SELECT
+    gen_random_uuid(), remote_actors.actor_id, seeded_notes.note_id,
+    'https://remote.example/announces/' || remote_actors.n || '/' || seeded_notes.n
+FROM remote_actors
+INNER JOIN seeded_notes ON seeded_notes.seq = 1 AND seeded_notes.n = remote_actors.n % {REMOTE_ACTORS} + 1;
+
+COMMIT;
+"#
+        ))
+        .await?;
+
+    let rebuild = query("RebuildHomeFeed");
+    let rows = client
+        .query("SELECT account_id FROM local_actors ORDER BY n", &[])
+        .await?;
+    let account_ids = rows
+        .iter()
+        .map(|row| row.get::<_, uuid::Uuid>(0))
+        .collect::<Vec<_>>();
+    for account_id in &account_ids {
+        client
+            .execute(&rebuild, &[&Some(account_id), &HOME_FEED_SIZE])
+            .await?;
+    }
+    client.batch_execute("ANALYZE").await?;
+
+    let author_id = client
+        .query_one("SELECT actor_id FROM remote_actors WHERE n = 1", &[])
+        .await?
+        .get(0);
+    Ok(Fixture {
+        client,
+        schema,
+        account_id: account_ids[0],
+        author_id,
+    })
+}
+
+fn bench_timeline(bencher: Bencher, sql: &str, limit: i64) {
+    let fixture = &*FIXTURE;
+    let statement = RUNTIME
+        .block_on(fixture.client.prepare(sql))
+        .expect("prepare timeline");
+    bencher.bench(|| {
+        RUNTIME.block_on(async {
+            let rows = fixture
+                .client
+                .query(
+                    &statement,
+                    &[
+                        &fixture.account_id,
+                        &None::<uuid::Uuid>,
+                        &None::<uuid::Uuid>,
+                        &false,
+                        &limit,
+                    ],
+                )
+                .await
+                .expect("home timeline");
+            assert_eq!(rows.len(), limit as usize);
+            rows
+        })
+    });
+}
+
+#[divan::bench(args = [20, 40])]
+fn materialized(bencher: Bencher, limit: i64) {
+    bench_timeline(bencher, &query("GetHomeTimeline"), limit);
+}
+
+#[divan::bench(args = [20, 40])]
+fn query_time(bencher: Bencher, limit: i64) {
+    bench_timeline(bencher, include_str!("home_feed.sql"), limit);
+}
+
+/// Write side of materialization: a note of an actor every local account follows
+#[divan::bench]
+fn fan_out(bencher: Bencher) {
+    let fixture = &*FIXTURE;
+    let (fan_out, trim) = (query("FanOutNote"), query("TrimHomeFeeds"));
+    bencher.bench(|| {
+        RUNTIME.block_on(async {
+            let note_id = uuid::Uuid::now_v7();
+            let source_id = uuid::Uuid::now_v7();
+            fixture
+                .client
+                .batch_execute(&format!(
+                    "BEGIN;
+                    INSERT INTO note_sources (id, content, note_id) VALUES ('{source_id}', 'new', '{note_id}');
+                    INSERT INTO notes (id, actor_id, source_id, content, note_url, published)
+                    VALUES ('{note_id}', '{author_id}', '{source_id}', '<p>new</p>', 'https://remote.example/new/{note_id}', now());
+                    COMMIT;",
+                    author_id = fixture.author_id,
+                ))
+                .await
+                .expect("insert note");
+            fixture
+                .client
+                .execute(&fan_out, &[&note_id, &fixture.author_id])
+                .await
+                .expect("fan out");
+            fixture
+                .client
+                .execute(&trim, &[&fixture.author_id, &HOME_FEED_SIZE])
+                .await
+                .expect("trim")
+        })
+    });
+}
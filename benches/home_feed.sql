@@ -0,0 +1,180 @@
+-- Home timeline assembled at query time from follows, for comparison with the
+-- materialized one
+WITH viewer_actors AS (
+    SELECT id FROM actors WHERE account_id = $1
+),
+sources AS (
+    SELECT id FROM viewer_actors
+    UNION
+    SELECT follows.followee_id FROM follows
+    WHERE follows.follower_id IN (SELECT id FROM viewer_actors) AND follows.accepted
+),
+entries AS (
+    SELECT
+        notes.id AS entry_id,
+        notes.id AS note_id,
+        NULL::UUID AS reblog_actor_id,
+        NULL::TEXT AS reblog_announce_url
+    FROM notes
+    WHERE notes.actor_id IN (SELECT id FROM sources)
+    UNION ALL
+    SELECT
+        reblogs.id,
+        reblogs.note_id,
+        reblogs.actor_id,
+        reblogs.announce_url
+    FROM reblogs
+    WHERE reblogs.actor_id IN (SELECT id FROM sources)
+)
+SELECT
+    entries.entry_id,
+    entries.reblog_actor_id,
+    entries.reblog_announce_url,
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $1
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $1
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $1
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $1
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM entries
+INNER JOIN notes ON entries.note_id = notes.id
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    notes.deleted IS NULL
+    AND ($2::UUID IS NULL OR entries.entry_id < $2)
+    AND ($3::UUID IS NULL OR entries.entry_id > $3)
+ORDER BY
+    CASE WHEN $4 THEN entries.entry_id END ASC,
+    entries.entry_id DESC
+LIMIT $5;
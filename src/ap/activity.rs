@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+use crate::domain::HttpUrl;
+
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#activity-types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ActivityType {
+    Accept,
+    Announce,
+    Create,
+    Delete,
+    /// Pleroma's emoji reaction
+    EmojiReact,
+    Follow,
+    Like,
+    Reject,
+    Undo,
+    Update,
+    /// Activities we do not handle
+    #[serde(other)]
+    Unknown,
+}
+
+/// See https://www.w3.org/TR/activitystreams-core/#activities
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Activity<T = serde_json::Value> {
+    pub id: HttpUrl,
+    #[serde(rename = "type")]
+    pub kind: ActivityType,
+    pub actor: HttpUrl,
+    pub object: T,
+
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
+    #[builder(default)]
+    pub to: Vec<String>,
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
+    #[builder(default)]
+    pub cc: Vec<String>,
+
+    /// emoji of a reaction, unicode or `:shortcode:` of a custom emoji in `tag`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub content: Option<String>,
+    /// Misskey's copy of `content` of reactions
+    #[serde(
+        rename = "_misskey_reaction",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default)]
+    pub misskey_reaction: Option<String>,
+    /// custom emoji of `content`
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub tag: Vec<super::Tag>,
+}
+
+impl Activity {
+    /// Return `id` of the object whether it is embedded or referenced
+    pub fn object_id(&self) -> Option<HttpUrl> {
+        object_id(&self.object)
+    }
+}
+
+impl<T> Activity<T> {
+    /// Emoji of a reaction, which Misskey sends as `_misskey_reaction` and others as `content`
+    pub fn reaction(&self) -> Option<&str> {
+        self.misskey_reaction
+            .as_deref()
+            .or(self.content.as_deref())
+            .map(str::trim)
+            .filter(|reaction| !reaction.is_empty())
+    }
+}
+
+/// Return `id` of an object or a link to it
+pub fn object_id(object: &serde_json::Value) -> Option<HttpUrl> {
+    let id = match object {
+        serde_json::Value::String(id) => id,
+        serde_json::Value::Object(map) => map.get("id")?.as_str()?,
+        _ => return None,
+    };
+    id.parse().ok()
+}
+
+/// Return `type` of an embedded object
+pub fn object_type(object: &serde_json::Value) -> Option<&str> {
+    object.get("type")?.as_str()
+}
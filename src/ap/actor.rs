@@ -1,8 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
 use crate::domain::HttpUrl;
 
+use super::{DocumentType, Tag};
+
 /// See https://www.w3.org/TR/activitystreams-vocabulary/#actor-types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActorType {
@@ -36,4 +39,129 @@ pub struct Actor {
     #[serde(rename = "preferredUsername")]
     #[builder(setter(into))]
     pub preferred_username: String,
+
+    /// actor bio in HTML
+    ///
+    /// See https://docs.joinmastodon.org/spec/activitypub/#properties-used-1
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub summary: Option<String>,
+
+    /// avatar
+    ///
+    /// See https://docs.joinmastodon.org/spec/activitypub/#as
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "super::de::first_valid"
+    )]
+    #[builder(default)]
+    pub icon: Option<Image>,
+
+    /// header image
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "super::de::first_valid"
+    )]
+    #[builder(default)]
+    pub image: Option<Image>,
+
+    /// profile fields
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub attachment: Vec<PropertyValue>,
+
+    /// custom emoji of the name, summary and fields
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub tag: Vec<Tag>,
+
+    /// when the actor was created
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub published: Option<DateTime<Utc>>,
+
+    /// See https://www.w3.org/TR/activitypub/#endpoints
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub endpoints: Option<Endpoints>,
+
+    /// key used to verify HTTP Signatures
+    ///
+    /// See https://docs.joinmastodon.org/spec/activitypub/#publicKey
+    #[serde(rename = "publicKey", skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub public_key: Option<PublicKey>,
+}
+
+/// See https://www.w3.org/TR/activitypub/#endpoints
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Endpoints {
+    #[serde(
+        rename = "sharedInbox",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default, setter(strip_option))]
+    pub shared_inbox: Option<HttpUrl>,
+}
+
+/// See https://w3id.org/security/v1
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct PublicKey {
+    #[builder(setter(into))]
+    pub id: String,
+    pub owner: HttpUrl,
+    #[serde(rename = "publicKeyPem")]
+    #[builder(setter(into))]
+    pub public_key_pem: String,
+}
+
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-image
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Image {
+    #[serde(rename = "type")]
+    pub kind: DocumentType,
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub media_type: Option<String>,
+    #[serde(deserialize_with = "super::de::url_or_link")]
+    pub url: HttpUrl,
+}
+
+/// See https://docs.joinmastodon.org/spec/activitypub/#PropertyValue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PropertyValueType {
+    PropertyValue,
+}
+
+/// Name and value shown on a profile
+///
+/// See https://docs.joinmastodon.org/spec/activitypub/#PropertyValue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyValue {
+    #[serde(rename = "type")]
+    pub kind: PropertyValueType,
+    pub name: String,
+    /// HTML
+    pub value: String,
+}
+
+impl PropertyValue {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            kind: PropertyValueType::PropertyValue,
+            name: name.into(),
+            value: value.into(),
+        }
+    }
 }
@@ -2,10 +2,17 @@
 use mime;
 use std::sync::LazyLock;
 
+pub mod activity;
 mod actor;
+mod note;
+pub mod signature;
 pub mod webfinger;
 
-pub use actor::{Actor, ActorType};
+pub use activity::{Activity, ActivityType};
+pub use actor::{Actor, ActorType, Endpoints, Image, PropertyValue, PublicKey};
+pub use note::{
+    Document, DocumentType, Note, ObjectType, QuestionOption, Replies, Source, Tag, Tombstone,
+};
 pub use webfinger::{WebFinger, WebFingerLink};
 
 use serde::{Deserialize, Serialize};
@@ -27,7 +34,25 @@ impl<T> Context<T> {
         Self {
             context: serde_json::json!([
                 "https://www.w3.org/ns/activitystreams",
-                // "https://w3id.org/security/v1",
+                "https://w3id.org/security/v1",
+                {
+                    "toot": "http://joinmastodon.org/ns#",
+                    "blurhash": "toot:blurhash",
+                    "focalPoint": {
+                        "@container": "@list",
+                        "@id": "toot:focalPoint",
+                    },
+                    "schema": "http://schema.org#",
+                    "PropertyValue": "schema:PropertyValue",
+                    "value": "schema:value",
+                    "Emoji": "toot:Emoji",
+                    "misskey": "https://misskey-hub.net/ns#",
+                    "_misskey_reaction": "misskey:_misskey_reaction",
+                    "litepub": "http://litepub.social/ns#",
+                    "EmojiReact": "litepub:EmojiReact",
+                    "votersCount": "toot:votersCount",
+                    "sensitive": "as:sensitive",
+                },
             ]),
             inner,
         }
@@ -43,6 +68,149 @@ impl<T> Context<T> {
     }
 }
 
+pub(crate) mod de {
+    use serde::{Deserialize, Deserializer};
+
+    use crate::domain::HttpUrl;
+
+    /// Accept a single value where an array is expected
+    ///
+    /// Properties like `to` are often sent as a plain string.
+    pub fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany<T> {
+            One(T),
+            Many(Vec<T>),
+        }
+
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(v) => vec![v],
+            OneOrMany::Many(v) => v,
+        })
+    }
+
+    /// Like [one_or_many] but skip values that do not deserialize as `T`
+    ///
+    /// Objects like attachments come in many shapes, and one odd value should not reject
+    /// the whole object.
+    pub fn one_or_many_valid<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: serde::de::DeserializeOwned,
+    {
+        let values = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Array(values) => values,
+            value => vec![value],
+        };
+        Ok(values
+            .into_iter()
+            .filter_map(|value| serde_json::from_value(value).ok())
+            .collect())
+    }
+
+    /// First value of [one_or_many_valid], for properties like `icon` that may be a list
+    pub fn first_valid<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(one_or_many_valid(deserializer)?.into_iter().next())
+    }
+
+    /// Accept a URL given as a string, a `Link` object or a list of them
+    ///
+    /// The first URL is used. See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-url
+    pub fn url_or_link<'de, D>(deserializer: D) -> Result<HttpUrl, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum UrlOrLink {
+            Url(HttpUrl),
+            Link { href: HttpUrl },
+        }
+
+        one_or_many::<_, UrlOrLink>(deserializer)?
+            .into_iter()
+            .next()
+            .map(|url| match url {
+                UrlOrLink::Url(url) | UrlOrLink::Link { href: url } => url,
+            })
+            .ok_or_else(|| serde::de::Error::custom("missing url"))
+    }
+
+    /// Author of an object given as a URL, an actor or a list of them
+    ///
+    /// PeerTube attributes videos to the account and its channel, where the account is the
+    /// `Person`. See https://docs.joinpeertube.org/api/activitypub#video
+    pub fn attributed_to<'de, D>(deserializer: D) -> Result<HttpUrl, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum UrlOrActor {
+            Url(HttpUrl),
+            Actor {
+                id: HttpUrl,
+                #[serde(rename = "type")]
+                kind: Option<String>,
+            },
+        }
+
+        let actors = one_or_many::<_, UrlOrActor>(deserializer)?;
+        let person = actors.iter().find_map(|actor| match actor {
+            UrlOrActor::Actor { id, kind } if kind.as_deref() == Some("Person") => Some(id),
+            _ => None,
+        });
+        let first = actors.first().map(|actor| match actor {
+            UrlOrActor::Url(url) | UrlOrActor::Actor { id: url, .. } => url,
+        });
+        person
+            .or(first)
+            .cloned()
+            .ok_or_else(|| serde::de::Error::custom("missing attributedTo"))
+    }
+
+    /// Web page of an object from its `url`, ignoring invalid values
+    ///
+    /// `text/html` links are preferred, as PeerTube lists the video files there too.
+    pub fn html_url<'de, D>(deserializer: D) -> Result<Option<HttpUrl>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum UrlOrLink {
+            Url(HttpUrl),
+            Link {
+                href: HttpUrl,
+                #[serde(rename = "mediaType")]
+                media_type: Option<String>,
+            },
+        }
+
+        let urls = one_or_many_valid::<_, UrlOrLink>(deserializer)?;
+        let html = urls.iter().find_map(|url| match url {
+            UrlOrLink::Url(url) => Some(url),
+            UrlOrLink::Link { href, media_type } => media_type
+                .as_deref()
+                .is_none_or(|media_type| media_type == "text/html")
+                .then_some(href),
+        });
+        let first = urls.first().map(|url| match url {
+            UrlOrLink::Url(url) | UrlOrLink::Link { href: url, .. } => url,
+        });
+        Ok(html.or(first).cloned())
+    }
+}
+
 pub mod constants {
     use super::*;
     /// ActivityPub media types
@@ -64,5 +232,10 @@ pub mod constants {
     pub static ACTIVITYPUB_MIME_ALT: LazyLock<mime::Mime> =
         LazyLock::new(|| ACTIVITYPUB_MEDIA_TYPE_ALT.parse().unwrap());
 
+    /// Special collection addressing everyone
+    ///
+    /// See https://www.w3.org/TR/activitypub/#public-addressing
+    pub const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
     pub use super::webfinger::{WEBFINGER_MEDIA_TYPE, WEBFINGER_MIME};
 }
@@ -0,0 +1,319 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+use crate::domain::HttpUrl;
+
+use super::Image;
+
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#object-types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectType {
+    Note,
+    /// Note with a poll
+    Question,
+    /// Long-form text with a title, like blog posts of WriteFreely and Plume
+    Article,
+    /// Lemmy posts
+    Page,
+    /// PeerTube videos
+    Video,
+    /// Mobilizon events
+    Event,
+    Image,
+    Tombstone,
+}
+
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-note
+///
+/// Polls are `Question`s with the same properties. See https://docs.joinmastodon.org/spec/activitypub/#Question
+///
+/// Other objects with content, like `Article`s, are read as notes too.
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Note {
+    pub id: HttpUrl,
+    #[serde(rename = "type")]
+    pub kind: ObjectType,
+    #[serde(rename = "attributedTo", deserialize_with = "super::de::attributed_to")]
+    pub attributed_to: HttpUrl,
+    /// HTML content, missing from poll votes
+    #[serde(default)]
+    pub content: String,
+
+    /// Content warning, plain text though it is HTML in ActivityStreams. Long-form objects like
+    /// `Article`s have an HTML excerpt here instead
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub summary: Option<String>,
+    /// The attachments are not safe for work, Mastodon's `as:sensitive` extension
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub sensitive: Option<bool>,
+
+    /// Option a poll vote is for, the vote replying to the `Question`, or title of an `Article`
+    /// and other long-form objects
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub name: Option<String>,
+
+    /// Web page of the object
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "super::de::html_url"
+    )]
+    #[builder(default)]
+    pub url: Option<HttpUrl>,
+
+    /// Text the content was rendered from
+    ///
+    /// See https://docs.joinmastodon.org/spec/activitypub/#source
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub source: Option<Source>,
+
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
+    #[builder(default)]
+    pub to: Vec<String>,
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
+    #[builder(default)]
+    pub cc: Vec<String>,
+
+    /// Note this one replies to
+    #[serde(rename = "inReplyTo", skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub in_reply_to: Option<HttpUrl>,
+
+    /// When the note was posted
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub published: Option<DateTime<Utc>>,
+
+    /// When the note was last edited
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub updated: Option<DateTime<Utc>>,
+
+    /// Hashtags, mentions and custom emoji of the content
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub tag: Vec<Tag>,
+
+    /// Files attached to the note
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub attachment: Vec<Document>,
+
+    /// Options of a `Question` where one can be chosen
+    #[serde(
+        rename = "oneOf",
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub one_of: Vec<QuestionOption>,
+    /// Options of a `Question` where several can be chosen
+    #[serde(
+        rename = "anyOf",
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "super::de::one_or_many_valid"
+    )]
+    #[builder(default)]
+    pub any_of: Vec<QuestionOption>,
+    /// When voting in a `Question` ends
+    #[serde(rename = "endTime", skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// When a `Question` was closed. Some servers send `true` instead, which is ignored
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "super::de::first_valid"
+    )]
+    #[builder(default)]
+    pub closed: Option<DateTime<Utc>>,
+    /// How many actors voted in a `Question`
+    #[serde(
+        rename = "votersCount",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default)]
+    pub voters_count: Option<u64>,
+}
+
+impl Note {
+    /// Names of the hashtags, like `#rust`
+    pub fn hashtags(&self) -> impl Iterator<Item = &str> {
+        self.tag.iter().filter_map(|tag| match tag {
+            Tag::Hashtag { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Option of a `Question`, with its votes as the total of its replies
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct QuestionOption {
+    #[serde(rename = "type")]
+    #[builder(default = ObjectType::Note)]
+    pub kind: ObjectType,
+    pub name: String,
+    #[serde(default)]
+    pub replies: Replies,
+}
+
+/// Collection of the replies to an object, of which only the size is told
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replies {
+    #[serde(rename = "type", default = "Replies::kind")]
+    kind: String,
+    #[serde(rename = "totalItems", default)]
+    pub total_items: u64,
+}
+
+impl Replies {
+    pub fn new(total_items: u64) -> Self {
+        Self {
+            kind: Self::kind(),
+            total_items,
+        }
+    }
+
+    fn kind() -> String {
+        "Collection".to_string()
+    }
+}
+
+impl Default for Replies {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Object linked from a note or an actor
+///
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Tag {
+    /// See https://docs.joinmastodon.org/spec/activitypub/#Hashtag
+    Hashtag {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        href: Option<HttpUrl>,
+    },
+    /// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-mention
+    Mention {
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        name: Option<String>,
+        href: HttpUrl,
+    },
+    /// Image shown in place of `:name:` in the text
+    ///
+    /// See https://docs.joinmastodon.org/spec/activitypub/#Emoji
+    Emoji {
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        id: Option<HttpUrl>,
+        /// shortcode with its colons, like `:blobcat:`
+        name: String,
+        icon: Image,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        updated: Option<DateTime<Utc>>,
+    },
+    /// Tags of other types, ignored
+    #[serde(other)]
+    Unknown,
+}
+
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocumentType {
+    Document,
+    Image,
+    Video,
+    Audio,
+    /// Attachments of other types, like `Link`
+    #[serde(other)]
+    Unknown,
+}
+
+/// File attached to a note
+///
+/// See https://docs.joinmastodon.org/spec/activitypub/#as
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Document {
+    #[serde(rename = "type")]
+    pub kind: DocumentType,
+    /// MIME type like `image/png`
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option, into))]
+    pub media_type: Option<String>,
+    #[serde(deserialize_with = "super::de::url_or_link")]
+    pub url: HttpUrl,
+    /// Alt text
+    #[serde(default)]
+    #[builder(default)]
+    pub name: Option<String>,
+    /// See https://docs.joinmastodon.org/spec/activitypub/#blurhash
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub blurhash: Option<String>,
+    /// Size of images in pixels
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default)]
+    pub height: Option<u32>,
+    /// See https://docs.joinmastodon.org/spec/activitypub/#focalPoint
+    #[serde(
+        rename = "focalPoint",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default)]
+    pub focal_point: Option<[f32; 2]>,
+}
+
+/// See https://www.w3.org/TR/activitypub/#source-property
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(setter(into)))]
+pub struct Source {
+    pub content: String,
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+}
+
+/// Placeholder for a deleted object
+///
+/// See https://www.w3.org/TR/activitystreams-vocabulary/#dfn-tombstone
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Tombstone {
+    pub id: HttpUrl,
+    #[serde(rename = "type")]
+    #[builder(default = ObjectType::Tombstone)]
+    pub kind: ObjectType,
+    /// Type of the object before deletion
+    #[serde(
+        rename = "formerType",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default, setter(strip_option))]
+    pub former_type: Option<ObjectType>,
+    /// When the object was deleted
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default, setter(strip_option))]
+    pub deleted: Option<DateTime<Utc>>,
+}
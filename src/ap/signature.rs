@@ -0,0 +1,364 @@
+//! HTTP Signatures
+//!
+//! See https://datatracker.ietf.org/doc/html/draft-cavage-http-signatures-12
+//! and https://docs.joinmastodon.org/spec/security/#http
+use std::str::FromStr;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::{DateTime, Utc};
+use rsa::{
+    RsaPrivateKey, RsaPublicKey,
+    pkcs1::DecodeRsaPublicKey as _,
+    pkcs1v15::{Signature as RsaSignature, SigningKey, VerifyingKey},
+    pkcs8::{DecodePrivateKey as _, DecodePublicKey as _},
+    signature::{SignatureEncoding as _, Signer as _, Verifier as _},
+};
+use sha2::{Digest as _, Sha256};
+
+/// Headers we sign on outgoing requests that have a body
+pub const SIGNED_HEADERS: &[&str] = &["(request-target)", "host", "date", "digest"];
+
+/// Headers we sign on outgoing requests that have no body
+pub const SIGNED_HEADERS_WITHOUT_BODY: &[&str] = &["(request-target)", "host", "date"];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("malformed signature header")]
+    Malformed,
+    #[error("missing header `{0}`")]
+    MissingHeader(String),
+    #[error("invalid key")]
+    InvalidKey,
+    #[error("signature does not match")]
+    Mismatch,
+}
+
+/// Parsed `Signature` header
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub key_id: String,
+    pub algorithm: Option<String>,
+    pub headers: Vec<String>,
+    /// base64 encoded signature
+    pub signature: String,
+}
+
+impl FromStr for Signature {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut key_id = None;
+        let mut algorithm = None;
+        let mut headers = None;
+        let mut signature = None;
+
+        for param in s.split(',') {
+            let (name, value) = param
+                .trim()
+                .split_once('=')
+                .ok_or(SignatureError::Malformed)?;
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .ok_or(SignatureError::Malformed)?
+                .to_string();
+            match name {
+                "keyId" => key_id = Some(value),
+                "algorithm" => algorithm = Some(value),
+                "headers" => headers = Some(value),
+                "signature" => signature = Some(value),
+                _ => {}
+            }
+        }
+
+        // the draft defaults to `date` alone, which would let the rest be changed
+        let headers = headers
+            .ok_or(SignatureError::Malformed)?
+            .split_ascii_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+
+        Ok(Self {
+            key_id: key_id.ok_or(SignatureError::Malformed)?,
+            algorithm,
+            headers,
+            signature: signature.ok_or(SignatureError::Malformed)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            r#"keyId="{}",algorithm="{}",headers="{}",signature="{}""#,
+            self.key_id,
+            self.algorithm.as_deref().unwrap_or("rsa-sha256"),
+            self.headers.join(" "),
+            self.signature
+        )
+    }
+}
+
+impl Signature {
+    /// Verify the signature over the request with `public_key_pem`
+    ///
+    /// `header` returns the value of a request header by its lowercase name
+    pub fn verify<'a>(
+        &self,
+        public_key_pem: &str,
+        method: &str,
+        path_and_query: &str,
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> Result<(), SignatureError> {
+        let signing_string = signing_string(method, path_and_query, &self.headers, header)?;
+        verify(public_key_pem, &signing_string, &self.signature)
+    }
+}
+
+/// Build the string to sign from the listed `headers`
+pub fn signing_string<'a, S: AsRef<str>>(
+    method: &str,
+    path_and_query: &str,
+    headers: &[S],
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Result<String, SignatureError> {
+    let lines = headers
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            let value = match name {
+                "(request-target)" => {
+                    format!("{} {}", method.to_lowercase(), path_and_query)
+                }
+                _ => header(name)
+                    .ok_or_else(|| SignatureError::MissingHeader(name.to_string()))?
+                    .to_string(),
+            };
+            Ok(format!("{name}: {value}"))
+        })
+        .collect::<Result<Vec<_>, SignatureError>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Sign with RSASSA-PKCS1-v1_5 SHA-256 and return the base64 encoded signature
+pub fn sign(private_key: &RsaPrivateKey, signing_string: &str) -> String {
+    let signing_key = SigningKey::<Sha256>::new(private_key.clone());
+    let signature = signing_key.sign(signing_string.as_bytes());
+    BASE64.encode(signature.to_bytes())
+}
+
+/// Verify a base64 encoded RSASSA-PKCS1-v1_5 SHA-256 signature
+pub fn verify(
+    public_key_pem: &str,
+    signing_string: &str,
+    signature: &str,
+) -> Result<(), SignatureError> {
+    let public_key = parse_public_key(public_key_pem)?;
+    let signature = BASE64
+        .decode(signature)
+        .map_err(|_| SignatureError::Malformed)?;
+    let signature =
+        RsaSignature::try_from(signature.as_slice()).map_err(|_| SignatureError::Malformed)?;
+
+    VerifyingKey::<Sha256>::new(public_key)
+        .verify(signing_string.as_bytes(), &signature)
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+pub fn parse_private_key(private_key_pem: &str) -> Result<RsaPrivateKey, SignatureError> {
+    RsaPrivateKey::from_pkcs8_pem(private_key_pem).map_err(|_| SignatureError::InvalidKey)
+}
+
+/// Parse SPKI (`BEGIN PUBLIC KEY`) or PKCS#1 (`BEGIN RSA PUBLIC KEY`) PEM
+pub fn parse_public_key(public_key_pem: &str) -> Result<RsaPublicKey, SignatureError> {
+    RsaPublicKey::from_public_key_pem(public_key_pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(public_key_pem))
+        .map_err(|_| SignatureError::InvalidKey)
+}
+
+/// Value of the `Digest` header for `body`
+///
+/// See https://datatracker.ietf.org/doc/html/rfc3230
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", BASE64.encode(Sha256::digest(body)))
+}
+
+/// Format as an HTTP date like `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse an HTTP date
+pub fn parse_http_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(s)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Return the headers to add to an outgoing request so that it is signed with `key_id`
+pub fn signed_headers(
+    key_id: &str,
+    private_key: &RsaPrivateKey,
+    method: &str,
+    url: &url::Url,
+    body: Option<&[u8]>,
+    at: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let path_and_query = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut headers = vec![("host", host), ("date", http_date(at))];
+    let names = match body {
+        Some(body) => {
+            headers.push(("digest", digest(body)));
+            SIGNED_HEADERS
+        }
+        None => SIGNED_HEADERS_WITHOUT_BODY,
+    };
+
+    let lookup = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    // every header is in `headers`, so this cannot fail
+    let signing_string = signing_string(method, &path_and_query, names, lookup)
+        .expect("signed headers must be present");
+
+    let signature = Signature {
+        key_id: key_id.to_string(),
+        algorithm: Some("rsa-sha256".to_string()),
+        headers: names.iter().map(|s| s.to_string()).collect(),
+        signature: sign(private_key, &signing_string),
+    };
+    headers.push(("signature", signature.to_string()));
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use pretty_assertions::assert_eq;
+    use rsa::pkcs8::{EncodePublicKey as _, LineEnding};
+    use rstest::rstest;
+
+    use super::*;
+
+    const KEY_ID: &str = "https://example.com/ap/actors/alice#main-key";
+
+    /// Generating keys is slow, so tests share these
+    static KEY: LazyLock<RsaPrivateKey> = LazyLock::new(generate_key);
+    static OTHER_KEY: LazyLock<RsaPrivateKey> = LazyLock::new(generate_key);
+
+    fn generate_key() -> RsaPrivateKey {
+        RsaPrivateKey::new(&mut rsa::rand_core::OsRng, 2048).unwrap()
+    }
+
+    fn public_key_pem(key: &RsaPrivateKey) -> String {
+        RsaPublicKey::from(key)
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap()
+    }
+
+    /// Headers of a POST to `https://example.com/ap/inbox`, signed with [KEY]
+    fn signed_post(body: &[u8]) -> Vec<(&'static str, String)> {
+        let url = "https://example.com/ap/inbox".parse().unwrap();
+        signed_headers(KEY_ID, &KEY, "post", &url, Some(body), Utc::now())
+    }
+
+    fn header<'a>(headers: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+        headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn verify_post(
+        headers: &[(&str, String)],
+        public_key_pem: &str,
+        path_and_query: &str,
+    ) -> Result<(), SignatureError> {
+        let signature: Signature = header(headers, "signature").unwrap().parse()?;
+        signature.verify(public_key_pem, "post", path_and_query, |name| {
+            header(headers, name)
+        })
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let headers = signed_post(b"{}");
+        let signature: Signature = header(&headers, "signature").unwrap().parse().unwrap();
+
+        assert_eq!(signature.key_id, KEY_ID);
+        assert_eq!(signature.headers, SIGNED_HEADERS);
+        assert_eq!(header(&headers, "digest"), Some(digest(b"{}").as_str()));
+        assert_eq!(
+            verify_post(&headers, &public_key_pem(&KEY), "/ap/inbox"),
+            Ok(())
+        );
+    }
+
+    #[rstest]
+    #[case::digest("digest", digest(b"{\"type\":\"Delete\"}"))]
+    #[case::date("date", http_date(Utc::now() - chrono::TimeDelta::days(1)))]
+    #[case::host("host", "other.example".to_string())]
+    fn test_tampered_header(#[case] name: &str, #[case] value: String) {
+        let mut headers = signed_post(b"{}");
+        headers.iter_mut().find(|(n, _)| *n == name).unwrap().1 = value;
+
+        assert_eq!(
+            verify_post(&headers, &public_key_pem(&KEY), "/ap/inbox"),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_other_path() {
+        let headers = signed_post(b"{}");
+        assert_eq!(
+            verify_post(&headers, &public_key_pem(&KEY), "/ap/actors/bob/inbox"),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let headers = signed_post(b"{}");
+        assert_eq!(
+            verify_post(&headers, &public_key_pem(&OTHER_KEY), "/ap/inbox"),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[rstest]
+    #[case::no_headers(r#"keyId="a",algorithm="rsa-sha256",signature="c2ln""#)]
+    #[case::no_key_id(r#"headers="date",signature="c2ln""#)]
+    #[case::no_signature(r#"keyId="a",headers="date""#)]
+    #[case::unquoted(r#"keyId=a,headers="date",signature="c2ln""#)]
+    fn test_parse_malformed(#[case] header: &str) {
+        assert_eq!(
+            header.parse::<Signature>().err(),
+            Some(SignatureError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let signature: Signature =
+            r#"keyId="a#main-key",algorithm="rsa-sha256",headers="(request-target) Host date",signature="c2ln""#
+                .parse()
+                .unwrap();
+        assert_eq!(signature.key_id, "a#main-key");
+        assert_eq!(signature.headers, ["(request-target)", "host", "date"]);
+        assert_eq!(signature.signature, "c2ln");
+    }
+}
@@ -15,6 +15,13 @@ pub struct WebFinger {
     links: Vec<WebFingerLink>,
 }
 
+impl WebFinger {
+    /// Return URL of the actor the resource resolves to
+    pub fn actor_url(&self) -> Option<&HttpUrl> {
+        self.links.iter().find_map(WebFingerLink::actor_link)
+    }
+}
+
 /// See https://datatracker.ietf.org/doc/html/rfc7033#section-4.4.4
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
 #[builder(field_defaults(setter(into)))]
@@ -1,17 +1,55 @@
 pub mod account;
 pub mod ap;
+pub mod auth;
 pub mod hosturl;
+pub mod media;
+pub mod stream;
+pub mod text;
 
 use std::{ops::Deref, str::FromStr};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug)]
 pub struct Id<T> {
     inner: uuid::Uuid,
     _marker: std::marker::PhantomData<T>,
 }
 
+// Implemented by hand because derive would require `T` to implement them too
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        Self::from_uuid(self.inner)
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.inner.cmp(&other.inner)
+    }
+}
+
+impl<T> std::hash::Hash for Id<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.inner.hash(state);
+    }
+}
+
 impl<T> Default for Id<T> {
     fn default() -> Self {
         Self::new()
@@ -32,6 +70,12 @@ impl<T> Id<T> {
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// When the id was generated, if it is a UUIDv7
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        let (secs, nanos) = self.inner.get_timestamp()?.to_unix();
+        DateTime::from_timestamp(i64::try_from(secs).ok()?, nanos)
+    }
 }
 
 impl<T> Deref for Id<T> {
@@ -179,3 +223,58 @@ impl<'de> Deserialize<'de> for HttpUrl {
         Self::new(url).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use uuid::{NoContext, Timestamp, Uuid};
+
+    use super::*;
+
+    type TestId = Id<()>;
+
+    fn id_at(secs: u64, nanos: u32) -> TestId {
+        Id::from_uuid(Uuid::new_v7(Timestamp::from_unix(NoContext, secs, nanos)))
+    }
+
+    #[test]
+    fn test_timestamp_of_new() {
+        // UUIDv7 keep milliseconds
+        let before = Utc::now() - TimeDelta::milliseconds(1);
+        let timestamp = TestId::new().timestamp().unwrap();
+        let after = Utc::now();
+        assert!(before < timestamp && timestamp <= after);
+    }
+
+    #[rstest]
+    #[case::epoch(0, 0, "1970-01-01T00:00:00Z")]
+    #[case::millis(1_700_000_000, 123_000_000, "2023-11-14T22:13:20.123Z")]
+    #[case::sub_millis_dropped(1_700_000_000, 123_999_999, "2023-11-14T22:13:20.123Z")]
+    #[case::last_milli(1_700_000_000, 999_000_000, "2023-11-14T22:13:20.999Z")]
+    fn test_timestamp(#[case] secs: u64, #[case] nanos: u32, #[case] expected: DateTime<Utc>) {
+        assert_eq!(id_at(secs, nanos).timestamp(), Some(expected));
+    }
+
+    #[rstest]
+    #[case::nil(Uuid::nil())]
+    #[case::v4("6fa459ea-ee8a-4ca4-894e-db77e160355e".parse().unwrap())]
+    fn test_timestamp_not_v7(#[case] uuid: Uuid) {
+        assert_eq!(TestId::from_uuid(uuid).timestamp(), None);
+    }
+
+    #[test]
+    fn test_order_across_second() {
+        let earlier = id_at(1_700_000_000, 999_000_000);
+        let later = id_at(1_700_000_001, 0);
+        assert!(earlier < later);
+        assert_eq!(
+            later.timestamp().unwrap() - earlier.timestamp().unwrap(),
+            TimeDelta::milliseconds(1)
+        );
+        // ids generated in order sort in order, whatever their random bits
+        let ids: Vec<TestId> = (0..100).map(|_| TestId::new()).collect();
+        assert!(ids.is_sorted());
+    }
+}
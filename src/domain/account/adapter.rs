@@ -1,18 +1,38 @@
+use chrono::{DateTime, Utc};
+
 use super::model::{
-    Account, AccountId, AccountName, CreateAccountError, CreateAccountRequest, FindAccountError,
+    Account, AccountId, AccountName, CreateAccountError, CreateAccountRequest, DeleteAccountError,
+    FindAccountError, PasswordHash,
 };
 
 #[async_trait::async_trait]
 pub trait AccountService: Send + Sync + 'static {
     async fn create(&self, req: CreateAccountRequest) -> Result<Account, CreateAccountError>;
-    #[allow(dead_code)]
-    async fn find_by_id(&self, id: &AccountId) -> Result<Option<Account>, FindAccountError>;
+    /// Return `FindAccountError::Gone` if the account was deleted
     async fn find_by_name(&self, name: &AccountName) -> Result<Option<Account>, FindAccountError>;
+    /// Delete the account and everything it owns, keeping its name reserved
+    async fn delete(&self, id: &AccountId) -> Result<(), DeleteAccountError>;
 }
 
 #[async_trait::async_trait]
 pub trait AccountRepository: Send + Sync + 'static {
-    async fn create(&self, account: Account) -> Result<Account, CreateAccountError>;
+    /// Fails with `AlreadyExists` for names of deleted accounts too
+    async fn create(
+        &self,
+        account: Account,
+        password_hash: &PasswordHash,
+    ) -> Result<Account, CreateAccountError>;
     async fn find_by_id(&self, id: &AccountId) -> Result<Option<Account>, FindAccountError>;
     async fn find_by_name(&self, name: &AccountName) -> Result<Option<Account>, FindAccountError>;
+    /// Delete the account and leave a tombstone with its name
+    async fn delete(
+        &self,
+        account: &Account,
+        deleted: DateTime<Utc>,
+    ) -> Result<(), DeleteAccountError>;
+    /// Return when the account with `name` was deleted
+    async fn find_tombstone(
+        &self,
+        name: &AccountName,
+    ) -> Result<Option<DateTime<Utc>>, FindAccountError>;
 }
@@ -1,4 +1,5 @@
 use crate::domain::Id;
+use chrono::{DateTime, Utc};
 use std::sync::LazyLock;
 
 pub type AccountId = Id<Account>;
@@ -53,14 +54,100 @@ impl AccountName {
     }
 }
 
+/// Plain text password as sent by the user
+#[derive(Clone)]
+pub struct Password(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PasswordError {
+    #[error("password must be between {MIN} and {MAX} characters", MIN = Password::MIN_LEN, MAX = Password::MAX_LEN)]
+    InvalidLength,
+}
+
+impl Password {
+    const MIN_LEN: usize = 8;
+    const MAX_LEN: usize = 128;
+
+    pub fn new(raw: &str) -> Result<Self, PasswordError> {
+        let len = raw.chars().count();
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&len) {
+            return Err(PasswordError::InvalidLength);
+        }
+
+        Ok(Self(raw.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Password").field(&"<redacted>").finish()
+    }
+}
+
+/// argon2 hash of a [Password] in PHC string format
+#[derive(Clone)]
+pub struct PasswordHash(String);
+
+impl PasswordHash {
+    /// Hash `password` with a random salt
+    ///
+    /// This is CPU heavy, call it from a blocking thread.
+    pub fn new(password: &Password) -> Result<Self, anyhow::Error> {
+        use argon2::{
+            Argon2, PasswordHasher,
+            password_hash::{SaltString, rand_core::OsRng},
+        };
+
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(password.as_str().as_bytes(), &salt)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(Self(hash.to_string()))
+    }
+
+    pub fn from_phc_string(raw: String) -> Self {
+        Self(raw)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `password` is the one that was hashed
+    ///
+    /// This is CPU heavy, call it from a blocking thread.
+    pub fn verify(&self, password: &Password) -> bool {
+        use argon2::{Argon2, PasswordVerifier};
+
+        let Ok(hash) = argon2::PasswordHash::new(&self.0) else {
+            tracing::error!("Stored password hash is malformed");
+            return false;
+        };
+        Argon2::default()
+            .verify_password(password.as_str().as_bytes(), &hash)
+            .is_ok()
+    }
+}
+
+impl std::fmt::Debug for PasswordHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PasswordHash").field(&"<redacted>").finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateAccountRequest {
     pub name: AccountName,
+    pub password: Password,
 }
 
 impl CreateAccountRequest {
-    pub fn new(name: AccountName) -> Self {
-        Self { name }
+    pub fn new(name: AccountName, password: Password) -> Self {
+        Self { name, password }
     }
 }
 
@@ -76,6 +163,8 @@ pub enum CreateAccountError {
 pub enum FindAccountError {
     #[error("{0} is invalid")]
     InvalidName(String),
+    #[error("account was deleted at {0}")]
+    Gone(DateTime<Utc>),
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -87,3 +176,22 @@ impl From<AccountNameError> for FindAccountError {
         }
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteAccountError {
+    #[error("account not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindAccountError> for DeleteAccountError {
+    fn from(e: FindAccountError) -> Self {
+        match e {
+            FindAccountError::InvalidName(_) | FindAccountError::Gone(_) => {
+                DeleteAccountError::NotFound
+            }
+            FindAccountError::Unknown(e) => DeleteAccountError::Unknown(e),
+        }
+    }
+}
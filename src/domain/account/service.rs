@@ -1,9 +1,15 @@
-use crate::domain::ap::{adapter::ApService, model::CreateLocalActorRequest};
+use chrono::Utc;
+
+use crate::domain::ap::{
+    adapter::ApService,
+    model::{CreateLocalActorRequest, actor::FindActorError},
+};
 
 use super::{
     adapter::{AccountRepository, AccountService},
     model::{
-        Account, AccountId, AccountName, CreateAccountError, CreateAccountRequest, FindAccountError,
+        Account, AccountId, AccountName, CreateAccountError, CreateAccountRequest,
+        DeleteAccountError, FindAccountError, PasswordHash,
     },
 };
 
@@ -32,9 +38,12 @@ where
     #[tracing::instrument(skip(self))]
     async fn create(&self, req: CreateAccountRequest) -> Result<Account, CreateAccountError> {
         tracing::info!("Creating account");
-        let CreateAccountRequest { name } = req;
+        let CreateAccountRequest { name, password } = req;
+        let password_hash = tokio::task::spawn_blocking(move || PasswordHash::new(&password))
+            .await
+            .map_err(|e| anyhow::anyhow!(e))??;
         let account = Account::new(name);
-        let created_account = self.repo.create(account).await?;
+        let created_account = self.repo.create(account, &password_hash).await?;
 
         tracing::info!("Creating actor");
 
@@ -54,14 +63,32 @@ where
     }
 
     #[tracing::instrument(skip(self))]
-    async fn find_by_id(&self, id: &AccountId) -> Result<Option<Account>, FindAccountError> {
-        let account = self.repo.find_by_id(id).await?;
-        Ok(account)
+    async fn find_by_name(&self, name: &AccountName) -> Result<Option<Account>, FindAccountError> {
+        if let Some(account) = self.repo.find_by_name(name).await? {
+            return Ok(Some(account));
+        }
+        match self.repo.find_tombstone(name).await? {
+            Some(deleted) => Err(FindAccountError::Gone(deleted)),
+            None => Ok(None),
+        }
     }
 
     #[tracing::instrument(skip(self))]
-    async fn find_by_name(&self, name: &AccountName) -> Result<Option<Account>, FindAccountError> {
-        let account = self.repo.find_by_name(name).await?;
-        Ok(account)
+    async fn delete(&self, id: &AccountId) -> Result<(), DeleteAccountError> {
+        let Some(account) = self.repo.find_by_id(id).await? else {
+            return Err(DeleteAccountError::NotFound);
+        };
+
+        // must happen first, the key signing the activity goes away with the account
+        match self.ap.announce_local_actor_deletion(id).await {
+            Ok(()) => {}
+            Err(FindActorError::NotFound) => {
+                tracing::warn!("Account has no actor, nothing to federate");
+            }
+            Err(FindActorError::Unknown(e)) => return Err(e.into()),
+        }
+
+        tracing::info!("Deleting account");
+        self.repo.delete(&account, Utc::now()).await
     }
 }
@@ -1,12 +1,50 @@
-use crate::domain::account::model::AccountId;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    ap::{Activity, WebFinger, webfinger::AcctUri},
+    domain::{
+        HttpUrl,
+        account::model::AccountId,
+        media::model::{AttachmentId, CustomEmoji, FindEmojiError},
+    },
+};
 
 use super::model::{
-    ActorRow, CreateActorError, CreateLocalActorError, CreateLocalActorRequest,
-    CreateRemoteActorError, CreateRemoteActorRequest, LocalActor, RemoteActor,
-    actor::{FindActorError, FindRemoteActorRequest},
+    ActorId, ActorRow, ActorView, CreateActorError, CreateLocalActorError, CreateLocalActorRequest,
+    CreateRemoteActorError, CreateRemoteActorRequest, LocalActor, RemoteActor, ResolveActorError,
+    actor::{
+        FindActorError, FindRemoteActorRequest, ProfileField, UpdateActorError, UpdateProfileError,
+        UpdateProfileRequest, UpdatedProfile,
+    },
+    federation::{DeliverRequest, FetchError, HtmlPage, ReceiveActivityError},
+    follow::{
+        CreateFollowError, DeleteFollowError, FindFollowError, Follow, FollowActorError,
+        FollowActorRequest, Relationship, UpdateFollowError,
+    },
+    key::{ActorKeyPair, CreateKeyError, FindKeyError, PublicKey, SigningKey},
     note::{
         CreateLocalNoteError, CreateLocalNoteRequest, CreateRemoteNoteError,
-        CreateRemoteNoteRequest, LocalNote, RemoteNote,
+        CreateRemoteNoteRequest, DeleteLocalNoteError, DeleteLocalNoteRequest, DeleteNoteError,
+        FindNoteError, LocalNote, NoteId, NoteRecipientsError, NoteRevision, NoteSource, NoteView,
+        RemoteNote, ResolveNoteError, ResolveNoteRequest, UpdateLocalNoteError,
+        UpdateLocalNoteRequest, UpdateNoteError,
+    },
+    notification::{
+        CreateNotificationError, DeleteNotificationError, FindNotificationError, Marker,
+        MarkerError, MarkerTimeline, Notification, NotificationGroupKey, NotificationId,
+        NotificationKind, NotificationView, NotificationsRequest,
+    },
+    poll::{
+        ClosePollError, CreateVoteError, FindVoteError, Poll, PollVote, VoteError, VoteRequest,
+    },
+    reaction::{
+        CreateReactionError, DeleteReactionError, EmojiReactRequest, EmojiReaction, Favourite,
+        FindReactionError, ReactError, ReactRequest, Reblog, ReblogId, ReblogView,
+    },
+    search::{Hashtag, SearchError, SearchRequest, SearchResults},
+    timeline::{
+        FindTimelineError, HomeTimelineRequest, PublicTimelineRequest, TimelineItem,
+        TimelineItemId, UpdateHomeFeedError,
     },
 };
 
@@ -21,33 +59,581 @@ pub trait ApService: Send + Sync + 'static {
         req: CreateRemoteActorRequest,
     ) -> Result<RemoteActor, CreateRemoteActorError>;
 
+    /// Return public key of the account's actor, generating the key pair if it has none yet
+    async fn find_local_public_key(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<PublicKey, FindKeyError>;
+
+    /// Send `Delete` of the account's actor to every known inbox
+    ///
+    /// The actor itself is deleted with its account.
+    async fn announce_local_actor_deletion(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<(), FindActorError>;
+
+    /// Return the account's actor with its counts
+    async fn find_local_actor_view(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<ActorView, FindActorError>;
+
+    async fn find_actor_view(&self, id: &ActorId) -> Result<ActorView, FindActorError>;
+
+    /// Change the profile of the account's actor and send it to its followers
+    async fn update_local_profile(
+        &self,
+        req: UpdateProfileRequest,
+    ) -> Result<UpdatedProfile, UpdateProfileError>;
+
+    /// Mark the profile fields of the account's actor linking to pages that link back to the
+    /// actor with `rel="me"` as verified
+    ///
+    /// Fetches every unverified link, so run it in the background. Returns how many were
+    /// verified.
+    async fn verify_profile_links(&self, account_id: &AccountId) -> Result<usize, FindActorError>;
+
+    /// Find actor owning `key_id`, fetching it if it is unknown
+    async fn resolve_actor_by_key_id(&self, key_id: &str)
+    -> Result<RemoteActor, ResolveActorError>;
+
     async fn create_local_note(
         &self,
         req: CreateLocalNoteRequest,
     ) -> Result<LocalNote, CreateLocalNoteError>;
 
+    async fn find_local_note(&self, id: &NoteId) -> Result<LocalNote, FindNoteError>;
+
+    async fn update_local_note(
+        &self,
+        req: UpdateLocalNoteRequest,
+    ) -> Result<LocalNote, UpdateLocalNoteError>;
+
+    /// Delete the note and tell everyone who received it
+    async fn delete_local_note(
+        &self,
+        req: DeleteLocalNoteRequest,
+    ) -> Result<(), DeleteLocalNoteError>;
+
+    /// Return every revision of the note, oldest first
+    async fn find_note_revisions(&self, id: &NoteId) -> Result<Vec<NoteRevision>, FindNoteError>;
+
     async fn create_remote_note(
         &self,
         req: CreateRemoteNoteRequest,
     ) -> Result<RemoteNote, CreateRemoteNoteError>;
+
+    /// Fetch the remote note at `req.url` with the notes it replies to, and return it as seen by
+    /// the requesting account
+    async fn resolve_remote_note(
+        &self,
+        req: ResolveNoteRequest,
+    ) -> Result<NoteView, ResolveNoteError>;
+
+    /// Return the note as seen by `viewer`
+    async fn find_note_view(
+        &self,
+        id: &NoteId,
+        viewer: Option<&AccountId>,
+    ) -> Result<NoteView, FindNoteError>;
+
+    /// Return the latest notes of the actor as seen by `viewer`, newest first
+    async fn find_actor_note_views(
+        &self,
+        actor_id: &ActorId,
+        viewer: Option<&AccountId>,
+        limit: u32,
+    ) -> Result<Vec<NoteView>, FindNoteError>;
+
+    /// Find actors, notes and hashtags matching the query
+    ///
+    /// When the query is a handle or URL, the actor or note it names comes first, fetched if
+    /// `req.resolve` is set.
+    async fn search(&self, req: SearchRequest) -> Result<SearchResults, SearchError>;
+
+    async fn find_relationship(
+        &self,
+        req: FollowActorRequest,
+    ) -> Result<Relationship, FollowActorError>;
+
+    /// Follow the actor. Remote actors have to `Accept` the follow first
+    async fn follow(&self, req: FollowActorRequest) -> Result<Relationship, FollowActorError>;
+
+    /// Stop following the actor or withdraw the follow request
+    async fn unfollow(&self, req: FollowActorRequest) -> Result<Relationship, FollowActorError>;
+
+    async fn favourite(&self, req: ReactRequest) -> Result<NoteView, ReactError>;
+
+    async fn unfavourite(&self, req: ReactRequest) -> Result<NoteView, ReactError>;
+
+    /// Vote in the poll of the note
+    async fn vote(&self, req: VoteRequest) -> Result<NoteView, VoteError>;
+
+    /// Close the local polls that expired, sending their final counts, and return how many
+    async fn close_expired_polls(&self) -> Result<usize, ClosePollError>;
+
+    /// React to the note with a unicode or custom emoji
+    async fn react(&self, req: EmojiReactRequest) -> Result<NoteView, ReactError>;
+
+    async fn unreact(&self, req: EmojiReactRequest) -> Result<NoteView, ReactError>;
+
+    /// Share the note with followers
+    async fn reblog(&self, req: ReactRequest) -> Result<ReblogView, ReactError>;
+
+    async fn unreblog(&self, req: ReactRequest) -> Result<NoteView, ReactError>;
+
+    /// Return a page of the home timeline, newest first
+    async fn home_timeline(
+        &self,
+        req: HomeTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError>;
+
+    /// Return a page of the public timeline, newest first
+    async fn public_timeline(
+        &self,
+        req: PublicTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError>;
+
+    /// Return the item if it is in the home timeline of the account
+    async fn find_home_timeline_item(
+        &self,
+        account_id: &AccountId,
+        item_id: &TimelineItemId,
+    ) -> Result<Option<TimelineItem>, FindTimelineError>;
+
+    /// Return a page of the account's notifications, newest first
+    async fn notifications(
+        &self,
+        req: NotificationsRequest,
+    ) -> Result<Vec<NotificationView>, FindNotificationError>;
+
+    async fn find_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<NotificationView, FindNotificationError>;
+
+    async fn dismiss_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<(), DeleteNotificationError>;
+
+    /// Dismiss every notification of the group
+    async fn dismiss_notification_group(
+        &self,
+        account_id: &AccountId,
+        key: &NotificationGroupKey,
+    ) -> Result<(), DeleteNotificationError>;
+
+    async fn clear_notifications(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<(), DeleteNotificationError>;
+
+    /// Count notifications newer than the notifications marker, up to `limit`
+    async fn unread_notification_count(
+        &self,
+        account_id: &AccountId,
+        limit: u32,
+    ) -> Result<u64, FindNotificationError>;
+
+    async fn find_markers(&self, account_id: &AccountId) -> Result<Vec<Marker>, MarkerError>;
+
+    /// Save the last read item of each timeline, returning every marker of the account
+    async fn update_markers(
+        &self,
+        account_id: &AccountId,
+        last_read_ids: &[(MarkerTimeline, uuid::Uuid)],
+    ) -> Result<Vec<Marker>, MarkerError>;
+
+    /// Process an activity posted to an inbox by `actor`
+    async fn receive_activity(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError>;
 }
 
 #[async_trait::async_trait]
 pub trait ActorRepository: Send + Sync + 'static {
     async fn upsert_actor(&self, req: ActorRow) -> Result<ActorRow, CreateActorError>;
+    /// Replace the profile fields of the actor, unless they changed from `current` meanwhile
+    ///
+    /// Returns whether they were replaced.
+    async fn update_actor_fields(
+        &self,
+        id: &ActorId,
+        current: &[ProfileField],
+        fields: &[ProfileField],
+    ) -> Result<bool, UpdateActorError>;
     async fn find_local_actor(&self, account_id: &AccountId) -> Result<ActorRow, FindActorError>;
     async fn find_remote_actor(
         &self,
         req: &FindRemoteActorRequest,
     ) -> Result<ActorRow, FindActorError>;
+    async fn find_actor_by_url(&self, actor_url: &HttpUrl) -> Result<ActorRow, FindActorError>;
+    async fn find_actor_by_key_id(&self, key_id: &str) -> Result<ActorRow, FindActorError>;
+    async fn find_actor_view(&self, id: &ActorId) -> Result<ActorView, FindActorError>;
+    /// Return inboxes of every known remote actor, shared inboxes deduplicated
+    async fn find_remote_inboxes(&self) -> Result<Vec<HttpUrl>, FindActorError>;
+    /// Return actors whose name or bio matches the query, best matches first
+    async fn search_actors(
+        &self,
+        query: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ActorId>, SearchError>;
+
+    async fn create_account_key(
+        &self,
+        account_id: &AccountId,
+        key: &ActorKeyPair,
+    ) -> Result<(), CreateKeyError>;
+    async fn find_account_key(&self, account_id: &AccountId) -> Result<ActorKeyPair, FindKeyError>;
+}
+
+#[async_trait::async_trait]
+pub trait FollowRepository: Send + Sync + 'static {
+    async fn upsert_follow(&self, follow: Follow) -> Result<Follow, CreateFollowError>;
+    async fn find_follow(
+        &self,
+        follower_id: &ActorId,
+        followee_id: &ActorId,
+    ) -> Result<Option<Follow>, FindFollowError>;
+    /// Mark the follow request `follow_url` to `followee_id` as accepted, returning the follower
+    async fn accept_follow(
+        &self,
+        followee_id: &ActorId,
+        follow_url: &HttpUrl,
+    ) -> Result<Option<ActorId>, UpdateFollowError>;
+    /// Drop the follow request `follow_url` to `followee_id`
+    async fn reject_follow(
+        &self,
+        followee_id: &ActorId,
+        follow_url: &HttpUrl,
+    ) -> Result<(), DeleteFollowError>;
+    async fn delete_follow(
+        &self,
+        follower_id: &ActorId,
+        followee_id: &ActorId,
+    ) -> Result<(), DeleteFollowError>;
+    async fn delete_follow_by_url(
+        &self,
+        follower_id: &ActorId,
+        follow_url: &HttpUrl,
+    ) -> Result<(), DeleteFollowError>;
+    /// Return inboxes of remote followers, shared inboxes deduplicated
+    async fn find_follower_inboxes(
+        &self,
+        followee_id: &ActorId,
+    ) -> Result<Vec<HttpUrl>, FindFollowError>;
 }
 
 #[async_trait::async_trait]
 pub trait NoteRepository: Send + Sync + 'static {
-    async fn create_local_note(&self, req: LocalNote) -> Result<LocalNote, CreateLocalNoteError>;
+    /// Store the note with the uploads of its author in `media_ids` attached
+    async fn create_local_note(
+        &self,
+        req: LocalNote,
+        media_ids: &[AttachmentId],
+    ) -> Result<LocalNote, CreateLocalNoteError>;
+
+    async fn find_local_note(&self, id: &NoteId) -> Result<LocalNote, FindNoteError>;
+
+    /// Add `note.source` as a new revision and make it current
+    async fn update_local_note(&self, note: &LocalNote) -> Result<(), UpdateNoteError>;
+
+    /// Soft delete the note and its sources
+    async fn delete_note(&self, id: &NoteId, deleted: DateTime<Utc>)
+    -> Result<(), DeleteNoteError>;
 
     async fn create_remote_note(
         &self,
         req: RemoteNote,
     ) -> Result<RemoteNote, CreateRemoteNoteError>;
+
+    async fn find_remote_note(&self, note_url: &HttpUrl) -> Result<RemoteNote, FindNoteError>;
+
+    /// Add `note.content` as a new revision and make it current, replacing the attachments
+    async fn update_remote_note(&self, note: &RemoteNote) -> Result<(), UpdateNoteError>;
+
+    /// Replace the hashtags of the note
+    async fn set_note_hashtags(&self, id: &NoteId, names: &[String])
+    -> Result<(), UpdateNoteError>;
+
+    /// Return the custom emoji of this instance named by `shortcodes`
+    async fn find_local_emojis(
+        &self,
+        shortcodes: &[String],
+    ) -> Result<Vec<CustomEmoji>, FindEmojiError>;
+
+    /// Return the custom emoji named `shortcode` of `host`, of this instance if `None`
+    async fn find_emoji_by_shortcode(
+        &self,
+        shortcode: &str,
+        host: Option<&str>,
+    ) -> Result<CustomEmoji, FindEmojiError>;
+
+    /// Return notes whose content matches the query, newest first
+    async fn search_notes(
+        &self,
+        query: &str,
+        actor_id: Option<&ActorId>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<NoteId>, SearchError>;
+
+    /// Return hashtags named like the query, best matches first
+    async fn search_hashtags(
+        &self,
+        query: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Hashtag>, SearchError>;
+
+    /// Return every source of the note, oldest first
+    async fn find_note_sources(&self, id: &NoteId) -> Result<Vec<NoteSource>, FindNoteError>;
+
+    /// Record inboxes the note was delivered to
+    async fn add_note_recipients(
+        &self,
+        id: &NoteId,
+        inboxes: &[HttpUrl],
+    ) -> Result<(), NoteRecipientsError>;
+
+    async fn find_note_recipients(&self, id: &NoteId) -> Result<Vec<HttpUrl>, NoteRecipientsError>;
+
+    async fn find_note_view(
+        &self,
+        id: &NoteId,
+        viewer: Option<&AccountId>,
+    ) -> Result<NoteView, FindNoteError>;
+
+    /// Return the latest notes of the actor, newest first
+    async fn find_actor_note_views(
+        &self,
+        actor_id: &ActorId,
+        viewer: Option<&AccountId>,
+        limit: u32,
+    ) -> Result<Vec<NoteView>, FindNoteError>;
+
+    /// Return the id and author of a note that is not deleted
+    async fn find_note_by_url(
+        &self,
+        note_url: &HttpUrl,
+    ) -> Result<(NoteId, ActorId), FindNoteError>;
+
+    /// Do nothing and return false if the actor already favourited the note
+    async fn create_favourite(&self, favourite: &Favourite) -> Result<bool, CreateReactionError>;
+
+    /// Return `like_url` of the deleted favourite
+    async fn delete_favourite(
+        &self,
+        actor_id: &ActorId,
+        note_id: &NoteId,
+    ) -> Result<Option<HttpUrl>, DeleteReactionError>;
+
+    async fn delete_favourite_by_url(
+        &self,
+        actor_id: &ActorId,
+        like_url: &HttpUrl,
+    ) -> Result<(), DeleteReactionError>;
+
+    /// Do nothing and return false if the actor already reacted to the note with the emoji
+    ///
+    /// A remote emoji of the reaction is stored or refreshed.
+    async fn create_emoji_reaction(
+        &self,
+        reaction: &EmojiReaction,
+    ) -> Result<bool, CreateReactionError>;
+
+    /// Return `reaction_url` of the deleted reaction
+    async fn delete_emoji_reaction(
+        &self,
+        actor_id: &ActorId,
+        note_id: &NoteId,
+        content: &str,
+    ) -> Result<Option<HttpUrl>, DeleteReactionError>;
+
+    async fn delete_emoji_reaction_by_url(
+        &self,
+        actor_id: &ActorId,
+        reaction_url: &HttpUrl,
+    ) -> Result<(), DeleteReactionError>;
+
+    /// Replace the poll of a remote note, with the counts its server sent
+    async fn update_poll(&self, note_id: &NoteId, poll: &Poll) -> Result<(), UpdateNoteError>;
+
+    /// Record the votes of an actor in a poll, skipping those already recorded, and return how
+    /// many were recorded
+    ///
+    /// With `once`, nothing is recorded if the actor already voted. The votes are added to the
+    /// stored counts of a remote poll, so that they show before its server sends new counts.
+    /// Only local actors vote in those, all of their choices at once.
+    async fn create_poll_votes(
+        &self,
+        votes: &[PollVote],
+        once: bool,
+    ) -> Result<u64, CreateVoteError>;
+
+    /// Return the inboxes of the remote actors who voted in the poll of the note
+    async fn find_poll_voter_inboxes(
+        &self,
+        note_id: &NoteId,
+    ) -> Result<Vec<HttpUrl>, FindVoteError>;
+
+    /// Close the local polls that expired by `now` and return their notes
+    async fn close_expired_polls(&self, now: DateTime<Utc>) -> Result<Vec<NoteId>, ClosePollError>;
+
+    /// Do nothing and return false if the actor already reblogged the note
+    async fn create_reblog(&self, reblog: &Reblog) -> Result<bool, CreateReactionError>;
+
+    async fn find_reblog(
+        &self,
+        actor_id: &ActorId,
+        note_id: &NoteId,
+    ) -> Result<Option<Reblog>, FindReactionError>;
+
+    async fn delete_reblog(
+        &self,
+        actor_id: &ActorId,
+        note_id: &NoteId,
+    ) -> Result<Option<Reblog>, DeleteReactionError>;
+
+    /// Return the id of the deleted reblog
+    async fn delete_reblog_by_url(
+        &self,
+        actor_id: &ActorId,
+        announce_url: &HttpUrl,
+    ) -> Result<Option<ReblogId>, DeleteReactionError>;
+
+    /// Return a page of the home timeline, newest first
+    async fn find_home_timeline(
+        &self,
+        req: &HomeTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError>;
+
+    /// Return a page of the public timeline, newest first
+    async fn find_public_timeline(
+        &self,
+        req: &PublicTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError>;
+
+    /// Return the entry `entry_id` of the account's home feed, if any
+    async fn find_home_feed_entry(
+        &self,
+        account_id: &AccountId,
+        entry_id: &TimelineItemId,
+    ) -> Result<Option<TimelineItem>, FindTimelineError>;
+
+    /// Add the note to the home feeds of its author and local followers, keeping `keep` entries
+    async fn fan_out_note(
+        &self,
+        note_id: &NoteId,
+        author_id: &ActorId,
+        keep: u32,
+    ) -> Result<(), UpdateHomeFeedError>;
+
+    /// Add the reblog to the home feeds of its actor and local followers, keeping `keep` entries
+    async fn fan_out_reblog(&self, reblog: &Reblog, keep: u32) -> Result<(), UpdateHomeFeedError>;
+
+    /// Add recent notes and reblogs of `followee_id` to the home feed of a new local follower
+    async fn merge_home_feed(
+        &self,
+        follower_id: &ActorId,
+        followee_id: &ActorId,
+        keep: u32,
+    ) -> Result<(), UpdateHomeFeedError>;
+
+    /// Remove notes and reblogs of `followee_id` from the home feed of a former follower
+    async fn unmerge_home_feed(
+        &self,
+        follower_id: &ActorId,
+        followee_id: &ActorId,
+    ) -> Result<(), UpdateHomeFeedError>;
+
+    /// Regenerate the home feed of the account from its follows
+    async fn rebuild_home_feed(
+        &self,
+        account_id: &AccountId,
+        keep: u32,
+    ) -> Result<(), UpdateHomeFeedError>;
+}
+
+#[async_trait::async_trait]
+pub trait NotificationRepository: Send + Sync + 'static {
+    async fn create_notification(
+        &self,
+        notification: &Notification,
+    ) -> Result<(), CreateNotificationError>;
+
+    /// Return a page of notifications, newest first. Those of deleted notes are skipped
+    async fn find_notifications(
+        &self,
+        req: &NotificationsRequest,
+    ) -> Result<Vec<Notification>, FindNotificationError>;
+
+    async fn find_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<Notification, FindNotificationError>;
+
+    async fn delete_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<(), DeleteNotificationError>;
+
+    /// Delete notifications of the account of `kind` about the note
+    async fn delete_note_notifications(
+        &self,
+        account_id: &AccountId,
+        kind: NotificationKind,
+        note_id: &NoteId,
+    ) -> Result<(), DeleteNotificationError>;
+
+    async fn clear_notifications(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<(), DeleteNotificationError>;
+
+    async fn count_unread_notifications(
+        &self,
+        account_id: &AccountId,
+        limit: u32,
+    ) -> Result<u64, FindNotificationError>;
+
+    async fn find_markers(&self, account_id: &AccountId) -> Result<Vec<Marker>, MarkerError>;
+
+    async fn upsert_marker(
+        &self,
+        account_id: &AccountId,
+        timeline: MarkerTimeline,
+        last_read_id: &uuid::Uuid,
+    ) -> Result<(), MarkerError>;
+}
+
+/// Client talking to other ActivityPub servers
+#[async_trait::async_trait]
+pub trait ApClient: Send + Sync + 'static {
+    /// GET an ActivityPub document, signed with `key` when given
+    ///
+    /// Servers in authorized fetch mode answer unsigned requests with 401.
+    async fn fetch(
+        &self,
+        url: &HttpUrl,
+        key: Option<&SigningKey>,
+    ) -> Result<serde_json::Value, FetchError>;
+
+    /// Look up the account with WebFinger on its host
+    async fn webfinger(&self, acct: &AcctUri) -> Result<WebFinger, FetchError>;
+
+    /// GET an HTML page of at most `max_size` bytes, like one a profile field links to
+    async fn fetch_html(&self, url: &HttpUrl, max_size: usize) -> Result<HtmlPage, FetchError>;
+
+    /// POST the activity to every inbox in the background
+    fn deliver(&self, req: DeliverRequest);
 }
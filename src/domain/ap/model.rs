@@ -1,8 +1,15 @@
 pub(crate) mod actor;
+pub(crate) mod federation;
+pub(crate) mod follow;
 pub(crate) mod key;
 pub(crate) mod note;
+pub(crate) mod notification;
+pub(crate) mod poll;
+pub(crate) mod reaction;
+pub(crate) mod search;
+pub(crate) mod timeline;
 
 pub use actor::{
-    ActorId, ActorRow, CreateActorError, CreateLocalActorError, CreateLocalActorRequest,
-    CreateRemoteActorError, CreateRemoteActorRequest, LocalActor, RemoteActor,
+    ActorId, ActorRow, ActorView, CreateActorError, CreateLocalActorError, CreateLocalActorRequest,
+    CreateRemoteActorError, CreateRemoteActorRequest, LocalActor, RemoteActor, ResolveActorError,
 };
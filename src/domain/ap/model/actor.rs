@@ -1,13 +1,51 @@
+use chrono::{DateTime, Utc};
+
 use crate::{
-    ap::ActorType,
+    ap::{self, ActorType},
     domain::{
         HttpUrl, Id,
         account::model::{AccountId, AccountName},
+        media::model::{CustomEmoji, ProfileImage},
     },
 };
 
+use super::{federation::FetchError, key::PublicKey};
+
 pub type ActorId = Id<ActorRow>;
 
+/// Most fields a profile shows, like Mastodon
+pub const MAX_PROFILE_FIELDS: usize = 4;
+/// Longest display name in characters
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 30;
+/// Longest bio in characters
+pub const MAX_SUMMARY_LENGTH: usize = 500;
+/// Longest name or value of a profile field in characters
+pub const MAX_PROFILE_FIELD_LENGTH: usize = 255;
+/// Most bytes of a page read when checking that it links back to a profile
+pub const MAX_VERIFIED_PAGE_SIZE: usize = 1024 * 1024;
+
+/// `PropertyValue` shown on a profile, like a website or pronouns
+///
+/// See https://docs.joinmastodon.org/spec/activitypub/#PropertyValue
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileField {
+    pub name: String,
+    /// value in HTML
+    pub value: String,
+    /// text the value was rendered from, for local actors
+    pub source: Option<String>,
+    /// when the page the value links to was found to link back with `rel="me"`, for local
+    /// actors
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl ProfileField {
+    /// URL of a page the value is, if it is one
+    pub fn link(&self) -> Option<HttpUrl> {
+        self.source.as_deref()?.parse().ok()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ActorRow {
     pub id: ActorId,
@@ -22,8 +60,27 @@ pub struct ActorRow {
     pub outbox_url: HttpUrl,
     /// actor shared inbox
     pub shared_inbox_url: Option<HttpUrl>,
+    /// bio in sanitized HTML
+    pub summary: Option<String>,
+    /// public key of a remote actor. Local actor keys are stored per account
+    pub public_key: Option<PublicKey>,
     /// account id
     pub account_id: Option<AccountId>,
+    /// when the actor says it was created. Remote actors do not always tell
+    pub published: Option<DateTime<Utc>>,
+    /// when the actor was first stored
+    pub created_at: DateTime<Utc>,
+    /// when the actor was last stored
+    pub updated_at: DateTime<Utc>,
+    /// name shown instead of `name`
+    pub display_name: Option<String>,
+    /// text the bio was rendered from, for local actors
+    pub summary_source: Option<String>,
+    pub avatar: Option<ProfileImage>,
+    pub header: Option<ProfileImage>,
+    pub fields: Vec<ProfileField>,
+    /// custom emoji of the display name, bio and fields
+    pub emojis: Vec<CustomEmoji>,
 }
 
 impl ActorRow {
@@ -32,6 +89,18 @@ impl ActorRow {
     }
 }
 
+/// Actor with the counts shown on its profile
+#[derive(Debug, Clone)]
+pub struct ActorView {
+    pub actor: ActorRow,
+    /// accepted followers
+    pub followers_count: u64,
+    /// accepted follows
+    pub following_count: u64,
+    /// notes that are not deleted
+    pub statuses_count: u64,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum CreateActorError {
     #[error("actor is already exists")]
@@ -54,8 +123,64 @@ pub struct LocalActor {
     pub outbox_url: HttpUrl,
     /// actor shared inbox
     pub shared_inbox_url: HttpUrl,
+    /// bio in HTML
+    pub summary: Option<String>,
     /// account id
     pub account_id: AccountId,
+    /// when the account's actor was created
+    pub published: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// name shown instead of `name`
+    pub display_name: Option<String>,
+    /// text the bio was rendered from
+    pub summary_source: Option<String>,
+    pub avatar: Option<ProfileImage>,
+    pub header: Option<ProfileImage>,
+    pub fields: Vec<ProfileField>,
+    /// custom emoji of the display name, bio and fields
+    pub emojis: Vec<CustomEmoji>,
+}
+
+impl LocalActor {
+    /// Actor document served at `actor_url` and sent in `Update` activities
+    pub fn to_ap(&self, public_key: PublicKey) -> ap::Actor {
+        let image = |image: &ProfileImage| {
+            ap::Image::builder()
+                .kind(ap::DocumentType::Image)
+                .url(image.url.clone())
+                .build()
+        };
+        let public_key = ap::PublicKey::builder()
+            .id(public_key.id)
+            .owner(self.actor_url.clone())
+            .public_key_pem(public_key.pem)
+            .build();
+        let endpoints = ap::Endpoints::builder()
+            .shared_inbox(self.shared_inbox_url.clone())
+            .build();
+        ap::Actor::builder()
+            .kind(self.actor_type)
+            .id(self.actor_url.clone())
+            .inbox(self.inbox_url.clone())
+            .outbox(self.outbox_url.clone())
+            .preferred_username(self.name.as_str())
+            .name(self.display_name.clone())
+            .summary(self.summary.clone())
+            .icon(self.avatar.as_ref().map(image))
+            .image(self.header.as_ref().map(image))
+            .attachment(
+                self.fields
+                    .iter()
+                    .map(|field| ap::PropertyValue::new(&field.name, &field.value))
+                    .collect(),
+            )
+            .tag(self.emojis.iter().map(ap::Tag::from).collect())
+            .endpoints(endpoints)
+            .public_key(public_key)
+            .published(self.published)
+            .build()
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -84,7 +209,17 @@ impl TryFrom<ActorRow> for LocalActor {
             inbox_url: row.inbox_url,
             outbox_url: row.outbox_url,
             shared_inbox_url,
+            summary: row.summary,
             account_id,
+            published: row.published.unwrap_or(row.created_at),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            display_name: row.display_name,
+            summary_source: row.summary_source,
+            avatar: row.avatar,
+            header: row.header,
+            fields: row.fields,
+            emojis: row.emojis,
         })
     }
 }
@@ -99,7 +234,18 @@ impl From<LocalActor> for ActorRow {
             inbox_url: actor.inbox_url,
             outbox_url: actor.outbox_url,
             shared_inbox_url: Some(actor.shared_inbox_url),
+            summary: actor.summary,
+            public_key: None,
             account_id: Some(actor.account_id),
+            published: Some(actor.published),
+            created_at: actor.created_at,
+            updated_at: actor.updated_at,
+            display_name: actor.display_name,
+            summary_source: actor.summary_source,
+            avatar: actor.avatar,
+            header: actor.header,
+            fields: actor.fields,
+            emojis: actor.emojis,
         }
     }
 }
@@ -134,6 +280,12 @@ impl From<CreateActorError> for CreateLocalActorError {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateActorError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum FindActorError {
     #[error("actor not found")]
@@ -162,6 +314,34 @@ pub struct RemoteActor {
     pub outbox_url: HttpUrl,
     /// actor shared inbox
     pub shared_inbox_url: Option<HttpUrl>,
+    /// bio in sanitized HTML
+    pub summary: Option<String>,
+    /// key to verify HTTP Signatures with
+    pub public_key: Option<PublicKey>,
+    /// when the actor says it was created. Remote actors do not always tell
+    pub published: Option<DateTime<Utc>>,
+    /// when the actor was first stored
+    pub created_at: DateTime<Utc>,
+    /// when the actor was last stored
+    pub updated_at: DateTime<Utc>,
+    /// name shown instead of `name`
+    pub display_name: Option<String>,
+    pub avatar: Option<ProfileImage>,
+    pub header: Option<ProfileImage>,
+    /// fields with sanitized HTML values
+    pub fields: Vec<ProfileField>,
+    /// custom emoji of the display name, bio and fields, linked to where they are hosted
+    pub emojis: Vec<CustomEmoji>,
+}
+
+impl RemoteActor {
+    pub fn id(&self) -> &ActorId {
+        &self.id
+    }
+
+    pub fn host(&self) -> &str {
+        self.actor_url.host()
+    }
 }
 
 impl From<ActorRow> for RemoteActor {
@@ -174,6 +354,16 @@ impl From<ActorRow> for RemoteActor {
             inbox_url: row.inbox_url,
             outbox_url: row.outbox_url,
             shared_inbox_url: row.shared_inbox_url,
+            summary: row.summary,
+            public_key: row.public_key,
+            published: row.published,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            display_name: row.display_name,
+            avatar: row.avatar,
+            header: row.header,
+            fields: row.fields,
+            emojis: row.emojis,
         }
     }
 }
@@ -188,7 +378,18 @@ impl From<RemoteActor> for ActorRow {
             inbox_url: actor.inbox_url,
             outbox_url: actor.outbox_url,
             shared_inbox_url: actor.shared_inbox_url,
+            summary: actor.summary,
+            public_key: actor.public_key,
             account_id: None,
+            published: actor.published,
+            created_at: actor.created_at,
+            updated_at: actor.updated_at,
+            display_name: actor.display_name,
+            summary_source: None,
+            avatar: actor.avatar,
+            header: actor.header,
+            fields: actor.fields,
+            emojis: actor.emojis,
         }
     }
 }
@@ -201,6 +402,16 @@ pub struct CreateRemoteActorRequest {
     pub inbox_url: HttpUrl,
     pub outbox_url: HttpUrl,
     pub shared_inbox_url: Option<HttpUrl>,
+    /// untrusted HTML bio as received
+    pub summary: Option<String>,
+    pub public_key: Option<PublicKey>,
+    pub published: Option<DateTime<Utc>>,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<HttpUrl>,
+    pub header_url: Option<HttpUrl>,
+    /// fields with untrusted HTML values as received
+    pub fields: Vec<ProfileField>,
+    pub emojis: Vec<CustomEmoji>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -227,9 +438,94 @@ pub struct FindRemoteActorRequest {
 }
 
 #[derive(Debug, thiserror::Error)]
-pub enum FindRemoteActorError {
-    #[error("remote actor not found")]
+pub enum ResolveActorError {
+    #[error("actor not found")]
     NotFound,
+    #[error("invalid actor: {0}")]
+    InvalidActor(String),
     #[error(transparent)]
-    Unknown(anyhow::Error),
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FetchError> for ResolveActorError {
+    fn from(err: FetchError) -> Self {
+        match err {
+            FetchError::NotFound(_) | FetchError::Gone(_) => Self::NotFound,
+            FetchError::InvalidResponse(url) | FetchError::NotPublic(url) => {
+                Self::InvalidActor(url.to_string())
+            }
+            FetchError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<FindActorError> for ResolveActorError {
+    fn from(err: FindActorError) -> Self {
+        match err {
+            FindActorError::NotFound => Self::NotFound,
+            FindActorError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<CreateActorError> for ResolveActorError {
+    fn from(err: CreateActorError) -> Self {
+        match err {
+            CreateActorError::AlreadyExists => {
+                Self::Unknown(anyhow::anyhow!("actor already exists"))
+            }
+            CreateActorError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+/// Changes to the profile of an account's actor. Parts left `None` are kept
+#[derive(Debug, Clone)]
+pub struct UpdateProfileRequest {
+    pub account_id: AccountId,
+    pub display_name: Option<String>,
+    /// bio in plain text
+    pub summary: Option<String>,
+    /// image stored with [crate::domain::media::adapter::MediaService::upload_profile_image]
+    pub avatar: Option<ProfileImage>,
+    pub header: Option<ProfileImage>,
+    /// `(name, value)` in plain text, replacing every field
+    pub fields: Option<Vec<(String, String)>>,
+}
+
+/// Profile after an update, with the images it no longer shows
+#[derive(Debug, Clone)]
+pub struct UpdatedProfile {
+    pub view: ActorView,
+    pub replaced_images: Vec<ProfileImage>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateProfileError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("actor not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindActorError> for UpdateProfileError {
+    fn from(err: FindActorError) -> Self {
+        match err {
+            FindActorError::NotFound => Self::NotFound,
+            FindActorError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<CreateActorError> for UpdateProfileError {
+    fn from(err: CreateActorError) -> Self {
+        match err {
+            CreateActorError::AlreadyExists => {
+                Self::Unknown(anyhow::anyhow!("actor already exists"))
+            }
+            CreateActorError::Unknown(err) => Self::Unknown(err),
+        }
+    }
 }
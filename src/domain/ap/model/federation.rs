@@ -0,0 +1,42 @@
+use crate::domain::HttpUrl;
+
+use super::key::SigningKey;
+
+/// HTML page fetched from `url`, after redirects
+#[derive(Debug, Clone)]
+pub struct HtmlPage {
+    pub url: HttpUrl,
+    pub html: String,
+}
+
+/// Activity to POST to remote inboxes
+#[derive(Debug, Clone)]
+pub struct DeliverRequest {
+    pub key: SigningKey,
+    pub inboxes: Vec<HttpUrl>,
+    pub activity: serde_json::Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("{0} not found")]
+    NotFound(HttpUrl),
+    #[error("{0} is gone")]
+    Gone(HttpUrl),
+    #[error("unexpected response from {0}")]
+    InvalidResponse(HttpUrl),
+    #[error("{0} is not a public address")]
+    NotPublic(HttpUrl),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReceiveActivityError {
+    #[error("invalid activity: {0}")]
+    InvalidActivity(String),
+    #[error("actor is not allowed to do this")]
+    Forbidden,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
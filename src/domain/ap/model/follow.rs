@@ -0,0 +1,88 @@
+use crate::domain::{HttpUrl, Id, account::model::AccountId};
+
+use super::{ActorId, actor::FindActorError};
+
+pub type FollowId = Id<Follow>;
+
+/// `follower` follows `followee`
+#[derive(Debug, Clone)]
+pub struct Follow {
+    pub id: FollowId,
+    pub follower_id: ActorId,
+    pub followee_id: ActorId,
+    /// `id` of the `Follow` activity
+    pub follow_url: HttpUrl,
+    /// `false` until a remote followee sends `Accept`
+    pub accepted: bool,
+}
+
+/// How the viewer relates to an actor
+#[derive(Debug, Clone)]
+pub struct Relationship {
+    pub actor_id: ActorId,
+    /// the viewer follows the actor
+    pub following: bool,
+    /// the viewer asked to follow the actor and waits for `Accept`
+    pub requested: bool,
+    /// the actor follows the viewer
+    pub followed_by: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FollowActorRequest {
+    /// who follows
+    pub(crate) account_id: AccountId,
+    /// whom to follow
+    pub(crate) actor_id: ActorId,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateFollowError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteFollowError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindFollowError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateFollowError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FollowActorError {
+    #[error("actor not found")]
+    ActorNotFound,
+    #[error("actors can not follow themselves")]
+    SelfFollow,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindActorError> for FollowActorError {
+    fn from(e: FindActorError) -> Self {
+        match e {
+            FindActorError::NotFound => FollowActorError::ActorNotFound,
+            FindActorError::Unknown(e) => FollowActorError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindFollowError> for FollowActorError {
+    fn from(e: FindFollowError) -> Self {
+        match e {
+            FindFollowError::Unknown(e) => FollowActorError::Unknown(e),
+        }
+    }
+}
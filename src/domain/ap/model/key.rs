@@ -1 +1,83 @@
+use rsa::{
+    RsaPrivateKey, RsaPublicKey,
+    pkcs8::{EncodePrivateKey as _, EncodePublicKey as _, LineEnding},
+};
 
+/// Key type stored in `account_keys`
+pub const RSA_KEY_TYPE: &str = "rsa";
+
+const RSA_KEY_BITS: usize = 2048;
+
+/// RSA key pair of a local actor used to sign HTTP requests
+#[derive(Clone)]
+pub struct ActorKeyPair {
+    /// SPKI PEM
+    pub(crate) public_key_pem: String,
+    /// PKCS#8 PEM
+    pub(crate) private_key_pem: String,
+}
+
+impl std::fmt::Debug for ActorKeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorKeyPair")
+            .field("public_key_pem", &self.public_key_pem)
+            .field("private_key_pem", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ActorKeyPair {
+    /// Generate a new key pair
+    ///
+    /// This takes a while, so call it from a blocking thread.
+    pub fn generate() -> Result<Self, anyhow::Error> {
+        let private_key = RsaPrivateKey::new(&mut rsa::rand_core::OsRng, RSA_KEY_BITS)?;
+        let public_key = RsaPublicKey::from(&private_key);
+
+        Ok(Self {
+            public_key_pem: public_key.to_public_key_pem(LineEnding::LF)?,
+            private_key_pem: private_key.to_pkcs8_pem(LineEnding::LF)?.to_string(),
+        })
+    }
+}
+
+/// Public key of an actor
+#[derive(Debug, Clone)]
+pub struct PublicKey {
+    /// `publicKey.id`, usually `{actor_url}#main-key`
+    pub id: String,
+    /// SPKI PEM
+    pub pem: String,
+}
+
+/// Key to sign outgoing requests with
+#[derive(Clone)]
+pub struct SigningKey {
+    pub key_id: String,
+    pub private_key_pem: String,
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("key_id", &self.key_id)
+            .field("private_key_pem", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindKeyError {
+    #[error("key not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateKeyError {
+    #[error("key already exists")]
+    AlreadyExists,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
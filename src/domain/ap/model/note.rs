@@ -1,15 +1,100 @@
-use crate::domain::{HttpUrl, Id, account::model::AccountId};
+use std::str::FromStr;
 
-use super::{ActorId, actor::FindActorError};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    ap::{self, constants},
+    domain::{
+        HttpUrl, Id,
+        account::model::AccountId,
+        media::model::{Attachment, AttachmentId, CustomEmoji},
+        text::{SourceMediaType, hashtag, render},
+    },
+};
+
+use super::{
+    ActorId,
+    actor::{ActorView, FindActorError, ResolveActorError},
+    federation::FetchError,
+    key::FindKeyError,
+    poll::{CreatePollRequest, Poll},
+    reaction::EmojiReactionCount,
+};
 
 pub type NoteId = Id<Note>;
+pub type NoteSourceId = Id<NoteSource>;
 
-#[derive(Debug, Clone)]
-pub enum Note {
-    Local(LocalNote),
-    Remote(RemoteNote),
+/// Type of the object a note is, as ActivityStreams names it
+///
+/// Notes and polls are shown as they are. Long-form objects are shown by their title and a link
+/// to where they can be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteType {
+    #[default]
+    Note,
+    Question,
+    Article,
+    Page,
+    Video,
+    Event,
+    Image,
+}
+
+impl NoteType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteType::Note => "Note",
+            NoteType::Question => "Question",
+            NoteType::Article => "Article",
+            NoteType::Page => "Page",
+            NoteType::Video => "Video",
+            NoteType::Event => "Event",
+            NoteType::Image => "Image",
+        }
+    }
+
+    /// Type of an ActivityStreams object, `None` if it is not stored as a note
+    pub fn from_object_type(kind: ap::ObjectType) -> Option<Self> {
+        match kind {
+            ap::ObjectType::Note => Some(NoteType::Note),
+            ap::ObjectType::Question => Some(NoteType::Question),
+            ap::ObjectType::Article => Some(NoteType::Article),
+            ap::ObjectType::Page => Some(NoteType::Page),
+            ap::ObjectType::Video => Some(NoteType::Video),
+            ap::ObjectType::Event => Some(NoteType::Event),
+            ap::ObjectType::Image => Some(NoteType::Image),
+            ap::ObjectType::Tombstone => None,
+        }
+    }
+
+    /// Whether the object is a status of its own rather than long-form content, so that its
+    /// summary is a content warning
+    pub fn is_status(&self) -> bool {
+        matches!(self, NoteType::Note | NoteType::Question)
+    }
+}
+
+impl FromStr for NoteType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Note" => Ok(NoteType::Note),
+            "Question" => Ok(NoteType::Question),
+            "Article" => Ok(NoteType::Article),
+            "Page" => Ok(NoteType::Page),
+            "Video" => Ok(NoteType::Video),
+            "Event" => Ok(NoteType::Event),
+            "Image" => Ok(NoteType::Image),
+            _ => Err(anyhow::anyhow!("unsupported object type {s}")),
+        }
+    }
 }
 
+/// Local or remote note, only used to type [NoteId]
+#[derive(Debug)]
+pub enum Note {}
+
 #[derive(Debug, Clone)]
 pub struct LocalNote {
     /// note id
@@ -17,24 +102,205 @@ pub struct LocalNote {
     /// who created the note
     pub(crate) account_id: AccountId,
     pub(crate) actor_id: ActorId,
-    /// content
+    /// actor url of the author
+    pub(crate) actor_url: HttpUrl,
+    /// rendered HTML content
     pub(crate) content: String,
+    /// content warning the content is folded behind
+    pub(crate) summary: Option<String>,
+    /// the attachments are not safe for work
+    pub(crate) sensitive: bool,
+    /// text the content was rendered from
+    pub(crate) source: NoteSource,
     /// note url
     pub(crate) note_url: HttpUrl,
+    /// when the note was last edited
+    pub(crate) updated: Option<DateTime<Utc>>,
+    /// when the note was posted
+    pub(crate) published: DateTime<Utc>,
+    /// when the note was stored
+    pub(crate) created_at: DateTime<Utc>,
+    /// when the note was last stored
+    pub(crate) updated_at: DateTime<Utc>,
+    /// attached files
+    pub(crate) attachments: Vec<Attachment>,
+    /// custom emoji of the content
+    pub(crate) emojis: Vec<CustomEmoji>,
+    pub(crate) poll: Option<Poll>,
+}
+
+impl LocalNote {
+    pub fn note_type(&self) -> NoteType {
+        match self.poll {
+            Some(_) => NoteType::Question,
+            None => NoteType::Note,
+        }
+    }
+}
+
+impl From<&LocalNote> for ap::Note {
+    fn from(note: &LocalNote) -> Self {
+        let source = ap::Source::builder()
+            .content(note.source.content.clone())
+            .media_type(note.source.media_type.as_str())
+            .build();
+        let (one_of, any_of) = match &note.poll {
+            Some(poll) if poll.multiple => (Vec::new(), poll.ap_options()),
+            Some(poll) => (poll.ap_options(), Vec::new()),
+            None => Default::default(),
+        };
+        ap::Note::builder()
+            .id(note.note_url.clone())
+            .kind(match note.poll {
+                Some(_) => ap::ObjectType::Question,
+                None => ap::ObjectType::Note,
+            })
+            .attributed_to(note.actor_url.clone())
+            .content(note.content.clone())
+            .summary(note.summary.clone())
+            .sensitive(Some(note.sensitive))
+            .source(source)
+            .to(vec![constants::PUBLIC.to_string()])
+            .published(note.published)
+            .updated(note.updated)
+            .tag(
+                hashtag::extract(&note.source.content)
+                    .into_iter()
+                    .map(|name| ap::Tag::Hashtag {
+                        name: format!("#{name}"),
+                        href: None,
+                    })
+                    .chain(note.emojis.iter().map(ap::Tag::from))
+                    .collect(),
+            )
+            .attachment(note.attachments.iter().map(ap::Document::from).collect())
+            .one_of(one_of)
+            .any_of(any_of)
+            .end_time(note.poll.as_ref().and_then(|poll| poll.expires_at))
+            .closed(note.poll.as_ref().and_then(|poll| poll.closed_at))
+            .voters_count(note.poll.as_ref().map(|poll| poll.voters_count))
+            .build()
+    }
+}
+
+/// Note as seen by a viewer, with its author and reactions
+#[derive(Debug, Clone)]
+pub struct NoteView {
+    pub(crate) id: NoteId,
+    pub(crate) author: ActorView,
+    /// rendered HTML content
+    pub(crate) content: String,
+    /// content warning the content is folded behind, or excerpt of long-form objects
+    pub(crate) summary: Option<String>,
+    /// the attachments are not safe for work
+    pub(crate) sensitive: bool,
+    /// current revision
+    pub(crate) source: NoteSource,
+    /// type of the object the note was received as
+    pub(crate) note_type: NoteType,
+    /// title of long-form objects like `Article`s
+    pub(crate) title: Option<String>,
+    /// web page of the object
+    pub(crate) url: Option<HttpUrl>,
+    pub(crate) note_url: HttpUrl,
+    /// when the note was last edited
+    pub(crate) updated: Option<DateTime<Utc>>,
+    /// when the note was posted
+    pub(crate) published: DateTime<Utc>,
+    /// note it replies to and its author
+    pub(crate) in_reply_to: Option<(NoteId, ActorId)>,
+    pub(crate) favourites_count: u64,
+    pub(crate) reblogs_count: u64,
+    /// the viewer favourited the note
+    pub(crate) favourited: bool,
+    /// the viewer reblogged the note
+    pub(crate) reblogged: bool,
+    /// emoji reactions, most used first
+    pub(crate) reactions: Vec<EmojiReactionCount>,
+    /// attached files
+    pub(crate) attachments: Vec<Attachment>,
+    /// custom emoji of the content
+    pub(crate) emojis: Vec<CustomEmoji>,
+    pub(crate) poll: Option<Poll>,
+    /// options of the poll the viewer chose
+    pub(crate) own_votes: Vec<u32>,
+}
+
+impl NoteView {
+    /// HTML to show the note with. Long-form objects are shown as their title, summary and a
+    /// link to them instead of their whole content
+    pub fn display_content(&self) -> String {
+        if self.note_type.is_status() {
+            return self.content.clone();
+        }
+        let mut html = String::new();
+        if let Some(title) = &self.title {
+            html.push_str(&format!(
+                "<p><strong>{}</strong></p>",
+                render::escape_html(title)
+            ));
+        }
+        html.push_str(self.summary.as_deref().unwrap_or(&self.content));
+        let url = render::escape_html(self.url.as_ref().unwrap_or(&self.note_url).as_str());
+        html.push_str(&format!(r#"<p><a href="{url}">{url}</a></p>"#));
+        html
+    }
+
+    /// Content warning to fold the content behind. Summaries of long-form objects are shown
+    /// instead
+    pub fn content_warning(&self) -> Option<&str> {
+        self.summary
+            .as_deref()
+            .filter(|_| self.note_type.is_status())
+    }
+}
+
+/// What the author wrote, kept so that edits can start from it
+///
+/// Every edit adds a new source, so the sources of a note are its revision history.
+#[derive(Debug, Clone)]
+pub struct NoteSource {
+    pub(crate) id: NoteSourceId,
+    pub(crate) content: String,
+    pub(crate) media_type: SourceMediaType,
+}
+
+/// A version of a note
+#[derive(Debug, Clone)]
+pub struct NoteRevision {
+    /// rendered HTML content
+    pub(crate) content: String,
+    pub(crate) source: NoteSource,
 }
 
 #[derive(Debug, Clone)]
 pub struct CreateLocalNoteRequest {
     /// who created the note
     pub(crate) account_id: AccountId,
-    /// content
+    /// source text
     pub(crate) content: String,
+    /// media type of `content`
+    pub(crate) media_type: SourceMediaType,
+    /// content warning, plain text
+    pub(crate) summary: Option<String>,
+    /// the attachments are not safe for work. Always set with a content warning
+    pub(crate) sensitive: bool,
+    /// uploads to attach
+    pub(crate) media_ids: Vec<AttachmentId>,
+    pub(crate) poll: Option<CreatePollRequest>,
 }
 
+/// Files a note can have, as many as Mastodon allows
+pub const MAX_ATTACHMENTS: usize = 4;
+
 #[derive(Debug, thiserror::Error)]
 pub enum CreateLocalNoteError {
     #[error("Actor not found")]
     ActorNotFound,
+    #[error("invalid media: {0}")]
+    InvalidMedia(String),
+    #[error("invalid poll: {0}")]
+    InvalidPoll(String),
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -48,12 +314,140 @@ impl From<FindActorError> for CreateLocalNoteError {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum FindNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error("note was deleted at {0}")]
+    Gone(DateTime<Utc>),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateLocalNoteRequest {
+    pub(crate) note_id: NoteId,
+    /// who is editing the note
+    pub(crate) account_id: AccountId,
+    /// new source text
+    pub(crate) content: String,
+    /// media type of `content`
+    pub(crate) media_type: SourceMediaType,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateLocalNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error("only the author can edit the note")]
+    Forbidden,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindNoteError> for UpdateLocalNoteError {
+    fn from(e: FindNoteError) -> Self {
+        match e {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => UpdateLocalNoteError::NotFound,
+            FindNoteError::Unknown(e) => UpdateLocalNoteError::Unknown(e),
+        }
+    }
+}
+
+impl From<UpdateNoteError> for UpdateLocalNoteError {
+    fn from(e: UpdateNoteError) -> Self {
+        match e {
+            UpdateNoteError::NotFound => UpdateLocalNoteError::NotFound,
+            UpdateNoteError::Unknown(e) => UpdateLocalNoteError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteLocalNoteRequest {
+    pub(crate) note_id: NoteId,
+    /// who is deleting the note
+    pub(crate) account_id: AccountId,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteLocalNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error("only the author can delete the note")]
+    Forbidden,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindNoteError> for DeleteLocalNoteError {
+    fn from(e: FindNoteError) -> Self {
+        match e {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => DeleteLocalNoteError::NotFound,
+            FindNoteError::Unknown(e) => DeleteLocalNoteError::Unknown(e),
+        }
+    }
+}
+
+impl From<DeleteNoteError> for DeleteLocalNoteError {
+    fn from(e: DeleteNoteError) -> Self {
+        match e {
+            DeleteNoteError::NotFound => DeleteLocalNoteError::NotFound,
+            DeleteNoteError::Unknown(e) => DeleteLocalNoteError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NoteRecipientsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateRemoteNoteRequest {
     pub(crate) name: String,
     pub(crate) host: String,
     pub(crate) content: String,
+    /// type of the object the note was received as
+    pub(crate) note_type: NoteType,
+    /// title of long-form objects like `Article`s
+    pub(crate) title: Option<String>,
+    /// web page of the object
+    pub(crate) url: Option<HttpUrl>,
+    /// content warning the content is folded behind, or excerpt of long-form objects
+    pub(crate) summary: Option<String>,
+    /// the attachments are not safe for work
+    pub(crate) sensitive: bool,
     pub(crate) note_url: HttpUrl,
+    /// hashtag names, lowercased without `#`
+    pub(crate) hashtags: Vec<String>,
+    /// note it replies to, if it is known
+    pub(crate) in_reply_to_id: Option<NoteId>,
+    /// when the note says it was posted
+    pub(crate) published: Option<DateTime<Utc>>,
+    /// attached files, linked to where they are hosted
+    pub(crate) attachments: Vec<Attachment>,
+    /// custom emoji of the content, linked to where they are hosted
+    pub(crate) emojis: Vec<CustomEmoji>,
+    /// poll of a `Question`, with the counts its server sent
+    pub(crate) poll: Option<Poll>,
 }
 
 #[derive(Debug, Clone)]
@@ -61,16 +455,46 @@ pub struct RemoteNote {
     /// note id
     pub(crate) id: NoteId,
     pub(crate) actor_id: ActorId,
-    /// content
+    /// sanitized HTML content
     pub(crate) content: String,
+    /// content warning the content is folded behind, or excerpt of long-form objects
+    pub(crate) summary: Option<String>,
+    /// the attachments are not safe for work
+    pub(crate) sensitive: bool,
+    /// revision holding `content`
+    pub(crate) source_id: NoteSourceId,
+    /// type of the object the note was received as
+    pub(crate) note_type: NoteType,
+    /// title of long-form objects like `Article`s
+    pub(crate) title: Option<String>,
+    /// web page of the object
+    pub(crate) url: Option<HttpUrl>,
     /// note url
     pub(crate) note_url: HttpUrl,
+    /// when the note was last edited
+    pub(crate) updated: Option<DateTime<Utc>>,
+    /// note it replies to, if it is known
+    pub(crate) in_reply_to_id: Option<NoteId>,
+    /// when the note was posted as sent, or received when it was not
+    pub(crate) published: DateTime<Utc>,
+    /// when the note was stored
+    pub(crate) created_at: DateTime<Utc>,
+    /// when the note was last stored
+    pub(crate) updated_at: DateTime<Utc>,
+    /// attached files, linked to where they are hosted
+    pub(crate) attachments: Vec<Attachment>,
+    /// custom emoji of the content, linked to where they are hosted
+    pub(crate) emojis: Vec<CustomEmoji>,
+    /// poll of a `Question`, with the counts its server sent
+    pub(crate) poll: Option<Poll>,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateRemoteNoteError {
     #[error("Actor not found")]
     ActorNotFound,
+    #[error("note already exists")]
+    AlreadyExists,
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -83,3 +507,66 @@ impl From<FindActorError> for CreateRemoteNoteError {
         }
     }
 }
+
+/// Notes a resolved note replies to that are fetched along with it, as threads can be long
+pub const MAX_FETCHED_ANCESTORS: u32 = 16;
+
+#[derive(Debug, Clone)]
+pub struct ResolveNoteRequest {
+    /// who asks, its key signs the fetches
+    pub(crate) account_id: AccountId,
+    /// ActivityPub id of the note
+    pub(crate) url: HttpUrl,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResolveNoteError {
+    #[error("note not found")]
+    NotFound,
+    #[error("invalid note: {0}")]
+    InvalidNote(String),
+    #[error("account has no actor")]
+    ActorNotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FetchError> for ResolveNoteError {
+    fn from(err: FetchError) -> Self {
+        match err {
+            FetchError::NotFound(_) | FetchError::Gone(_) => Self::NotFound,
+            FetchError::InvalidResponse(url) | FetchError::NotPublic(url) => {
+                Self::InvalidNote(url.to_string())
+            }
+            FetchError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<FindNoteError> for ResolveNoteError {
+    fn from(err: FindNoteError) -> Self {
+        match err {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => Self::NotFound,
+            FindNoteError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<ResolveActorError> for ResolveNoteError {
+    fn from(err: ResolveActorError) -> Self {
+        match err {
+            ResolveActorError::NotFound => Self::InvalidNote("author not found".to_string()),
+            ResolveActorError::InvalidActor(e) => Self::InvalidNote(format!("invalid author: {e}")),
+            ResolveActorError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
+
+impl From<FindKeyError> for ResolveNoteError {
+    fn from(err: FindKeyError) -> Self {
+        match err {
+            FindKeyError::NotFound => Self::ActorNotFound,
+            FindKeyError::Unknown(err) => Self::Unknown(err),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Id, account::model::AccountId};
+
+use super::{
+    ActorId,
+    actor::ActorView,
+    note::{NoteId, NoteView},
+    timeline::TimelinePage,
+};
+
+pub type NotificationId = Id<Notification>;
+
+/// What was done to the notified account
+///
+/// See https://docs.joinmastodon.org/entities/Notification/#type
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::AsRefStr,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum NotificationKind {
+    /// someone followed the account
+    Follow,
+    /// someone addressed a note to the account or replied to one of its notes
+    Mention,
+    /// someone favourited a note of the account
+    Favourite,
+    /// someone reblogged a note of the account
+    Reblog,
+}
+
+/// Something an actor did to a local account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: NotificationId,
+    /// who is notified
+    pub account_id: AccountId,
+    pub kind: NotificationKind,
+    /// who did it
+    pub actor_id: ActorId,
+    /// note it was done to
+    pub note_id: Option<NoteId>,
+}
+
+/// Notification as its recipient sees it
+#[derive(Debug, Clone)]
+pub struct NotificationView {
+    pub(crate) id: NotificationId,
+    pub(crate) kind: NotificationKind,
+    pub(crate) actor: ActorView,
+    pub(crate) note: Option<NoteView>,
+}
+
+impl NotificationView {
+    /// Key of the group the notification is shown in
+    pub fn group_key(&self) -> NotificationGroupKey {
+        match (self.kind, &self.note) {
+            (NotificationKind::Favourite | NotificationKind::Reblog, Some(note)) => {
+                NotificationGroupKey::Note(self.kind, note.id.clone())
+            }
+            _ => NotificationGroupKey::Ungrouped(self.id.clone()),
+        }
+    }
+}
+
+/// What notifications are grouped by. Favourites and reblogs of a note are shown together
+///
+/// See https://docs.joinmastodon.org/entities/GroupedNotificationsResults/#NotificationGroup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationGroupKey {
+    /// like `favourite-{note_id}`
+    Note(NotificationKind, NoteId),
+    /// like `ungrouped-{id}`
+    Ungrouped(NotificationId),
+}
+
+impl std::fmt::Display for NotificationGroupKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationGroupKey::Note(kind, note_id) => write!(f, "{kind}-{note_id}"),
+            NotificationGroupKey::Ungrouped(id) => write!(f, "ungrouped-{id}"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid notification group key {0}")]
+pub struct NotificationGroupKeyError(String);
+
+impl FromStr for NotificationGroupKey {
+    type Err = NotificationGroupKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || NotificationGroupKeyError(s.to_string());
+        let (prefix, id) = s.split_once('-').ok_or_else(err)?;
+        if prefix == "ungrouped" {
+            return Ok(NotificationGroupKey::Ungrouped(
+                id.parse().map_err(|_| err())?,
+            ));
+        }
+        match prefix.parse().map_err(|_| err())? {
+            kind @ (NotificationKind::Favourite | NotificationKind::Reblog) => Ok(
+                NotificationGroupKey::Note(kind, id.parse().map_err(|_| err())?),
+            ),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Notifications of a page sharing a group key, newest first
+#[derive(Debug, Clone)]
+pub struct NotificationGroup {
+    pub(crate) key: NotificationGroupKey,
+    pub(crate) notifications: Vec<NotificationView>,
+}
+
+impl NotificationGroup {
+    /// Group notifications of a page, keeping the order of their newest notification
+    pub fn group(views: Vec<NotificationView>) -> Vec<NotificationGroup> {
+        let mut groups = Vec::<NotificationGroup>::new();
+        for view in views {
+            let key = view.group_key();
+            match groups.iter_mut().find(|group| group.key == key) {
+                Some(group) => group.notifications.push(view),
+                None => groups.push(NotificationGroup {
+                    key,
+                    notifications: vec![view],
+                }),
+            }
+        }
+        groups
+    }
+}
+
+/// Page of the notifications of an account
+#[derive(Debug, Clone)]
+pub struct NotificationsRequest {
+    pub(crate) account_id: AccountId,
+    /// only these kinds, every kind when empty
+    pub(crate) kinds: Vec<NotificationKind>,
+    pub(crate) exclude_kinds: Vec<NotificationKind>,
+    /// only notifications caused by this actor
+    pub(crate) actor_id: Option<ActorId>,
+    pub(crate) page: TimelinePage<Notification>,
+}
+
+/// Timeline whose reading position is saved across devices
+///
+/// See https://docs.joinmastodon.org/methods/markers/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum MarkerTimeline {
+    Home,
+    Notifications,
+}
+
+/// Last item of a timeline the account has read
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub(crate) timeline: MarkerTimeline,
+    pub(crate) last_read_id: uuid::Uuid,
+    /// incremented on every update, for clients to detect conflicts
+    pub(crate) version: i64,
+    pub(crate) updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateNotificationError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindNotificationError {
+    #[error("notification not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteNotificationError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MarkerError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,258 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    ap,
+    domain::{HttpUrl, Id, account::model::AccountId},
+};
+
+use super::{
+    ActorId,
+    actor::FindActorError,
+    note::{FindNoteError, NoteId},
+};
+
+pub type PollVoteId = Id<PollVote>;
+
+/// Most options a local poll can have, like Mastodon's
+pub const MAX_POLL_OPTIONS: usize = 4;
+
+/// Longest option of a local poll, in characters
+pub const MAX_POLL_OPTION_CHARS: usize = 50;
+
+/// Shortest and longest time a local poll can be open for, in seconds
+pub const POLL_DURATION_SECS: std::ops::RangeInclusive<i64> = 300..=2_629_746;
+
+/// Question a note asks, with the options to vote for
+#[derive(Debug, Clone)]
+pub struct Poll {
+    pub(crate) options: Vec<PollOption>,
+    /// several options can be chosen
+    pub(crate) multiple: bool,
+    pub(crate) expires_at: Option<DateTime<Utc>>,
+    /// when the poll was closed with its final counts
+    pub(crate) closed_at: Option<DateTime<Utc>>,
+    /// how many actors voted
+    pub(crate) voters_count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PollOption {
+    pub(crate) title: String,
+    pub(crate) votes_count: u64,
+}
+
+impl Poll {
+    /// Whether votes are no longer accepted
+    pub fn is_expired(&self) -> bool {
+        self.closed_at.is_some() || self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Check a vote for `choices` can be cast and return them sorted without duplicates
+    pub fn choose(&self, mut choices: Vec<u32>) -> Result<Vec<u32>, VoteError> {
+        if self.is_expired() {
+            return Err(VoteError::Expired);
+        }
+        choices.sort_unstable();
+        choices.dedup();
+        if choices.is_empty() {
+            return Err(VoteError::InvalidChoice("no option chosen".to_string()));
+        }
+        if !self.multiple && choices.len() > 1 {
+            return Err(VoteError::InvalidChoice(
+                "only one option can be chosen".to_string(),
+            ));
+        }
+        if choices
+            .iter()
+            .any(|&choice| choice as usize >= self.options.len())
+        {
+            return Err(VoteError::InvalidChoice("no such option".to_string()));
+        }
+        Ok(choices)
+    }
+
+    /// Poll of a remote `Question`, with the counts its server sent
+    ///
+    /// Return `None` if the object has no options.
+    pub fn from_remote(question: &ap::Note) -> Option<Self> {
+        let (options, multiple) = match (&question.one_of[..], &question.any_of[..]) {
+            ([], []) => return None,
+            ([], any_of) => (any_of, true),
+            (one_of, _) => (one_of, false),
+        };
+        let options = options
+            .iter()
+            .map(|option| PollOption {
+                title: option.name.clone(),
+                votes_count: option.replies.total_items,
+            })
+            .collect::<Vec<_>>();
+        // single choice polls have as many voters as votes
+        let voters_count = question.voters_count.unwrap_or_else(|| {
+            if multiple {
+                0
+            } else {
+                options.iter().map(|option| option.votes_count).sum()
+            }
+        });
+        Some(Self {
+            options,
+            multiple,
+            expires_at: question.end_time,
+            closed_at: question.closed,
+            voters_count,
+        })
+    }
+
+    /// Options as `oneOf` or `anyOf` of a `Question`
+    pub fn ap_options(&self) -> Vec<ap::QuestionOption> {
+        self.options
+            .iter()
+            .map(|option| {
+                ap::QuestionOption::builder()
+                    .name(option.title.clone())
+                    .replies(ap::Replies::new(option.votes_count))
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// Poll of a note to be posted
+#[derive(Debug, Clone)]
+pub struct CreatePollRequest {
+    pub(crate) options: Vec<String>,
+    /// seconds the poll is open for
+    pub(crate) expires_in: i64,
+    pub(crate) multiple: bool,
+}
+
+/// `actor` chose an option of the poll of a note
+#[derive(Debug, Clone)]
+pub struct PollVote {
+    pub id: PollVoteId,
+    pub note_id: NoteId,
+    pub actor_id: ActorId,
+    /// index of the option
+    pub choice: u32,
+    /// `id` of the `Note` naming the option
+    pub vote_url: HttpUrl,
+}
+
+#[derive(Debug, Clone)]
+pub struct VoteRequest {
+    /// who votes
+    pub(crate) account_id: AccountId,
+    pub(crate) note_id: NoteId,
+    /// indices of the chosen options
+    pub(crate) choices: Vec<u32>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VoteError {
+    #[error("poll not found")]
+    NotFound,
+    #[error("the poll has already ended")]
+    Expired,
+    #[error("already voted")]
+    AlreadyVoted,
+    #[error("cannot vote in your own poll")]
+    OwnPoll,
+    #[error("{0}")]
+    InvalidChoice(String),
+    #[error("actor not found")]
+    ActorNotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindNoteError> for VoteError {
+    fn from(e: FindNoteError) -> Self {
+        match e {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => VoteError::NotFound,
+            FindNoteError::Unknown(e) => VoteError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindActorError> for VoteError {
+    fn from(e: FindActorError) -> Self {
+        match e {
+            FindActorError::NotFound => VoteError::ActorNotFound,
+            FindActorError::Unknown(e) => VoteError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateVoteError {
+    #[error("the actor already voted")]
+    AlreadyVoted,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindVoteError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClosePollError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn poll(multiple: bool, expires_in: i64, closed: bool) -> Poll {
+        let now = Utc::now();
+        Poll {
+            options: ["a", "b", "c"]
+                .into_iter()
+                .map(|title| PollOption {
+                    title: title.to_string(),
+                    votes_count: 0,
+                })
+                .collect(),
+            multiple,
+            expires_at: Some(now + TimeDelta::seconds(expires_in)),
+            closed_at: closed.then_some(now),
+            voters_count: 0,
+        }
+    }
+
+    #[rstest]
+    #[case::single(false, vec![1], vec![1])]
+    #[case::single_repeated(false, vec![1, 1], vec![1])]
+    #[case::multiple(true, vec![2, 0], vec![0, 2])]
+    #[case::multiple_repeated(true, vec![2, 0, 2], vec![0, 2])]
+    fn test_choose(#[case] multiple: bool, #[case] choices: Vec<u32>, #[case] expected: Vec<u32>) {
+        assert_eq!(poll(multiple, 60, false).choose(choices).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::several_in_single(poll(false, 60, false), vec![0, 1])]
+    #[case::none(poll(true, 60, false), vec![])]
+    #[case::no_such_option(poll(true, 60, false), vec![0, 3])]
+    fn test_choose_invalid(#[case] poll: Poll, #[case] choices: Vec<u32>) {
+        assert!(matches!(
+            poll.choose(choices),
+            Err(VoteError::InvalidChoice(_))
+        ));
+    }
+
+    #[rstest]
+    #[case::past_end(poll(false, -1, false))]
+    #[case::closed(poll(false, 60, true))]
+    fn test_choose_expired(#[case] poll: Poll) {
+        assert!(matches!(poll.choose(vec![0]), Err(VoteError::Expired)));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::domain::{
+    HttpUrl, Id,
+    account::model::AccountId,
+    media::model::{CustomEmoji, FindEmojiError},
+};
+
+use super::{
+    ActorId,
+    actor::{ActorView, FindActorError},
+    note::{FindNoteError, NoteId, NoteView},
+};
+
+pub type FavouriteId = Id<Favourite>;
+pub type ReblogId = Id<Reblog>;
+pub type EmojiReactionId = Id<EmojiReaction>;
+
+/// `actor` liked the note
+#[derive(Debug, Clone)]
+pub struct Favourite {
+    pub id: FavouriteId,
+    pub actor_id: ActorId,
+    pub note_id: NoteId,
+    /// `id` of the `Like` activity
+    pub like_url: HttpUrl,
+}
+
+/// `actor` reacted to the note with an emoji
+#[derive(Debug, Clone)]
+pub struct EmojiReaction {
+    pub id: EmojiReactionId,
+    pub actor_id: ActorId,
+    pub note_id: NoteId,
+    /// `id` of the `Like` or `EmojiReact` activity
+    pub reaction_url: HttpUrl,
+    /// unicode emoji, or `:shortcode:` of `emoji`
+    pub content: String,
+    pub emoji: Option<CustomEmoji>,
+}
+
+/// Reactions to a note with the same emoji
+#[derive(Debug, Clone)]
+pub struct EmojiReactionCount {
+    /// unicode emoji, or `:shortcode:` of `emoji`
+    pub(crate) content: String,
+    pub(crate) emoji: Option<CustomEmoji>,
+    pub(crate) count: u64,
+    /// the viewer is one of those who reacted
+    pub(crate) me: bool,
+}
+
+/// `actor` shared the note with its followers
+#[derive(Debug, Clone)]
+pub struct Reblog {
+    pub id: ReblogId,
+    pub actor_id: ActorId,
+    pub note_id: NoteId,
+    /// `id` of the `Announce` activity
+    pub announce_url: HttpUrl,
+}
+
+/// Reblog as seen by a viewer
+#[derive(Debug, Clone)]
+pub struct ReblogView {
+    pub(crate) id: ReblogId,
+    /// `id` of the `Announce` activity
+    pub(crate) announce_url: HttpUrl,
+    /// who reblogged the note
+    pub(crate) actor: ActorView,
+    pub(crate) note: NoteView,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReactRequest {
+    /// who reacts
+    pub(crate) account_id: AccountId,
+    pub(crate) note_id: NoteId,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmojiReactRequest {
+    /// who reacts
+    pub(crate) account_id: AccountId,
+    pub(crate) note_id: NoteId,
+    /// unicode emoji, or shortcode of a custom emoji with `@host` for remote ones
+    pub(crate) emoji: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReactError {
+    #[error("note not found")]
+    NoteNotFound,
+    #[error("emoji not found")]
+    EmojiNotFound,
+    #[error("actor not found")]
+    ActorNotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindNoteError> for ReactError {
+    fn from(e: FindNoteError) -> Self {
+        match e {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => ReactError::NoteNotFound,
+            FindNoteError::Unknown(e) => ReactError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindActorError> for ReactError {
+    fn from(e: FindActorError) -> Self {
+        match e {
+            FindActorError::NotFound => ReactError::ActorNotFound,
+            FindActorError::Unknown(e) => ReactError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindEmojiError> for ReactError {
+    fn from(e: FindEmojiError) -> Self {
+        match e {
+            FindEmojiError::NotFound => ReactError::EmojiNotFound,
+            FindEmojiError::Unknown(e) => ReactError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateReactionError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindReactionError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteReactionError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,55 @@
+use crate::domain::account::model::AccountId;
+
+use super::{ActorId, ActorView, note::NoteView};
+
+/// What a search looks for
+///
+/// See https://docs.joinmastodon.org/methods/search/#v2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum SearchKind {
+    Accounts,
+    Statuses,
+    Hashtags,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchRequest {
+    /// who searches, needed to resolve remote objects
+    pub(crate) viewer: Option<AccountId>,
+    /// words, `#hashtag`, `@user@host` handle or URL
+    pub(crate) query: String,
+    /// every kind when `None`
+    pub(crate) kind: Option<SearchKind>,
+    /// fetch the actor or note the query points to when it is not known yet
+    pub(crate) resolve: bool,
+    /// only statuses of this actor
+    pub(crate) actor_id: Option<ActorId>,
+    pub(crate) limit: u32,
+    pub(crate) offset: u32,
+}
+
+impl SearchRequest {
+    pub fn includes(&self, kind: SearchKind) -> bool {
+        self.kind.is_none_or(|k| k == kind)
+    }
+}
+
+/// Hashtag used in notes, lowercased without `#`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hashtag {
+    pub(crate) name: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    pub(crate) actors: Vec<ActorView>,
+    pub(crate) notes: Vec<NoteView>,
+    pub(crate) hashtags: Vec<Hashtag>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
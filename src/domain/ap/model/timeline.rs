@@ -0,0 +1,82 @@
+use crate::domain::{Id, account::model::AccountId};
+
+use super::{note::NoteView, reaction::ReblogView};
+
+pub type TimelineItemId = Id<TimelineItem>;
+
+/// Entries kept in each materialized home feed, newest first
+pub const HOME_FEED_SIZE: u32 = 400;
+
+/// Entry of a timeline
+#[derive(Debug, Clone)]
+pub enum TimelineItem {
+    Note(Box<NoteView>),
+    Reblog(Box<ReblogView>),
+}
+
+impl TimelineItem {
+    /// Cursor of the item. Notes and reblogs are ordered together since both have UUIDv7 ids
+    pub fn id(&self) -> TimelineItemId {
+        match self {
+            TimelineItem::Note(note) => TimelineItemId::from_uuid(*note.id),
+            TimelineItem::Reblog(reblog) => TimelineItemId::from_uuid(*reblog.id),
+        }
+    }
+}
+
+/// Page of a timeline, or of anything else with UUIDv7 ids like notifications, by ids of its items
+///
+/// See https://docs.joinmastodon.org/api/guidelines/#pagination
+#[derive(Debug, Clone)]
+pub struct TimelinePage<T = TimelineItem> {
+    /// only items older than this
+    pub(crate) max_id: Option<Id<T>>,
+    /// only items newer than this, newest first
+    pub(crate) since_id: Option<Id<T>>,
+    /// only items newer than this, the ones right after it
+    pub(crate) min_id: Option<Id<T>>,
+    pub(crate) limit: u32,
+}
+
+impl<T> TimelinePage<T> {
+    /// Exclusive lower bound of ids. `min_id` wins over `since_id`
+    pub fn lower_bound(&self) -> Option<&Id<T>> {
+        self.min_id.as_ref().or(self.since_id.as_ref())
+    }
+
+    /// Whether items are taken upwards from the lower bound rather than downwards from the newest
+    pub fn ascending(&self) -> bool {
+        self.min_id.is_some()
+    }
+}
+
+/// Own notes and those of followed actors, reblogs included
+#[derive(Debug, Clone)]
+pub struct HomeTimelineRequest {
+    pub(crate) account_id: AccountId,
+    pub(crate) page: TimelinePage,
+}
+
+/// Every known note, without reblogs
+#[derive(Debug, Clone)]
+pub struct PublicTimelineRequest {
+    /// who is reading, to mark favourited and reblogged notes
+    pub(crate) viewer: Option<AccountId>,
+    /// only notes of local actors
+    pub(crate) local: bool,
+    /// only notes of remote actors
+    pub(crate) remote: bool,
+    pub(crate) page: TimelinePage,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindTimelineError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateHomeFeedError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -1,52 +1,1530 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
 use crate::{
-    ap::ActorType,
+    ap::{self, Activity, ActivityType, ActorType, constants, webfinger::AcctUri},
     domain::{
-        ap::model::{ActorId, ActorRow},
+        HttpUrl, Id,
+        account::model::AccountId,
+        ap::model::{ActorId, ActorRow, ActorView},
         hosturl::HostUrlService,
+        media::model::{Attachment, CustomEmoji, ProfileImage},
+        stream::{adapter::EventBus, model::StreamEvent},
+        text::{emoji, hashtag, mention, rel_me, render, sanitize},
     },
 };
 
 use super::{
-    adapter::{ActorRepository, ApService, NoteRepository},
+    adapter::{
+        ActorRepository, ApClient, ApService, FollowRepository, NoteRepository,
+        NotificationRepository,
+    },
     model::{
         CreateLocalActorError, CreateLocalActorRequest, CreateRemoteActorError,
-        CreateRemoteActorRequest, LocalActor, RemoteActor,
-        actor::FindRemoteActorRequest,
+        CreateRemoteActorRequest, LocalActor, RemoteActor, ResolveActorError,
+        actor::{
+            FindActorError, FindRemoteActorRequest, MAX_DISPLAY_NAME_LENGTH,
+            MAX_PROFILE_FIELD_LENGTH, MAX_PROFILE_FIELDS, MAX_SUMMARY_LENGTH,
+            MAX_VERIFIED_PAGE_SIZE, ProfileField, UpdateActorError, UpdateProfileError,
+            UpdateProfileRequest, UpdatedProfile,
+        },
+        federation::{DeliverRequest, FetchError, ReceiveActivityError},
+        follow::{Follow, FollowActorError, FollowActorRequest, FollowId, Relationship},
+        key::{ActorKeyPair, CreateKeyError, FindKeyError, PublicKey, SigningKey},
         note::{
             CreateLocalNoteError, CreateLocalNoteRequest, CreateRemoteNoteError,
-            CreateRemoteNoteRequest, LocalNote, NoteId, RemoteNote,
+            CreateRemoteNoteRequest, DeleteLocalNoteError, DeleteLocalNoteRequest, FindNoteError,
+            LocalNote, MAX_ATTACHMENTS, MAX_FETCHED_ANCESTORS, NoteId, NoteRevision, NoteSource,
+            NoteSourceId, NoteType, NoteView, RemoteNote, ResolveNoteError, ResolveNoteRequest,
+            UpdateLocalNoteError, UpdateLocalNoteRequest,
+        },
+        notification::{
+            DeleteNotificationError, FindNotificationError, Marker, MarkerError, MarkerTimeline,
+            Notification, NotificationGroupKey, NotificationId, NotificationKind, NotificationView,
+            NotificationsRequest,
+        },
+        poll::{
+            ClosePollError, CreatePollRequest, CreateVoteError, MAX_POLL_OPTION_CHARS,
+            MAX_POLL_OPTIONS, POLL_DURATION_SECS, Poll, PollOption, PollVote, PollVoteId,
+            VoteError, VoteRequest,
+        },
+        reaction::{
+            EmojiReactRequest, EmojiReaction, EmojiReactionId, Favourite, FavouriteId, ReactError,
+            ReactRequest, Reblog, ReblogId, ReblogView,
+        },
+        search::{SearchError, SearchKind, SearchRequest, SearchResults},
+        timeline::{
+            FindTimelineError, HOME_FEED_SIZE, HomeTimelineRequest, PublicTimelineRequest,
+            TimelineItem, TimelineItemId,
         },
     },
 };
 
 #[derive(Debug, Clone)]
-pub struct Service<AR, NR, H> {
+pub struct Service<AR, NR, FR, NFR, H, C, B> {
     actor_repo: AR,
     note_repo: NR,
+    follow_repo: FR,
+    notification_repo: NFR,
     host_url: H,
+    client: C,
+    bus: B,
 }
 
-impl<AR, NR, H> Service<AR, NR, H>
+impl<AR, NR, FR, NFR, H, C, B> Service<AR, NR, FR, NFR, H, C, B>
 where
     AR: ActorRepository,
     NR: NoteRepository,
+    FR: FollowRepository,
+    NFR: NotificationRepository,
     H: HostUrlService,
+    C: ApClient,
+    B: EventBus,
 {
-    pub fn new(actor_repo: AR, note_repo: NR, host_url: H) -> Self {
+    pub fn new(
+        actor_repo: AR,
+        note_repo: NR,
+        follow_repo: FR,
+        notification_repo: NFR,
+        host_url: H,
+        client: C,
+        bus: B,
+    ) -> Self {
         Self {
             actor_repo,
             note_repo,
+            follow_repo,
+            notification_repo,
             host_url,
+            client,
+            bus,
+        }
+    }
+
+    /// Return key pair of the account, generating one if it has none yet
+    async fn local_key_pair(&self, account_id: &AccountId) -> Result<ActorKeyPair, FindKeyError> {
+        match self.actor_repo.find_account_key(account_id).await {
+            Err(FindKeyError::NotFound) => {}
+            res => return res,
+        }
+
+        tracing::info!(account_id = %account_id, "Generating key pair");
+        let key = generate_key_pair().await?;
+        match self.actor_repo.create_account_key(account_id, &key).await {
+            Ok(()) => Ok(key),
+            // generated concurrently by someone else
+            Err(CreateKeyError::AlreadyExists) => {
+                self.actor_repo.find_account_key(account_id).await
+            }
+            Err(CreateKeyError::Unknown(e)) => Err(FindKeyError::Unknown(e)),
+        }
+    }
+
+    async fn signing_key(&self, actor: &LocalActor) -> Result<SigningKey, FindKeyError> {
+        let key = self.local_key_pair(&actor.account_id).await?;
+        Ok(SigningKey {
+            key_id: self.host_url.key_id(&actor.name),
+            private_key_pem: key.private_key_pem,
+        })
+    }
+
+    /// Sign `activity` as `actor` and deliver it to `inboxes` in the background
+    async fn deliver<T: serde::Serialize>(
+        &self,
+        actor: &LocalActor,
+        inboxes: Vec<HttpUrl>,
+        activity: T,
+    ) -> Result<(), anyhow::Error> {
+        if inboxes.is_empty() {
+            return Ok(());
+        }
+        let key = self.signing_key(actor).await?;
+        let activity = serde_json::to_value(ap::Context::new(activity))?;
+        self.client.deliver(DeliverRequest {
+            key,
+            inboxes,
+            activity,
+        });
+        Ok(())
+    }
+
+    /// Return the note's recipients plus current followers, recording the new ones
+    async fn note_audience(
+        &self,
+        note_id: &NoteId,
+        actor_id: &ActorId,
+    ) -> Result<Vec<HttpUrl>, anyhow::Error> {
+        let mut inboxes = self.note_repo.find_note_recipients(note_id).await?;
+        let followers = self.follow_repo.find_follower_inboxes(actor_id).await?;
+        let new_inboxes = followers
+            .into_iter()
+            .filter(|inbox| !inboxes.contains(inbox))
+            .collect::<Vec<_>>();
+
+        self.note_repo
+            .add_note_recipients(note_id, &new_inboxes)
+            .await?;
+        inboxes.extend(new_inboxes);
+        Ok(inboxes)
+    }
+
+    async fn find_local_actor_by_url(&self, url: &HttpUrl) -> Result<LocalActor, FindActorError> {
+        let row = self.actor_repo.find_actor_by_url(url).await?;
+        LocalActor::try_from(row).map_err(|_| FindActorError::NotFound)
+    }
+
+    async fn find_local_actor(&self, account_id: &AccountId) -> Result<LocalActor, FindActorError> {
+        let row = self.actor_repo.find_local_actor(account_id).await?;
+        LocalActor::try_from(row).map_err(|e| FindActorError::Unknown(e.into()))
+    }
+
+    async fn relationship(
+        &self,
+        actor_id: &ActorId,
+        other_id: &ActorId,
+    ) -> Result<Relationship, FollowActorError> {
+        let following = self.follow_repo.find_follow(actor_id, other_id).await?;
+        let followed_by = self.follow_repo.find_follow(other_id, actor_id).await?;
+        Ok(Relationship {
+            actor_id: other_id.clone(),
+            following: following.as_ref().is_some_and(|follow| follow.accepted),
+            requested: following.is_some_and(|follow| !follow.accepted),
+            followed_by: followed_by.is_some_and(|follow| follow.accepted),
+        })
+    }
+
+    /// Put the note in home feeds. Feeds missing it are fixed by rebuilding them
+    async fn fan_out_note(&self, note_id: &NoteId, author_id: &ActorId) {
+        if let Err(e) = self
+            .note_repo
+            .fan_out_note(note_id, author_id, HOME_FEED_SIZE)
+            .await
+        {
+            tracing::error!(error = %e, %note_id, "Failed to fan out note");
+        }
+    }
+
+    async fn tag_note(&self, note_id: &NoteId, hashtags: &[String]) {
+        if let Err(e) = self.note_repo.set_note_hashtags(note_id, hashtags).await {
+            tracing::error!(error = %e, %note_id, "Failed to index hashtags");
+        }
+    }
+
+    /// Custom emoji of this instance written as `:shortcode:` in `texts`. They only decorate
+    /// the text, so failing to find them does not fail what is being written
+    async fn local_emojis(&self, texts: &[&str]) -> Vec<CustomEmoji> {
+        let mut shortcodes = Vec::new();
+        for shortcode in texts.iter().flat_map(|text| emoji::extract(text)) {
+            if !shortcodes.contains(&shortcode) {
+                shortcodes.push(shortcode);
+            }
+        }
+        if shortcodes.is_empty() {
+            return Vec::new();
+        }
+        self.note_repo
+            .find_local_emojis(&shortcodes)
+            .await
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to find custom emojis"))
+            .unwrap_or_default()
+    }
+
+    /// Content and custom emoji of a reaction a local user named
+    async fn reaction_emoji(
+        &self,
+        name: &str,
+    ) -> Result<(String, Option<CustomEmoji>), ReactError> {
+        match emoji::Reaction::parse(name) {
+            Some(emoji::Reaction::Unicode(emoji)) => Ok((emoji.to_string(), None)),
+            Some(emoji::Reaction::Custom { shortcode, host }) => {
+                let emoji = self
+                    .note_repo
+                    .find_emoji_by_shortcode(shortcode, host)
+                    .await?;
+                Ok((format!(":{}:", emoji.shortcode), Some(emoji)))
+            }
+            None => Err(ReactError::EmojiNotFound),
+        }
+    }
+
+    /// Send the final counts of a closed poll to those who saw it and the remote voters
+    async fn deliver_poll_results(&self, note_id: &NoteId) -> Result<(), anyhow::Error> {
+        let note = self.note_repo.find_local_note(note_id).await?;
+        let actor =
+            LocalActor::try_from(self.actor_repo.find_local_actor(&note.account_id).await?)?;
+        let mut inboxes = self.note_audience(&note.id, &actor.id).await?;
+        for inbox in self.note_repo.find_poll_voter_inboxes(&note.id).await? {
+            if !inboxes.contains(&inbox) {
+                inboxes.push(inbox);
+            }
+        }
+        let update = Activity::builder()
+            .id(format!("{}#updates/closed", note.note_url).parse()?)
+            .kind(ActivityType::Update)
+            .actor(actor.actor_url.clone())
+            .object(ap::Note::from(&note))
+            .to(vec![constants::PUBLIC.to_string()])
+            .build();
+        self.deliver(&actor, inboxes, update).await
+    }
+
+    async fn fan_out_reblog(&self, reblog: &Reblog) {
+        if let Err(e) = self.note_repo.fan_out_reblog(reblog, HOME_FEED_SIZE).await {
+            tracing::error!(error = %e, reblog_id = %reblog.id, "Failed to fan out reblog");
+        }
+    }
+
+    /// Bring recent posts of a newly followed actor into the follower's home feed
+    async fn merge_home_feed(&self, follower_id: &ActorId, followee_id: &ActorId) {
+        if let Err(e) = self
+            .note_repo
+            .merge_home_feed(follower_id, followee_id, HOME_FEED_SIZE)
+            .await
+        {
+            tracing::error!(error = %e, %follower_id, %followee_id, "Failed to merge home feed");
+        }
+    }
+
+    /// Tell streaming clients. Those missing it catch up by reloading timelines
+    async fn publish(&self, event: StreamEvent) {
+        if let Err(e) = self.bus.publish(event).await {
+            tracing::error!(error = %e, "Failed to publish stream event");
+        }
+    }
+
+    async fn publish_note(&self, note_id: &NoteId, local: bool) {
+        self.publish(StreamEvent::Update {
+            item_id: TimelineItemId::from_uuid(**note_id),
+            note_id: note_id.clone(),
+            reblog: false,
+            local,
+        })
+        .await;
+    }
+
+    async fn publish_reblog(&self, reblog: &Reblog, local: bool) {
+        self.publish(StreamEvent::Update {
+            item_id: TimelineItemId::from_uuid(*reblog.id),
+            note_id: reblog.note_id.clone(),
+            reblog: true,
+            local,
+        })
+        .await;
+    }
+
+    async fn publish_delete(&self, item_id: uuid::Uuid) {
+        self.publish(StreamEvent::Delete {
+            item_id: TimelineItemId::from_uuid(item_id),
+        })
+        .await;
+    }
+
+    /// Tell the local account what `actor_id` did, now and when it next looks
+    async fn notify(
+        &self,
+        account_id: &AccountId,
+        kind: NotificationKind,
+        actor_id: &ActorId,
+        note_id: Option<&NoteId>,
+    ) {
+        let notification = Notification {
+            id: NotificationId::new(),
+            account_id: account_id.clone(),
+            kind,
+            actor_id: actor_id.clone(),
+            note_id: note_id.cloned(),
+        };
+        if let Err(e) = self
+            .notification_repo
+            .create_notification(&notification)
+            .await
+        {
+            tracing::error!(error = %e, %account_id, "Failed to create notification");
+            return;
+        }
+        self.publish(StreamEvent::Notification(notification)).await;
+    }
+
+    /// Notify local actors other than the author that a note is addressed to, or whose note it
+    /// replies to
+    async fn notify_mentions(
+        &self,
+        note_id: &NoteId,
+        author_id: &ActorId,
+        recipients: &[HttpUrl],
+        in_reply_to: Option<&HttpUrl>,
+    ) {
+        let mut account_ids = Vec::new();
+        let local_urls = recipients
+            .iter()
+            .filter(|url| url.host() == self.host_url.host());
+        for url in local_urls {
+            if let Ok(recipient) = self.find_local_actor_by_url(url).await
+                && recipient.id != *author_id
+            {
+                account_ids.push(recipient.account_id);
+            }
+        }
+        if let Some(in_reply_to) = in_reply_to
+            && in_reply_to.host() == self.host_url.host()
+            && let Ok((_, parent_author_id)) = self.note_repo.find_note_by_url(in_reply_to).await
+            && parent_author_id != *author_id
+            && let Ok(parent_author) = self.actor_repo.find_actor_view(&parent_author_id).await
+            && let Some(account_id) = parent_author.actor.account_id
+        {
+            account_ids.push(account_id);
+        }
+
+        account_ids.sort();
+        account_ids.dedup();
+        for account_id in &account_ids {
+            self.notify(
+                account_id,
+                NotificationKind::Mention,
+                author_id,
+                Some(note_id),
+            )
+            .await;
+        }
+    }
+
+    /// Return the notification as its recipient sees it, `None` if its note is gone
+    async fn notification_view(
+        &self,
+        notification: Notification,
+    ) -> Result<Option<NotificationView>, FindNotificationError> {
+        let actor = self
+            .actor_repo
+            .find_actor_view(&notification.actor_id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let note = match &notification.note_id {
+            Some(note_id) => match self
+                .note_repo
+                .find_note_view(note_id, Some(&notification.account_id))
+                .await
+            {
+                Ok(note) => Some(note),
+                Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => return Ok(None),
+                Err(FindNoteError::Unknown(e)) => return Err(e.into()),
+            },
+            None => None,
+        };
+        Ok(Some(NotificationView {
+            id: notification.id,
+            kind: notification.kind,
+            actor,
+            note,
+        }))
+    }
+
+    /// Notify the author of the note if it is a local actor other than `actor_id`
+    async fn notify_author(
+        &self,
+        author_id: &ActorId,
+        kind: NotificationKind,
+        actor_id: &ActorId,
+        note_id: &NoteId,
+    ) {
+        if author_id == actor_id {
+            return;
+        }
+        match self.actor_repo.find_actor_view(author_id).await {
+            Ok(author) => {
+                if let Some(account_id) = &author.actor.account_id {
+                    self.notify(account_id, kind, actor_id, Some(note_id)).await;
+                }
+            }
+            Err(e) => tracing::error!(error = %e, %author_id, "Failed to find note author"),
+        }
+    }
+
+    /// Inboxes that learn about reblogs of `note` by `actor`
+    async fn reblog_audience(
+        &self,
+        actor: &LocalActor,
+        note: &NoteView,
+    ) -> Result<Vec<HttpUrl>, anyhow::Error> {
+        let mut inboxes = self.follow_repo.find_follower_inboxes(&actor.id).await?;
+        let author = &note.author.actor;
+        if author.account_id.is_none() {
+            let inbox = author
+                .shared_inbox_url
+                .as_ref()
+                .unwrap_or(&author.inbox_url);
+            if !inboxes.contains(inbox) {
+                inboxes.push(inbox.clone());
+            }
+        }
+        Ok(inboxes)
+    }
+
+    /// Fetch a remote actor, signed with `key` when given, and store it
+    async fn fetch_remote_actor(
+        &self,
+        url: &HttpUrl,
+        key: Option<&SigningKey>,
+    ) -> Result<RemoteActor, ResolveActorError> {
+        if url.host() == self.host_url.host() {
+            return Err(ResolveActorError::NotFound);
+        }
+
+        let json = self.client.fetch(url, key).await?;
+        self.store_fetched_actor(url, json).await
+    }
+
+    /// Store the actor of a document fetched from `url`
+    async fn store_fetched_actor(
+        &self,
+        url: &HttpUrl,
+        json: serde_json::Value,
+    ) -> Result<RemoteActor, ResolveActorError> {
+        let actor = serde_json::from_value::<ap::Actor>(json)
+            .map_err(|e| ResolveActorError::InvalidActor(e.to_string()))?;
+
+        // the document must come from where the actor lives
+        if actor.id.host() != url.host() {
+            return Err(ResolveActorError::InvalidActor(format!(
+                "{} is served from {}",
+                actor.id,
+                url.host()
+            )));
+        }
+
+        let req = CreateRemoteActorRequest {
+            actor_type: actor.kind,
+            name: actor.preferred_username,
+            actor_url: actor.id,
+            inbox_url: actor.inbox,
+            outbox_url: actor.outbox,
+            shared_inbox_url: actor.endpoints.and_then(|e| e.shared_inbox),
+            summary: actor.summary,
+            public_key: actor.public_key.map(|key| PublicKey {
+                id: key.id,
+                pem: key.public_key_pem,
+            }),
+            published: actor.published,
+            display_name: actor.name,
+            avatar_url: actor.icon.map(|icon| icon.url),
+            header_url: actor.image.map(|image| image.url),
+            fields: actor
+                .attachment
+                .into_iter()
+                .map(|field| ProfileField {
+                    name: field.name,
+                    value: field.value,
+                    source: None,
+                    verified_at: None,
+                })
+                .collect(),
+            emojis: remote_emojis(&actor.tag, url.host()),
+        };
+        let actor = self
+            .create_remote_actor(req)
+            .await
+            .map_err(|e| ResolveActorError::Unknown(e.into()))?;
+        Ok(actor)
+    }
+
+    /// Return the remote actor at `url`, fetching it if it is unknown
+    async fn find_or_fetch_actor(
+        &self,
+        url: &HttpUrl,
+        key: Option<&SigningKey>,
+    ) -> Result<RemoteActor, ResolveActorError> {
+        match self.actor_repo.find_actor_by_url(url).await {
+            Ok(row) if row.account_id.is_none() => Ok(row.into()),
+            Ok(_) => Err(ResolveActorError::NotFound),
+            Err(FindActorError::NotFound) => self.fetch_remote_actor(url, key).await,
+            Err(FindActorError::Unknown(e)) => Err(e.into()),
+        }
+    }
+
+    /// Actor or note a handle or URL query names
+    ///
+    /// Unknown ones are fetched, signed with `fetch_key`, only when it is given.
+    async fn resolve_query(
+        &self,
+        query: &str,
+        fetch_key: Option<&SigningKey>,
+    ) -> Result<Option<Resolved>, anyhow::Error> {
+        if let Some(acct) = parse_handle(query) {
+            let actor_id = self.resolve_handle(&acct, fetch_key).await?;
+            return Ok(actor_id.map(Resolved::Actor));
+        }
+        match query.parse::<HttpUrl>() {
+            Ok(url) => self.resolve_url(&url, fetch_key).await,
+            Err(_) => Ok(None),
         }
     }
+
+    async fn resolve_handle(
+        &self,
+        acct: &AcctUri,
+        fetch_key: Option<&SigningKey>,
+    ) -> Result<Option<ActorId>, anyhow::Error> {
+        let req = FindRemoteActorRequest {
+            name: acct.user.clone(),
+            host: acct.host.clone(),
+        };
+        match self.actor_repo.find_remote_actor(&req).await {
+            Ok(actor) => return Ok(Some(actor.id)),
+            Err(FindActorError::NotFound) => {}
+            Err(FindActorError::Unknown(e)) => return Err(e),
+        }
+        let Some(key) = fetch_key else {
+            return Ok(None);
+        };
+        if acct.host == self.host_url.host() {
+            return Ok(None);
+        }
+
+        let webfinger = match self.client.webfinger(acct).await {
+            Ok(webfinger) => webfinger,
+            Err(FetchError::NotFound(_) | FetchError::Gone(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(actor_url) = webfinger.actor_url() else {
+            return Ok(None);
+        };
+        let actor = self.find_or_fetch_actor(actor_url, Some(key)).await?;
+        Ok(Some(actor.id().clone()))
+    }
+
+    async fn resolve_url(
+        &self,
+        url: &HttpUrl,
+        fetch_key: Option<&SigningKey>,
+    ) -> Result<Option<Resolved>, anyhow::Error> {
+        match self.note_repo.find_note_by_url(url).await {
+            Ok((note_id, _)) => return Ok(Some(Resolved::Note(note_id))),
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => {}
+            Err(FindNoteError::Unknown(e)) => return Err(e),
+        }
+        match self.actor_repo.find_actor_by_url(url).await {
+            Ok(actor) => return Ok(Some(Resolved::Actor(actor.id))),
+            Err(FindActorError::NotFound) => {}
+            Err(FindActorError::Unknown(e)) => return Err(e),
+        }
+        let Some(key) = fetch_key else {
+            return Ok(None);
+        };
+        if url.host() == self.host_url.host() {
+            return Ok(None);
+        }
+
+        let json = match self.client.fetch(url, Some(key)).await {
+            Ok(json) => json,
+            Err(FetchError::NotFound(_) | FetchError::Gone(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if ap::activity::object_type(&json).is_none_or(|kind| kind.parse::<NoteType>().is_err()) {
+            return match self.store_fetched_actor(url, json).await {
+                Ok(actor) => Ok(Some(Resolved::Actor(actor.id().clone()))),
+                Err(ResolveActorError::InvalidActor(_)) => Ok(None),
+                Err(e) => Err(e.into()),
+            };
+        }
+
+        let note = serde_json::from_value::<ap::Note>(json)?;
+        match self
+            .import_remote_note(url, note, Some(key), MAX_FETCHED_ANCESTORS)
+            .await
+        {
+            Ok(note_id) => Ok(Some(Resolved::Note(note_id))),
+            Err(ResolveNoteError::NotFound | ResolveNoteError::InvalidNote(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the note at `url`, fetching it and up to `depth` of the notes it replies to when it
+    /// is unknown
+    async fn fetch_remote_note(
+        &self,
+        url: &HttpUrl,
+        key: Option<&SigningKey>,
+        depth: u32,
+    ) -> Result<NoteId, ResolveNoteError> {
+        match self.note_repo.find_note_by_url(url).await {
+            Ok((note_id, _)) => return Ok(note_id),
+            Err(FindNoteError::NotFound) => {}
+            Err(e) => return Err(e.into()),
+        }
+        if url.host() == self.host_url.host() {
+            return Err(ResolveNoteError::NotFound);
+        }
+
+        let json = self.client.fetch(url, key).await?;
+        if ap::activity::object_type(&json).is_none_or(|kind| kind.parse::<NoteType>().is_err()) {
+            return Err(ResolveNoteError::InvalidNote(format!(
+                "{url} is not a note"
+            )));
+        }
+        let note = serde_json::from_value::<ap::Note>(json)
+            .map_err(|e| ResolveNoteError::InvalidNote(e.to_string()))?;
+        self.import_remote_note(url, note, key, depth).await
+    }
+
+    /// Store a note fetched from `url` with its author, and up to `depth` of the notes it
+    /// replies to
+    async fn import_remote_note(
+        &self,
+        url: &HttpUrl,
+        note: ap::Note,
+        key: Option<&SigningKey>,
+        depth: u32,
+    ) -> Result<NoteId, ResolveNoteError> {
+        // the document must come from where the note and its author live
+        if note.id.host() != url.host() || note.attributed_to.host() != url.host() {
+            return Err(ResolveNoteError::InvalidNote(format!(
+                "{} is served from {}",
+                note.id,
+                url.host()
+            )));
+        }
+        if note.id != *url
+            && let Ok((note_id, _)) = self.note_repo.find_note_by_url(&note.id).await
+        {
+            return Ok(note_id);
+        }
+
+        let author = self.find_or_fetch_actor(&note.attributed_to, key).await?;
+        let in_reply_to_id = match &note.in_reply_to {
+            Some(parent_url) if depth > 0 => {
+                // boxed as the future recurses
+                match Box::pin(self.fetch_remote_note(parent_url, key, depth - 1)).await {
+                    Ok(parent_id) => Some(parent_id),
+                    Err(e) => {
+                        tracing::info!(error = %e, %parent_url, "Failed to fetch replied note");
+                        None
+                    }
+                }
+            }
+            Some(parent_url) => self.find_note_id_by_url(parent_url).await?,
+            None => None,
+        };
+
+        let req = CreateRemoteNoteRequest {
+            name: author.name.clone(),
+            host: author.host().to_string(),
+            content: note.content.clone(),
+            note_type: remote_note_type(&note),
+            title: remote_title(&note),
+            url: note.url.clone(),
+            summary: remote_summary(&note),
+            sensitive: note.sensitive.unwrap_or_default(),
+            note_url: note.id.clone(),
+            hashtags: remote_hashtags(&note),
+            in_reply_to_id,
+            published: note.published,
+            attachments: remote_attachments(&note),
+            emojis: remote_emojis(&note.tag, note.id.host()),
+            poll: Poll::from_remote(&note),
+        };
+        match self.create_remote_note(req).await {
+            Ok(remote_note) => Ok(remote_note.id),
+            Err(CreateRemoteNoteError::AlreadyExists) => {
+                let (note_id, _) = self.note_repo.find_note_by_url(&note.id).await?;
+                Ok(note_id)
+            }
+            Err(CreateRemoteNoteError::ActorNotFound) => Err(ResolveNoteError::InvalidNote(
+                "author not found".to_string(),
+            )),
+            Err(CreateRemoteNoteError::Unknown(e)) => Err(e.into()),
+        }
+    }
+
+    /// Id of the known note at `url`
+    async fn find_note_id_by_url(&self, url: &HttpUrl) -> Result<Option<NoteId>, anyhow::Error> {
+        match self.note_repo.find_note_by_url(url).await {
+            Ok((note_id, _)) => Ok(Some(note_id)),
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => Ok(None),
+            Err(FindNoteError::Unknown(e)) => Err(e),
+        }
+    }
+
+    async fn receive_follow(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        let object_id = activity
+            .object_id()
+            .ok_or_else(|| ReceiveActivityError::InvalidActivity("missing object".into()))?;
+        let followee = match self.find_local_actor_by_url(&object_id).await {
+            Ok(followee) => followee,
+            Err(FindActorError::NotFound) => {
+                return Err(ReceiveActivityError::InvalidActivity(format!(
+                    "{object_id} is not a local actor"
+                )));
+            }
+            Err(FindActorError::Unknown(e)) => return Err(e.into()),
+        };
+
+        let id = FollowId::new();
+        let follow = Follow {
+            id: id.clone(),
+            follower_id: actor.id().clone(),
+            followee_id: followee.id.clone(),
+            follow_url: activity.id.clone(),
+            accepted: true,
+        };
+        let follow = self
+            .follow_repo
+            .upsert_follow(follow)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        tracing::info!(follower = %actor.actor_url, followee = %followee.actor_url, "Followed");
+        // a repeated follow keeps the id of the first
+        if follow.id == id {
+            self.notify(
+                &followee.account_id,
+                NotificationKind::Follow,
+                actor.id(),
+                None,
+            )
+            .await;
+        }
+
+        let accept = Activity::builder()
+            .id(
+                format!("{}#accepts/follows/{}", followee.actor_url, follow.id)
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("{e}"))?,
+            )
+            .kind(ActivityType::Accept)
+            .actor(followee.actor_url.clone())
+            .object(serde_json::to_value(&activity).map_err(|e| anyhow::anyhow!(e))?)
+            .to(vec![actor.actor_url.to_string()])
+            .build();
+        self.deliver(&followee, vec![actor.inbox_url.clone()], accept)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn receive_undo(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        let object = &activity.object;
+        let result = match (ap::activity::object_type(object), object.as_str()) {
+            (Some("Follow"), _) => {
+                let Some(followee_url) = object.get("object").and_then(ap::activity::object_id)
+                else {
+                    return Err(ReceiveActivityError::InvalidActivity(
+                        "missing followee".into(),
+                    ));
+                };
+                let followee = match self.actor_repo.find_actor_by_url(&followee_url).await {
+                    Ok(followee) => followee,
+                    Err(FindActorError::NotFound) => return Ok(()),
+                    Err(FindActorError::Unknown(e)) => return Err(e.into()),
+                };
+                self.follow_repo
+                    .delete_follow(actor.id(), &followee.id)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+            }
+            // a `Like` may have been an emoji reaction
+            (Some("Like" | "EmojiReact"), _) => {
+                let Some(like_url) = activity.object_id() else {
+                    return Err(ReceiveActivityError::InvalidActivity(
+                        "invalid object".into(),
+                    ));
+                };
+                async {
+                    self.note_repo
+                        .delete_favourite_by_url(actor.id(), &like_url)
+                        .await?;
+                    self.note_repo
+                        .delete_emoji_reaction_by_url(actor.id(), &like_url)
+                        .await?;
+                    Ok::<(), anyhow::Error>(())
+                }
+                .await
+            }
+            (Some("Announce"), _) => {
+                let Some(announce_url) = activity.object_id() else {
+                    return Err(ReceiveActivityError::InvalidActivity(
+                        "invalid object".into(),
+                    ));
+                };
+                let reblog_id = self
+                    .note_repo
+                    .delete_reblog_by_url(actor.id(), &announce_url)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                if let Some(reblog_id) = reblog_id {
+                    self.publish_delete(*reblog_id).await;
+                }
+                Ok(())
+            }
+            // only the id of the undone activity, which may be any of them
+            (None, Some(_)) => {
+                let Some(object_url) = activity.object_id() else {
+                    return Err(ReceiveActivityError::InvalidActivity(
+                        "invalid object".into(),
+                    ));
+                };
+                async {
+                    self.follow_repo
+                        .delete_follow_by_url(actor.id(), &object_url)
+                        .await?;
+                    self.note_repo
+                        .delete_favourite_by_url(actor.id(), &object_url)
+                        .await?;
+                    self.note_repo
+                        .delete_emoji_reaction_by_url(actor.id(), &object_url)
+                        .await?;
+                    if let Some(reblog_id) = self
+                        .note_repo
+                        .delete_reblog_by_url(actor.id(), &object_url)
+                        .await?
+                    {
+                        self.publish_delete(*reblog_id).await;
+                    }
+                    Ok::<(), anyhow::Error>(())
+                }
+                .await
+            }
+            _ => {
+                tracing::debug!(id = %activity.id, "Ignore undo of unsupported activity");
+                return Ok(());
+            }
+        };
+        result?;
+        Ok(())
+    }
+
+    /// Handle `Accept` or `Reject` of a follow request we sent to `actor`
+    async fn receive_follow_response(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        let Some(follow_url) = activity.object_id() else {
+            return Err(ReceiveActivityError::InvalidActivity(
+                "missing object".into(),
+            ));
+        };
+        if activity.kind == ActivityType::Accept {
+            let follower_id = self
+                .follow_repo
+                .accept_follow(actor.id(), &follow_url)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            tracing::info!(followee = %actor.actor_url, %follow_url, "Follow accepted");
+            if let Some(follower_id) = follower_id {
+                self.merge_home_feed(&follower_id, actor.id()).await;
+            }
+        } else {
+            self.follow_repo
+                .reject_follow(actor.id(), &follow_url)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            tracing::info!(followee = %actor.actor_url, %follow_url, "Follow rejected");
+        }
+        Ok(())
+    }
+
+    /// Record `Like`, `EmojiReact` or `Announce` of a known note
+    ///
+    /// Misskey sends emoji reactions as `Like` with the emoji, and those without one are
+    /// favourites.
+    async fn receive_reaction(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        let Some(note_url) = activity.object_id() else {
+            return Err(ReceiveActivityError::InvalidActivity(
+                "missing object".into(),
+            ));
+        };
+        let (note_id, author_id) = match self.note_repo.find_note_by_url(&note_url).await {
+            Ok(note) => note,
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => {
+                tracing::debug!(%note_url, "Ignore reaction to unknown note");
+                return Ok(());
+            }
+            Err(FindNoteError::Unknown(e)) => return Err(e.into()),
+        };
+
+        if matches!(activity.kind, ActivityType::Like | ActivityType::EmojiReact)
+            && activity.reaction().is_some()
+        {
+            let Some(reaction) = remote_emoji_reaction(actor, &activity, note_id.clone()) else {
+                tracing::debug!(id = %activity.id, "Ignore reaction with an unknown emoji");
+                return Ok(());
+            };
+            self.note_repo
+                .create_emoji_reaction(&reaction)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+        } else if activity.kind == ActivityType::EmojiReact {
+            tracing::debug!(id = %activity.id, "Ignore emoji reaction without an emoji");
+        } else if activity.kind == ActivityType::Like {
+            let favourite = Favourite {
+                id: FavouriteId::new(),
+                actor_id: actor.id().clone(),
+                note_id,
+                like_url: activity.id,
+            };
+            let created = self
+                .note_repo
+                .create_favourite(&favourite)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            if created {
+                self.notify_author(
+                    &author_id,
+                    NotificationKind::Favourite,
+                    actor.id(),
+                    &favourite.note_id,
+                )
+                .await;
+            }
+        } else {
+            let reblog = Reblog {
+                id: ReblogId::new(),
+                actor_id: actor.id().clone(),
+                note_id,
+                announce_url: activity.id,
+            };
+            let created = self
+                .note_repo
+                .create_reblog(&reblog)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            if created {
+                self.fan_out_reblog(&reblog).await;
+                self.publish_reblog(&reblog, false).await;
+                self.notify_author(
+                    &author_id,
+                    NotificationKind::Reblog,
+                    actor.id(),
+                    &reblog.note_id,
+                )
+                .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the object of `activity` as a note written by `actor`
+    fn remote_note_object(
+        actor: &RemoteActor,
+        activity: &Activity,
+    ) -> Result<Option<ap::Note>, ReceiveActivityError> {
+        if ap::activity::object_type(&activity.object)
+            .is_none_or(|kind| kind.parse::<NoteType>().is_err())
+        {
+            return Ok(None);
+        }
+        let note = serde_json::from_value::<ap::Note>(activity.object.clone())
+            .map_err(|e| ReceiveActivityError::InvalidActivity(e.to_string()))?;
+        if note.attributed_to != actor.actor_url || note.id.host() != actor.host() {
+            return Err(ReceiveActivityError::Forbidden);
+        }
+        Ok(Some(note))
+    }
+
+    async fn receive_create(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        let Some(note) = Self::remote_note_object(actor, &activity)? else {
+            tracing::debug!(id = %activity.id, "Ignore create of unsupported object");
+            return Ok(());
+        };
+        if self.receive_vote(actor, &note).await? {
+            return Ok(());
+        }
+        // replies to unknown notes are not worth a fetch on every delivery
+        let in_reply_to_id = match &note.in_reply_to {
+            Some(parent_url) => self.find_note_id_by_url(parent_url).await?,
+            None => None,
+        };
+
+        let req = CreateRemoteNoteRequest {
+            name: actor.name.clone(),
+            host: actor.host().to_string(),
+            content: note.content.clone(),
+            note_type: remote_note_type(&note),
+            title: remote_title(&note),
+            url: note.url.clone(),
+            summary: remote_summary(&note),
+            sensitive: note.sensitive.unwrap_or_default(),
+            note_url: note.id.clone(),
+            hashtags: remote_hashtags(&note),
+            in_reply_to_id,
+            published: note.published,
+            attachments: remote_attachments(&note),
+            emojis: remote_emojis(&note.tag, note.id.host()),
+            poll: Poll::from_remote(&note),
+        };
+        match self.create_remote_note(req).await {
+            Ok(remote_note) => {
+                let recipients: Vec<HttpUrl> = note
+                    .to
+                    .iter()
+                    .chain(&note.cc)
+                    .filter_map(|url| url.parse().ok())
+                    .collect();
+                self.notify_mentions(
+                    &remote_note.id,
+                    actor.id(),
+                    &recipients,
+                    note.in_reply_to.as_ref(),
+                )
+                .await;
+                Ok(())
+            }
+            Err(CreateRemoteNoteError::AlreadyExists) => Ok(()),
+            Err(CreateRemoteNoteError::ActorNotFound) => Err(anyhow::anyhow!(
+                "actor {} disappeared while receiving a note",
+                actor.actor_url
+            )
+            .into()),
+            Err(CreateRemoteNoteError::Unknown(e)) => Err(e.into()),
+        }
+    }
+
+    /// Record a vote in a local poll, which is a note replying to the poll with the name of an
+    /// option. Return false if the note is not a vote
+    async fn receive_vote(
+        &self,
+        actor: &RemoteActor,
+        note: &ap::Note,
+    ) -> Result<bool, ReceiveActivityError> {
+        let (ap::ObjectType::Note, Some(name), Some(poll_url)) =
+            (note.kind, &note.name, &note.in_reply_to)
+        else {
+            return Ok(false);
+        };
+        if poll_url.host() != self.host_url.host() {
+            return Ok(false);
+        }
+        let Some(note_id) = self.find_note_id_by_url(poll_url).await? else {
+            return Ok(false);
+        };
+        let poll_note = match self.note_repo.find_local_note(&note_id).await {
+            Ok(poll_note) => poll_note,
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => return Ok(false),
+            Err(FindNoteError::Unknown(e)) => return Err(e.into()),
+        };
+        let Some(poll) = &poll_note.poll else {
+            return Ok(false);
+        };
+        let Some(choice) = (0..)
+            .zip(&poll.options)
+            .find_map(|(choice, option)| (option.title == *name).then_some(choice))
+        else {
+            return Ok(false);
+        };
+
+        if poll.is_expired() {
+            tracing::debug!(vote_url = %note.id, "Ignore vote in a closed poll");
+            return Ok(true);
+        }
+        let vote = PollVote {
+            id: PollVoteId::new(),
+            note_id,
+            actor_id: actor.id().clone(),
+            choice,
+            vote_url: note.id.clone(),
+        };
+        // voters in a multiple choice poll send a vote per option
+        match self
+            .note_repo
+            .create_poll_votes(&[vote], !poll.multiple)
+            .await
+        {
+            Ok(_) => {}
+            Err(CreateVoteError::AlreadyVoted) => {
+                tracing::debug!(vote_url = %note.id, "Ignore another vote in a single choice poll");
+            }
+            Err(CreateVoteError::Unknown(e)) => return Err(e.into()),
+        }
+        Ok(true)
+    }
+
+    async fn receive_update(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        if ap::activity::object_type(&activity.object)
+            .is_some_and(|kind| ACTOR_TYPES.contains(&kind))
+        {
+            if activity.object_id().as_ref() != Some(&actor.actor_url) {
+                return Err(ReceiveActivityError::Forbidden);
+            }
+            self.store_fetched_actor(&actor.actor_url, activity.object)
+                .await
+                .map_err(|e| match e {
+                    ResolveActorError::InvalidActor(e) => ReceiveActivityError::InvalidActivity(e),
+                    e => ReceiveActivityError::Unknown(e.into()),
+                })?;
+            return Ok(());
+        }
+        let Some(note) = Self::remote_note_object(actor, &activity)? else {
+            tracing::debug!(id = %activity.id, "Ignore update of unsupported object");
+            return Ok(());
+        };
+
+        let mut remote_note = match self.note_repo.find_remote_note(&note.id).await {
+            Ok(remote_note) => remote_note,
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => {
+                tracing::debug!(note_url = %note.id, "Ignore update of unknown note");
+                return Ok(());
+            }
+            Err(FindNoteError::Unknown(e)) => return Err(e.into()),
+        };
+        if remote_note.actor_id != *actor.id() {
+            return Err(ReceiveActivityError::Forbidden);
+        }
+
+        let poll = Poll::from_remote(&note);
+        // votes change the counts of polls without editing them
+        if let Some(poll) = &poll
+            && note.updated == remote_note.updated
+        {
+            self.note_repo
+                .update_poll(&remote_note.id, poll)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            return Ok(());
+        }
+
+        remote_note.content = sanitize::sanitize_html(&note.content);
+        remote_note.note_type = remote_note_type(&note);
+        remote_note.title = remote_title(&note);
+        remote_note.url = note.url.clone();
+        remote_note.summary = remote_summary(&note);
+        remote_note.sensitive = note.sensitive.unwrap_or_default();
+        remote_note.source_id = NoteSourceId::new();
+        remote_note.updated = Some(note.updated.unwrap_or_else(Utc::now));
+        remote_note.updated_at = Utc::now();
+        remote_note.attachments = remote_attachments(&note);
+        remote_note.emojis = remote_emojis(&note.tag, note.id.host());
+        remote_note.poll = poll;
+        self.note_repo
+            .update_remote_note(&remote_note)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.tag_note(&remote_note.id, &remote_hashtags(&note))
+            .await;
+        Ok(())
+    }
+}
+
+/// `type` of actor documents, whose `Update` refreshes the stored actor
+const ACTOR_TYPES: [&str; 5] = ["Person", "Application", "Service", "Group", "Organization"];
+
+/// What a search query names
+enum Resolved {
+    Actor(ActorId),
+    Note(NoteId),
+}
+
+/// `user@host` handle of a query like `@user@host` or `acct:user@host`
+fn parse_handle(query: &str) -> Option<AcctUri> {
+    let handle = query
+        .strip_prefix("acct:")
+        .or_else(|| query.strip_prefix('@'))
+        .unwrap_or(query);
+    let valid = handle.matches('@').count() == 1
+        && !handle.contains(|c: char| c.is_whitespace() || c == '/' || c == ':');
+    if !valid {
+        return None;
+    }
+    format!("acct:{handle}").parse().ok()
+}
+
+/// Hashtag names of a remote note, lowercased without `#`
+fn remote_hashtags(note: &ap::Note) -> Vec<String> {
+    let mut names = Vec::new();
+    for name in note.hashtags().filter_map(hashtag::normalize) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Content warning of a note, `None` if it is blank
+fn content_warning(summary: Option<&str>) -> Option<String> {
+    summary
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+        .map(str::to_string)
+}
+
+fn remote_note_type(note: &ap::Note) -> NoteType {
+    NoteType::from_object_type(note.kind).unwrap_or_default()
+}
+
+/// Title of a remote long-form object, `None` for notes whose `name` means something else
+fn remote_title(note: &ap::Note) -> Option<String> {
+    if remote_note_type(note).is_status() {
+        return None;
+    }
+    content_warning(note.name.as_deref())
+}
+
+/// Content warning of a remote note, or sanitized excerpt of a long-form object
+fn remote_summary(note: &ap::Note) -> Option<String> {
+    let summary = content_warning(note.summary.as_deref())?;
+    if remote_note_type(note).is_status() {
+        Some(summary)
+    } else {
+        Some(sanitize::sanitize_html(&summary))
+    }
+}
+
+/// Custom emoji of a remote note or actor on `host`, ignoring invalid ones
+fn remote_emojis(tags: &[ap::Tag], host: &str) -> Vec<CustomEmoji> {
+    let mut emojis = Vec::<CustomEmoji>::new();
+    for emoji in tags
+        .iter()
+        .filter_map(|tag| CustomEmoji::from_remote(tag, host))
+    {
+        if !emojis.iter().any(|e| e.shortcode == emoji.shortcode) {
+            emojis.push(emoji);
+        }
+    }
+    emojis
+}
+
+/// Poll of a note to be posted, open from `now`
+fn local_poll(req: CreatePollRequest, now: DateTime<Utc>) -> Result<Poll, CreateLocalNoteError> {
+    let invalid = |message: String| Err(CreateLocalNoteError::InvalidPoll(message));
+    if !(2..=MAX_POLL_OPTIONS).contains(&req.options.len()) {
+        return invalid(format!("a poll needs 2 to {MAX_POLL_OPTIONS} options"));
+    }
+    let mut options = Vec::<PollOption>::new();
+    for title in req.options {
+        let title = title.trim();
+        if title.is_empty() || title.chars().count() > MAX_POLL_OPTION_CHARS {
+            return invalid(format!(
+                "options must be 1 to {MAX_POLL_OPTION_CHARS} characters long"
+            ));
+        }
+        if options.iter().any(|option| option.title == title) {
+            return invalid("options must be different".to_string());
+        }
+        options.push(PollOption {
+            title: title.to_string(),
+            votes_count: 0,
+        });
+    }
+    if !POLL_DURATION_SECS.contains(&req.expires_in) {
+        return invalid(format!(
+            "a poll must be open for {} to {} seconds",
+            POLL_DURATION_SECS.start(),
+            POLL_DURATION_SECS.end()
+        ));
+    }
+    Ok(Poll {
+        options,
+        multiple: req.multiple,
+        expires_at: Some(now + chrono::Duration::seconds(req.expires_in)),
+        closed_at: None,
+        voters_count: 0,
+    })
+}
+
+/// Emoji reaction of `activity` to the note, with its custom emoji from the `Emoji` tags
+///
+/// Return `None` if the emoji is not valid or the tag of a custom one is missing.
+fn remote_emoji_reaction(
+    actor: &RemoteActor,
+    activity: &Activity,
+    note_id: NoteId,
+) -> Option<EmojiReaction> {
+    let content = activity.reaction()?;
+    let emoji = match emoji::Reaction::parse(content)? {
+        emoji::Reaction::Unicode(_) => None,
+        emoji::Reaction::Custom { shortcode, .. } => Some(
+            remote_emojis(&activity.tag, actor.host())
+                .into_iter()
+                .find(|emoji| emoji.shortcode == shortcode)?,
+        ),
+    };
+    Some(EmojiReaction {
+        id: EmojiReactionId::new(),
+        actor_id: actor.id().clone(),
+        note_id,
+        reaction_url: activity.id.clone(),
+        content: match &emoji {
+            Some(emoji) => format!(":{}:", emoji.shortcode),
+            None => content.to_string(),
+        },
+        emoji,
+    })
+}
+
+/// Files attached to a remote note, ignoring what is not a document
+fn remote_attachments(note: &ap::Note) -> Vec<Attachment> {
+    note.attachment
+        .iter()
+        .filter(|document| document.kind != ap::DocumentType::Unknown)
+        .take(MAX_ATTACHMENTS)
+        .map(Attachment::from_remote)
+        .collect()
+}
+
+async fn generate_key_pair() -> Result<ActorKeyPair, anyhow::Error> {
+    tokio::task::spawn_blocking(ActorKeyPair::generate).await?
+}
+
+/// `id` for an activity of `actor` like `{actor_url}#likes/{id}`
+fn activity_url(
+    actor: &LocalActor,
+    collection: &str,
+    id: impl std::fmt::Display,
+) -> Result<HttpUrl, anyhow::Error> {
+    Ok(format!("{}#{collection}/{id}", actor.actor_url).parse()?)
+}
+
+fn follow_activity(actor: &LocalActor, follow: &Follow, followee: &ActorRow) -> Activity<HttpUrl> {
+    Activity::builder()
+        .id(follow.follow_url.clone())
+        .kind(ActivityType::Follow)
+        .actor(actor.actor_url.clone())
+        .object(followee.actor_url.clone())
+        .to(vec![followee.actor_url.to_string()])
+        .build()
+}
+
+fn like_activity(actor: &LocalActor, like_url: HttpUrl, note: &NoteView) -> Activity<HttpUrl> {
+    Activity::builder()
+        .id(like_url)
+        .kind(ActivityType::Like)
+        .actor(actor.actor_url.clone())
+        .object(note.note_url.clone())
+        .to(vec![note.author.actor.actor_url.to_string()])
+        .build()
+}
+
+/// `Like` with the emoji in `content` and `_misskey_reaction`, so that Pleroma and Misskey
+/// take it as a reaction. Others see a favourite
+fn reaction_activity(
+    actor: &LocalActor,
+    reaction: &EmojiReaction,
+    note: &NoteView,
+) -> Activity<HttpUrl> {
+    Activity::builder()
+        .id(reaction.reaction_url.clone())
+        .kind(ActivityType::Like)
+        .actor(actor.actor_url.clone())
+        .object(note.note_url.clone())
+        .to(vec![note.author.actor.actor_url.to_string()])
+        .content(Some(reaction.content.clone()))
+        .misskey_reaction(Some(reaction.content.clone()))
+        .tag(reaction.emoji.iter().map(ap::Tag::from).collect())
+        .build()
+}
+
+/// `Create` of a `Note` naming the chosen option and replying to the poll, as Mastodon sends
+/// votes
+fn vote_activity(
+    actor: &LocalActor,
+    vote: &PollVote,
+    title: &str,
+    note: &NoteView,
+) -> Result<Activity<ap::Note>, anyhow::Error> {
+    let to = vec![note.author.actor.actor_url.to_string()];
+    let object = ap::Note::builder()
+        .id(vote.vote_url.clone())
+        .kind(ap::ObjectType::Note)
+        .attributed_to(actor.actor_url.clone())
+        .content(String::new())
+        .name(Some(title.to_string()))
+        .in_reply_to(Some(note.note_url.clone()))
+        .to(to.clone())
+        .build();
+    Ok(Activity::builder()
+        .id(format!("{}/activity", vote.vote_url).parse()?)
+        .kind(ActivityType::Create)
+        .actor(actor.actor_url.clone())
+        .object(object)
+        .to(to)
+        .build())
+}
+
+fn announce_activity(actor: &LocalActor, reblog: &Reblog, note: &NoteView) -> Activity<HttpUrl> {
+    Activity::builder()
+        .id(reblog.announce_url.clone())
+        .kind(ActivityType::Announce)
+        .actor(actor.actor_url.clone())
+        .object(note.note_url.clone())
+        .to(vec![constants::PUBLIC.to_string()])
+        .cc(vec![note.author.actor.actor_url.to_string()])
+        .build()
+}
+
+/// Wrap `activity` in an `Undo` sent to the same audience
+fn undo_activity<T>(activity: Activity<T>) -> Result<Activity<Activity<T>>, anyhow::Error> {
+    Ok(Activity::builder()
+        .id(format!("{}/undo", activity.id).parse()?)
+        .kind(ActivityType::Undo)
+        .actor(activity.actor.clone())
+        .to(activity.to.clone())
+        .cc(activity.cc.clone())
+        .object(activity)
+        .build())
+}
+
+/// Remove the repeated ids, keeping the first of each where it is
+fn dedup_in_order<T>(ids: &mut Vec<Id<T>>) {
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+}
+
+/// Mark the unverified `fields` linking to pages that link back to `actor_url` as verified
+///
+/// Returns how many were verified.
+async fn verify_links<C: ApClient>(
+    client: &C,
+    fields: &mut [ProfileField],
+    actor_url: &HttpUrl,
+) -> usize {
+    let mut verified = 0;
+    for field in fields
+        .iter_mut()
+        .filter(|field| field.verified_at.is_none())
+    {
+        let Some(url) = field.link() else {
+            continue;
+        };
+        match client.fetch_html(&url, MAX_VERIFIED_PAGE_SIZE).await {
+            Ok(page) if rel_me::links_to(&page.html, &page.url, actor_url) => {
+                field.verified_at = Some(Utc::now());
+                verified += 1;
+            }
+            Ok(_) => tracing::debug!(%url, "Profile link does not link back"),
+            Err(e) => tracing::info!(error = %e, %url, "Failed to verify profile link"),
+        }
+    }
+    verified
 }
 
 #[async_trait::async_trait]
-impl<AR, NR, H> ApService for Service<AR, NR, H>
+impl<AR, NR, FR, NFR, H, C, B> ApService for Service<AR, NR, FR, NFR, H, C, B>
 where
     AR: ActorRepository,
     NR: NoteRepository,
+    FR: FollowRepository,
+    NFR: NotificationRepository,
     H: HostUrlService,
+    C: ApClient,
+    B: EventBus,
 {
     async fn create_local_actor(
         &self,
@@ -58,6 +1536,7 @@ where
         let outbox_url = self.host_url.outbox_url(name.as_str());
         let actor_url = self.host_url.actor_url(name.as_str());
         let shared_inbox_url = self.host_url.shared_inbox_url();
+        let now = Utc::now();
         let row = ActorRow {
             id: ActorId::new(),
             actor_type: ActorType::Person,
@@ -65,13 +1544,32 @@ where
             inbox_url,
             outbox_url,
             actor_url,
-            account_id: account_id.into(),
+            account_id: account_id.clone().into(),
             shared_inbox_url: shared_inbox_url.into(),
+            summary: None,
+            public_key: None,
+            published: Some(now),
+            created_at: now,
+            updated_at: now,
+            display_name: None,
+            summary_source: None,
+            avatar: None,
+            header: None,
+            fields: Vec::new(),
+            emojis: Vec::new(),
         };
         let actor_row = self.actor_repo.upsert_actor(row).await?;
 
         let local_actor = LocalActor::try_from(actor_row)?;
 
+        let key = generate_key_pair()
+            .await
+            .map_err(CreateLocalActorError::Unknown)?;
+        self.actor_repo
+            .create_account_key(&account_id, &key)
+            .await
+            .map_err(|e| CreateLocalActorError::Unknown(e.into()))?;
+
         Ok(local_actor)
     }
 
@@ -86,8 +1584,17 @@ where
             name,
             outbox_url,
             shared_inbox_url,
+            summary,
+            public_key,
+            published,
+            display_name,
+            avatar_url,
+            header_url,
+            fields,
+            emojis,
         } = req;
 
+        let now = Utc::now();
         let actor_row = ActorRow {
             id: ActorId::new(),
             actor_type,
@@ -97,33 +1604,453 @@ where
             actor_url,
             account_id: None,
             shared_inbox_url,
+            summary: summary.as_deref().map(sanitize::sanitize_html),
+            public_key,
+            published,
+            created_at: now,
+            updated_at: now,
+            display_name,
+            summary_source: None,
+            avatar: avatar_url.map(ProfileImage::remote),
+            header: header_url.map(ProfileImage::remote),
+            fields: fields
+                .into_iter()
+                .take(MAX_PROFILE_FIELDS)
+                .map(|field| ProfileField {
+                    name: field.name,
+                    value: sanitize::sanitize_html(&field.value),
+                    source: None,
+                    verified_at: None,
+                })
+                .collect(),
+            emojis,
         };
         let actor_row = self.actor_repo.upsert_actor(actor_row).await?;
         let remote_actor = RemoteActor::from(actor_row);
         Ok(remote_actor)
     }
 
+    async fn find_local_public_key(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<PublicKey, FindKeyError> {
+        let actor = self
+            .actor_repo
+            .find_local_actor(account_id)
+            .await
+            .map_err(|e| match e {
+                FindActorError::NotFound => FindKeyError::NotFound,
+                FindActorError::Unknown(e) => FindKeyError::Unknown(e),
+            })?;
+        let key = self.local_key_pair(account_id).await?;
+        Ok(PublicKey {
+            id: self.host_url.key_id(&actor.name),
+            pem: key.public_key_pem,
+        })
+    }
+
+    async fn announce_local_actor_deletion(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<(), FindActorError> {
+        let actor = self.actor_repo.find_local_actor(account_id).await?;
+        let actor = LocalActor::try_from(actor).map_err(|e| anyhow::anyhow!(e))?;
+        let inboxes = self.actor_repo.find_remote_inboxes().await?;
+
+        let delete = Activity::builder()
+            .id(format!("{}#delete", actor.actor_url)
+                .parse()
+                .map_err(|e| anyhow::anyhow!("{e}"))?)
+            .kind(ActivityType::Delete)
+            .actor(actor.actor_url.clone())
+            .object(actor.actor_url.clone())
+            .to(vec![constants::PUBLIC.to_string()])
+            .build();
+        self.deliver(&actor, inboxes, delete).await?;
+        Ok(())
+    }
+
+    async fn find_local_actor_view(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<ActorView, FindActorError> {
+        let actor = self.actor_repo.find_local_actor(account_id).await?;
+        self.actor_repo.find_actor_view(&actor.id).await
+    }
+
+    async fn find_actor_view(&self, id: &ActorId) -> Result<ActorView, FindActorError> {
+        self.actor_repo.find_actor_view(id).await
+    }
+
+    async fn update_local_profile(
+        &self,
+        req: UpdateProfileRequest,
+    ) -> Result<UpdatedProfile, UpdateProfileError> {
+        let UpdateProfileRequest {
+            account_id,
+            display_name,
+            summary,
+            avatar,
+            header,
+            fields,
+        } = req;
+        let too_long = |text: &str, max: usize| text.chars().count() > max;
+        if display_name
+            .as_deref()
+            .is_some_and(|name| too_long(name, MAX_DISPLAY_NAME_LENGTH))
+        {
+            return Err(UpdateProfileError::Invalid(format!(
+                "display name is longer than {MAX_DISPLAY_NAME_LENGTH} characters"
+            )));
+        }
+        if summary
+            .as_deref()
+            .is_some_and(|summary| too_long(summary, MAX_SUMMARY_LENGTH))
+        {
+            return Err(UpdateProfileError::Invalid(format!(
+                "bio is longer than {MAX_SUMMARY_LENGTH} characters"
+            )));
+        }
+        if let Some(fields) = &fields {
+            if fields.len() > MAX_PROFILE_FIELDS {
+                return Err(UpdateProfileError::Invalid(format!(
+                    "more than {MAX_PROFILE_FIELDS} profile fields"
+                )));
+            }
+            if fields.iter().any(|(name, value)| {
+                too_long(name, MAX_PROFILE_FIELD_LENGTH)
+                    || too_long(value, MAX_PROFILE_FIELD_LENGTH)
+            }) {
+                return Err(UpdateProfileError::Invalid(format!(
+                    "profile field is longer than {MAX_PROFILE_FIELD_LENGTH} characters"
+                )));
+            }
+        }
+
+        let mut actor = self.find_local_actor(&account_id).await?;
+        if let Some(display_name) = display_name {
+            let display_name = display_name.trim();
+            actor.display_name = (!display_name.is_empty()).then(|| display_name.to_string());
+        }
+        if let Some(summary) = summary {
+            let summary = summary.trim();
+            actor.summary = (!summary.is_empty()).then(|| render::render_plain_text(summary));
+            actor.summary_source = (!summary.is_empty()).then(|| summary.to_string());
+        }
+        let mut replaced_images = Vec::new();
+        if let Some(avatar) = avatar {
+            replaced_images.extend(actor.avatar.replace(avatar));
+        }
+        if let Some(header) = header {
+            replaced_images.extend(actor.header.replace(header));
+        }
+        if let Some(fields) = fields {
+            let previous = std::mem::take(&mut actor.fields);
+            actor.fields = fields
+                .into_iter()
+                .filter(|(name, value)| !name.trim().is_empty() || !value.trim().is_empty())
+                .map(|(name, value)| {
+                    let source = value.trim().to_string();
+                    // links stay verified until they change
+                    let verified_at = previous
+                        .iter()
+                        .find(|field| field.source.as_ref() == Some(&source))
+                        .and_then(|field| field.verified_at);
+                    ProfileField {
+                        name: name.trim().to_string(),
+                        value: render::render_inline(&source),
+                        source: Some(source),
+                        verified_at,
+                    }
+                })
+                .collect();
+        }
+        let texts = actor
+            .display_name
+            .iter()
+            .chain(&actor.summary_source)
+            .chain(
+                actor
+                    .fields
+                    .iter()
+                    .flat_map(|field| [&field.name, &field.value]),
+            )
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        actor.emojis = self.local_emojis(&texts).await;
+        actor.updated_at = Utc::now();
+        let row = self.actor_repo.upsert_actor(actor.clone().into()).await?;
+
+        let result = async {
+            let public_key = self
+                .find_local_public_key(&account_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            let inboxes = self.follow_repo.find_follower_inboxes(&row.id).await?;
+            let update = Activity::builder()
+                .id(format!(
+                    "{}#updates/{}",
+                    actor.actor_url,
+                    actor.updated_at.timestamp_millis()
+                )
+                .parse()?)
+                .kind(ActivityType::Update)
+                .actor(actor.actor_url.clone())
+                .object(actor.to_ap(public_key))
+                .to(vec![constants::PUBLIC.to_string()])
+                .build();
+            self.deliver(&actor, inboxes, update).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to deliver profile update");
+        }
+
+        let view = self.actor_repo.find_actor_view(&row.id).await?;
+        Ok(UpdatedProfile {
+            view,
+            replaced_images,
+        })
+    }
+
+    async fn verify_profile_links(&self, account_id: &AccountId) -> Result<usize, FindActorError> {
+        let actor = self.find_local_actor(account_id).await?;
+        let mut fields = actor.fields.clone();
+        let verified = verify_links(&self.client, &mut fields, &actor.actor_url).await;
+        if verified == 0 {
+            return Ok(0);
+        }
+
+        // the profile may have been edited while the links were fetched
+        let updated = self
+            .actor_repo
+            .update_actor_fields(&actor.id, &actor.fields, &fields)
+            .await
+            .map_err(|e| match e {
+                UpdateActorError::Unknown(e) => FindActorError::Unknown(e),
+            })?;
+        Ok(if updated { verified } else { 0 })
+    }
+
+    async fn resolve_actor_by_key_id(
+        &self,
+        key_id: &str,
+    ) -> Result<RemoteActor, ResolveActorError> {
+        match self.actor_repo.find_actor_by_key_id(key_id).await {
+            Ok(row) if row.account_id.is_none() => return Ok(row.into()),
+            Ok(_) => return Err(ResolveActorError::NotFound),
+            Err(FindActorError::NotFound) => {}
+            Err(FindActorError::Unknown(e)) => return Err(e.into()),
+        }
+
+        // keys are usually a fragment of the actor document like `{actor_url}#main-key`
+        let mut url: url::Url = key_id
+            .parse()
+            .map_err(|_| ResolveActorError::InvalidActor(key_id.to_string()))?;
+        url.set_fragment(None);
+        let url = HttpUrl::new(url).map_err(|e| ResolveActorError::InvalidActor(e.to_string()))?;
+
+        let actor = self.fetch_remote_actor(&url, None).await?;
+        match &actor.public_key {
+            Some(key) if key.id == key_id => Ok(actor),
+            _ => Err(ResolveActorError::InvalidActor(format!(
+                "{} does not own {}",
+                actor.actor_url, key_id
+            ))),
+        }
+    }
+
     async fn create_local_note(
         &self,
         req: CreateLocalNoteRequest,
     ) -> Result<LocalNote, CreateLocalNoteError> {
+        if req.media_ids.len() > MAX_ATTACHMENTS {
+            return Err(CreateLocalNoteError::InvalidMedia(format!(
+                "at most {MAX_ATTACHMENTS} files can be attached"
+            )));
+        }
+        let now = Utc::now();
+        let poll = req.poll.map(|poll| local_poll(poll, now)).transpose()?;
+        if poll.is_some() && !req.media_ids.is_empty() {
+            return Err(CreateLocalNoteError::InvalidPoll(
+                "files cannot be attached to a poll".to_string(),
+            ));
+        }
         let actor = self.actor_repo.find_local_actor(&req.account_id).await?;
 
         let note_id = NoteId::new();
         let note_url = self.host_url.note_url(&note_id.to_string());
 
+        let content = render::render(&req.content, req.media_type);
+        let emojis = self.local_emojis(&[&req.content]).await;
+        let summary = content_warning(req.summary.as_deref());
+
         let note = LocalNote {
             id: note_id,
-            actor_id: actor.id,
+            actor_id: actor.id.clone(),
+            actor_url: actor.actor_url.clone(),
             account_id: req.account_id,
-            content: req.content,
+            content,
+            // a content warning hides the media too
+            sensitive: req.sensitive || summary.is_some(),
+            summary,
+            source: NoteSource {
+                id: NoteSourceId::new(),
+                content: req.content,
+                media_type: req.media_type,
+            },
             note_url,
+            updated: None,
+            published: now,
+            created_at: now,
+            updated_at: now,
+            attachments: Vec::new(),
+            emojis,
+            poll,
         };
 
-        let note = self.note_repo.create_local_note(note).await?;
+        let note = self
+            .note_repo
+            .create_local_note(note, &req.media_ids)
+            .await?;
+        self.tag_note(&note.id, &hashtag::extract(&note.source.content))
+            .await;
+        let mentioned: Vec<HttpUrl> =
+            mention::extract_local(&note.source.content, self.host_url.host())
+                .iter()
+                .map(|name| self.host_url.actor_url(name))
+                .collect();
+        self.notify_mentions(&note.id, &note.actor_id, &mentioned, None)
+            .await;
+        self.fan_out_note(&note.id, &note.actor_id).await;
+        self.publish_note(&note.id, true).await;
+
+        let Ok(actor) = LocalActor::try_from(actor) else {
+            return Ok(note);
+        };
+        let result = async {
+            let inboxes = self.note_audience(&note.id, &actor.id).await?;
+            let create = Activity::builder()
+                .id(format!("{}/activity", note.note_url).parse()?)
+                .kind(ActivityType::Create)
+                .actor(actor.actor_url.clone())
+                .object(ap::Note::from(&note))
+                .to(vec![constants::PUBLIC.to_string()])
+                .build();
+            self.deliver(&actor, inboxes, create).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, note_id = %note.id, "Failed to deliver note");
+        }
+
         Ok(note)
     }
 
+    async fn find_local_note(&self, id: &NoteId) -> Result<LocalNote, FindNoteError> {
+        self.note_repo.find_local_note(id).await
+    }
+
+    async fn update_local_note(
+        &self,
+        req: UpdateLocalNoteRequest,
+    ) -> Result<LocalNote, UpdateLocalNoteError> {
+        let mut note = self.note_repo.find_local_note(&req.note_id).await?;
+        if note.account_id != req.account_id {
+            return Err(UpdateLocalNoteError::Forbidden);
+        }
+
+        note.content = render::render(&req.content, req.media_type);
+        note.emojis = self.local_emojis(&[&req.content]).await;
+        note.source = NoteSource {
+            id: NoteSourceId::new(),
+            content: req.content,
+            media_type: req.media_type,
+        };
+        let now = Utc::now();
+        note.updated = Some(now);
+        note.updated_at = now;
+        self.note_repo.update_local_note(&note).await?;
+        self.tag_note(&note.id, &hashtag::extract(&note.source.content))
+            .await;
+
+        let result = async {
+            let actor =
+                LocalActor::try_from(self.actor_repo.find_local_actor(&note.account_id).await?)?;
+            let inboxes = self.note_audience(&note.id, &actor.id).await?;
+            let update = Activity::builder()
+                .id(format!("{}#updates/{}", note.note_url, note.source.id).parse()?)
+                .kind(ActivityType::Update)
+                .actor(actor.actor_url.clone())
+                .object(ap::Note::from(&note))
+                .to(vec![constants::PUBLIC.to_string()])
+                .build();
+            self.deliver(&actor, inboxes, update).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, note_id = %note.id, "Failed to deliver note update");
+        }
+
+        Ok(note)
+    }
+
+    async fn delete_local_note(
+        &self,
+        req: DeleteLocalNoteRequest,
+    ) -> Result<(), DeleteLocalNoteError> {
+        let note = self.note_repo.find_local_note(&req.note_id).await?;
+        if note.account_id != req.account_id {
+            return Err(DeleteLocalNoteError::Forbidden);
+        }
+
+        let deleted = Utc::now();
+        self.note_repo.delete_note(&note.id, deleted).await?;
+        self.publish_delete(*note.id).await;
+
+        let result = async {
+            let actor =
+                LocalActor::try_from(self.actor_repo.find_local_actor(&note.account_id).await?)?;
+            // only those who have seen the note need to forget it
+            let inboxes = self.note_repo.find_note_recipients(&note.id).await?;
+            let tombstone = ap::Tombstone::builder()
+                .id(note.note_url.clone())
+                .former_type(ap::ObjectType::Note)
+                .deleted(deleted)
+                .build();
+            let delete = Activity::builder()
+                .id(format!("{}#delete", note.note_url).parse()?)
+                .kind(ActivityType::Delete)
+                .actor(actor.actor_url.clone())
+                .object(tombstone)
+                .to(vec![constants::PUBLIC.to_string()])
+                .build();
+            self.deliver(&actor, inboxes, delete).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, note_id = %note.id, "Failed to deliver note deletion");
+        }
+
+        Ok(())
+    }
+
+    async fn find_note_revisions(&self, id: &NoteId) -> Result<Vec<NoteRevision>, FindNoteError> {
+        let sources = self.note_repo.find_note_sources(id).await?;
+        if sources.is_empty() {
+            return Err(FindNoteError::NotFound);
+        }
+
+        let revisions = sources
+            .into_iter()
+            .map(|source| NoteRevision {
+                content: render::render(&source.content, source.media_type),
+                source,
+            })
+            .collect();
+        Ok(revisions)
+    }
+
     async fn create_remote_note(
         &self,
         req: CreateRemoteNoteRequest,
@@ -135,13 +2062,822 @@ where
         let actor = self.actor_repo.find_remote_actor(&remote_actor_req).await?;
         let note_id = NoteId::new();
 
+        let now = Utc::now();
         let remote_note = RemoteNote {
             id: note_id,
             actor_id: actor.id,
-            content: req.content,
+            content: sanitize::sanitize_html(&req.content),
+            summary: req.summary,
+            sensitive: req.sensitive,
+            source_id: NoteSourceId::new(),
+            note_type: req.note_type,
+            title: req.title,
+            url: req.url,
             note_url: req.note_url,
+            updated: None,
+            in_reply_to_id: req.in_reply_to_id,
+            published: req.published.unwrap_or(now),
+            created_at: now,
+            updated_at: now,
+            attachments: req.attachments,
+            emojis: req.emojis,
+            poll: req.poll,
         };
         let note = self.note_repo.create_remote_note(remote_note).await?;
+        self.tag_note(&note.id, &req.hashtags).await;
+        self.fan_out_note(&note.id, &note.actor_id).await;
+        self.publish_note(&note.id, false).await;
         Ok(note)
     }
+
+    async fn resolve_remote_note(
+        &self,
+        req: ResolveNoteRequest,
+    ) -> Result<NoteView, ResolveNoteError> {
+        let actor = match self.find_local_actor(&req.account_id).await {
+            Ok(actor) => actor,
+            Err(FindActorError::NotFound) => return Err(ResolveNoteError::ActorNotFound),
+            Err(FindActorError::Unknown(e)) => return Err(e.into()),
+        };
+        let key = self.signing_key(&actor).await?;
+        let note_id = self
+            .fetch_remote_note(&req.url, Some(&key), MAX_FETCHED_ANCESTORS)
+            .await?;
+        Ok(self
+            .note_repo
+            .find_note_view(&note_id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn find_note_view(
+        &self,
+        id: &NoteId,
+        viewer: Option<&AccountId>,
+    ) -> Result<NoteView, FindNoteError> {
+        self.note_repo.find_note_view(id, viewer).await
+    }
+
+    async fn find_actor_note_views(
+        &self,
+        actor_id: &ActorId,
+        viewer: Option<&AccountId>,
+        limit: u32,
+    ) -> Result<Vec<NoteView>, FindNoteError> {
+        self.note_repo
+            .find_actor_note_views(actor_id, viewer, limit)
+            .await
+    }
+
+    async fn search(&self, req: SearchRequest) -> Result<SearchResults, SearchError> {
+        let mut results = SearchResults::default();
+        let query = req.query.trim();
+        if query.is_empty() {
+            return Ok(results);
+        }
+        let viewer = req.viewer.as_ref();
+
+        let mut actor_ids = Vec::new();
+        let mut note_ids = Vec::new();
+        // only remote lookups need an account, to spare the server from anonymous fetches
+        if req.offset == 0 {
+            let fetch_key = match viewer {
+                Some(account_id) if req.resolve => {
+                    let actor = self.find_local_actor(account_id).await.map_err(|e| {
+                        anyhow::anyhow!("failed to find the actor of {account_id}: {e}")
+                    })?;
+                    Some(
+                        self.signing_key(&actor)
+                            .await
+                            .map_err(anyhow::Error::from)?,
+                    )
+                }
+                _ => None,
+            };
+            match self.resolve_query(query, fetch_key.as_ref()).await {
+                Ok(Some(Resolved::Actor(id))) => actor_ids.push(id),
+                Ok(Some(Resolved::Note(id))) => note_ids.push(id),
+                Ok(None) => {}
+                Err(e) => tracing::info!(error = %e, query, "Failed to resolve search query"),
+            }
+        }
+
+        if req.includes(SearchKind::Accounts) {
+            let name = query.strip_prefix('@').unwrap_or(query);
+            actor_ids.extend(
+                self.actor_repo
+                    .search_actors(name, req.limit, req.offset)
+                    .await?,
+            );
+            dedup_in_order(&mut actor_ids);
+            for id in actor_ids.iter().take(req.limit as usize) {
+                match self.actor_repo.find_actor_view(id).await {
+                    Ok(view) => results.actors.push(view),
+                    // deleted since it was found
+                    Err(FindActorError::NotFound) => {}
+                    Err(FindActorError::Unknown(e)) => return Err(e.into()),
+                }
+            }
+        }
+
+        if req.includes(SearchKind::Statuses) {
+            note_ids.extend(
+                self.note_repo
+                    .search_notes(query, req.actor_id.as_ref(), req.limit, req.offset)
+                    .await?,
+            );
+            dedup_in_order(&mut note_ids);
+            for id in note_ids.iter().take(req.limit as usize) {
+                match self.note_repo.find_note_view(id, viewer).await {
+                    Ok(view) => results.notes.push(view),
+                    Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => {}
+                    Err(FindNoteError::Unknown(e)) => return Err(e.into()),
+                }
+            }
+        }
+
+        if req.includes(SearchKind::Hashtags)
+            && let Some(name) = hashtag::normalize(query)
+        {
+            results.hashtags = self
+                .note_repo
+                .search_hashtags(&name, req.limit, req.offset)
+                .await?;
+        }
+        Ok(results)
+    }
+
+    async fn find_relationship(
+        &self,
+        req: FollowActorRequest,
+    ) -> Result<Relationship, FollowActorError> {
+        let actor = self.actor_repo.find_local_actor(&req.account_id).await?;
+        self.relationship(&actor.id, &req.actor_id).await
+    }
+
+    async fn follow(&self, req: FollowActorRequest) -> Result<Relationship, FollowActorError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        if actor.id == req.actor_id {
+            return Err(FollowActorError::SelfFollow);
+        }
+        let followee = self.actor_repo.find_actor_view(&req.actor_id).await?.actor;
+
+        if self
+            .follow_repo
+            .find_follow(&actor.id, &followee.id)
+            .await?
+            .is_none()
+        {
+            let id = FollowId::new();
+            let follow = Follow {
+                follower_id: actor.id.clone(),
+                followee_id: followee.id.clone(),
+                follow_url: activity_url(&actor, "follows", &id)?,
+                // local actors accept everyone
+                accepted: followee.account_id.is_some(),
+                id,
+            };
+            let follow = self
+                .follow_repo
+                .upsert_follow(follow)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            tracing::info!(follower = %actor.actor_url, followee = %followee.actor_url, "Follow");
+
+            if follow.accepted {
+                self.merge_home_feed(&actor.id, &followee.id).await;
+                if let Some(account_id) = &followee.account_id {
+                    self.notify(account_id, NotificationKind::Follow, &actor.id, None)
+                        .await;
+                }
+            } else {
+                let activity = follow_activity(&actor, &follow, &followee);
+                if let Err(e) = self
+                    .deliver(&actor, vec![followee.inbox_url.clone()], activity)
+                    .await
+                {
+                    tracing::error!(error = %e, follow_id = %follow.id, "Failed to deliver follow");
+                }
+            }
+        }
+
+        self.relationship(&actor.id, &followee.id).await
+    }
+
+    async fn unfollow(&self, req: FollowActorRequest) -> Result<Relationship, FollowActorError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let followee = self.actor_repo.find_actor_view(&req.actor_id).await?.actor;
+
+        if let Some(follow) = self
+            .follow_repo
+            .find_follow(&actor.id, &followee.id)
+            .await?
+        {
+            self.follow_repo
+                .delete_follow(&actor.id, &followee.id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+            tracing::info!(follower = %actor.actor_url, followee = %followee.actor_url, "Unfollow");
+            if let Err(e) = self
+                .note_repo
+                .unmerge_home_feed(&actor.id, &followee.id)
+                .await
+            {
+                tracing::error!(error = %e, follow_id = %follow.id, "Failed to unmerge home feed");
+            }
+
+            if followee.account_id.is_none() {
+                let result = async {
+                    let undo = undo_activity(follow_activity(&actor, &follow, &followee))?;
+                    self.deliver(&actor, vec![followee.inbox_url.clone()], undo)
+                        .await
+                }
+                .await;
+                if let Err(e) = result {
+                    tracing::error!(error = %e, follow_id = %follow.id, "Failed to deliver unfollow");
+                }
+            }
+        }
+
+        self.relationship(&actor.id, &followee.id).await
+    }
+
+    async fn favourite(&self, req: ReactRequest) -> Result<NoteView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+        if note.favourited {
+            return Ok(note);
+        }
+
+        let id = FavouriteId::new();
+        let favourite = Favourite {
+            actor_id: actor.id.clone(),
+            note_id: note.id.clone(),
+            like_url: activity_url(&actor, "likes", &id)?,
+            id,
+        };
+        let created = self
+            .note_repo
+            .create_favourite(&favourite)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        let author = &note.author.actor;
+        if let Some(account_id) = &author.account_id {
+            if created && author.id != actor.id {
+                self.notify(
+                    account_id,
+                    NotificationKind::Favourite,
+                    &actor.id,
+                    Some(&note.id),
+                )
+                .await;
+            }
+        } else {
+            let like = like_activity(&actor, favourite.like_url, &note);
+            if let Err(e) = self
+                .deliver(&actor, vec![author.inbox_url.clone()], like)
+                .await
+            {
+                tracing::error!(error = %e, note_id = %note.id, "Failed to deliver like");
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn unfavourite(&self, req: ReactRequest) -> Result<NoteView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+
+        let like_url = self
+            .note_repo
+            .delete_favourite(&actor.id, &note.id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let author = &note.author.actor;
+        if let Some(like_url) = like_url
+            && author.account_id.is_none()
+        {
+            let result = async {
+                let undo = undo_activity(like_activity(&actor, like_url, &note))?;
+                self.deliver(&actor, vec![author.inbox_url.clone()], undo)
+                    .await
+            }
+            .await;
+            if let Err(e) = result {
+                tracing::error!(error = %e, note_id = %note.id, "Failed to deliver unlike");
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn vote(&self, req: VoteRequest) -> Result<NoteView, VoteError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+        let Some(poll) = &note.poll else {
+            return Err(VoteError::NotFound);
+        };
+        if note.author.actor.id == actor.id {
+            return Err(VoteError::OwnPoll);
+        }
+        let choices = poll.choose(req.choices)?;
+        let titles: Vec<_> = choices
+            .iter()
+            .map(|&choice| &poll.options[choice as usize].title)
+            .collect();
+
+        let votes = choices
+            .iter()
+            .map(|&choice| {
+                let id = PollVoteId::new();
+                Ok(PollVote {
+                    note_id: note.id.clone(),
+                    actor_id: actor.id.clone(),
+                    choice,
+                    vote_url: activity_url(&actor, "votes", &id)?,
+                    id,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        // a second request is refused even while the first is being recorded
+        self.note_repo
+            .create_poll_votes(&votes, true)
+            .await
+            .map_err(|e| match e {
+                CreateVoteError::AlreadyVoted => VoteError::AlreadyVoted,
+                CreateVoteError::Unknown(e) => VoteError::Unknown(e),
+            })?;
+
+        let author = &note.author.actor;
+        if author.account_id.is_none() {
+            // one vote per option, as Mastodon sends them
+            for (vote, title) in votes.iter().zip(titles) {
+                let result = async {
+                    let create = vote_activity(&actor, vote, title, &note)?;
+                    self.deliver(&actor, vec![author.inbox_url.clone()], create)
+                        .await
+                }
+                .await;
+                if let Err(e) = result {
+                    tracing::error!(error = %e, note_id = %note.id, "Failed to deliver vote");
+                }
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn close_expired_polls(&self) -> Result<usize, ClosePollError> {
+        let note_ids = self.note_repo.close_expired_polls(Utc::now()).await?;
+        for note_id in &note_ids {
+            if let Err(e) = self.deliver_poll_results(note_id).await {
+                tracing::error!(error = %e, %note_id, "Failed to deliver poll results");
+            }
+        }
+        Ok(note_ids.len())
+    }
+
+    async fn react(&self, req: EmojiReactRequest) -> Result<NoteView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+        let (content, emoji) = self.reaction_emoji(&req.emoji).await?;
+
+        let id = EmojiReactionId::new();
+        let reaction = EmojiReaction {
+            actor_id: actor.id.clone(),
+            note_id: note.id.clone(),
+            reaction_url: activity_url(&actor, "reactions", &id)?,
+            id,
+            content,
+            emoji,
+        };
+        let created = self
+            .note_repo
+            .create_emoji_reaction(&reaction)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        let author = &note.author.actor;
+        if created && author.account_id.is_none() {
+            let activity = reaction_activity(&actor, &reaction, &note);
+            if let Err(e) = self
+                .deliver(&actor, vec![author.inbox_url.clone()], activity)
+                .await
+            {
+                tracing::error!(error = %e, note_id = %note.id, "Failed to deliver reaction");
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn unreact(&self, req: EmojiReactRequest) -> Result<NoteView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+        let (content, emoji) = self.reaction_emoji(&req.emoji).await?;
+
+        let reaction_url = self
+            .note_repo
+            .delete_emoji_reaction(&actor.id, &note.id, &content)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let author = &note.author.actor;
+        if let Some(reaction_url) = reaction_url
+            && author.account_id.is_none()
+        {
+            let reaction = EmojiReaction {
+                id: EmojiReactionId::new(),
+                actor_id: actor.id.clone(),
+                note_id: note.id.clone(),
+                reaction_url,
+                content,
+                emoji,
+            };
+            let result = async {
+                let undo = undo_activity(reaction_activity(&actor, &reaction, &note))?;
+                self.deliver(&actor, vec![author.inbox_url.clone()], undo)
+                    .await
+            }
+            .await;
+            if let Err(e) = result {
+                tracing::error!(error = %e, note_id = %note.id, "Failed to deliver reaction undo");
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn reblog(&self, req: ReactRequest) -> Result<ReblogView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+
+        let existing = self
+            .note_repo
+            .find_reblog(&actor.id, &note.id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let reblog = match existing {
+            Some(reblog) => reblog,
+            None => {
+                let id = ReblogId::new();
+                let reblog = Reblog {
+                    actor_id: actor.id.clone(),
+                    note_id: note.id.clone(),
+                    announce_url: activity_url(&actor, "announces", &id)?,
+                    id,
+                };
+                let created = self
+                    .note_repo
+                    .create_reblog(&reblog)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                if created {
+                    self.fan_out_reblog(&reblog).await;
+                    self.publish_reblog(&reblog, true).await;
+                    self.notify_author(
+                        &note.author.actor.id,
+                        NotificationKind::Reblog,
+                        &actor.id,
+                        &note.id,
+                    )
+                    .await;
+                }
+
+                let result = async {
+                    let inboxes = self.reblog_audience(&actor, &note).await?;
+                    let announce = announce_activity(&actor, &reblog, &note);
+                    self.deliver(&actor, inboxes, announce).await
+                }
+                .await;
+                if let Err(e) = result {
+                    tracing::error!(error = %e, note_id = %note.id, "Failed to deliver reblog");
+                }
+                reblog
+            }
+        };
+
+        let note = self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?;
+        let actor = self.actor_repo.find_actor_view(&actor.id).await?;
+        Ok(ReblogView {
+            id: reblog.id,
+            announce_url: reblog.announce_url,
+            actor,
+            note,
+        })
+    }
+
+    async fn unreblog(&self, req: ReactRequest) -> Result<NoteView, ReactError> {
+        let actor = self.find_local_actor(&req.account_id).await?;
+        let note = self
+            .note_repo
+            .find_note_view(&req.note_id, Some(&req.account_id))
+            .await?;
+
+        let reblog = self
+            .note_repo
+            .delete_reblog(&actor.id, &note.id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if let Some(reblog) = reblog {
+            self.publish_delete(*reblog.id).await;
+            let result = async {
+                let inboxes = self.reblog_audience(&actor, &note).await?;
+                let undo = undo_activity(announce_activity(&actor, &reblog, &note))?;
+                self.deliver(&actor, inboxes, undo).await
+            }
+            .await;
+            if let Err(e) = result {
+                tracing::error!(error = %e, note_id = %note.id, "Failed to deliver unreblog");
+            }
+        }
+
+        Ok(self
+            .note_repo
+            .find_note_view(&note.id, Some(&req.account_id))
+            .await?)
+    }
+
+    async fn home_timeline(
+        &self,
+        req: HomeTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError> {
+        self.note_repo.find_home_timeline(&req).await
+    }
+
+    async fn public_timeline(
+        &self,
+        req: PublicTimelineRequest,
+    ) -> Result<Vec<TimelineItem>, FindTimelineError> {
+        self.note_repo.find_public_timeline(&req).await
+    }
+
+    async fn find_home_timeline_item(
+        &self,
+        account_id: &AccountId,
+        item_id: &TimelineItemId,
+    ) -> Result<Option<TimelineItem>, FindTimelineError> {
+        self.note_repo
+            .find_home_feed_entry(account_id, item_id)
+            .await
+    }
+
+    async fn notifications(
+        &self,
+        req: NotificationsRequest,
+    ) -> Result<Vec<NotificationView>, FindNotificationError> {
+        let notifications = self.notification_repo.find_notifications(&req).await?;
+        let mut views = Vec::with_capacity(notifications.len());
+        for notification in notifications {
+            views.extend(self.notification_view(notification).await?);
+        }
+        Ok(views)
+    }
+
+    async fn find_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<NotificationView, FindNotificationError> {
+        let notification = self
+            .notification_repo
+            .find_notification(account_id, id)
+            .await?;
+        self.notification_view(notification)
+            .await?
+            .ok_or(FindNotificationError::NotFound)
+    }
+
+    async fn dismiss_notification(
+        &self,
+        account_id: &AccountId,
+        id: &NotificationId,
+    ) -> Result<(), DeleteNotificationError> {
+        self.notification_repo
+            .delete_notification(account_id, id)
+            .await
+    }
+
+    async fn dismiss_notification_group(
+        &self,
+        account_id: &AccountId,
+        key: &NotificationGroupKey,
+    ) -> Result<(), DeleteNotificationError> {
+        match key {
+            NotificationGroupKey::Note(kind, note_id) => {
+                self.notification_repo
+                    .delete_note_notifications(account_id, *kind, note_id)
+                    .await
+            }
+            NotificationGroupKey::Ungrouped(id) => {
+                self.notification_repo
+                    .delete_notification(account_id, id)
+                    .await
+            }
+        }
+    }
+
+    async fn clear_notifications(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<(), DeleteNotificationError> {
+        self.notification_repo.clear_notifications(account_id).await
+    }
+
+    async fn unread_notification_count(
+        &self,
+        account_id: &AccountId,
+        limit: u32,
+    ) -> Result<u64, FindNotificationError> {
+        self.notification_repo
+            .count_unread_notifications(account_id, limit)
+            .await
+    }
+
+    async fn find_markers(&self, account_id: &AccountId) -> Result<Vec<Marker>, MarkerError> {
+        self.notification_repo.find_markers(account_id).await
+    }
+
+    async fn update_markers(
+        &self,
+        account_id: &AccountId,
+        last_read_ids: &[(MarkerTimeline, uuid::Uuid)],
+    ) -> Result<Vec<Marker>, MarkerError> {
+        for (timeline, last_read_id) in last_read_ids {
+            self.notification_repo
+                .upsert_marker(account_id, *timeline, last_read_id)
+                .await?;
+        }
+        self.notification_repo.find_markers(account_id).await
+    }
+
+    async fn receive_activity(
+        &self,
+        actor: &RemoteActor,
+        activity: Activity,
+    ) -> Result<(), ReceiveActivityError> {
+        if activity.actor != actor.actor_url {
+            return Err(ReceiveActivityError::Forbidden);
+        }
+
+        match activity.kind {
+            ActivityType::Follow => self.receive_follow(actor, activity).await,
+            ActivityType::Undo => self.receive_undo(actor, activity).await,
+            ActivityType::Create => self.receive_create(actor, activity).await,
+            ActivityType::Update => self.receive_update(actor, activity).await,
+            ActivityType::Accept | ActivityType::Reject => {
+                self.receive_follow_response(actor, activity).await
+            }
+            ActivityType::Like | ActivityType::EmojiReact | ActivityType::Announce => {
+                self.receive_reaction(actor, activity).await
+            }
+            kind => {
+                tracing::debug!(id = %activity.id, ?kind, "Ignore unsupported activity");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, extract::Path, response::Html, response::Redirect, routing};
+    use pretty_assertions::assert_eq;
+
+    use crate::infrastructure::ap_client::ApHttpClient;
+
+    use super::*;
+
+    const ACTOR_URL: &str = "https://example.com/users/alice";
+
+    async fn serve_pages() -> String {
+        let backlink = format!(r#"<a rel="me" href="{ACTOR_URL}">alice</a>"#);
+        let oversized = format!("{backlink}{}", " ".repeat(MAX_VERIFIED_PAGE_SIZE));
+        let app = Router::new()
+            .route("/a", routing::get(Html(backlink.clone())))
+            .route(
+                "/link",
+                routing::get(Html(format!(r#"<link rel="me" href="{ACTOR_URL}">"#))),
+            )
+            .route(
+                "/none",
+                routing::get(Html(format!(r#"<a href="{ACTOR_URL}">alice</a>"#))),
+            )
+            .route(
+                "/redirect/{n}",
+                routing::get(async |Path(n): Path<u32>| match n {
+                    0 => Redirect::temporary("/a"),
+                    n => Redirect::temporary(&format!("/redirect/{}", n - 1)),
+                }),
+            )
+            .route("/oversized", routing::get(Html(oversized)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_verify_links() {
+        let base = serve_pages().await;
+        let paths = [
+            "/a",
+            "/link",
+            "/none",
+            "/redirect/2",
+            "/redirect/3",
+            "/oversized",
+        ];
+        let mut fields: Vec<_> = paths
+            .iter()
+            .map(|path| ProfileField {
+                name: path.to_string(),
+                value: String::new(),
+                source: Some(format!("{base}{path}")),
+                verified_at: None,
+            })
+            .collect();
+
+        let actor_url = ACTOR_URL.parse().unwrap();
+        let verified = verify_links(&ApHttpClient::new(true), &mut fields, &actor_url).await;
+
+        let verified_paths: Vec<_> = fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.verified_at.is_some()))
+            .collect();
+        assert_eq!(
+            verified_paths,
+            vec![
+                ("/a", true),
+                ("/link", true),
+                ("/none", false),
+                // 3 redirects are followed, 4 are too many
+                ("/redirect/2", true),
+                ("/redirect/3", false),
+                ("/oversized", false),
+            ]
+        );
+        assert_eq!(verified, 3);
+    }
+
+    #[tokio::test]
+    async fn test_verify_links_private() {
+        let base = serve_pages().await;
+        let mut fields = vec![ProfileField {
+            name: "/a".to_string(),
+            value: String::new(),
+            source: Some(format!("{base}/a")),
+            verified_at: None,
+        }];
+
+        let actor_url = ACTOR_URL.parse().unwrap();
+        let verified = verify_links(&ApHttpClient::new(false), &mut fields, &actor_url).await;
+
+        assert_eq!(verified, 0);
+        assert_eq!(fields[0].verified_at, None);
+    }
+
+    #[test]
+    fn test_dedup_in_order() {
+        let [a, b, c] = [NoteId::new(), NoteId::new(), NoteId::new()];
+        let mut ids = vec![b.clone(), a.clone(), c.clone(), b.clone(), a.clone()];
+        dedup_in_order(&mut ids);
+        assert_eq!(ids, vec![b, a, c]);
+    }
 }
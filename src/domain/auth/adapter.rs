@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::account::model::{Account, AccountName, PasswordHash};
+
+use super::model::{
+    AuthenticateError, CreateSessionError, LoginError, LoginRequest, LogoutError, Principal,
+    Session, SessionToken,
+    oauth::{
+        App, AppId, AuthorizationCode, AuthorizationGrant, AuthorizationRequest, AuthorizeError,
+        AuthorizeRequest, ClientSecret, FindAppError, RegisterAppError, RegisterAppRequest,
+        RegisteredApp, RevokeRequest, TokenError, TokenRequest,
+    },
+};
+
+#[async_trait::async_trait]
+pub trait AuthService: Send + Sync + 'static {
+    /// Check the password and start a new session
+    async fn login(&self, req: LoginRequest) -> Result<Session, LoginError>;
+    /// Check the password without starting a session, to confirm destructive actions
+    async fn check_password(&self, req: LoginRequest) -> Result<Account, LoginError>;
+    /// Return who the token was issued to
+    async fn authenticate(&self, token: &SessionToken) -> Result<Principal, AuthenticateError>;
+    /// End the session of the token. Unknown tokens are ignored
+    async fn logout(&self, token: &SessionToken) -> Result<(), LogoutError>;
+
+    async fn register_app(
+        &self,
+        req: RegisterAppRequest,
+    ) -> Result<RegisteredApp, RegisterAppError>;
+    async fn find_app(&self, id: &AppId) -> Result<Option<App>, FindAppError>;
+    /// Check that the client may ask for the authorization, before asking the user
+    async fn check_authorization(&self, req: &AuthorizationRequest) -> Result<App, AuthorizeError>;
+    /// Check the password of the user and issue a code for the client
+    async fn authorize(&self, req: AuthorizeRequest) -> Result<AuthorizationCode, AuthorizeError>;
+    /// Token endpoint
+    async fn issue_token(&self, req: TokenRequest) -> Result<Session, TokenError>;
+    /// Revoke a token issued to the client. Unknown tokens are ignored
+    async fn revoke_token(&self, req: RevokeRequest) -> Result<(), TokenError>;
+}
+
+#[async_trait::async_trait]
+pub trait SessionRepository: Send + Sync + 'static {
+    /// Return the account with `name` and its password hash, if it has one
+    async fn find_credentials(
+        &self,
+        name: &AccountName,
+    ) -> Result<Option<(Account, Option<PasswordHash>)>, LoginError>;
+    async fn create_session(&self, session: &Session) -> Result<(), CreateSessionError>;
+    /// Return the principal of an unexpired session
+    async fn find_session(
+        &self,
+        token: &SessionToken,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Principal>, AuthenticateError>;
+    async fn delete_session(&self, token: &SessionToken) -> Result<(), LogoutError>;
+    /// Delete the session only if it was issued to the app
+    async fn delete_app_session(
+        &self,
+        token: &SessionToken,
+        app_id: &AppId,
+    ) -> Result<(), TokenError>;
+}
+
+#[async_trait::async_trait]
+pub trait OAuthRepository: Send + Sync + 'static {
+    async fn create_app(
+        &self,
+        app: &App,
+        client_secret: &ClientSecret,
+        created: DateTime<Utc>,
+    ) -> Result<(), RegisterAppError>;
+    async fn find_app(&self, id: &AppId) -> Result<Option<App>, FindAppError>;
+    /// Return the app with its secret hash
+    async fn find_client(&self, client_id: &str) -> Result<Option<(App, String)>, FindAppError>;
+    async fn create_authorization_code(
+        &self,
+        code: &AuthorizationCode,
+        grant: &AuthorizationGrant,
+    ) -> Result<(), AuthorizeError>;
+    /// Remove the code and return what it granted. Codes can be used only once
+    async fn take_authorization_code(
+        &self,
+        code: &AuthorizationCode,
+    ) -> Result<Option<AuthorizationGrant>, TokenError>;
+}
@@ -0,0 +1,136 @@
+pub(crate) mod oauth;
+pub(crate) mod scope;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use rsa::rand_core::{OsRng, RngCore as _};
+use sha2::{Digest as _, Sha256};
+
+use crate::domain::{
+    Id,
+    account::model::{Account, AccountName, Password},
+};
+
+pub use oauth::AppId;
+pub use scope::{Scope, Scopes};
+
+pub type SessionId = Id<Session>;
+
+const TOKEN_BYTES: usize = 32;
+
+/// Define a random string given to clients
+///
+/// Only its `hash` is stored, so a leaked database does not leak the secrets.
+macro_rules! secret {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, Eq)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn generate() -> Self {
+                Self($crate::domain::auth::model::random_token())
+            }
+
+            pub fn from_raw(raw: &str) -> Self {
+                Self(raw.to_string())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// base64 SHA-256 of the secret
+            pub fn hash(&self) -> String {
+                $crate::domain::auth::model::hash_token(&self.0)
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&"<redacted>").finish()
+            }
+        }
+    };
+}
+pub(crate) use secret;
+
+fn random_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+secret! {
+    /// Bearer token handed to the client on login or by the token endpoint
+    SessionToken
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub(crate) id: SessionId,
+    pub(crate) token: SessionToken,
+    /// `None` for tokens an app got for itself
+    pub(crate) account: Option<Account>,
+    /// `None` for password login
+    pub(crate) app_id: Option<AppId>,
+    pub(crate) scopes: Scopes,
+    pub(crate) created: DateTime<Utc>,
+    pub(crate) expires: DateTime<Utc>,
+}
+
+/// Who a token was issued to and what it allows
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub(crate) account: Option<Account>,
+    pub(crate) app_id: Option<AppId>,
+    pub(crate) scopes: Scopes,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginRequest {
+    pub(crate) name: AccountName,
+    pub(crate) password: Password,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    /// Unknown account, wrong password or an account without a password
+    #[error("invalid credentials")]
+    InvalidCredentials,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthenticateError {
+    /// Unknown or expired token
+    #[error("invalid token")]
+    InvalidToken,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateSessionError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<CreateSessionError> for LoginError {
+    fn from(e: CreateSessionError) -> Self {
+        match e {
+            CreateSessionError::Unknown(e) => LoginError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogoutError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
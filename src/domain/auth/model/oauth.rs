@@ -0,0 +1,268 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use sha2::{Digest as _, Sha256};
+
+use crate::domain::{
+    Id,
+    account::model::{Account, AccountName, Password},
+};
+
+use super::{CreateSessionError, LoginError, Scopes, SessionToken, secret};
+
+pub type AppId = Id<App>;
+
+/// Redirect URI of clients that show the code to the user instead of receiving it
+pub const OOB_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+secret! {
+    /// Secret of an app, only known to the app
+    ClientSecret
+}
+
+secret! {
+    /// Code of the authorization code flow, exchanged for a token once
+    AuthorizationCode
+}
+
+/// A registered OAuth client
+#[derive(Debug, Clone)]
+pub struct App {
+    pub(crate) id: AppId,
+    pub(crate) client_id: String,
+    pub(crate) name: String,
+    pub(crate) redirect_uris: Vec<String>,
+    /// scopes the app may ask for
+    pub(crate) scopes: Scopes,
+    pub(crate) website: Option<String>,
+}
+
+impl App {
+    pub fn has_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisterAppRequest {
+    pub(crate) name: String,
+    pub(crate) redirect_uris: Vec<String>,
+    pub(crate) scopes: Scopes,
+    pub(crate) website: Option<String>,
+}
+
+/// An app right after registration, the only time its secret is known
+#[derive(Debug, Clone)]
+pub struct RegisteredApp {
+    pub(crate) app: App,
+    pub(crate) client_secret: ClientSecret,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegisterAppError {
+    #[error("invalid redirect URI {0}")]
+    InvalidRedirectUri(String),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::IntoStaticStr)]
+pub enum CodeChallengeMethod {
+    #[strum(serialize = "plain")]
+    Plain,
+    #[strum(serialize = "S256")]
+    S256,
+}
+
+/// PKCE code challenge, RFC 7636
+#[derive(Debug, Clone)]
+pub struct CodeChallenge {
+    pub(crate) challenge: String,
+    pub(crate) method: CodeChallengeMethod,
+}
+
+impl CodeChallenge {
+    pub fn verify(&self, verifier: &str) -> bool {
+        let expected = match self.method {
+            CodeChallengeMethod::Plain => verifier.to_string(),
+            CodeChallengeMethod::S256 => URL_SAFE_NO_PAD.encode(Sha256::digest(verifier)),
+        };
+        // compare digests so the time taken does not leak the challenge
+        Sha256::digest(expected) == Sha256::digest(&self.challenge)
+    }
+}
+
+/// What a client asks the user to allow, from `GET /oauth/authorize`
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub(crate) client_id: String,
+    pub(crate) redirect_uri: String,
+    /// defaults to [Scopes::default_request]
+    pub(crate) scopes: Option<Scopes>,
+    pub(crate) code_challenge: Option<CodeChallenge>,
+}
+
+/// The user allowing an [AuthorizationRequest]
+#[derive(Debug, Clone)]
+pub struct AuthorizeRequest {
+    pub(crate) authorization: AuthorizationRequest,
+    pub(crate) name: AccountName,
+    pub(crate) password: Password,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthorizeError {
+    #[error("unknown client")]
+    InvalidClient,
+    #[error("redirect URI is not registered")]
+    InvalidRedirectUri,
+    #[error("scope is not allowed for the client")]
+    InvalidScope,
+    #[error("invalid credentials")]
+    InvalidCredentials,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindAppError> for AuthorizeError {
+    fn from(e: FindAppError) -> Self {
+        match e {
+            FindAppError::Unknown(e) => AuthorizeError::Unknown(e),
+        }
+    }
+}
+
+impl From<LoginError> for AuthorizeError {
+    fn from(e: LoginError) -> Self {
+        match e {
+            LoginError::InvalidCredentials => AuthorizeError::InvalidCredentials,
+            LoginError::Unknown(e) => AuthorizeError::Unknown(e),
+        }
+    }
+}
+
+/// An issued [AuthorizationCode] as stored
+#[derive(Debug, Clone)]
+pub struct AuthorizationGrant {
+    pub(crate) app_id: AppId,
+    /// who allowed the app
+    pub(crate) account: Account,
+    pub(crate) redirect_uri: String,
+    pub(crate) scopes: Scopes,
+    pub(crate) code_challenge: Option<CodeChallenge>,
+    pub(crate) expires: DateTime<Utc>,
+}
+
+/// How a client identifies itself. Public clients using PKCE may leave out the secret
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    pub(crate) client_id: String,
+    pub(crate) client_secret: Option<ClientSecret>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenRequest {
+    AuthorizationCode {
+        client: ClientCredentials,
+        code: AuthorizationCode,
+        redirect_uri: String,
+        code_verifier: Option<String>,
+    },
+    /// A token for the app itself, without an account
+    ClientCredentials {
+        client: ClientCredentials,
+        scopes: Option<Scopes>,
+    },
+}
+
+/// Errors of the token endpoint, RFC 6749 section 5.2
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error("client authentication failed")]
+    InvalidClient,
+    #[error("authorization code is invalid, expired or was issued to another client")]
+    InvalidGrant,
+    #[error("scope is not allowed for the client")]
+    InvalidScope,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<CreateSessionError> for TokenError {
+    fn from(e: CreateSessionError) -> Self {
+        match e {
+            CreateSessionError::Unknown(e) => TokenError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindAppError> for TokenError {
+    fn from(e: FindAppError) -> Self {
+        match e {
+            FindAppError::Unknown(e) => TokenError::Unknown(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RevokeRequest {
+    pub(crate) client: ClientCredentials,
+    pub(crate) token: SessionToken,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindAppError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    // RFC 7636 appendix B
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const S256_CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[rstest]
+    #[case::s256(CodeChallengeMethod::S256, S256_CHALLENGE, VERIFIER, true)]
+    #[case::s256_wrong_verifier(
+        CodeChallengeMethod::S256,
+        S256_CHALLENGE,
+        "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXK",
+        false
+    )]
+    #[case::s256_verifier_as_challenge(CodeChallengeMethod::S256, VERIFIER, VERIFIER, false)]
+    #[case::s256_padded(
+        CodeChallengeMethod::S256,
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM=",
+        VERIFIER,
+        false
+    )]
+    #[case::plain(CodeChallengeMethod::Plain, VERIFIER, VERIFIER, true)]
+    #[case::plain_wrong_verifier(CodeChallengeMethod::Plain, VERIFIER, S256_CHALLENGE, false)]
+    fn test_code_challenge_verify(
+        #[case] method: CodeChallengeMethod,
+        #[case] challenge: &str,
+        #[case] verifier: &str,
+        #[case] expected: bool,
+    ) {
+        let challenge = CodeChallenge {
+            challenge: challenge.to_string(),
+            method,
+        };
+        assert_eq!(challenge.verify(verifier), expected);
+    }
+
+    #[rstest]
+    #[case("S256", Some(CodeChallengeMethod::S256))]
+    #[case("plain", Some(CodeChallengeMethod::Plain))]
+    #[case("s256", None)]
+    fn test_parse_code_challenge_method(
+        #[case] method: &str,
+        #[case] expected: Option<CodeChallengeMethod>,
+    ) {
+        assert_eq!(method.parse().ok(), expected);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+/// OAuth scope
+///
+/// See https://docs.joinmastodon.org/api/oauth-scopes/
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::IntoStaticStr,
+)]
+pub enum Scope {
+    #[strum(serialize = "read")]
+    Read,
+    #[strum(serialize = "read:accounts")]
+    ReadAccounts,
+    #[strum(serialize = "read:blocks")]
+    ReadBlocks,
+    #[strum(serialize = "read:bookmarks")]
+    ReadBookmarks,
+    #[strum(serialize = "read:favourites")]
+    ReadFavourites,
+    #[strum(serialize = "read:filters")]
+    ReadFilters,
+    #[strum(serialize = "read:follows")]
+    ReadFollows,
+    #[strum(serialize = "read:lists")]
+    ReadLists,
+    #[strum(serialize = "read:mutes")]
+    ReadMutes,
+    #[strum(serialize = "read:notifications")]
+    ReadNotifications,
+    #[strum(serialize = "read:search")]
+    ReadSearch,
+    #[strum(serialize = "read:statuses")]
+    ReadStatuses,
+    #[strum(serialize = "write")]
+    Write,
+    #[strum(serialize = "write:accounts")]
+    WriteAccounts,
+    #[strum(serialize = "write:blocks")]
+    WriteBlocks,
+    #[strum(serialize = "write:bookmarks")]
+    WriteBookmarks,
+    #[strum(serialize = "write:conversations")]
+    WriteConversations,
+    #[strum(serialize = "write:favourites")]
+    WriteFavourites,
+    #[strum(serialize = "write:filters")]
+    WriteFilters,
+    #[strum(serialize = "write:follows")]
+    WriteFollows,
+    #[strum(serialize = "write:lists")]
+    WriteLists,
+    #[strum(serialize = "write:media")]
+    WriteMedia,
+    #[strum(serialize = "write:mutes")]
+    WriteMutes,
+    #[strum(serialize = "write:notifications")]
+    WriteNotifications,
+    #[strum(serialize = "write:reports")]
+    WriteReports,
+    #[strum(serialize = "write:statuses")]
+    WriteStatuses,
+    /// Deprecated, same as the `read:` and `write:` scopes of follows, blocks and mutes
+    #[strum(serialize = "follow")]
+    Follow,
+    #[strum(serialize = "push")]
+    Push,
+    /// Only `GET /api/v1/accounts/verify_credentials`, which `read:accounts` allows too
+    #[strum(serialize = "profile")]
+    Profile,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Whether a token with this scope may do what `required` allows
+    pub fn grants(self, required: Scope) -> bool {
+        use Scope::*;
+
+        if self == required {
+            return true;
+        }
+        match self {
+            Read => required.as_str().starts_with("read:") || required == Profile,
+            ReadAccounts => required == Profile,
+            Write => required.as_str().starts_with("write:"),
+            Follow => matches!(
+                required,
+                ReadFollows | WriteFollows | ReadBlocks | WriteBlocks | ReadMutes | WriteMutes
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScopeError {
+    #[error("scope {0} is unknown")]
+    Unknown(String),
+}
+
+/// Set of scopes, written space separated
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scopes(BTreeSet<Scope>);
+
+impl Scopes {
+    /// Scope of apps and authorizations that don't ask for one
+    pub fn default_request() -> Self {
+        Self(BTreeSet::from([Scope::Read]))
+    }
+
+    /// Everything but admin. Given to sessions from password login
+    pub fn all() -> Self {
+        Self(BTreeSet::from([
+            Scope::Read,
+            Scope::Write,
+            Scope::Follow,
+            Scope::Push,
+        ]))
+    }
+
+    pub fn allows(&self, required: Scope) -> bool {
+        self.0.iter().any(|scope| scope.grants(required))
+    }
+
+    /// Whether every scope of `self` is granted by `other`
+    pub fn is_within(&self, other: &Scopes) -> bool {
+        self.0.iter().all(|scope| other.allows(*scope))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromStr for Scopes {
+    type Err = ScopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(|scope| {
+                scope
+                    .parse()
+                    .map_err(|_| ScopeError::Unknown(scope.to_string()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl std::fmt::Display for Scopes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, scope) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(scope.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::same(Scope::ReadStatuses, Scope::ReadStatuses, true)]
+    #[case::read(Scope::Read, Scope::ReadStatuses, true)]
+    #[case::read_not_write(Scope::Read, Scope::WriteStatuses, false)]
+    #[case::write(Scope::Write, Scope::WriteMedia, true)]
+    #[case::write_not_read(Scope::Write, Scope::ReadAccounts, false)]
+    #[case::sub_scope_not_parent(Scope::ReadStatuses, Scope::Read, false)]
+    #[case::sibling(Scope::ReadStatuses, Scope::ReadAccounts, false)]
+    #[case::follow(Scope::Follow, Scope::WriteFollows, true)]
+    #[case::follow_blocks(Scope::Follow, Scope::ReadBlocks, true)]
+    #[case::follow_not_statuses(Scope::Follow, Scope::ReadStatuses, false)]
+    #[case::profile(Scope::Profile, Scope::Profile, true)]
+    #[case::profile_not_accounts(Scope::Profile, Scope::ReadAccounts, false)]
+    #[case::read_accounts_profile(Scope::ReadAccounts, Scope::Profile, true)]
+    #[case::read_profile(Scope::Read, Scope::Profile, true)]
+    #[case::write_not_profile(Scope::Write, Scope::Profile, false)]
+    #[case::push(Scope::Push, Scope::ReadNotifications, false)]
+    fn test_grants(#[case] scope: Scope, #[case] required: Scope, #[case] expected: bool) {
+        assert_eq!(scope.grants(required), expected);
+    }
+
+    #[rstest]
+    #[case::covered("read:statuses write", "read write follow push", true)]
+    #[case::not_covered("read write", "read", false)]
+    #[case::profile("profile", "read:accounts", true)]
+    fn test_is_within(#[case] scopes: &str, #[case] granted: &str, #[case] expected: bool) {
+        let scopes: Scopes = scopes.parse().unwrap();
+        let granted: Scopes = granted.parse().unwrap();
+        assert_eq!(scopes.is_within(&granted), expected);
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        assert_eq!(
+            "read admin:read".parse::<Scopes>(),
+            Err(ScopeError::Unknown("admin:read".to_string()))
+        );
+    }
+}
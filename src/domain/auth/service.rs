@@ -0,0 +1,318 @@
+use std::sync::LazyLock;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sha2::{Digest as _, Sha256};
+
+use crate::domain::account::model::{Account, AccountName, Password, PasswordHash};
+
+use super::{
+    adapter::{AuthService, OAuthRepository, SessionRepository},
+    model::{
+        AuthenticateError, CreateSessionError, LoginError, LoginRequest, LogoutError, Principal,
+        Scopes, Session, SessionId, SessionToken,
+        oauth::{
+            App, AppId, AuthorizationCode, AuthorizationGrant, AuthorizationRequest,
+            AuthorizeError, AuthorizeRequest, ClientCredentials, ClientSecret, FindAppError,
+            OOB_REDIRECT_URI, RegisterAppError, RegisterAppRequest, RegisteredApp, RevokeRequest,
+            TokenError, TokenRequest,
+        },
+    },
+};
+
+/// How long a session lasts after login
+const SESSION_LIFETIME: TimeDelta = TimeDelta::days(30);
+/// How long tokens from the token endpoint last.
+/// Mastodon clients have no way to refresh them, so they practically live until revoked.
+const ACCESS_TOKEN_LIFETIME: TimeDelta = TimeDelta::days(365 * 10);
+/// How long the client has to exchange an authorization code
+const AUTHORIZATION_CODE_LIFETIME: TimeDelta = TimeDelta::minutes(10);
+
+/// Verified against when the account does not exist, so that unknown names take as long as
+/// wrong passwords
+static DUMMY_PASSWORD_HASH: LazyLock<Option<PasswordHash>> = LazyLock::new(|| {
+    let password = Password::new("dummy password").ok()?;
+    PasswordHash::new(&password).ok()
+});
+
+#[derive(Debug, Clone)]
+pub struct Service<R, O> {
+    repo: R,
+    oauth_repo: O,
+}
+
+impl<R, O> Service<R, O>
+where
+    R: SessionRepository,
+    O: OAuthRepository,
+{
+    pub fn new(repo: R, oauth_repo: O) -> Self {
+        Self { repo, oauth_repo }
+    }
+
+    /// Return the account if the password is right
+    async fn verify_password(
+        &self,
+        name: &AccountName,
+        password: Password,
+    ) -> Result<Account, LoginError> {
+        let (account, password_hash) = match self.repo.find_credentials(name).await? {
+            Some((account, hash)) => (Some(account), hash),
+            None => (None, None),
+        };
+        let verified = tokio::task::spawn_blocking(move || match &password_hash {
+            Some(hash) => hash.verify(&password),
+            None => {
+                if let Some(dummy) = DUMMY_PASSWORD_HASH.as_ref() {
+                    dummy.verify(&password);
+                }
+                false
+            }
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+        account
+            .filter(|_| verified)
+            .ok_or(LoginError::InvalidCredentials)
+    }
+
+    async fn create_session(
+        &self,
+        account: Option<Account>,
+        app_id: Option<AppId>,
+        scopes: Scopes,
+        lifetime: TimeDelta,
+    ) -> Result<Session, CreateSessionError> {
+        let created = Utc::now();
+        let session = Session {
+            id: SessionId::new(),
+            token: SessionToken::generate(),
+            account,
+            app_id,
+            scopes,
+            created,
+            expires: created + lifetime,
+        };
+        self.repo.create_session(&session).await?;
+        Ok(session)
+    }
+
+    /// Return the app if the client authenticated, or may go without a secret
+    async fn authenticate_client(
+        &self,
+        client: &ClientCredentials,
+        secret_required: bool,
+    ) -> Result<App, TokenError> {
+        let Some((app, secret_hash)) = self.oauth_repo.find_client(&client.client_id).await? else {
+            return Err(TokenError::InvalidClient);
+        };
+        match &client.client_secret {
+            Some(secret) if is_same_hash(&secret.hash(), &secret_hash) => Ok(app),
+            None if !secret_required => Ok(app),
+            _ => Err(TokenError::InvalidClient),
+        }
+    }
+
+    async fn exchange_code(
+        &self,
+        client: ClientCredentials,
+        code: AuthorizationCode,
+        redirect_uri: String,
+        code_verifier: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<Session, TokenError> {
+        // taken before anything else, so a leaked code is burnt by the first attempt
+        let Some(grant) = self.oauth_repo.take_authorization_code(&code).await? else {
+            return Err(TokenError::InvalidGrant);
+        };
+        let app = self
+            .authenticate_client(&client, grant.code_challenge.is_none())
+            .await?;
+
+        let verified = match (&grant.code_challenge, &code_verifier) {
+            (Some(challenge), Some(verifier)) => challenge.verify(verifier),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        if grant.app_id != app.id
+            || grant.redirect_uri != redirect_uri
+            || grant.expires <= now
+            || !verified
+        {
+            return Err(TokenError::InvalidGrant);
+        }
+
+        let session = self
+            .create_session(
+                Some(grant.account),
+                Some(app.id),
+                grant.scopes,
+                ACCESS_TOKEN_LIFETIME,
+            )
+            .await?;
+        Ok(session)
+    }
+}
+
+/// Compare digests so the time taken does not leak the secret
+fn is_same_hash(a: &str, b: &str) -> bool {
+    Sha256::digest(a) == Sha256::digest(b)
+}
+
+fn is_valid_redirect_uri(uri: &str) -> bool {
+    uri == OOB_REDIRECT_URI || url::Url::parse(uri).is_ok_and(|url| url.fragment().is_none())
+}
+
+#[async_trait::async_trait]
+impl<R, O> AuthService for Service<R, O>
+where
+    R: SessionRepository,
+    O: OAuthRepository,
+{
+    #[tracing::instrument(skip(self))]
+    async fn login(&self, req: LoginRequest) -> Result<Session, LoginError> {
+        let LoginRequest { name, password } = req;
+        let account = self
+            .verify_password(&name, password)
+            .await
+            .inspect_err(|_| tracing::info!("Login failed"))?;
+
+        let session = self
+            .create_session(Some(account), None, Scopes::all(), SESSION_LIFETIME)
+            .await?;
+        tracing::info!("Logged in");
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn check_password(&self, req: LoginRequest) -> Result<Account, LoginError> {
+        let LoginRequest { name, password } = req;
+        self.verify_password(&name, password).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authenticate(&self, token: &SessionToken) -> Result<Principal, AuthenticateError> {
+        self.repo
+            .find_session(token, Utc::now())
+            .await?
+            .ok_or(AuthenticateError::InvalidToken)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn logout(&self, token: &SessionToken) -> Result<(), LogoutError> {
+        self.repo.delete_session(token).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn register_app(
+        &self,
+        req: RegisterAppRequest,
+    ) -> Result<RegisteredApp, RegisterAppError> {
+        let RegisterAppRequest {
+            name,
+            redirect_uris,
+            scopes,
+            website,
+        } = req;
+        if let Some(uri) = redirect_uris.iter().find(|uri| !is_valid_redirect_uri(uri)) {
+            return Err(RegisterAppError::InvalidRedirectUri(uri.clone()));
+        }
+
+        let app = App {
+            id: AppId::new(),
+            client_id: SessionToken::generate().as_str().to_string(),
+            name,
+            redirect_uris,
+            scopes,
+            website,
+        };
+        let client_secret = ClientSecret::generate();
+        self.oauth_repo
+            .create_app(&app, &client_secret, Utc::now())
+            .await?;
+        tracing::info!(app_id = %app.id, "Registered app");
+
+        Ok(RegisteredApp { app, client_secret })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn find_app(&self, id: &AppId) -> Result<Option<App>, FindAppError> {
+        self.oauth_repo.find_app(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn check_authorization(&self, req: &AuthorizationRequest) -> Result<App, AuthorizeError> {
+        let Some((app, _)) = self.oauth_repo.find_client(&req.client_id).await? else {
+            return Err(AuthorizeError::InvalidClient);
+        };
+        if !app.has_redirect_uri(&req.redirect_uri) {
+            return Err(AuthorizeError::InvalidRedirectUri);
+        }
+        if let Some(scopes) = &req.scopes
+            && (scopes.is_empty() || !scopes.is_within(&app.scopes))
+        {
+            return Err(AuthorizeError::InvalidScope);
+        }
+        Ok(app)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authorize(&self, req: AuthorizeRequest) -> Result<AuthorizationCode, AuthorizeError> {
+        let AuthorizeRequest {
+            authorization,
+            name,
+            password,
+        } = req;
+        let app = self.check_authorization(&authorization).await?;
+        let account = self.verify_password(&name, password).await?;
+
+        let code = AuthorizationCode::generate();
+        let grant = AuthorizationGrant {
+            app_id: app.id,
+            account,
+            redirect_uri: authorization.redirect_uri,
+            scopes: authorization.scopes.unwrap_or_else(Scopes::default_request),
+            code_challenge: authorization.code_challenge,
+            expires: Utc::now() + AUTHORIZATION_CODE_LIFETIME,
+        };
+        self.oauth_repo
+            .create_authorization_code(&code, &grant)
+            .await?;
+        tracing::info!(app_id = %grant.app_id, account_id = %grant.account.id(), "Authorized app");
+
+        Ok(code)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn issue_token(&self, req: TokenRequest) -> Result<Session, TokenError> {
+        match req {
+            TokenRequest::AuthorizationCode {
+                client,
+                code,
+                redirect_uri,
+                code_verifier,
+            } => {
+                self.exchange_code(client, code, redirect_uri, code_verifier, Utc::now())
+                    .await
+            }
+            TokenRequest::ClientCredentials { client, scopes } => {
+                let app = self.authenticate_client(&client, true).await?;
+                let scopes = scopes.unwrap_or_else(Scopes::default_request);
+                if scopes.is_empty() || !scopes.is_within(&app.scopes) {
+                    return Err(TokenError::InvalidScope);
+                }
+                let session = self
+                    .create_session(None, Some(app.id), scopes, ACCESS_TOKEN_LIFETIME)
+                    .await?;
+                Ok(session)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn revoke_token(&self, req: RevokeRequest) -> Result<(), TokenError> {
+        let app = self.authenticate_client(&req.client, true).await?;
+        self.repo.delete_app_session(&req.token, &app.id).await
+    }
+}
@@ -42,14 +42,21 @@ pub trait HostUrlService: Send + Sync + 'static {
 
     /// Return actor URL
     fn actor_url(&self, user: &str) -> HttpUrl {
-        format!("{}/ap/actors/{}", self.base_url(), user)
+        format!("{}://{}/ap/actors/{}", self.scheme(), self.host(), user)
             .parse()
             .unwrap()
     }
 
+    /// Return id of the actor public key
+    fn key_id(&self, user: &str) -> String {
+        format!("{}#main-key", self.actor_url(user))
+    }
+
     /// Return shared inbox URL
     fn shared_inbox_url(&self) -> HttpUrl {
-        format!("{}/ap/inbox", self.base_url()).parse().unwrap()
+        format!("{}://{}/ap/inbox", self.scheme(), self.host())
+            .parse()
+            .unwrap()
     }
 
     /// Return actor_name inbox URL
@@ -68,7 +75,28 @@ pub trait HostUrlService: Send + Sync + 'static {
 
     /// Return user note URL
     fn note_url(&self, note_id: &str) -> HttpUrl {
-        format!("{}/ap/notes/{}", self.base_url(), note_id)
+        format!("{}://{}/ap/notes/{}", self.scheme(), self.host(), note_id)
+            .parse()
+            .unwrap()
+    }
+
+    /// Return id of the custom emoji `id` of this instance
+    fn emoji_url(&self, id: &str) -> HttpUrl {
+        format!("{}://{}/ap/emojis/{}", self.scheme(), self.host(), id)
+            .parse()
+            .unwrap()
+    }
+
+    /// Return URL of the uploaded file stored at `key`
+    fn media_url(&self, key: &str) -> HttpUrl {
+        format!("{}://{}/media/{}", self.scheme(), self.host(), key)
+            .parse()
+            .unwrap()
+    }
+
+    /// Return URL of the remote file `id` in the media proxy
+    fn media_proxy_url(&self, id: &str) -> HttpUrl {
+        format!("{}://{}/media/proxy/{}", self.scheme(), self.host(), id)
             .parse()
             .unwrap()
     }
@@ -0,0 +1,4 @@
+pub(crate) mod adapter;
+pub(crate) mod model;
+pub(crate) mod process;
+pub(crate) mod service;
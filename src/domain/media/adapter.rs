@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use crate::domain::account::model::AccountId;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::HttpUrl;
+
+use super::model::{
+    Attachment, AttachmentId, CreateAttachmentError, CreateEmojiError, CreateEmojiRequest,
+    CustomEmoji, CustomEmojiId, DeleteEmojiError, FindAttachmentError, FindEmojiError,
+    MediaCacheError, MediaFile, ProfileImage, ProfileImageKind, ProxyMediaError, RemoteMedia,
+    RemoteMediaId, StorageError, UploadMediaError, UploadMediaRequest,
+};
+
+#[async_trait::async_trait]
+pub trait MediaService: Send + Sync + 'static {
+    /// Store an uploaded file. It is attached to a note when the note is posted with its id
+    async fn upload(&self, req: UploadMediaRequest) -> Result<Attachment, UploadMediaError>;
+
+    /// Return the attachment if `account_id` uploaded it
+    async fn find_attachment(
+        &self,
+        account_id: &AccountId,
+        id: &AttachmentId,
+    ) -> Result<Attachment, FindAttachmentError>;
+
+    /// Store an avatar or header, cropped to its size. The profile shows it once updated with it
+    async fn upload_profile_image(
+        &self,
+        kind: ProfileImageKind,
+        file: MediaFile,
+    ) -> Result<ProfileImage, UploadMediaError>;
+
+    /// Delete the stored file of an avatar or header no longer shown
+    async fn delete_profile_image(&self, image: &ProfileImage) -> Result<(), StorageError>;
+
+    /// Store a custom emoji of this instance
+    async fn create_emoji(&self, req: CreateEmojiRequest) -> Result<CustomEmoji, CreateEmojiError>;
+
+    /// Custom emoji of this instance, by shortcode
+    async fn find_local_emojis(&self) -> Result<Vec<CustomEmoji>, FindEmojiError>;
+
+    async fn find_emoji(&self, id: &CustomEmojiId) -> Result<CustomEmoji, FindEmojiError>;
+
+    /// Delete a custom emoji of this instance and its image
+    async fn delete_emoji(&self, shortcode: &str) -> Result<(), DeleteEmojiError>;
+
+    /// Return the uploaded file stored at `key`
+    async fn find_file(&self, key: &str) -> Result<MediaFile, FindAttachmentError>;
+
+    /// Return the remote file, from the cache or fetched into it
+    async fn proxy(&self, id: &RemoteMediaId) -> Result<MediaFile, ProxyMediaError>;
+
+    /// Drop cached remote files as the cache policy says, returning how many were dropped
+    async fn evict_cache(&self) -> Result<usize, MediaCacheError>;
+}
+
+#[async_trait::async_trait]
+pub trait MediaRepository: Send + Sync + 'static {
+    async fn create_attachment(&self, attachment: &Attachment)
+    -> Result<(), CreateAttachmentError>;
+
+    async fn find_attachment(&self, id: &AttachmentId) -> Result<Attachment, FindAttachmentError>;
+
+    async fn find_attachment_by_storage_key(
+        &self,
+        key: &str,
+    ) -> Result<Attachment, FindAttachmentError>;
+
+    /// Whether the image stored at `key` is an avatar, a header or a custom emoji
+    async fn is_stored_image(&self, key: &str) -> Result<bool, FindAttachmentError>;
+
+    /// Fail with [CreateEmojiError::AlreadyExists] if the shortcode is taken
+    async fn create_emoji(&self, emoji: &CustomEmoji) -> Result<(), CreateEmojiError>;
+
+    async fn find_emoji(&self, id: &CustomEmojiId) -> Result<CustomEmoji, FindEmojiError>;
+
+    /// Custom emoji of this instance, by shortcode
+    async fn find_local_emojis(&self) -> Result<Vec<CustomEmoji>, FindEmojiError>;
+
+    /// Return the storage key of the deleted emoji's image
+    async fn delete_local_emoji(&self, shortcode: &str)
+    -> Result<Option<String>, DeleteEmojiError>;
+
+    async fn find_remote_media(&self, id: &RemoteMediaId) -> Result<RemoteMedia, ProxyMediaError>;
+
+    /// Record the copy of the remote file stored at `storage_key`
+    async fn set_remote_media_cached(
+        &self,
+        id: &RemoteMediaId,
+        media_type: &str,
+        storage_key: &str,
+        size: u64,
+    ) -> Result<(), MediaCacheError>;
+
+    /// Record that the cached copy of the remote file was used
+    async fn touch_remote_media(&self, id: &RemoteMediaId) -> Result<(), MediaCacheError>;
+
+    /// Cached remote files not used since `accessed_before`, then the least recently used ones
+    /// beyond `max_size` bytes in total, the least recently used first
+    async fn find_evictable_remote_media(
+        &self,
+        accessed_before: &DateTime<Utc>,
+        max_size: u64,
+        limit: u32,
+    ) -> Result<Vec<RemoteMedia>, MediaCacheError>;
+
+    /// Forget the cached copy of the remote file, unless it was replaced since `media` was read
+    async fn unset_remote_media_cached(&self, media: &RemoteMedia) -> Result<(), MediaCacheError>;
+}
+
+/// Fetches remote files for the media proxy
+#[async_trait::async_trait]
+pub trait MediaClient: Send + Sync + 'static {
+    /// GET the file at `url`, failing with [ProxyMediaError::TooLarge] past `max_size` bytes
+    async fn fetch(&self, url: &HttpUrl, max_size: usize) -> Result<MediaFile, ProxyMediaError>;
+}
+
+/// Where uploaded files are kept
+#[async_trait::async_trait]
+pub trait MediaStorage: Send + Sync + 'static {
+    /// Store `file` at `key`, replacing what was there
+    async fn put(&self, key: &str, file: &MediaFile) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<S: MediaStorage + ?Sized> MediaStorage for Arc<S> {
+    async fn put(&self, key: &str, file: &MediaFile) -> Result<(), StorageError> {
+        self.as_ref().put(key, file).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        self.as_ref().get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.as_ref().delete(key).await
+    }
+}
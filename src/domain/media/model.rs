@@ -0,0 +1,487 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest as _, Sha256};
+
+use crate::{
+    ap,
+    domain::{HttpUrl, Id, account::model::AccountId, ap::model::note::NoteId},
+};
+
+pub type AttachmentId = Id<Attachment>;
+pub type CustomEmojiId = Id<CustomEmoji>;
+
+/// Largest file that can be uploaded
+pub const MAX_UPLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+/// Largest remote file the media proxy fetches
+pub const MAX_REMOTE_MEDIA_SIZE: usize = 40 * 1024 * 1024;
+
+/// Largest custom emoji image that can be uploaded, like Mastodon's
+pub const MAX_EMOJI_SIZE: usize = 256 * 1024;
+
+/// Media types of custom emoji images, which must stay small and may be animated
+pub const EMOJI_MEDIA_TYPES: &[&str] = &["image/png", "image/gif", "image/webp"];
+
+/// What an attachment shows, named like Mastodon does
+///
+/// See https://docs.joinmastodon.org/entities/MediaAttachment/#type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum AttachmentKind {
+    Image,
+    Gifv,
+    Video,
+    Audio,
+    Unknown,
+}
+
+impl AttachmentKind {
+    /// Kind of a file of `media_type`, like `image/png`
+    pub fn from_media_type(media_type: &str) -> Self {
+        match media_type.split('/').next() {
+            Some("image") => Self::Image,
+            Some("video") => Self::Video,
+            Some("audio") => Self::Audio,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// File attached to a note, or uploaded to be
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub(crate) id: AttachmentId,
+    /// who uploaded it. Remote attachments have no account
+    pub(crate) account_id: Option<AccountId>,
+    /// note it is attached to, `None` until the upload is posted
+    pub(crate) note_id: Option<NoteId>,
+    pub(crate) kind: AttachmentKind,
+    /// MIME type like `image/png`
+    pub(crate) media_type: String,
+    /// where the file is served from
+    pub(crate) url: HttpUrl,
+    /// key of uploaded files in the media storage
+    pub(crate) storage_key: Option<String>,
+    /// alt text
+    pub(crate) description: Option<String>,
+    /// size of images in pixels
+    pub(crate) size: Option<(u32, u32)>,
+    /// See https://blurha.sh
+    pub(crate) blurhash: Option<String>,
+    pub(crate) focus: Option<Focus>,
+    /// downscaled image
+    pub(crate) preview: Option<Preview>,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+/// Downscaled version of an image attachment
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub(crate) url: HttpUrl,
+    /// key in the media storage
+    pub(crate) storage_key: String,
+    /// size in pixels
+    pub(crate) size: (u32, u32),
+}
+
+/// Avatar or header of an actor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileImage {
+    pub(crate) url: HttpUrl,
+    /// key of images of local actors in the media storage
+    pub(crate) storage_key: Option<String>,
+}
+
+impl ProfileImage {
+    /// Image of a remote actor, served by its server
+    pub fn remote(url: HttpUrl) -> Self {
+        Self {
+            url,
+            storage_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ProfileImageKind {
+    Avatar,
+    Header,
+}
+
+impl ProfileImageKind {
+    /// Size in pixels images are cropped to, like Mastodon's
+    pub fn size(self) -> (u32, u32) {
+        match self {
+            Self::Avatar => (400, 400),
+            Self::Header => (1500, 500),
+        }
+    }
+}
+
+/// Image shown in place of `:shortcode:` in notes and profiles
+#[derive(Debug, Clone)]
+pub struct CustomEmoji {
+    pub(crate) id: CustomEmojiId,
+    /// name without colons, like `blobcat`
+    pub(crate) shortcode: String,
+    /// server of remote emoji, `None` for the ones of this instance
+    pub(crate) host: Option<String>,
+    /// ActivityPub id of the `Emoji` object
+    pub(crate) uri: HttpUrl,
+    /// where the image is served from
+    pub(crate) url: HttpUrl,
+    /// MIME type like `image/png`, if known
+    pub(crate) media_type: Option<String>,
+    /// key of uploaded images in the media storage
+    pub(crate) storage_key: Option<String>,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) updated_at: DateTime<Utc>,
+}
+
+impl CustomEmoji {
+    /// Whether `shortcode` can name an emoji: two or more ASCII letters, digits or `_`
+    pub fn is_valid_shortcode(shortcode: &str) -> bool {
+        shortcode.len() >= 2
+            && shortcode
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    }
+
+    /// Emoji of a remote note or actor on `host`, as described by an `Emoji` tag
+    ///
+    /// Return `None` if the tag is not a valid emoji.
+    pub fn from_remote(tag: &ap::Tag, host: &str) -> Option<Self> {
+        let ap::Tag::Emoji {
+            id,
+            name,
+            icon,
+            updated,
+        } = tag
+        else {
+            return None;
+        };
+        let shortcode = name.trim_matches(':');
+        if !Self::is_valid_shortcode(shortcode) {
+            return None;
+        }
+        let now = Utc::now();
+        Some(Self {
+            id: CustomEmojiId::new(),
+            shortcode: shortcode.to_string(),
+            host: Some(host.to_string()),
+            uri: id.clone().unwrap_or_else(|| icon.url.clone()),
+            url: icon.url.clone(),
+            media_type: icon.media_type.clone(),
+            storage_key: None,
+            created_at: now,
+            updated_at: updated.unwrap_or(now),
+        })
+    }
+}
+
+impl From<&CustomEmoji> for ap::Tag {
+    fn from(emoji: &CustomEmoji) -> Self {
+        ap::Tag::Emoji {
+            id: Some(emoji.uri.clone()),
+            name: format!(":{}:", emoji.shortcode),
+            icon: ap::Image::builder()
+                .kind(ap::DocumentType::Image)
+                .media_type(emoji.media_type.clone())
+                .url(emoji.url.clone())
+                .build(),
+            updated: Some(emoji.updated_at),
+        }
+    }
+}
+
+/// Point of an image to keep in view when it is cropped, from -1.0 to 1.0 with up and right
+/// positive
+///
+/// See https://docs.joinmastodon.org/api/guidelines/#focal-points
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Focus {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+}
+
+impl Focus {
+    pub fn new(x: f32, y: f32) -> Option<Self> {
+        ((-1.0..=1.0).contains(&x) && (-1.0..=1.0).contains(&y)).then_some(Self { x, y })
+    }
+}
+
+impl std::str::FromStr for Focus {
+    type Err = UploadMediaError;
+
+    /// Parse `x,y` like `-0.5,0.25`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || UploadMediaError::InvalidFile(format!("invalid focus {s}"));
+        let (x, y) = s.split_once(',').ok_or_else(invalid)?;
+        let x = x.trim().parse().map_err(|_| invalid())?;
+        let y = y.trim().parse().map_err(|_| invalid())?;
+        Self::new(x, y).ok_or_else(invalid)
+    }
+}
+
+impl Attachment {
+    /// Attachment of a remote note, as described by its `attachment` property
+    pub fn from_remote(document: &ap::Document) -> Self {
+        let media_type = document
+            .media_type
+            .clone()
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let kind = match document.kind {
+            ap::DocumentType::Image => AttachmentKind::Image,
+            ap::DocumentType::Video => AttachmentKind::Video,
+            ap::DocumentType::Audio => AttachmentKind::Audio,
+            ap::DocumentType::Document | ap::DocumentType::Unknown => {
+                AttachmentKind::from_media_type(&media_type)
+            }
+        };
+        Self {
+            id: AttachmentId::new(),
+            account_id: None,
+            note_id: None,
+            kind,
+            media_type,
+            url: document.url.clone(),
+            storage_key: None,
+            description: document.name.clone().filter(|name| !name.is_empty()),
+            size: document.width.zip(document.height),
+            blurhash: document.blurhash.clone(),
+            focus: document.focal_point.and_then(|[x, y]| Focus::new(x, y)),
+            preview: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl From<&Attachment> for ap::Document {
+    fn from(attachment: &Attachment) -> Self {
+        ap::Document::builder()
+            .kind(ap::DocumentType::Document)
+            .media_type(attachment.media_type.clone())
+            .url(attachment.url.clone())
+            .name(attachment.description.clone())
+            .blurhash(attachment.blurhash.clone())
+            .width(attachment.size.map(|(width, _)| width))
+            .height(attachment.size.map(|(_, height)| height))
+            .focal_point(attachment.focus.map(|focus| [focus.x, focus.y]))
+            .build()
+    }
+}
+
+/// Content of a stored file
+#[derive(Clone)]
+pub struct MediaFile {
+    /// MIME type like `image/png`
+    pub(crate) media_type: String,
+    pub(crate) data: Vec<u8>,
+}
+
+impl std::fmt::Debug for MediaFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MediaFile")
+            .field("media_type", &self.media_type)
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// Id of a remote file in the media proxy, the hex SHA-256 of its URL, so that links to the
+/// proxy are made without looking anything up
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteMediaId(String);
+
+impl RemoteMediaId {
+    pub fn of(url: &HttpUrl) -> Self {
+        Self(format!("{:x}", Sha256::digest(url.as_str().as_bytes())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RemoteMediaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for RemoteMediaId {
+    type Err = ProxyMediaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            Ok(Self(s.to_string()))
+        } else {
+            Err(ProxyMediaError::NotFound)
+        }
+    }
+}
+
+/// Remote file known to the media proxy, with the copy of it in the media storage if any
+#[derive(Debug, Clone)]
+pub struct RemoteMedia {
+    pub(crate) id: RemoteMediaId,
+    pub(crate) url: HttpUrl,
+    /// MIME type of the cached copy
+    pub(crate) media_type: Option<String>,
+    /// key of the cached copy in the media storage
+    pub(crate) storage_key: Option<String>,
+}
+
+/// When cached copies of remote files are dropped
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    /// total size of the cache in bytes, beyond which the least recently used files go
+    pub(crate) max_size: u64,
+    /// how long files are kept since they were last used
+    pub(crate) max_age: chrono::Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct UploadMediaRequest {
+    pub(crate) account_id: AccountId,
+    pub(crate) file: MediaFile,
+    /// alt text
+    pub(crate) description: Option<String>,
+    /// focal point of images, computed when not given
+    pub(crate) focus: Option<Focus>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadMediaError {
+    #[error("invalid file: {0}")]
+    InvalidFile(String),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateEmojiRequest {
+    /// name without colons
+    pub(crate) shortcode: String,
+    pub(crate) file: MediaFile,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateEmojiError {
+    #[error("invalid emoji: {0}")]
+    Invalid(String),
+    #[error("emoji already exists")]
+    AlreadyExists,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindEmojiError {
+    #[error("emoji not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteEmojiError {
+    #[error("emoji not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateAttachmentError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindAttachmentError {
+    #[error("attachment not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProxyMediaError {
+    #[error("media not found")]
+    NotFound,
+    #[error("remote file larger than {MAX_REMOTE_MEDIA_SIZE} bytes")]
+    TooLarge,
+    #[error("unsupported media type {0}")]
+    Unsupported(String),
+    #[error("remote file unavailable: {0}")]
+    Unavailable(String),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MediaCacheError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("file not found")]
+    NotFound,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<CreateAttachmentError> for UploadMediaError {
+    fn from(err: CreateAttachmentError) -> Self {
+        match err {
+            CreateAttachmentError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
+
+impl From<StorageError> for UploadMediaError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::NotFound => Self::Unknown(anyhow::anyhow!("stored file disappeared")),
+            StorageError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
+
+impl From<StorageError> for CreateEmojiError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::NotFound => Self::Unknown(anyhow::anyhow!("stored file disappeared")),
+            StorageError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
+
+impl From<StorageError> for FindAttachmentError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::NotFound => Self::NotFound,
+            StorageError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
+
+impl From<StorageError> for ProxyMediaError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::NotFound => Self::Unknown(anyhow::anyhow!("stored file disappeared")),
+            StorageError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
+
+impl From<MediaCacheError> for ProxyMediaError {
+    fn from(err: MediaCacheError) -> Self {
+        match err {
+            MediaCacheError::Unknown(e) => Self::Unknown(e),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Process uploaded images before they are stored and served
+//!
+//! Images are decoded and encoded again, which drops EXIF and other metadata like GPS
+//! positions after its orientation has been applied to the pixels.
+use std::io::Cursor;
+
+use image::{
+    DynamicImage, GenericImageView as _, ImageDecoder as _, ImageFormat, ImageReader, Limits,
+    codecs::jpeg::JpegEncoder, imageops::FilterType,
+};
+
+use super::model::{Focus, MediaFile};
+
+/// Largest image kept, in pixels, like Mastodon's 3840x2160. Larger ones are downscaled
+const MAX_PIXELS: u32 = 3840 * 2160;
+/// Largest width or height of a decoded image, against decompression bombs
+const MAX_DIMENSION: u32 = 16384;
+/// Previews fit in a box of this size
+const PREVIEW_SIZE: u32 = 640;
+/// Thumbnails blurhash and focal points are computed from fit in a box of this size
+const THUMBNAIL_SIZE: u32 = 64;
+const JPEG_QUALITY: u8 = 90;
+const PREVIEW_JPEG_QUALITY: u8 = 80;
+
+/// Image ready to be stored, with what clients show while loading it
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    /// the image without metadata
+    pub(crate) original: MediaFile,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// downscaled image for timelines
+    pub(crate) preview: MediaFile,
+    pub(crate) preview_width: u32,
+    pub(crate) preview_height: u32,
+    /// See https://blurha.sh
+    pub(crate) blurhash: String,
+    /// where the subject is, so that crops keep it in view
+    pub(crate) focus: Focus,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessImageError {
+    #[error("unsupported image type {0}")]
+    Unsupported(String),
+    #[error("invalid image: {0}")]
+    Invalid(#[from] image::ImageError),
+}
+
+/// Decode `file`, strip its metadata and generate its preview, blurhash and focal point
+///
+/// This is CPU bound, so run it off the async runtime.
+pub fn process_image(file: &MediaFile) -> Result<ProcessedImage, ProcessImageError> {
+    let (mut image, format) = decode(file)?;
+
+    let (width, height) = image.dimensions();
+    let downscale = u64::from(width) * u64::from(height) > u64::from(MAX_PIXELS);
+    if downscale {
+        let scale = (f64::from(MAX_PIXELS) / (f64::from(width) * f64::from(height))).sqrt();
+        image = image.resize(
+            (f64::from(width) * scale) as u32,
+            (f64::from(height) * scale) as u32,
+            FilterType::CatmullRom,
+        );
+    }
+    let (width, height) = image.dimensions();
+
+    let original = encode(file, format, &image, downscale)?;
+
+    let preview_image = if width > PREVIEW_SIZE || height > PREVIEW_SIZE {
+        image.resize(PREVIEW_SIZE, PREVIEW_SIZE, FilterType::Triangle)
+    } else {
+        image.clone()
+    };
+    let (preview_width, preview_height) = preview_image.dimensions();
+    let preview = encode_opaque_as_jpeg(&preview_image, PREVIEW_JPEG_QUALITY)?;
+
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    let blurhash = blurhash(&thumbnail);
+    let focus = focal_point(&thumbnail);
+
+    Ok(ProcessedImage {
+        original,
+        width,
+        height,
+        preview,
+        preview_width,
+        preview_height,
+        blurhash,
+        focus,
+    })
+}
+
+/// Decode `file`, crop it around its center to the aspect ratio of the given size and downscale
+/// it to that size, stripping its metadata like [process_image]
+pub fn process_profile_image(
+    file: &MediaFile,
+    (max_width, max_height): (u32, u32),
+) -> Result<MediaFile, ProcessImageError> {
+    let (mut image, format) = decode(file)?;
+
+    let (width, height) = image.dimensions();
+    let target = f64::from(max_width) / f64::from(max_height);
+    let (crop_width, crop_height) = if f64::from(width) / f64::from(height) > target {
+        (((f64::from(height) * target) as u32).max(1), height)
+    } else {
+        (width, ((f64::from(width) / target) as u32).max(1))
+    };
+    let mut changed = false;
+    if (crop_width, crop_height) != (width, height) {
+        image = image.crop_imm(
+            (width - crop_width) / 2,
+            (height - crop_height) / 2,
+            crop_width,
+            crop_height,
+        );
+        changed = true;
+    }
+    if crop_width > max_width {
+        image = image.resize_exact(max_width, max_height, FilterType::CatmullRom);
+        changed = true;
+    }
+    Ok(encode(file, format, &image, changed)?)
+}
+
+/// Decode `file` and strip its metadata like [process_image], keeping GIFs as they are so that
+/// animated emoji stay animated
+pub fn process_emoji(file: &MediaFile) -> Result<MediaFile, ProcessImageError> {
+    let (image, format) = decode(file)?;
+    match format {
+        ImageFormat::Gif => Ok(file.clone()),
+        _ => Ok(encode_png(&image)?),
+    }
+}
+
+/// Decode `file` with its orientation applied, within the size limits
+fn decode(file: &MediaFile) -> Result<(DynamicImage, ImageFormat), ProcessImageError> {
+    let format = match file.media_type.as_str() {
+        "image/png" => ImageFormat::Png,
+        "image/jpeg" => ImageFormat::Jpeg,
+        "image/gif" => ImageFormat::Gif,
+        "image/webp" => ImageFormat::WebP,
+        media_type => return Err(ProcessImageError::Unsupported(media_type.to_string())),
+    };
+
+    let mut reader = ImageReader::with_format(Cursor::new(&file.data), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok((image, format))
+}
+
+/// Encode `image` decoded from `file` in a format close to the original one
+///
+/// `changed` tells whether the pixels differ from those of `file`.
+fn encode(
+    file: &MediaFile,
+    format: ImageFormat,
+    image: &DynamicImage,
+    changed: bool,
+) -> Result<MediaFile, image::ImageError> {
+    match format {
+        // re-encoding would lose the animation, and GIFs carry no EXIF
+        ImageFormat::Gif if !changed => Ok(file.clone()),
+        ImageFormat::Png | ImageFormat::Gif => encode_png(image),
+        ImageFormat::Jpeg => encode_jpeg(image, JPEG_QUALITY),
+        // WebP can only be encoded losslessly, which would bloat photos
+        _ => encode_opaque_as_jpeg(image, JPEG_QUALITY),
+    }
+}
+
+fn encode_png(image: &DynamicImage) -> Result<MediaFile, image::ImageError> {
+    let mut data = Vec::new();
+    image.write_to(&mut Cursor::new(&mut data), ImageFormat::Png)?;
+    Ok(MediaFile {
+        media_type: "image/png".to_string(),
+        data,
+    })
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> Result<MediaFile, image::ImageError> {
+    let mut data = Vec::new();
+    // JPEG has no alpha channel
+    let rgb = DynamicImage::ImageRgb8(image.to_rgb8());
+    rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut data, quality))?;
+    Ok(MediaFile {
+        media_type: "image/jpeg".to_string(),
+        data,
+    })
+}
+
+/// JPEG unless the image is transparent, PNG otherwise
+fn encode_opaque_as_jpeg(
+    image: &DynamicImage,
+    quality: u8,
+) -> Result<MediaFile, image::ImageError> {
+    let transparent = image.color().has_alpha() && image.to_rgba8().pixels().any(|p| p[3] < 255);
+    if transparent {
+        encode_png(image)
+    } else {
+        encode_jpeg(image, quality)
+    }
+}
+
+fn blurhash(thumbnail: &DynamicImage) -> String {
+    let (width, height) = thumbnail.dimensions();
+    // components follow the aspect ratio, as blurha.sh suggests
+    let (x, y) = if width >= height { (4, 3) } else { (3, 4) };
+    blurhash::encode(x, y, width, height, thumbnail.to_rgba8().as_raw())
+        .expect("components are in range")
+}
+
+/// Center of the detail of the image, where edges are
+///
+/// A plain image has its focus at the center.
+fn focal_point(thumbnail: &DynamicImage) -> Focus {
+    let gray = thumbnail.to_luma8();
+    let (width, height) = gray.dimensions();
+    if width < 3 || height < 3 {
+        return Focus::default();
+    }
+
+    let (mut sum, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let at = |x: u32, y: u32| f64::from(gray.get_pixel(x, y)[0]);
+            let dx = at(x + 1, y) - at(x - 1, y);
+            let dy = at(x, y + 1) - at(x, y - 1);
+            let energy = (dx * dx + dy * dy).sqrt();
+            sum += energy;
+            sum_x += energy * f64::from(x);
+            sum_y += energy * f64::from(y);
+        }
+    }
+    if sum < f64::EPSILON {
+        return Focus::default();
+    }
+
+    let round = |v: f64| ((v * 100.0).round() / 100.0).clamp(-1.0, 1.0) as f32;
+    Focus {
+        x: round(sum_x / sum / f64::from(width - 1) * 2.0 - 1.0),
+        // up is positive
+        y: round(1.0 - sum_y / sum / f64::from(height - 1) * 2.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// EXIF telling the image is to be turned clockwise, with a GPS position
+    fn exif_segment() -> Vec<u8> {
+        let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec();
+        // IFD0: orientation 6, then the offset of the GPS IFD
+        tiff.extend([0, 2]);
+        tiff.extend([0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+        tiff.extend([0x88, 0x25, 0, 4, 0, 0, 0, 1, 0, 0, 0, 38]);
+        tiff.extend([0, 0, 0, 0]);
+        // GPS IFD: latitude reference "N"
+        tiff.extend([0, 1]);
+        tiff.extend([0, 1, 0, 2, 0, 0, 0, 2, b'N', 0, 0, 0]);
+        tiff.extend([0, 0, 0, 0]);
+
+        let mut segment = vec![0xff, 0xe1];
+        segment.extend(u16::try_from(2 + 6 + tiff.len()).unwrap().to_be_bytes());
+        segment.extend(b"Exif\0\0");
+        segment.extend(tiff);
+        segment
+    }
+
+    fn red(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([200, 0, 0])))
+    }
+
+    #[test]
+    fn test_process_image_strips_exif() {
+        let plain = encode_jpeg(&red(8, 4), JPEG_QUALITY).unwrap().data;
+        // APP1 goes right after the start of image marker
+        let mut data = plain[..2].to_vec();
+        data.extend(exif_segment());
+        data.extend(&plain[2..]);
+        let file = MediaFile {
+            media_type: "image/jpeg".to_string(),
+            data,
+        };
+
+        let processed = process_image(&file).unwrap();
+
+        // turned as the EXIF tells, then dropped with the GPS position
+        assert_eq!((processed.width, processed.height), (4, 8));
+        for output in [&processed.original, &processed.preview] {
+            assert_eq!(output.media_type, "image/jpeg");
+            assert!(!output.data.windows(2).any(|marker| marker == [0xff, 0xe1]));
+            assert!(!output.data.windows(4).any(|bytes| bytes == b"Exif"));
+        }
+    }
+
+    #[rstest]
+    #[case::landscape(8, 4, 'L')]
+    #[case::portrait(4, 8, 'T')]
+    fn test_blurhash(#[case] width: u32, #[case] height: u32, #[case] components: char) {
+        let file = encode_png(&red(width, height)).unwrap();
+
+        let blurhash = process_image(&file).unwrap().blurhash;
+
+        // the first character tells the components, 4x3 or 3x4 each taking 2 characters after
+        // the 6 of the size, maximum and average color
+        assert_eq!(blurhash.chars().next(), Some(components));
+        assert_eq!(blurhash.len(), 6 + 2 * 11);
+        // the plain color comes back, within the rounding of the components
+        let pixels = blurhash::decode(&blurhash, 4, 4, 1.0).unwrap();
+        for pixel in pixels.chunks(4) {
+            let [r, g, b, _] = pixel else { unreachable!() };
+            assert!(r.abs_diff(200) < 16 && *g < 16 && *b < 16, "{pixel:?}");
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::{account::model::AccountId, hosturl::HostUrlService};
+
+use super::{
+    adapter::{MediaClient, MediaRepository, MediaService, MediaStorage},
+    model::{
+        Attachment, AttachmentId, AttachmentKind, CachePolicy, CreateEmojiError,
+        CreateEmojiRequest, CustomEmoji, CustomEmojiId, DeleteEmojiError, EMOJI_MEDIA_TYPES,
+        FindAttachmentError, FindEmojiError, MAX_EMOJI_SIZE, MAX_REMOTE_MEDIA_SIZE,
+        MediaCacheError, MediaFile, Preview, ProfileImage, ProfileImageKind, ProxyMediaError,
+        RemoteMediaId, StorageError, UploadMediaError, UploadMediaRequest,
+    },
+    process,
+};
+
+/// How many cached files are evicted per query
+const EVICTION_BATCH: u32 = 100;
+
+#[derive(Debug, Clone)]
+pub struct Service<MR, S, C, H> {
+    media_repo: MR,
+    storage: S,
+    client: C,
+    host_url: H,
+    cache_policy: CachePolicy,
+}
+
+impl<MR, S, C, H> Service<MR, S, C, H>
+where
+    MR: MediaRepository,
+    S: MediaStorage,
+    C: MediaClient,
+    H: HostUrlService,
+{
+    pub fn new(
+        media_repo: MR,
+        storage: S,
+        client: C,
+        host_url: H,
+        cache_policy: CachePolicy,
+    ) -> Self {
+        Self {
+            media_repo,
+            storage,
+            client,
+            host_url,
+            cache_policy,
+        }
+    }
+}
+
+/// File name extensions of media types, so that stored files open with the right program
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("video/mp4", "mp4"),
+    ("video/webm", "webm"),
+    ("video/quicktime", "mov"),
+    ("audio/mpeg", "mp3"),
+    ("audio/ogg", "ogg"),
+    ("audio/wav", "wav"),
+    ("audio/flac", "flac"),
+];
+
+fn extension(media_type: &str) -> &'static str {
+    EXTENSIONS
+        .iter()
+        .find(|(m, _)| *m == media_type)
+        .map_or("bin", |(_, extension)| extension)
+}
+
+/// Media type of a stored file from the extension of its key
+fn media_type_of(key: &str) -> &'static str {
+    let extension = key.rsplit_once('.').map_or("", |(_, extension)| extension);
+    EXTENSIONS
+        .iter()
+        .find(|(_, e)| *e == extension)
+        .map_or("application/octet-stream", |(media_type, _)| media_type)
+}
+
+#[async_trait::async_trait]
+impl<MR, S, C, H> MediaService for Service<MR, S, C, H>
+where
+    MR: MediaRepository,
+    S: MediaStorage,
+    C: MediaClient,
+    H: HostUrlService,
+{
+    async fn upload(&self, req: UploadMediaRequest) -> Result<Attachment, UploadMediaError> {
+        let UploadMediaRequest {
+            account_id,
+            file,
+            description,
+            focus,
+        } = req;
+        if file.data.is_empty() {
+            return Err(UploadMediaError::InvalidFile("empty file".to_string()));
+        }
+        let kind = AttachmentKind::from_media_type(&file.media_type);
+        if kind == AttachmentKind::Unknown || extension(&file.media_type) == "bin" {
+            return Err(UploadMediaError::InvalidFile(format!(
+                "unsupported media type {}",
+                file.media_type
+            )));
+        }
+
+        let id = AttachmentId::new();
+        let mut attachment = Attachment {
+            id: id.clone(),
+            account_id: Some(account_id),
+            note_id: None,
+            kind,
+            media_type: file.media_type.clone(),
+            url: self
+                .host_url
+                .media_url(&format!("{id}.{}", extension(&file.media_type))),
+            storage_key: None,
+            description: description.filter(|d| !d.is_empty()),
+            size: None,
+            blurhash: None,
+            focus: None,
+            preview: None,
+            created_at: Utc::now(),
+        };
+
+        // images are not served as uploaded, as they may tell where they were taken
+        let mut files = Vec::new();
+        if kind == AttachmentKind::Image {
+            let image = tokio::task::spawn_blocking(move || process::process_image(&file))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?
+                .map_err(|e| UploadMediaError::InvalidFile(e.to_string()))?;
+            let key = format!("{id}.{}", extension(&image.original.media_type));
+            let preview_key = format!("{id}-small.{}", extension(&image.preview.media_type));
+            attachment.media_type = image.original.media_type.clone();
+            attachment.url = self.host_url.media_url(&key);
+            attachment.storage_key = Some(key.clone());
+            attachment.size = Some((image.width, image.height));
+            attachment.blurhash = Some(image.blurhash);
+            attachment.focus = Some(focus.unwrap_or(image.focus));
+            attachment.preview = Some(Preview {
+                url: self.host_url.media_url(&preview_key),
+                storage_key: preview_key.clone(),
+                size: (image.preview_width, image.preview_height),
+            });
+            files.push((key, image.original));
+            files.push((preview_key, image.preview));
+        } else {
+            let key = format!("{id}.{}", extension(&file.media_type));
+            attachment.storage_key = Some(key.clone());
+            files.push((key, file));
+        }
+
+        let mut stored = Vec::new();
+        let mut result = Ok(());
+        for (key, file) in &files {
+            if let Err(e) = self.storage.put(key, file).await {
+                result = Err(UploadMediaError::from(e));
+                break;
+            }
+            stored.push(key);
+        }
+        if result.is_ok() {
+            result = self
+                .media_repo
+                .create_attachment(&attachment)
+                .await
+                .map_err(UploadMediaError::from);
+        }
+        if let Err(e) = result {
+            for key in stored {
+                if let Err(e) = self.storage.delete(key).await {
+                    tracing::warn!(error = %e, key, "Failed to delete orphaned file");
+                }
+            }
+            return Err(e);
+        }
+        Ok(attachment)
+    }
+
+    async fn find_attachment(
+        &self,
+        account_id: &AccountId,
+        id: &AttachmentId,
+    ) -> Result<Attachment, FindAttachmentError> {
+        let attachment = self.media_repo.find_attachment(id).await?;
+        if attachment.account_id.as_ref() != Some(account_id) {
+            return Err(FindAttachmentError::NotFound);
+        }
+        Ok(attachment)
+    }
+
+    async fn upload_profile_image(
+        &self,
+        kind: ProfileImageKind,
+        file: MediaFile,
+    ) -> Result<ProfileImage, UploadMediaError> {
+        if file.data.is_empty() {
+            return Err(UploadMediaError::InvalidFile("empty file".to_string()));
+        }
+        if AttachmentKind::from_media_type(&file.media_type) != AttachmentKind::Image {
+            return Err(UploadMediaError::InvalidFile(format!(
+                "unsupported media type {}",
+                file.media_type
+            )));
+        }
+        let file =
+            tokio::task::spawn_blocking(move || process::process_profile_image(&file, kind.size()))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?
+                .map_err(|e| UploadMediaError::InvalidFile(e.to_string()))?;
+
+        let key = format!(
+            "{kind}-{}.{}",
+            uuid::Uuid::now_v7(),
+            extension(&file.media_type)
+        );
+        self.storage.put(&key, &file).await?;
+        Ok(ProfileImage {
+            url: self.host_url.media_url(&key),
+            storage_key: Some(key),
+        })
+    }
+
+    async fn delete_profile_image(&self, image: &ProfileImage) -> Result<(), StorageError> {
+        match &image.storage_key {
+            Some(key) => self.storage.delete(key).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn create_emoji(&self, req: CreateEmojiRequest) -> Result<CustomEmoji, CreateEmojiError> {
+        let CreateEmojiRequest { shortcode, file } = req;
+        if !CustomEmoji::is_valid_shortcode(&shortcode) {
+            return Err(CreateEmojiError::Invalid(
+                "shortcode must be 2 or more letters, digits or underscores".to_string(),
+            ));
+        }
+        if file.data.is_empty() {
+            return Err(CreateEmojiError::Invalid("empty file".to_string()));
+        }
+        if file.data.len() > MAX_EMOJI_SIZE {
+            return Err(CreateEmojiError::Invalid(format!(
+                "image larger than {MAX_EMOJI_SIZE} bytes"
+            )));
+        }
+        if !EMOJI_MEDIA_TYPES.contains(&file.media_type.as_str()) {
+            return Err(CreateEmojiError::Invalid(format!(
+                "unsupported media type {}",
+                file.media_type
+            )));
+        }
+        let file = tokio::task::spawn_blocking(move || process::process_emoji(&file))
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map_err(|e| CreateEmojiError::Invalid(e.to_string()))?;
+
+        let id = CustomEmojiId::new();
+        let key = format!("emoji-{id}.{}", extension(&file.media_type));
+        let now = Utc::now();
+        let emoji = CustomEmoji {
+            id: id.clone(),
+            shortcode,
+            host: None,
+            uri: self.host_url.emoji_url(&id.to_string()),
+            url: self.host_url.media_url(&key),
+            media_type: Some(file.media_type.clone()),
+            storage_key: Some(key.clone()),
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.put(&key, &file).await?;
+        if let Err(e) = self.media_repo.create_emoji(&emoji).await {
+            if let Err(e) = self.storage.delete(&key).await {
+                tracing::warn!(error = %e, key, "Failed to delete orphaned file");
+            }
+            return Err(e);
+        }
+        Ok(emoji)
+    }
+
+    async fn find_local_emojis(&self) -> Result<Vec<CustomEmoji>, FindEmojiError> {
+        self.media_repo.find_local_emojis().await
+    }
+
+    async fn find_emoji(&self, id: &CustomEmojiId) -> Result<CustomEmoji, FindEmojiError> {
+        self.media_repo.find_emoji(id).await
+    }
+
+    async fn delete_emoji(&self, shortcode: &str) -> Result<(), DeleteEmojiError> {
+        // forgotten first, so that notes stop showing it before the image goes
+        let key = self.media_repo.delete_local_emoji(shortcode).await?;
+        if let Some(key) = key {
+            match self.storage.delete(&key).await {
+                Ok(()) | Err(StorageError::NotFound) => {}
+                Err(e) => tracing::warn!(error = %e, key, "Failed to delete emoji image"),
+            }
+        }
+        Ok(())
+    }
+
+    async fn find_file(&self, key: &str) -> Result<MediaFile, FindAttachmentError> {
+        let media_type = match self.media_repo.find_attachment_by_storage_key(key).await {
+            Ok(attachment) if attachment.storage_key.as_deref() == Some(key) => {
+                attachment.media_type
+            }
+            Ok(_) => media_type_of(key).to_string(),
+            Err(FindAttachmentError::NotFound) if self.media_repo.is_stored_image(key).await? => {
+                media_type_of(key).to_string()
+            }
+            Err(e) => return Err(e),
+        };
+        let data = self.storage.get(key).await?;
+        Ok(MediaFile { media_type, data })
+    }
+
+    async fn proxy(&self, id: &RemoteMediaId) -> Result<MediaFile, ProxyMediaError> {
+        let media = self.media_repo.find_remote_media(id).await?;
+        if let (Some(media_type), Some(key)) = (&media.media_type, &media.storage_key) {
+            match self.storage.get(key).await {
+                Ok(data) => {
+                    if let Err(e) = self.media_repo.touch_remote_media(id).await {
+                        tracing::warn!(error = %e, "Failed to touch cached file");
+                    }
+                    return Ok(MediaFile {
+                        media_type: media_type.clone(),
+                        data,
+                    });
+                }
+                // evicted while it was being read
+                Err(StorageError::NotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let file = self.client.fetch(&media.url, MAX_REMOTE_MEDIA_SIZE).await?;
+        // only what could be uploaded is served, so that nothing else runs on our origin
+        if AttachmentKind::from_media_type(&file.media_type) == AttachmentKind::Unknown
+            || extension(&file.media_type) == "bin"
+        {
+            return Err(ProxyMediaError::Unsupported(file.media_type));
+        }
+        let key = format!("cache-{id}.{}", extension(&file.media_type));
+        self.storage.put(&key, &file).await?;
+        self.media_repo
+            .set_remote_media_cached(id, &file.media_type, &key, file.data.len() as u64)
+            .await?;
+        Ok(file)
+    }
+
+    async fn evict_cache(&self) -> Result<usize, MediaCacheError> {
+        let accessed_before = Utc::now()
+            .checked_sub_signed(self.cache_policy.max_age)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let mut evicted = 0;
+        loop {
+            let batch = self
+                .media_repo
+                .find_evictable_remote_media(
+                    &accessed_before,
+                    self.cache_policy.max_size,
+                    EVICTION_BATCH,
+                )
+                .await?;
+            for media in &batch {
+                let Some(key) = &media.storage_key else {
+                    continue;
+                };
+                // forgotten first, so that the file is fetched again rather than found missing
+                self.media_repo.unset_remote_media_cached(media).await?;
+                match self.storage.delete(key).await {
+                    Ok(()) | Err(StorageError::NotFound) => {}
+                    Err(e) => tracing::warn!(error = %e, key, "Failed to delete cached file"),
+                }
+                evicted += 1;
+            }
+            if batch.len() < EVICTION_BATCH as usize {
+                return Ok(evicted);
+            }
+        }
+    }
+}
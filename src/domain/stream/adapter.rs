@@ -0,0 +1,28 @@
+use crate::domain::account::model::AccountId;
+
+use super::model::{
+    PublishEventError, Stream, StreamError, StreamEvent, StreamMessage, Subscription,
+};
+
+#[async_trait::async_trait]
+pub trait StreamService: Send + Sync + 'static {
+    /// Start receiving events
+    fn subscribe(&self) -> Subscription;
+
+    /// Messages the event makes on `streams` of `viewer`. Empty if it does not concern them
+    async fn messages(
+        &self,
+        event: &StreamEvent,
+        streams: &[Stream],
+        viewer: Option<&AccountId>,
+    ) -> Result<Vec<StreamMessage>, StreamError>;
+}
+
+/// Broadcast of events between every mozu process
+#[async_trait::async_trait]
+pub trait EventBus: Send + Sync + 'static {
+    /// Send the event to the subscribers of every process, this one included
+    async fn publish(&self, event: StreamEvent) -> Result<(), PublishEventError>;
+
+    fn subscribe(&self) -> Subscription;
+}
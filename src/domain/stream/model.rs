@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::domain::ap::model::{
+    note::{FindNoteError, NoteId},
+    notification::{Notification, NotificationView},
+    timeline::{FindTimelineError, TimelineItem, TimelineItemId},
+};
+
+/// Timeline a client follows as it changes
+///
+/// See https://docs.joinmastodon.org/methods/streaming/#streams
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::AsRefStr, strum::Display, strum::EnumString,
+)]
+pub enum Stream {
+    /// home timeline and notifications of the account
+    #[strum(serialize = "user")]
+    User,
+    /// notifications of the account
+    #[strum(serialize = "user:notification")]
+    UserNotification,
+    /// every note, without reblogs
+    #[strum(serialize = "public")]
+    Public,
+    /// notes of local actors
+    #[strum(serialize = "public:local")]
+    PublicLocal,
+    /// notes of remote actors
+    #[strum(serialize = "public:remote")]
+    PublicRemote,
+}
+
+impl Stream {
+    /// Whether the stream belongs to an account, rather than being visible to anyone
+    pub fn is_user(&self) -> bool {
+        matches!(self, Stream::User | Stream::UserNotification)
+    }
+
+    /// Whether a note, local or not, shows in this public stream
+    pub fn shows_note(&self, local: bool) -> bool {
+        match self {
+            Stream::Public => true,
+            Stream::PublicLocal => local,
+            Stream::PublicRemote => !local,
+            Stream::User | Stream::UserNotification => false,
+        }
+    }
+
+    /// Whether deletions are sent on the stream
+    pub fn shows_deletes(&self) -> bool {
+        *self != Stream::UserNotification
+    }
+}
+
+/// What happened, as published to every process
+///
+/// Only ids are carried, each subscriber looks up what its viewer sees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// Note or reblog `item_id` entered home feeds and, for notes, public timelines
+    Update {
+        item_id: TimelineItemId,
+        note_id: NoteId,
+        reblog: bool,
+        /// whether the note or reblog was made by a local actor
+        local: bool,
+    },
+    /// Note or reblog `item_id` was deleted
+    Delete {
+        item_id: TimelineItemId,
+    },
+    Notification(Notification),
+}
+
+/// Events published from the time of subscription
+#[derive(Debug)]
+pub struct Subscription(broadcast::Receiver<StreamEvent>);
+
+impl Subscription {
+    pub fn new(receiver: broadcast::Receiver<StreamEvent>) -> Self {
+        Self(receiver)
+    }
+
+    /// Wait for the next event. Events missed by a slow subscriber are skipped
+    ///
+    /// Return `None` once nothing is published anymore.
+    pub async fn recv(&mut self) -> Option<StreamEvent> {
+        loop {
+            match self.0.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Stream subscriber lagged behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// What a subscriber is told
+#[derive(Debug, Clone)]
+pub enum StreamPayload {
+    Update(TimelineItem),
+    Delete(TimelineItemId),
+    Notification(Box<NotificationView>),
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamMessage {
+    /// stream the message is sent on
+    pub(crate) stream: Stream,
+    pub(crate) payload: StreamPayload,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PublishEventError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+impl From<FindTimelineError> for StreamError {
+    fn from(e: FindTimelineError) -> Self {
+        match e {
+            FindTimelineError::Unknown(e) => StreamError::Unknown(e),
+        }
+    }
+}
+
+impl From<FindNoteError> for StreamError {
+    fn from(e: FindNoteError) -> Self {
+        StreamError::Unknown(e.into())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::domain::{
+    account::model::AccountId,
+    ap::{
+        adapter::ApService,
+        model::{
+            note::{FindNoteError, NoteId},
+            notification::{FindNotificationError, Notification, NotificationView},
+            timeline::TimelineItem,
+        },
+    },
+};
+
+use super::{
+    adapter::{EventBus, StreamService},
+    model::{Stream, StreamError, StreamEvent, StreamMessage, StreamPayload, Subscription},
+};
+
+#[derive(Debug, Clone)]
+pub struct Service<B, AP> {
+    bus: B,
+    ap: AP,
+}
+
+impl<B, AP> Service<B, AP>
+where
+    B: EventBus,
+    AP: ApService,
+{
+    pub fn new(bus: B, ap: AP) -> Self {
+        Self { bus, ap }
+    }
+
+    /// Return the note unless it was deleted since
+    async fn note(
+        &self,
+        note_id: &NoteId,
+        viewer: Option<&AccountId>,
+    ) -> Result<Option<TimelineItem>, StreamError> {
+        match self.ap.find_note_view(note_id, viewer).await {
+            Ok(note) => Ok(Some(TimelineItem::Note(Box::new(note)))),
+            Err(FindNoteError::NotFound | FindNoteError::Gone(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the notification unless it was dismissed or its note deleted since
+    async fn notification(
+        &self,
+        notification: &Notification,
+    ) -> Result<Option<NotificationView>, StreamError> {
+        match self
+            .ap
+            .find_notification(&notification.account_id, &notification.id)
+            .await
+        {
+            Ok(view) => Ok(Some(view)),
+            Err(FindNotificationError::NotFound) => Ok(None),
+            Err(FindNotificationError::Unknown(e)) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<B, AP> StreamService for Service<B, AP>
+where
+    B: EventBus,
+    AP: ApService,
+{
+    fn subscribe(&self) -> Subscription {
+        self.bus.subscribe()
+    }
+
+    async fn messages(
+        &self,
+        event: &StreamEvent,
+        streams: &[Stream],
+        viewer: Option<&AccountId>,
+    ) -> Result<Vec<StreamMessage>, StreamError> {
+        let mut messages = Vec::new();
+        match event {
+            StreamEvent::Update {
+                item_id,
+                note_id,
+                reblog,
+                local,
+            } => {
+                // looked up once for every public stream
+                let mut public_item = None;
+                for &stream in streams {
+                    let item = match (stream, viewer) {
+                        (Stream::User, Some(viewer)) => {
+                            self.ap.find_home_timeline_item(viewer, item_id).await?
+                        }
+                        _ if !reblog && stream.shows_note(*local) => {
+                            if public_item.is_none() {
+                                public_item = Some(self.note(note_id, viewer).await?);
+                            }
+                            public_item.clone().flatten()
+                        }
+                        _ => None,
+                    };
+                    if let Some(item) = item {
+                        messages.push(StreamMessage {
+                            stream,
+                            payload: StreamPayload::Update(item),
+                        });
+                    }
+                }
+            }
+            StreamEvent::Delete { item_id } => {
+                messages.extend(streams.iter().filter(|s| s.shows_deletes()).map(|&stream| {
+                    StreamMessage {
+                        stream,
+                        payload: StreamPayload::Delete(item_id.clone()),
+                    }
+                }));
+            }
+            StreamEvent::Notification(notification) => {
+                if viewer != Some(&notification.account_id) {
+                    return Ok(messages);
+                }
+                let streams = streams.iter().filter(|stream| stream.is_user());
+                let mut view = None;
+                for &stream in streams {
+                    if view.is_none() {
+                        view = Some(self.notification(notification).await?);
+                    }
+                    if let Some(Some(view)) = &view {
+                        messages.push(StreamMessage {
+                            stream,
+                            payload: StreamPayload::Notification(Box::new(view.clone())),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(messages)
+    }
+}
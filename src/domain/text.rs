@@ -0,0 +1,67 @@
+pub(crate) mod emoji;
+pub(crate) mod hashtag;
+pub(crate) mod mention;
+pub(crate) mod rel_me;
+pub(crate) mod render;
+pub(crate) mod sanitize;
+
+use std::str::FromStr;
+
+/// Media type of the text a user wrote
+///
+/// See https://docs.joinmastodon.org/spec/activitypub/#source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SourceMediaType {
+    /// Plain text. URLs are linked and line breaks are kept
+    #[default]
+    PlainText,
+    /// CommonMark
+    Markdown,
+    /// HTML. Sanitized before use
+    Html,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SourceMediaTypeError {
+    #[error("media type {0} is not supported")]
+    Unsupported(String),
+}
+
+impl SourceMediaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceMediaType::PlainText => "text/plain",
+            SourceMediaType::Markdown => "text/markdown",
+            SourceMediaType::Html => "text/html",
+        }
+    }
+
+    /// Parse a media type local notes may be written in
+    ///
+    /// `text/html` is rejected because it is only kept as the source of remote notes.
+    pub fn from_local_str(s: &str) -> Result<Self, SourceMediaTypeError> {
+        match s.parse()? {
+            SourceMediaType::Html => Err(SourceMediaTypeError::Unsupported(s.to_string())),
+            media_type => Ok(media_type),
+        }
+    }
+}
+
+impl FromStr for SourceMediaType {
+    type Err = SourceMediaTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text/plain" => Ok(SourceMediaType::PlainText),
+            "text/markdown" => Ok(SourceMediaType::Markdown),
+            "text/html" => Ok(SourceMediaType::Html),
+            _ => Err(SourceMediaTypeError::Unsupported(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for SourceMediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
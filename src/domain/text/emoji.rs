@@ -0,0 +1,155 @@
+//! Custom emoji written as `:shortcode:` in notes and profiles
+use std::sync::LazyLock;
+
+static SHORTCODE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r":([a-zA-Z0-9_]{2,}):").unwrap());
+
+/// Whether `c` may stand next to a shortcode. Like Mastodon, shortcodes glued to letters,
+/// digits or other colons are not emoji, so that times like `12:30:45` are left alone
+fn is_boundary(c: Option<char>) -> bool {
+    c.is_none_or(|c| !c.is_alphanumeric() && c != ':')
+}
+
+/// Shortcodes of the text in the order they appear, deduplicated
+pub fn extract(text: &str) -> Vec<String> {
+    let mut shortcodes = Vec::new();
+    let mut start = 0;
+    while let Some(m) = SHORTCODE.captures_at(text, start) {
+        let (whole, shortcode) = (m.get(0).unwrap(), m.get(1).unwrap());
+        let before = text[..whole.start()].chars().next_back();
+        let after = text[whole.end()..].chars().next();
+        if is_boundary(before) && is_boundary(after) {
+            if !shortcodes.iter().any(|s| s == shortcode.as_str()) {
+                shortcodes.push(shortcode.as_str().to_string());
+            }
+            start = whole.end();
+        } else {
+            // the closing colon may open the next candidate
+            start = shortcode.end();
+        }
+    }
+    shortcodes
+}
+
+/// Emoji a reaction is made with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction<'a> {
+    /// emoji of Unicode, like `👍`
+    Unicode(&'a str),
+    /// custom emoji named like `blobcat`, `:blobcat:` or `blobcat@example.com`
+    Custom {
+        shortcode: &'a str,
+        host: Option<&'a str>,
+    },
+}
+
+impl<'a> Reaction<'a> {
+    /// Return `None` if `reaction` is neither a shortcode nor looks like an emoji
+    pub fn parse(reaction: &'a str) -> Option<Self> {
+        let reaction = reaction.trim();
+        let name = reaction
+            .strip_prefix(':')
+            .and_then(|name| name.strip_suffix(':'))
+            .unwrap_or(reaction);
+        let (shortcode, host) = match name.split_once('@') {
+            // Misskey names local emoji `blobcat@.`
+            Some((shortcode, host)) => (shortcode, Some(host).filter(|host| *host != ".")),
+            None => (name, None),
+        };
+        if shortcode.len() >= 2
+            && shortcode
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            return Some(Self::Custom { shortcode, host });
+        }
+        is_unicode_emoji(reaction).then_some(Self::Unicode(reaction))
+    }
+}
+
+/// Whether `s` looks like a single emoji of Unicode, sequences of them joined by ZWJ included
+///
+/// Checking the Unicode emoji data exactly is not worth it, so this only rejects text: a few
+/// characters, none of them whitespace or ASCII other than keycap bases like `1` of `1️⃣`, and
+/// at least one in the symbol and pictograph blocks.
+fn is_unicode_emoji(s: &str) -> bool {
+    let is_symbol = |c: char| matches!(c, '\u{a9}' | '\u{ae}' | '\u{203c}'..='\u{3299}' | '\u{1f000}'..='\u{1faff}');
+    (1..=16).contains(&s.chars().count())
+        && s.chars().all(|c| {
+            !c.is_whitespace()
+                && !c.is_control()
+                && (!c.is_ascii() || c.is_ascii_digit() || c == '#' || c == '*')
+        })
+        && s.chars().any(is_symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::alone(":blobcat:", &["blobcat"])]
+    #[case::in_sentence("hi :blobcat: there", &["blobcat"])]
+    #[case::adjacent(":a_b::cd:", &[])]
+    #[case::separated(":ab: :cd:", &["ab", "cd"])]
+    #[case::repeated(":ab: :cd: :ab:", &["ab", "cd"])]
+    #[case::too_short(":a:b:", &[])]
+    #[case::shared_colon(":ab:cd:", &[])]
+    #[case::time("12:30:45", &[])]
+    #[case::glued_word("word:blobcat:", &[])]
+    #[case::glued_word_after(":blobcat:s", &[])]
+    #[case::punctuation("(:blobcat:)!", &["blobcat"])]
+    #[case::url("https://example.com/:blobcat:/a", &["blobcat"])]
+    #[case::url_port("https://example.com:8080:", &[])]
+    #[case::not_ascii(":café:", &[])]
+    fn test_extract(#[case] text: &str, #[case] expected: &[&str]) {
+        assert_eq!(extract(text), expected);
+    }
+
+    #[rstest]
+    #[case::thumbs_up("👍", true)]
+    #[case::skin_tone("👍🏽", true)]
+    #[case::variation("❤️", true)]
+    #[case::copyright("©", true)]
+    #[case::flag("🇯🇵", true)]
+    #[case::keycap_digit("1️⃣", true)]
+    #[case::keycap_hash("#️⃣", true)]
+    #[case::keycap_star("*️⃣", true)]
+    #[case::zwj_family("👨‍👩‍👧", true)]
+    #[case::zwj_profession("🧑‍💻", true)]
+    #[case::empty("", false)]
+    #[case::digit("1", false)]
+    #[case::hash("#", false)]
+    #[case::letter("a", false)]
+    #[case::word("hello", false)]
+    #[case::punctuation(":)", false)]
+    #[case::emoji_and_ascii("👍a", false)]
+    #[case::emoji_and_space("👍 👍", false)]
+    #[case::letters_only("日本", false)]
+    #[case::too_long(&"👍".repeat(17), false)]
+    fn test_is_unicode_emoji(#[case] s: &str, #[case] expected: bool) {
+        assert_eq!(is_unicode_emoji(s), expected);
+    }
+
+    #[rstest]
+    #[case::unicode("👍", Some(Reaction::Unicode("👍")))]
+    #[case::keycap("1️⃣", Some(Reaction::Unicode("1️⃣")))]
+    #[case::name("blobcat", Some(Reaction::Custom { shortcode: "blobcat", host: None }))]
+    #[case::shortcode(":blobcat:", Some(Reaction::Custom { shortcode: "blobcat", host: None }))]
+    #[case::trimmed(" :blobcat: ", Some(Reaction::Custom { shortcode: "blobcat", host: None }))]
+    #[case::remote(
+        ":blobcat@example.com:",
+        Some(Reaction::Custom { shortcode: "blobcat", host: Some("example.com") })
+    )]
+    #[case::misskey_local("blobcat@.", Some(Reaction::Custom { shortcode: "blobcat", host: None }))]
+    #[case::too_short(":a:", None)]
+    #[case::nested(":a:b:", None)]
+    #[case::ascii("hello world", None)]
+    #[case::digit("1", None)]
+    fn test_parse_reaction(#[case] reaction: &str, #[case] expected: Option<Reaction>) {
+        assert_eq!(Reaction::parse(reaction), expected);
+    }
+}
@@ -0,0 +1,30 @@
+//! Hashtags written in notes, indexed for search
+use std::sync::LazyLock;
+
+/// `#` at a word start followed by letters, digits or `_`, not only digits
+static HASHTAG: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?:^|[^\w/#&])#([\p{L}\p{N}_]*[\p{L}_][\p{L}\p{N}_]*)").unwrap()
+});
+
+/// Hashtag names of the text in the order they appear, normalized and deduplicated
+pub fn extract(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for captures in HASHTAG.captures_iter(text) {
+        let name = captures[1].to_lowercase();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Name a hashtag is stored under: lowercased without the leading `#`
+///
+/// Return `None` if it is not a valid hashtag.
+pub fn normalize(name: &str) -> Option<String> {
+    let name = name.strip_prefix('#').unwrap_or(name);
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !name.chars().all(|c| c.is_numeric());
+    valid.then(|| name.to_lowercase())
+}
@@ -0,0 +1,49 @@
+//! Mentions of local accounts written in notes
+use std::sync::LazyLock;
+
+/// `@name` at a word start, optionally followed by `@host`
+static MENTION: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?:^|[^\w/@.])@([a-zA-Z0-9_]{1,24})(?:@([\w-]+(?:\.[\w-]+)*))?\b").unwrap()
+});
+
+/// Names of the local accounts mentioned in the text in the order they appear, deduplicated
+///
+/// A mention is local when it has no host, or `host` as written in the actor URLs.
+pub fn extract_local(text: &str, host: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for captures in MENTION.captures_iter(text) {
+        let local = captures
+            .get(2)
+            .is_none_or(|mention_host| mention_host.as_str().eq_ignore_ascii_case(host));
+        let name = captures[1].to_string();
+        if local && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::local_to_local("@alice hi", &["alice"])]
+    #[case::local_with_host("hi @alice@example.com!", &["alice"])]
+    #[case::host_case("@alice@Example.COM", &["alice"])]
+    #[case::remote("@alice@remote.test", &[])]
+    #[case::remote_subdomain("@alice@example.com.remote.test", &[])]
+    #[case::several("@bob, @alice and @bob", &["bob", "alice"])]
+    #[case::punctuation("(@alice).", &["alice"])]
+    #[case::email("alice@example.com", &[])]
+    #[case::url("https://example.com/@alice", &[])]
+    #[case::double_at("@@alice", &[])]
+    #[case::too_long("@a123456789012345678901234", &[])]
+    #[case::none("hello", &[])]
+    fn test_extract_local(#[case] text: &str, #[case] expected: &[&str]) {
+        assert_eq!(extract_local(text, "example.com"), expected);
+    }
+}
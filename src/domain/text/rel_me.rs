@@ -0,0 +1,69 @@
+//! Find `rel="me"` links, with which a page says it belongs to the owner of a profile
+//!
+//! See https://microformats.org/wiki/rel-me
+use std::cell::RefCell;
+
+use html5ever::{
+    local_name,
+    tendril::StrTendril,
+    tokenizer::{
+        BufferQueue, StartTag, Tag, TagToken, Token, TokenSink, TokenSinkResult, Tokenizer,
+        TokenizerOpts,
+    },
+};
+
+use crate::domain::HttpUrl;
+
+/// Whether the page at `base` with `html` has an `<a>` or `<link>` with `rel="me"` to `target`
+///
+/// Relative links are resolved against `base`.
+pub fn links_to(html: &str, base: &HttpUrl, target: &HttpUrl) -> bool {
+    links(html).iter().any(|href| {
+        base.join(href)
+            .is_ok_and(|url| url.as_str() == target.as_str())
+    })
+}
+
+/// `href` of every `<a>` and `<link>` with `me` among its `rel` values
+fn links(html: &str) -> Vec<String> {
+    let input = BufferQueue::default();
+    input.push_back(StrTendril::from_slice(html));
+    let tokenizer = Tokenizer::new(RelMeSink::default(), TokenizerOpts::default());
+    let _ = tokenizer.feed(&input);
+    tokenizer.end();
+    tokenizer.sink.links.take()
+}
+
+#[derive(Default)]
+struct RelMeSink {
+    links: RefCell<Vec<String>>,
+}
+
+impl RelMeSink {
+    fn attribute<'t>(tag: &'t Tag, name: &html5ever::LocalName) -> Option<&'t str> {
+        tag.attrs
+            .iter()
+            .find(|attr| attr.name.local == *name)
+            .map(|attr| &*attr.value)
+    }
+}
+
+impl TokenSink for RelMeSink {
+    type Handle = ();
+
+    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        if let TagToken(tag) = token
+            && tag.kind == StartTag
+            && (tag.name == local_name!("a") || tag.name == local_name!("link"))
+        {
+            let is_me = Self::attribute(&tag, &local_name!("rel")).is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|value| value.eq_ignore_ascii_case("me"))
+            });
+            if let (true, Some(href)) = (is_me, Self::attribute(&tag, &local_name!("href"))) {
+                self.links.borrow_mut().push(href.trim().to_string());
+            }
+        }
+        TokenSinkResult::Continue
+    }
+}
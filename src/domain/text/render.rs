@@ -0,0 +1,249 @@
+//! Render user written source text into HTML served as `content`
+use std::sync::LazyLock;
+
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd};
+
+use super::{SourceMediaType, sanitize::sanitize_html};
+
+/// URL schemes we create links for
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+const LINK_ATTRIBUTES: &str = r#"rel="nofollow noopener noreferrer" target="_blank""#;
+
+/// Render source text into HTML
+///
+/// Plain text and Markdown output contains only markup produced by this module, so raw HTML in
+/// them is always escaped. HTML sources are sanitized instead.
+pub fn render(source: &str, media_type: SourceMediaType) -> String {
+    match media_type {
+        SourceMediaType::PlainText => render_plain_text(source),
+        SourceMediaType::Markdown => render_markdown(source),
+        SourceMediaType::Html => sanitize_html(source),
+    }
+}
+
+/// Render plain text
+///
+/// Blank lines separate paragraphs, other line breaks become `<br>` and `http(s)` URLs become links.
+pub fn render_plain_text(source: &str) -> String {
+    static PARAGRAPH: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\n[ \t]*\n\s*").unwrap());
+
+    let source = source.replace("\r\n", "\n");
+    let source = source.trim();
+
+    let mut html = String::with_capacity(source.len());
+    for paragraph in PARAGRAPH.split(source) {
+        html.push_str("<p>");
+        for (i, line) in paragraph.lines().enumerate() {
+            if i > 0 {
+                html.push_str("<br>");
+            }
+            push_autolinked(&mut html, line);
+        }
+        html.push_str("</p>");
+    }
+    html
+}
+
+/// Render a single line of plain text, like a profile field value
+///
+/// Unlike [render_plain_text] it is not wrapped in a paragraph and line breaks become spaces.
+pub fn render_inline(source: &str) -> String {
+    let mut html = String::with_capacity(source.len());
+    for (i, line) in source.trim().lines().enumerate() {
+        if i > 0 {
+            html.push(' ');
+        }
+        push_autolinked(&mut html, line.trim());
+    }
+    html
+}
+
+/// Render CommonMark
+///
+/// Raw HTML is escaped, links with unsupported schemes are reduced to their text and images
+/// become links to the image.
+pub fn render_markdown(source: &str) -> String {
+    let parser = Parser::new_ext(source, Options::ENABLE_STRIKETHROUGH);
+
+    // whether each open link was emitted, so the matching end tag can be skipped too
+    let mut links: Vec<bool> = Vec::new();
+    let mut in_code_block = false;
+
+    let events = parser.filter_map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Some(Event::Text(raw)),
+        Event::SoftBreak => Some(Event::HardBreak),
+        Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. }) => {
+            let emitted = is_linkable(&dest_url);
+            links.push(emitted);
+            emitted.then(|| Event::Html(open_link(&dest_url).into()))
+        }
+        Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => links
+            .pop()
+            .unwrap_or(false)
+            .then(|| Event::Html("</a>".into())),
+        Event::Start(Tag::CodeBlock(kind)) => {
+            in_code_block = true;
+            Some(Event::Start(Tag::CodeBlock(kind)))
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            in_code_block = false;
+            Some(Event::End(TagEnd::CodeBlock))
+        }
+        Event::Text(text) if links.is_empty() && !in_code_block => {
+            let mut html = String::with_capacity(text.len());
+            push_autolinked(&mut html, &text);
+            Some(Event::Html(CowStr::from(html)))
+        }
+        event => Some(event),
+    });
+
+    let mut html = String::with_capacity(source.len());
+    pulldown_cmark::html::push_html(&mut html, events);
+    html.trim_end().to_string()
+}
+
+/// Escape text for use in HTML content or a quoted attribute
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    push_escaped(&mut escaped, text);
+    escaped
+}
+
+fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn is_linkable(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| LINK_SCHEMES.contains(&url.scheme()))
+}
+
+fn open_link(url: &str) -> String {
+    format!(r#"<a href="{}" {}>"#, escape_html(url), LINK_ATTRIBUTES)
+}
+
+/// Push escaped `text` turning bare `http(s)` URLs into links
+fn push_autolinked(out: &mut String, text: &str) {
+    static URL: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r#"https?://[^\s<>"]+"#).unwrap());
+
+    let mut last = 0;
+    for m in URL.find_iter(text) {
+        let url = trim_url_end(m.as_str());
+        if !is_linkable(url) {
+            continue;
+        }
+        push_escaped(out, &text[last..m.start()]);
+        out.push_str(&open_link(url));
+        push_escaped(out, url);
+        out.push_str("</a>");
+        last = m.start() + url.len();
+    }
+    push_escaped(out, &text[last..]);
+}
+
+/// Drop trailing punctuation that most likely belongs to the sentence, not the URL
+fn trim_url_end(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ':', ';', '!', '?', '\'']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(rest) if trimmed.matches('(').count() < trimmed.matches(')').count() => rest,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::escape(
+        "a < b && \"c\" > 'd'",
+        "<p>a &lt; b &amp;&amp; &quot;c&quot; &gt; &#39;d&#39;</p>"
+    )]
+    #[case::script(
+        "<script>alert(1)</script>",
+        "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>"
+    )]
+    #[case::line_break("one\ntwo", "<p>one<br>two</p>")]
+    #[case::crlf("one\r\ntwo", "<p>one<br>two</p>")]
+    #[case::paragraphs("one\n\ntwo\n \n\nthree", "<p>one</p><p>two</p><p>three</p>")]
+    #[case::trimmed("\n  hello  \n", "<p>hello</p>")]
+    #[case::autolink(
+        "see https://example.com/a?b=c&d",
+        r#"<p>see <a href="https://example.com/a?b=c&amp;d" rel="nofollow noopener noreferrer" target="_blank">https://example.com/a?b=c&amp;d</a></p>"#
+    )]
+    #[case::trailing_punctuation(
+        "(at http://example.com/x).",
+        r#"<p>(at <a href="http://example.com/x" rel="nofollow noopener noreferrer" target="_blank">http://example.com/x</a>).</p>"#
+    )]
+    #[case::javascript_not_linked("javascript:alert(1)", "<p>javascript:alert(1)</p>")]
+    #[case::ftp_not_linked("ftp://example.com/file", "<p>ftp://example.com/file</p>")]
+    fn test_render_plain_text(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(render(source, SourceMediaType::PlainText), expected);
+    }
+
+    #[rstest]
+    #[case::emphasis("*a* **b** ~~c~~", "<p><em>a</em> <strong>b</strong> <del>c</del></p>")]
+    #[case::soft_break("one\ntwo", "<p>one<br />\ntwo</p>")]
+    #[case::paragraphs("one\n\ntwo", "<p>one</p>\n<p>two</p>")]
+    #[case::link(
+        "[home](https://example.com)",
+        r#"<p><a href="https://example.com" rel="nofollow noopener noreferrer" target="_blank">home</a></p>"#
+    )]
+    #[case::mailto(
+        "[mail](mailto:a@example.com)",
+        r#"<p><a href="mailto:a@example.com" rel="nofollow noopener noreferrer" target="_blank">mail</a></p>"#
+    )]
+    #[case::autolink(
+        "go to https://example.com",
+        r#"<p>go to <a href="https://example.com" rel="nofollow noopener noreferrer" target="_blank">https://example.com</a></p>"#
+    )]
+    #[case::javascript_link("[click](javascript:alert(1))", "<p>click</p>")]
+    #[case::image(
+        "![cat](https://example.com/cat.png)",
+        r#"<p><a href="https://example.com/cat.png" rel="nofollow noopener noreferrer" target="_blank">cat</a></p>"#
+    )]
+    #[case::raw_html("<script>alert(1)</script>", "&lt;script&gt;alert(1)&lt;/script&gt;")]
+    #[case::inline_html(
+        "a <b onclick=\"x\">b</b>",
+        r#"<p>a &lt;b onclick="x"&gt;b&lt;/b&gt;</p>"#
+    )]
+    #[case::code_block(
+        "```\nhttps://example.com <b>\n```",
+        "<pre><code>https://example.com &lt;b&gt;\n</code></pre>"
+    )]
+    fn test_render_markdown(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(render(source, SourceMediaType::Markdown), expected);
+    }
+
+    #[rstest]
+    #[case::line_breaks(" a\nb ", "a b")]
+    #[case::escape("<i>x</i>", "&lt;i&gt;x&lt;/i&gt;")]
+    #[case::autolink(
+        "https://example.com",
+        r#"<a href="https://example.com" rel="nofollow noopener noreferrer" target="_blank">https://example.com</a>"#
+    )]
+    fn test_render_inline(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(render_inline(source), expected);
+    }
+}
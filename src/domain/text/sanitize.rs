@@ -0,0 +1,146 @@
+//! Sanitize HTML received from remote servers
+//!
+//! The allowlist follows Mastodon's so that remote content looks the same as it does there.
+//! See https://github.com/mastodon/mastodon/blob/main/lib/sanitize_ext/sanitize_config.rb
+use std::{borrow::Cow, collections::HashSet, sync::LazyLock};
+
+use ammonia::{Builder, UrlRelative};
+
+const TAGS: &[&str] = &[
+    "p",
+    "br",
+    "span",
+    "a",
+    "abbr",
+    "del",
+    "s",
+    "pre",
+    "blockquote",
+    "code",
+    "b",
+    "strong",
+    "u",
+    "i",
+    "em",
+    "ul",
+    "ol",
+    "li",
+    "ruby",
+    "rt",
+    "rp",
+];
+
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "class", "translate"]),
+    ("abbr", &["title"]),
+    ("span", &["class", "translate"]),
+    ("blockquote", &["cite"]),
+    ("ol", &["start", "reversed"]),
+    ("li", &["value"]),
+];
+
+/// Schemes allowed in `href` and `cite`
+const URL_SCHEMES: &[&str] = &[
+    "http", "https", "dat", "dweb", "ipfs", "ipns", "ssb", "gopher", "xmpp", "magnet", "gemini",
+];
+
+/// Classes used by microformats and Mastodon to mark up mentions and hashtags
+const CLASS_PREFIXES: &[&str] = &["h-", "p-", "u-", "dt-", "e-"];
+const CLASSES: &[&str] = &["mention", "hashtag", "ellipsis", "invisible"];
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(TAGS.iter().copied().collect())
+        .tag_attributes(
+            TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect()))
+                .collect(),
+        )
+        .generic_attributes(HashSet::new())
+        .url_schemes(URL_SCHEMES.iter().copied().collect())
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .set_tag_attribute_value("a", "target", "_blank")
+        .clean_content_tags(["script", "style"].into_iter().collect())
+        .strip_comments(true)
+        .attribute_filter(filter_attribute);
+    builder
+});
+
+/// Sanitize untrusted HTML
+///
+/// Disallowed tags are unwrapped, `script` and `style` are dropped with their content and links
+/// with disallowed or relative URLs lose their `href`.
+pub fn sanitize_html(html: &str) -> String {
+    SANITIZER.clean(html).to_string()
+}
+
+fn filter_attribute<'u>(element: &str, attribute: &str, value: &'u str) -> Option<Cow<'u, str>> {
+    match (element, attribute) {
+        (_, "class") => {
+            let classes = value
+                .split_ascii_whitespace()
+                .filter(|class| {
+                    CLASSES.contains(class)
+                        || CLASS_PREFIXES
+                            .iter()
+                            .any(|prefix| class.starts_with(prefix))
+                })
+                .collect::<Vec<_>>();
+            (!classes.is_empty()).then(|| classes.join(" ").into())
+        }
+        (_, "translate") => (value == "no").then_some(value.into()),
+        _ => Some(value.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::allowed("<p>a<br><strong>b</strong></p>", "<p>a<br><strong>b</strong></p>")]
+    #[case::script("<p>a</p><script>alert(1)</script>", "<p>a</p>")]
+    #[case::style_tag("<style>p { color: red }</style><p>a</p>", "<p>a</p>")]
+    #[case::unwrapped("<div><h1>title</h1></div>", "title")]
+    #[case::onerror(r#"<img src="x" onerror="alert(1)">"#, "")]
+    #[case::onclick(r#"<p onclick="alert(1)">a</p>"#, "<p>a</p>")]
+    #[case::style_attribute(r#"<p style="color: red">a</p>"#, "<p>a</p>")]
+    #[case::comment("<p>a<!-- b --></p>", "<p>a</p>")]
+    #[case::link(
+        r#"<a href="https://example.com/">a</a>"#,
+        r#"<a href="https://example.com/" target="_blank" rel="nofollow noopener noreferrer">a</a>"#
+    )]
+    #[case::link_rel_replaced(
+        r#"<a href="https://example.com/" rel="me" target="_self">a</a>"#,
+        r#"<a href="https://example.com/" target="_blank" rel="nofollow noopener noreferrer">a</a>"#
+    )]
+    #[case::javascript_href(
+        r#"<a href="javascript:alert(1)">a</a>"#,
+        r#"<a target="_blank" rel="nofollow noopener noreferrer">a</a>"#
+    )]
+    #[case::data_href(
+        r#"<a href="data:text/html,<script>alert(1)</script>">a</a>"#,
+        r#"<a target="_blank" rel="nofollow noopener noreferrer">a</a>"#
+    )]
+    #[case::relative_href(
+        r#"<a href="/tags/rust">a</a>"#,
+        r#"<a target="_blank" rel="nofollow noopener noreferrer">a</a>"#
+    )]
+    #[case::mention_classes(
+        r#"<span class="h-card evil"><a href="https://example.com/@a" class="u-url mention x">@a</a></span>"#,
+        r#"<span class="h-card"><a href="https://example.com/@a" class="u-url mention" target="_blank" rel="nofollow noopener noreferrer">@a</a></span>"#
+    )]
+    #[case::translate(
+        r#"<span translate="no">a</span><span translate="yes">b</span>"#,
+        r#"<span translate="no">a</span><span>b</span>"#
+    )]
+    fn test_sanitize_html(#[case] html: &str, #[case] expected: &str) {
+        assert_eq!(sanitize_html(html), expected);
+    }
+}
@@ -1,23 +1,56 @@
 pub(crate) mod accounts;
+pub(crate) mod admin;
 pub(crate) mod ap;
+pub(crate) mod auth;
+pub(crate) mod mastodon;
+pub(crate) mod media;
+pub(crate) mod oauth;
 pub(crate) mod posts;
 pub(crate) mod state;
 pub(crate) mod utils;
 pub(crate) mod well_known;
+use std::time::Duration;
+
 use state::AppRegistryExt as _;
 use tokio::signal;
 use typed_builder::TypedBuilder;
 
-use crate::{domain::hosturl::HostUrl, infrastructure::postgres::Postgres};
+use crate::{
+    domain::hosturl::HostUrl,
+    infrastructure::{
+        media_storage::{MediaCacheConfig, MediaStorageConfig},
+        postgres::Postgres,
+    },
+};
 
 #[derive(Debug, TypedBuilder)]
 pub struct HttpServerConfig {
     port: u16,
     host_url: String,
+    /// bearer token for `/admin`. Admin routes are disabled without it, or if it is blank
+    #[builder(default)]
+    admin_token: Option<String>,
+    /// where uploads are stored, in `./media` by default
+    #[builder(default)]
+    media_storage: MediaStorageConfig,
+    /// how much remote media the media proxy keeps, 1 GiB for a week by default
+    #[builder(default)]
+    media_cache: MediaCacheConfig,
+    /// let requests to URLs from federated content reach loopback and private addresses, to
+    /// federate with servers on the same machine or network. Off by default
+    #[builder(default)]
+    allow_private_addresses: bool,
 }
 
+/// How often cached remote media is checked for eviction
+const CACHE_EVICTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How often expired polls are closed and their results sent
+const POLL_CLOSE_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct HttpServer {
     port: u16,
+    admin_token: Option<String>,
     registry: state::AppRegistry,
 }
 
@@ -25,9 +58,16 @@ impl HttpServer {
     pub fn new(config: HttpServerConfig, pg: Postgres) -> Self {
         let host_url_service = HostUrl::new(&config.host_url);
 
-        let registry = state::AppRegistry::from_pg_host_url(pg, host_url_service);
+        let registry = state::AppRegistry::from_pg_host_url(
+            pg,
+            host_url_service,
+            config.media_storage.build(),
+            config.media_cache.policy(),
+            config.allow_private_addresses,
+        );
         Self {
             port: config.port,
+            admin_token: config.admin_token.filter(|token| !token.trim().is_empty()),
             registry,
         }
     }
@@ -38,12 +78,44 @@ impl HttpServer {
         use tower_http::trace::TraceLayer;
 
         let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, self.port)).await?;
-        let router = axum::Router::new()
+        let mut router = axum::Router::new()
             .nest("/accounts", accounts::router(self.registry.clone()))
             .nest("/posts", posts::router(self.registry.clone()))
             .nest("/.well-known", well_known::router(self.registry.clone()))
             .nest("/ap", ap::router(self.registry.clone()))
-            .layer(TraceLayer::new_for_http());
+            .nest("/oauth", oauth::router(self.registry.clone()))
+            .nest("/api", mastodon::router(self.registry.clone()))
+            .nest("/media", media::router(self.registry.clone()));
+        if let Some(admin_token) = &self.admin_token {
+            router = router.nest("/admin", admin::router(self.registry.clone(), admin_token));
+        }
+        let router = router.layer(TraceLayer::new_for_http());
+
+        let media_service = self.registry.media_service();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CACHE_EVICTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                match media_service.evict_cache().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!(count, "Evicted cached media"),
+                    Err(e) => tracing::error!(error = %e, "Failed to evict cached media"),
+                }
+            }
+        });
+
+        let ap_service = self.registry.ap_service();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_CLOSE_INTERVAL);
+            loop {
+                interval.tick().await;
+                match ap_service.close_expired_polls().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!(count, "Closed expired polls"),
+                    Err(e) => tracing::error!(error = %e, "Failed to close expired polls"),
+                }
+            }
+        });
 
         tracing::info!("Listening on {}", listener.local_addr()?);
         tracing::info!("Host URL: {}", self.registry.host_url_service().base_url());
@@ -1,16 +1,32 @@
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse, routing};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
 use serde::{Deserialize, Serialize};
 
+use chrono::{DateTime, Utc};
+
 use crate::{
-    domain::account::model::{
-        AccountId, AccountName, AccountNameError, CreateAccountError, CreateAccountRequest,
+    domain::{
+        account::model::{
+            AccountId, AccountName, AccountNameError, CreateAccountError, CreateAccountRequest,
+            DeleteAccountError, Password, PasswordError,
+        },
+        auth::model::{LoginError, LoginRequest, LogoutError},
+    },
+    http::{
+        auth::{Authenticated, AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt},
     },
-    http::state::{AppRegistry, AppRegistryExt},
 };
 
 #[derive(Debug, Deserialize)]
 pub struct CreateAccountJson {
     username: String,
+    password: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -21,6 +37,9 @@ pub struct CreateAccountResponseJson {
 
 pub enum ApiError {
     BadRequest(String),
+    Unauthorized,
+    Forbidden,
+    NotFound,
     Conflict,
     InternalServerError,
 }
@@ -29,6 +48,11 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         match self {
             ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            ApiError::Unauthorized => {
+                (StatusCode::UNAUTHORIZED, "Invalid username or password").into_response()
+            }
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden").into_response(),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Account not found").into_response(),
             ApiError::Conflict => (StatusCode::CONFLICT, "Account already exists").into_response(),
             ApiError::InternalServerError => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
@@ -47,6 +71,12 @@ impl From<AccountNameError> for ApiError {
     }
 }
 
+impl From<PasswordError> for ApiError {
+    fn from(err: PasswordError) -> Self {
+        ApiError::BadRequest(err.to_string())
+    }
+}
+
 impl From<CreateAccountError> for ApiError {
     fn from(err: CreateAccountError) -> Self {
         match err {
@@ -56,13 +86,40 @@ impl From<CreateAccountError> for ApiError {
     }
 }
 
+impl From<DeleteAccountError> for ApiError {
+    fn from(err: DeleteAccountError) -> Self {
+        match err {
+            DeleteAccountError::NotFound => ApiError::NotFound,
+            DeleteAccountError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<LoginError> for ApiError {
+    fn from(err: LoginError) -> Self {
+        match err {
+            LoginError::InvalidCredentials => ApiError::Unauthorized,
+            LoginError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<LogoutError> for ApiError {
+    fn from(err: LogoutError) -> Self {
+        match err {
+            LogoutError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn signup(
     State(registry): State<AppRegistry>,
     Json(payload): Json<CreateAccountJson>,
 ) -> Result<impl IntoResponse, ApiError> {
     let account_name = AccountName::new(&payload.username)?;
-    let req = CreateAccountRequest::new(account_name);
+    let password = Password::new(&payload.password)?;
+    let req = CreateAccountRequest::new(account_name, password);
 
     let account_service = registry.account_service();
     let account = account_service.create(req).await?;
@@ -75,8 +132,99 @@ pub async fn signup(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LoginJson {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponseJson {
+    access_token: String,
+    token_type: &'static str,
+    expires_at: DateTime<Utc>,
+}
+
+/// Exchange username and password for a bearer token
+#[tracing::instrument(skip_all)]
+pub async fn login(
+    State(registry): State<AppRegistry>,
+    Json(payload): Json<LoginJson>,
+) -> Result<Json<LoginResponseJson>, ApiError> {
+    // malformed credentials can't belong to anyone
+    let (Ok(name), Ok(password)) = (
+        AccountName::new(&payload.username),
+        Password::new(&payload.password),
+    ) else {
+        return Err(ApiError::Unauthorized);
+    };
+
+    let session = registry
+        .auth_service()
+        .login(LoginRequest { name, password })
+        .await?;
+
+    Ok(Json(LoginResponseJson {
+        access_token: session.token.as_str().to_string(),
+        token_type: "Bearer",
+        expires_at: session.expires,
+    }))
+}
+
+/// Revoke the token the request was made with
+#[tracing::instrument(skip_all)]
+pub async fn logout(
+    State(registry): State<AppRegistry>,
+    auth: Authenticated,
+) -> Result<StatusCode, ApiError> {
+    registry.auth_service().logout(&auth.token).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountParams {
+    account_id: AccountId,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountJson {
+    /// current password, as asked by Mastodon's `/settings/delete`
+    password: String,
+}
+
+/// Delete own account
+#[tracing::instrument(skip(registry, auth, payload))]
+pub async fn delete_account(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteAccounts>,
+    Path(params): Path<AccountParams>,
+    Json(payload): Json<DeleteAccountJson>,
+) -> Result<StatusCode, ApiError> {
+    if auth.account.id() != &params.account_id {
+        return Err(ApiError::Forbidden);
+    }
+    let password = Password::new(&payload.password).map_err(|_| ApiError::Forbidden)?;
+    let req = LoginRequest {
+        name: auth.account.name().clone(),
+        password,
+    };
+    match registry.auth_service().check_password(req).await {
+        Ok(_) => {}
+        Err(LoginError::InvalidCredentials) => return Err(ApiError::Forbidden),
+        Err(LoginError::Unknown(_)) => return Err(ApiError::InternalServerError),
+    }
+    let account_service = registry.account_service();
+    account_service.delete(&params.account_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub fn router(registry: AppRegistry) -> axum::Router {
     axum::Router::new()
         .route("/signup", routing::post(signup))
+        .route("/login", routing::post(login))
+        .route("/logout", routing::post(logout))
+        .route("/{account_id}", routing::delete(delete_account))
         .with_state(registry)
 }
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{DefaultBodyLimit, Multipart, Path, Request, State, multipart::MultipartError},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing,
+};
+use sha2::{Digest as _, Sha256};
+
+use crate::{
+    domain::{
+        account::model::{AccountName, AccountNameError, DeleteAccountError, FindAccountError},
+        media::model::{
+            CreateEmojiError, CreateEmojiRequest, CustomEmoji, DeleteEmojiError, MAX_EMOJI_SIZE,
+            MediaFile,
+        },
+    },
+    http::state::{AppRegistry, AppRegistryExt as _},
+};
+
+/// Room for the multipart boundary and the shortcode around an emoji image
+const MULTIPART_OVERHEAD: usize = 16 * 1024;
+
+pub enum ApiError {
+    Unauthorized,
+    NotFound(&'static str),
+    Conflict(&'static str),
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized").into_response(),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, message).into_response(),
+            ApiError::UnprocessableEntity(message) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+            }
+            ApiError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<AccountNameError> for ApiError {
+    fn from(err: AccountNameError) -> Self {
+        match err {
+            AccountNameError::InvalidName(_) => ApiError::NotFound("Account not found"),
+        }
+    }
+}
+
+impl From<FindAccountError> for ApiError {
+    fn from(err: FindAccountError) -> Self {
+        match err {
+            FindAccountError::InvalidName(_) | FindAccountError::Gone(_) => {
+                ApiError::NotFound("Account not found")
+            }
+            FindAccountError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<DeleteAccountError> for ApiError {
+    fn from(err: DeleteAccountError) -> Self {
+        match err {
+            DeleteAccountError::NotFound => ApiError::NotFound("Account not found"),
+            DeleteAccountError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<CreateEmojiError> for ApiError {
+    fn from(err: CreateEmojiError) -> Self {
+        match err {
+            CreateEmojiError::Invalid(message) => ApiError::UnprocessableEntity(message),
+            CreateEmojiError::AlreadyExists => ApiError::Conflict("Emoji already exists"),
+            CreateEmojiError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<DeleteEmojiError> for ApiError {
+    fn from(err: DeleteEmojiError) -> Self {
+        match err {
+            DeleteEmojiError::NotFound => ApiError::NotFound("Emoji not found"),
+            DeleteEmojiError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(err: MultipartError) -> Self {
+        ApiError::UnprocessableEntity(err.body_text())
+    }
+}
+
+/// Let requests through only with `Authorization: Bearer {admin_token}`
+async fn require_admin_token(
+    State(admin_token): State<Arc<str>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(ApiError::Unauthorized)?;
+
+    // compare digests so the time taken does not leak the token
+    if Sha256::digest(token) != Sha256::digest(&*admin_token) {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(next.run(req).await)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AccountParams {
+    user_name: String,
+}
+
+#[tracing::instrument(skip(registry))]
+pub async fn delete_account(
+    State(registry): State<AppRegistry>,
+    Path(params): Path<AccountParams>,
+) -> Result<StatusCode, ApiError> {
+    let account_name = AccountName::new(&params.user_name)?;
+    let account_service = registry.account_service();
+    let Some(account) = account_service.find_by_name(&account_name).await? else {
+        return Err(ApiError::NotFound("Account not found"));
+    };
+    account_service.delete(account.id()).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct EmojiJson {
+    id: String,
+    shortcode: String,
+    url: String,
+}
+
+impl From<CustomEmoji> for EmojiJson {
+    fn from(emoji: CustomEmoji) -> Self {
+        Self {
+            id: emoji.id.to_string(),
+            shortcode: emoji.shortcode,
+            url: emoji.url.to_string(),
+        }
+    }
+}
+
+/// Add a custom emoji, sent as `multipart/form-data` with `shortcode` and `image`
+#[tracing::instrument(skip(registry, multipart))]
+pub async fn create_emoji(
+    State(registry): State<AppRegistry>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<EmojiJson>), ApiError> {
+    let mut shortcode = None;
+    let mut file = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("shortcode") => shortcode = Some(field.text().await?),
+            Some("image") => {
+                let media_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field.bytes().await?.to_vec();
+                file = Some(MediaFile { media_type, data });
+            }
+            _ => {}
+        }
+    }
+    let (Some(shortcode), Some(file)) = (shortcode, file) else {
+        return Err(ApiError::UnprocessableEntity(
+            "shortcode and image are required".to_string(),
+        ));
+    };
+
+    let req = CreateEmojiRequest { shortcode, file };
+    let emoji = registry.media_service().create_emoji(req).await?;
+    Ok((StatusCode::CREATED, Json(emoji.into())))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct EmojiParams {
+    shortcode: String,
+}
+
+#[tracing::instrument(skip(registry))]
+pub async fn delete_emoji(
+    State(registry): State<AppRegistry>,
+    Path(params): Path<EmojiParams>,
+) -> Result<StatusCode, ApiError> {
+    registry
+        .media_service()
+        .delete_emoji(&params.shortcode)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router(registry: AppRegistry, admin_token: &str) -> axum::Router {
+    let admin_token: Arc<str> = admin_token.into();
+    axum::Router::new()
+        .route("/accounts/{user_name}", routing::delete(delete_account))
+        .route(
+            "/emojis",
+            routing::post(create_emoji)
+                .layer(DefaultBodyLimit::max(MAX_EMOJI_SIZE + MULTIPART_OVERHEAD)),
+        )
+        .route("/emojis/{shortcode}", routing::delete(delete_emoji))
+        .layer(middleware::from_fn_with_state(
+            admin_token,
+            require_admin_token,
+        ))
+        .with_state(registry)
+}
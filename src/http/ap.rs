@@ -4,7 +4,9 @@ use serde::Deserialize;
 use super::state::AppRegistry;
 
 mod actor;
+mod emoji;
 mod inbox;
+mod note;
 mod outbox;
 
 #[derive(Deserialize)]
@@ -18,5 +20,7 @@ pub fn router(registry: AppRegistry) -> axum::Router {
         .route("/actors/{user_name}", routing::get(actor::actor))
         .route("/actors/{user_name}/inbox", routing::post(inbox::inbox))
         .route("/actors/{user_name}/outbox", routing::post(outbox::outbox))
+        .route("/notes/{note_id}", routing::get(note::note))
+        .route("/emojis/{emoji_id}", routing::get(emoji::emoji))
         .with_state(registry)
 }
@@ -1,6 +1,9 @@
 use crate::{
-    ap::{Actor, ActorType, Context},
-    domain::account::model::{AccountName, AccountNameError, FindAccountError},
+    ap::{Context, Tombstone},
+    domain::{
+        account::model::{AccountName, AccountNameError, FindAccountError},
+        ap::model::{LocalActor, actor::FindActorError, key::FindKeyError},
+    },
     http::{
         state::{AppRegistry, AppRegistryExt as _},
         utils::ActivityJson,
@@ -17,6 +20,7 @@ use super::Params;
 #[derive(Serialize)]
 pub enum ApiError {
     NotFound,
+    Gone(Tombstone),
     InternalServerError,
 }
 
@@ -24,6 +28,11 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         match self {
             ApiError::NotFound => (axum::http::StatusCode::NOT_FOUND, "Not found").into_response(),
+            ApiError::Gone(tombstone) => (
+                axum::http::StatusCode::GONE,
+                ActivityJson(Context::new(tombstone)),
+            )
+                .into_response(),
             ApiError::InternalServerError => (
                 axum::http::StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error",
@@ -41,10 +50,30 @@ impl From<AccountNameError> for ApiError {
     }
 }
 
+impl From<FindKeyError> for ApiError {
+    fn from(err: FindKeyError) -> Self {
+        match err {
+            FindKeyError::NotFound => ApiError::NotFound,
+            FindKeyError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<FindActorError> for ApiError {
+    fn from(err: FindActorError) -> Self {
+        match err {
+            FindActorError::NotFound => ApiError::NotFound,
+            FindActorError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
 impl From<FindAccountError> for ApiError {
     fn from(err: FindAccountError) -> Self {
         match err {
             FindAccountError::InvalidName(_) => ApiError::NotFound,
+            // handled by the caller, which knows the actor url
+            FindAccountError::Gone(_) => ApiError::NotFound,
             FindAccountError::Unknown(_) => ApiError::InternalServerError,
         }
     }
@@ -56,20 +85,27 @@ pub async fn actor(
     Path(params): Path<Params>,
 ) -> Result<impl IntoResponse, ApiError> {
     let account_name = AccountName::new(&params.user_name)?;
+    let hosturl_service = registry.host_url_service();
+    let actor_url = hosturl_service.actor_url(account_name.as_str());
+
     let account_service = registry.account_service();
-    let account = account_service.find_by_name(&account_name).await?;
-    let Some(_) = account else {
-        return Err(ApiError::NotFound);
+    let account = match account_service.find_by_name(&account_name).await {
+        Ok(Some(account)) => account,
+        Ok(None) => return Err(ApiError::NotFound),
+        Err(FindAccountError::Gone(deleted)) => {
+            let tombstone = Tombstone::builder().id(actor_url).deleted(deleted).build();
+            return Err(ApiError::Gone(tombstone));
+        }
+        Err(e) => return Err(e.into()),
     };
 
-    let hosturl_service = registry.host_url_service();
-    let actor = Actor::builder()
-        .kind(ActorType::Person)
-        .id(hosturl_service.actor_url(account_name.as_str()))
-        .inbox(hosturl_service.inbox_url(account_name.as_str()))
-        .outbox(hosturl_service.outbox_url(account_name.as_str()))
-        .preferred_username(account_name.as_str())
-        .build();
+    let ap_service = registry.ap_service();
+    let view = ap_service.find_local_actor_view(account.id()).await?;
+    let public_key = ap_service.find_local_public_key(account.id()).await?;
+    let actor = LocalActor::try_from(view.actor).map_err(|e| {
+        tracing::error!(error = %e, "expected account actor to be local");
+        ApiError::InternalServerError
+    })?;
 
-    Ok(ActivityJson(Context::new(actor)))
+    Ok(ActivityJson(Context::new(actor.to_ap(public_key))))
 }
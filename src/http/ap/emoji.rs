@@ -0,0 +1,61 @@
+use crate::{
+    ap::{Context, Tag},
+    domain::media::model::{CustomEmojiId, FindEmojiError},
+    http::{
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::ActivityJson,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct EmojiParams {
+    emoji_id: CustomEmojiId,
+}
+
+pub enum ApiError {
+    NotFound,
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            ApiError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<FindEmojiError> for ApiError {
+    fn from(err: FindEmojiError) -> Self {
+        match err {
+            FindEmojiError::NotFound => ApiError::NotFound,
+            FindEmojiError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// `Emoji` object a custom emoji of this instance is identified by
+#[tracing::instrument(skip_all)]
+pub async fn emoji(
+    State(registry): State<AppRegistry>,
+    Path(params): Path<EmojiParams>,
+) -> Result<Response, ApiError> {
+    let emoji = registry
+        .media_service()
+        .find_emoji(&params.emoji_id)
+        .await?;
+    // remote emoji are served by their own servers
+    if emoji.host.is_some() {
+        return Err(ApiError::NotFound);
+    }
+    Ok(ActivityJson(Context::new(Tag::from(&emoji))).into_response())
+}
@@ -1,8 +1,209 @@
-use axum::{extract::Path, response::IntoResponse};
+use axum::{
+    body::Bytes,
+    extract::{OriginalUri, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{TimeDelta, Utc};
 
-use super::Params;
+use crate::{
+    ap::{
+        Activity,
+        signature::{self, Signature, SignatureError},
+    },
+    domain::ap::model::{ResolveActorError, federation::ReceiveActivityError},
+    http::state::{AppRegistry, AppRegistryExt as _},
+};
 
+/// How far `Date` may be from now
+const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::hours(12);
+
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden,
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            ApiError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message).into_response(),
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden").into_response(),
+            ApiError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<SignatureError> for ApiError {
+    fn from(err: SignatureError) -> Self {
+        ApiError::Unauthorized(err.to_string())
+    }
+}
+
+impl From<ResolveActorError> for ApiError {
+    fn from(err: ResolveActorError) -> Self {
+        match err {
+            ResolveActorError::NotFound | ResolveActorError::InvalidActor(_) => {
+                ApiError::Unauthorized(err.to_string())
+            }
+            ResolveActorError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<ReceiveActivityError> for ApiError {
+    fn from(err: ReceiveActivityError) -> Self {
+        match err {
+            ReceiveActivityError::InvalidActivity(message) => ApiError::BadRequest(message),
+            ReceiveActivityError::Forbidden => ApiError::Forbidden,
+            ReceiveActivityError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Check the request headers that the signature does not cover by itself
+fn verify_headers(headers: &HeaderMap, signature: &Signature, body: &[u8]) -> Result<(), ApiError> {
+    // unsigned, the request could be replayed to another path or host
+    for &name in signature::SIGNED_HEADERS {
+        if !signature.headers.iter().any(|h| h == name) {
+            return Err(ApiError::Unauthorized(format!("`{name}` must be signed")));
+        }
+    }
+
+    let date = headers
+        .get("date")
+        .and_then(|v| v.to_str().ok())
+        .and_then(signature::parse_http_date)
+        .ok_or_else(|| ApiError::Unauthorized("invalid `Date`".to_string()))?;
+    if (Utc::now() - date).abs() > MAX_CLOCK_SKEW {
+        return Err(ApiError::Unauthorized(
+            "`Date` is too far from now".to_string(),
+        ));
+    }
+
+    let digest = headers.get("digest").and_then(|v| v.to_str().ok());
+    if digest != Some(signature::digest(body).as_str()) {
+        return Err(ApiError::Unauthorized(
+            "`Digest` does not match the body".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Shared inbox and per actor inboxes
+///
+/// Requests must carry an HTTP Signature of the actor sending the activity.
 #[tracing::instrument(skip_all)]
-pub async fn inbox(Path(_): Path<Params>) -> impl IntoResponse {
-    "TODO: inbox"
+pub async fn inbox(
+    State(registry): State<AppRegistry>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, ApiError> {
+    let signature: Signature = headers
+        .get("signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| ApiError::Unauthorized("missing `Signature`".to_string()))?
+        .parse()?;
+    verify_headers(&headers, &signature, &body)?;
+
+    let ap_service = registry.ap_service();
+    let actor = ap_service
+        .resolve_actor_by_key_id(&signature.key_id)
+        .await?;
+    let Some(public_key) = &actor.public_key else {
+        return Err(ApiError::Unauthorized(
+            "actor has no public key".to_string(),
+        ));
+    };
+
+    let path_and_query = uri.path_and_query().map_or(uri.path(), |p| p.as_str());
+    signature.verify(&public_key.pem, "post", path_and_query, |name| {
+        headers.get(name).and_then(|v| v.to_str().ok())
+    })?;
+
+    let activity: Activity =
+        serde_json::from_slice(&body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    tracing::info!(id = %activity.id, kind = ?activity.kind, actor = %actor.actor_url, "Received activity");
+    ap_service.receive_activity(&actor, activity).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use rstest::rstest;
+
+    use super::*;
+
+    const BODY: &[u8] = br#"{"type":"Follow"}"#;
+
+    fn request(
+        signed: &[&str],
+        date: chrono::DateTime<Utc>,
+        body: &[u8],
+    ) -> (HeaderMap, Signature) {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "date",
+            HeaderValue::from_str(&signature::http_date(date)).unwrap(),
+        );
+        headers.insert(
+            "digest",
+            HeaderValue::from_str(&signature::digest(body)).unwrap(),
+        );
+        let signature = Signature {
+            key_id: "https://example.com/ap/actors/alice#main-key".to_string(),
+            algorithm: Some("rsa-sha256".to_string()),
+            headers: signed.iter().map(|s| s.to_string()).collect(),
+            signature: String::new(),
+        };
+        (headers, signature)
+    }
+
+    #[test]
+    fn test_verify_headers() {
+        let (headers, signature) = request(signature::SIGNED_HEADERS, Utc::now(), BODY);
+        assert!(verify_headers(&headers, &signature, BODY).is_ok());
+    }
+
+    #[rstest]
+    #[case::no_request_target(&["host", "date", "digest"])]
+    #[case::no_host(&["(request-target)", "date", "digest"])]
+    #[case::no_date(&["(request-target)", "host", "digest"])]
+    #[case::no_digest(&["(request-target)", "host", "date"])]
+    #[case::date_only(&["date"])]
+    fn test_verify_headers_unsigned(#[case] signed: &[&str]) {
+        let (headers, signature) = request(signed, Utc::now(), BODY);
+        assert!(matches!(
+            verify_headers(&headers, &signature, BODY),
+            Err(ApiError::Unauthorized(_))
+        ));
+    }
+
+    #[rstest]
+    #[case::past(-MAX_CLOCK_SKEW - TimeDelta::minutes(1))]
+    #[case::future(MAX_CLOCK_SKEW + TimeDelta::minutes(1))]
+    fn test_verify_headers_stale_date(#[case] skew: TimeDelta) {
+        let (headers, signature) = request(signature::SIGNED_HEADERS, Utc::now() + skew, BODY);
+        assert!(matches!(
+            verify_headers(&headers, &signature, BODY),
+            Err(ApiError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_headers_tampered_body() {
+        let (headers, signature) = request(signature::SIGNED_HEADERS, Utc::now(), BODY);
+        assert!(matches!(
+            verify_headers(&headers, &signature, br#"{"type":"Delete"}"#),
+            Err(ApiError::Unauthorized(_))
+        ));
+    }
 }
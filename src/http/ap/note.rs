@@ -0,0 +1,65 @@
+use crate::{
+    ap::{Context, Note, ObjectType, Tombstone},
+    domain::ap::model::note::{FindNoteError, NoteId},
+    http::{
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::ActivityJson,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct NoteParams {
+    note_id: NoteId,
+}
+
+pub enum ApiError {
+    NotFound,
+    Gone(Tombstone),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            ApiError::Gone(tombstone) => {
+                (StatusCode::GONE, ActivityJson(Context::new(tombstone))).into_response()
+            }
+            ApiError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn note(
+    State(registry): State<AppRegistry>,
+    Path(params): Path<NoteParams>,
+) -> Result<Response, ApiError> {
+    let ap_service = registry.ap_service();
+    let note = match ap_service.find_local_note(&params.note_id).await {
+        Ok(note) => note,
+        Err(FindNoteError::NotFound) => return Err(ApiError::NotFound),
+        Err(FindNoteError::Gone(deleted)) => {
+            let note_url = registry
+                .host_url_service()
+                .note_url(&params.note_id.to_string());
+            let tombstone = Tombstone::builder()
+                .id(note_url)
+                .former_type(ObjectType::Note)
+                .deleted(deleted)
+                .build();
+            return Err(ApiError::Gone(tombstone));
+        }
+        Err(FindNoteError::Unknown(_)) => return Err(ApiError::InternalServerError),
+    };
+
+    Ok(ActivityJson(Context::new(Note::from(&note))).into_response())
+}
@@ -0,0 +1,205 @@
+use std::marker::PhantomData;
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{HeaderMap, HeaderValue, StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::{
+        account::model::Account,
+        auth::model::{AppId, AuthenticateError, Principal, Scope, SessionToken},
+    },
+    http::state::{AppRegistry, AppRegistryExt as _},
+};
+
+/// Scope a handler requires, see [scope]
+pub trait RequiredScope: Send + Sync + 'static {
+    /// `None` lets any valid token through
+    const SCOPE: Option<Scope>;
+}
+
+/// Markers for [Authenticated] and [AuthenticatedAccount]
+pub mod scope {
+    use super::{RequiredScope, Scope};
+
+    macro_rules! required_scopes {
+        ($($(#[$meta:meta])* $marker:ident => $scope:expr;)*) => {$(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy)]
+            pub struct $marker;
+
+            impl RequiredScope for $marker {
+                const SCOPE: Option<Scope> = $scope;
+            }
+        )*};
+    }
+
+    required_scopes! {
+        /// Any valid token
+        Any => None;
+        Profile => Some(Scope::Profile);
+        ReadFollows => Some(Scope::ReadFollows);
+        ReadNotifications => Some(Scope::ReadNotifications);
+        ReadSearch => Some(Scope::ReadSearch);
+        ReadStatuses => Some(Scope::ReadStatuses);
+        WriteAccounts => Some(Scope::WriteAccounts);
+        WriteFavourites => Some(Scope::WriteFavourites);
+        WriteFollows => Some(Scope::WriteFollows);
+        WriteMedia => Some(Scope::WriteMedia);
+        WriteNotifications => Some(Scope::WriteNotifications);
+        WriteStatuses => Some(Scope::WriteStatuses);
+    }
+}
+
+/// Who a request was made as, from `Authorization: Bearer {token}`
+///
+/// The token must have the scope `S`.
+#[derive(Debug, Clone)]
+pub struct Authenticated<S: RequiredScope = scope::Any> {
+    pub account: Option<Account>,
+    /// app the token was issued to. `None` for password login
+    pub app_id: Option<AppId>,
+    /// token the request was authenticated with
+    pub token: SessionToken,
+    _scope: PhantomData<S>,
+}
+
+/// Like [Authenticated] but the token must belong to an account
+#[derive(Debug, Clone)]
+pub struct AuthenticatedAccount<S: RequiredScope = scope::Any> {
+    pub account: Account,
+    _scope: PhantomData<S>,
+}
+
+pub enum AuthError {
+    Unauthorized,
+    InsufficientScope(Scope),
+    AccountRequired,
+    InternalServerError,
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))],
+                "Unauthorized",
+            )
+                .into_response(),
+            // RFC 6750 section 3.1
+            AuthError::InsufficientScope(scope) => {
+                let challenge = format!(r#"Bearer error="insufficient_scope", scope="{scope}""#);
+                (
+                    StatusCode::FORBIDDEN,
+                    [(header::WWW_AUTHENTICATE, challenge)],
+                    format!("This action requires the {scope} scope"),
+                )
+                    .into_response()
+            }
+            AuthError::AccountRequired => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "This method requires an authenticated user",
+            )
+                .into_response(),
+            AuthError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<AuthenticateError> for AuthError {
+    fn from(err: AuthenticateError) -> Self {
+        match err {
+            AuthenticateError::InvalidToken => AuthError::Unauthorized,
+            AuthenticateError::Unknown(_) => AuthError::InternalServerError,
+        }
+    }
+}
+
+/// Token of `Authorization: Bearer {token}`
+pub fn bearer_token(headers: &HeaderMap) -> Option<SessionToken> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(SessionToken::from_raw)
+}
+
+impl<S: RequiredScope> Authenticated<S> {
+    /// Authenticate a token found elsewhere than in `Authorization`
+    pub async fn from_token(
+        registry: &AppRegistry,
+        token: SessionToken,
+    ) -> Result<Self, AuthError> {
+        let Principal {
+            account,
+            app_id,
+            scopes,
+        } = registry.auth_service().authenticate(&token).await?;
+        if let Some(required) = S::SCOPE
+            && !scopes.allows(required)
+        {
+            return Err(AuthError::InsufficientScope(required));
+        }
+
+        Ok(Self {
+            account,
+            app_id,
+            token,
+            _scope: PhantomData,
+        })
+    }
+}
+
+impl<S: RequiredScope> FromRequestParts<AppRegistry> for Authenticated<S> {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(AuthError::Unauthorized)?;
+        Self::from_token(registry, token).await
+    }
+}
+
+/// Anonymous without `Authorization`, but an invalid token is still rejected
+impl<S: RequiredScope> OptionalFromRequestParts<AppRegistry> for Authenticated<S> {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key(header::AUTHORIZATION) {
+            return Ok(None);
+        }
+        let auth =
+            <Self as FromRequestParts<AppRegistry>>::from_request_parts(parts, registry).await?;
+        Ok(Some(auth))
+    }
+}
+
+impl<S: RequiredScope> FromRequestParts<AppRegistry> for AuthenticatedAccount<S> {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = <Authenticated<S> as FromRequestParts<AppRegistry>>::from_request_parts(
+            parts, registry,
+        )
+        .await?;
+        let account = auth.account.ok_or(AuthError::AccountRequired)?;
+
+        Ok(Self {
+            account,
+            _scope: PhantomData,
+        })
+    }
+}
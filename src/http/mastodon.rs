@@ -0,0 +1,164 @@
+use axum::{
+    Json,
+    extract::DefaultBodyLimit,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing,
+};
+use serde::Serialize;
+
+use crate::domain::media::model::MAX_UPLOAD_SIZE;
+
+use super::state::AppRegistry;
+
+/// Room for the multipart boundaries and fields around an uploaded file
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
+mod accounts;
+mod apps;
+mod custom_emojis;
+mod entities;
+mod markers;
+mod media;
+mod notifications;
+mod pagination;
+mod polls;
+mod search;
+mod statuses;
+mod streaming;
+mod timelines;
+
+/// Error body of the Mastodon API
+#[derive(Debug, Serialize)]
+pub struct ErrorJson {
+    error: String,
+}
+
+/// Respond like Mastodon does, with `{"error": message}`
+pub fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    let json = ErrorJson {
+        error: message.into(),
+    };
+    (status, Json(json)).into_response()
+}
+
+/// Mastodon client API
+///
+/// See https://docs.joinmastodon.org/methods/
+pub fn router(registry: AppRegistry) -> axum::Router {
+    axum::Router::new()
+        .route("/v1/apps", routing::post(apps::create_app))
+        .route(
+            "/v1/apps/verify_credentials",
+            routing::get(apps::verify_credentials),
+        )
+        .route(
+            "/v1/accounts/verify_credentials",
+            routing::get(accounts::verify_credentials),
+        )
+        .route(
+            "/v1/accounts/update_credentials",
+            routing::patch(accounts::update_credentials).layer(DefaultBodyLimit::max(
+                2 * MAX_UPLOAD_SIZE + MULTIPART_OVERHEAD,
+            )),
+        )
+        .route(
+            "/v1/accounts/relationships",
+            routing::get(accounts::relationships),
+        )
+        .route("/v1/accounts/{id}", routing::get(accounts::get_account))
+        .route(
+            "/v1/accounts/{id}/statuses",
+            routing::get(accounts::account_statuses),
+        )
+        .route("/v1/accounts/{id}/follow", routing::post(accounts::follow))
+        .route(
+            "/v1/accounts/{id}/unfollow",
+            routing::post(accounts::unfollow),
+        )
+        .route("/v1/statuses", routing::post(statuses::create_status))
+        .route(
+            "/v1/statuses/resolve",
+            routing::get(statuses::resolve_status),
+        )
+        .route(
+            "/v1/statuses/{id}",
+            routing::get(statuses::get_status).delete(statuses::delete_status),
+        )
+        .route(
+            "/v1/statuses/{id}/favourite",
+            routing::post(statuses::favourite),
+        )
+        .route(
+            "/v1/statuses/{id}/unfavourite",
+            routing::post(statuses::unfavourite),
+        )
+        .route("/v1/statuses/{id}/reblog", routing::post(statuses::reblog))
+        .route(
+            "/v1/statuses/{id}/unreblog",
+            routing::post(statuses::unreblog),
+        )
+        .route(
+            "/v1/pleroma/statuses/{id}/reactions/{emoji}",
+            routing::put(statuses::react).delete(statuses::unreact),
+        )
+        .route("/v1/polls/{id}", routing::get(polls::get_poll))
+        .route("/v1/polls/{id}/votes", routing::post(polls::vote))
+        .route(
+            "/v1/media",
+            routing::post(media::upload)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE + MULTIPART_OVERHEAD)),
+        )
+        .route(
+            "/v2/media",
+            routing::post(media::upload)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE + MULTIPART_OVERHEAD)),
+        )
+        .route("/v1/media/{id}", routing::get(media::get_media))
+        .route(
+            "/v1/custom_emojis",
+            routing::get(custom_emojis::custom_emojis),
+        )
+        .route("/v2/search", routing::get(search::search))
+        .route("/v1/timelines/home", routing::get(timelines::home))
+        .route("/v1/timelines/public", routing::get(timelines::public))
+        .route(
+            "/v1/notifications",
+            routing::get(notifications::notifications),
+        )
+        .route(
+            "/v1/notifications/unread_count",
+            routing::get(notifications::unread_count),
+        )
+        .route(
+            "/v1/notifications/clear",
+            routing::post(notifications::clear),
+        )
+        .route(
+            "/v1/notifications/{id}",
+            routing::get(notifications::get_notification),
+        )
+        .route(
+            "/v1/notifications/{id}/dismiss",
+            routing::post(notifications::dismiss),
+        )
+        .route(
+            "/v2/notifications",
+            routing::get(notifications::grouped_notifications),
+        )
+        .route(
+            "/v2/notifications/{group_key}/dismiss",
+            routing::post(notifications::dismiss_group),
+        )
+        .route(
+            "/v1/markers",
+            routing::get(markers::get_markers).post(markers::update_markers),
+        )
+        .route("/v1/streaming", routing::get(streaming::streaming))
+        .route("/v1/streaming/health", routing::get(streaming::health))
+        .route(
+            "/v1/streaming/{*stream}",
+            routing::get(streaming::streaming_path),
+        )
+        .with_state(registry)
+}
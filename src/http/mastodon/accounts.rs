@@ -0,0 +1,362 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, Query, RawQuery, State, multipart::MultipartError},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::{
+    domain::{
+        ap::model::{
+            ActorId,
+            actor::{FindActorError, UpdateProfileError, UpdateProfileRequest},
+            follow::{FollowActorError, FollowActorRequest},
+            note::FindNoteError,
+        },
+        media::model::{MediaFile, ProfileImageKind, UploadMediaError},
+    },
+    http::{
+        auth::{Authenticated, AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{
+    entities::{AccountJson, RelationshipJson, StatusJson},
+    error_response,
+};
+
+pub enum ApiError {
+    NotFound,
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => error_response(StatusCode::NOT_FOUND, "Record not found"),
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindActorError> for ApiError {
+    fn from(err: FindActorError) -> Self {
+        match err {
+            FindActorError::NotFound => ApiError::NotFound,
+            FindActorError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<FindNoteError> for ApiError {
+    fn from(err: FindNoteError) -> Self {
+        match err {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => ApiError::NotFound,
+            FindNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<FollowActorError> for ApiError {
+    fn from(err: FollowActorError) -> Self {
+        match err {
+            FollowActorError::ActorNotFound => ApiError::NotFound,
+            FollowActorError::SelfFollow => ApiError::UnprocessableEntity(err.to_string()),
+            FollowActorError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<UpdateProfileError> for ApiError {
+    fn from(err: UpdateProfileError) -> Self {
+        match err {
+            UpdateProfileError::Invalid(message) => {
+                ApiError::UnprocessableEntity(format!("Validation failed: {message}"))
+            }
+            UpdateProfileError::NotFound => ApiError::NotFound,
+            UpdateProfileError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<UploadMediaError> for ApiError {
+    fn from(err: UploadMediaError) -> Self {
+        match err {
+            UploadMediaError::InvalidFile(message) => ApiError::UnprocessableEntity(message),
+            UploadMediaError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(err: MultipartError) -> Self {
+        ApiError::UnprocessableEntity(err.body_text())
+    }
+}
+
+/// The authenticated account with its profile source
+#[tracing::instrument(skip_all)]
+pub async fn verify_credentials(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::Profile>,
+) -> Result<Json<AccountJson>, ApiError> {
+    let view = registry
+        .ap_service()
+        .find_local_actor_view(auth.account.id())
+        .await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(AccountJson::new(view, &*host_url).with_source()))
+}
+
+/// Index and part of a `fields_attributes[0][name]` form field
+fn parse_field_attribute(name: &str) -> Option<(usize, &str)> {
+    let rest = name.strip_prefix("fields_attributes[")?;
+    let (index, rest) = rest.split_once("][")?;
+    let part = rest.strip_suffix(']')?;
+    Some((index.parse().ok()?, part))
+}
+
+/// Change the profile of the authenticated account, sent as `multipart/form-data` with
+/// `display_name`, `note`, `avatar`, `header` and `fields_attributes[i][name|value]`
+///
+/// Parts that are left out are kept. Followers receive the new profile in an `Update`, and links
+/// in fields are verified in the background.
+/// See https://docs.joinmastodon.org/methods/accounts/#update_credentials
+#[tracing::instrument(skip(registry, auth, multipart))]
+pub async fn update_credentials(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteAccounts>,
+    mut multipart: Multipart,
+) -> Result<Json<AccountJson>, ApiError> {
+    let mut display_name = None;
+    let mut note = None;
+    let mut avatar = None;
+    let mut header = None;
+    let mut fields = BTreeMap::<usize, (String, String)>::new();
+    let mut has_fields = false;
+    while let Some(field) = multipart.next_field().await? {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+        match name.as_str() {
+            "display_name" => display_name = Some(field.text().await?),
+            "note" => note = Some(field.text().await?),
+            "avatar" | "header" => {
+                let media_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field.bytes().await?.to_vec();
+                let file = Some(MediaFile { media_type, data });
+                match name.as_str() {
+                    "avatar" => avatar = file,
+                    _ => header = file,
+                }
+            }
+            name => {
+                let Some((index, part)) = parse_field_attribute(name) else {
+                    continue;
+                };
+                has_fields = true;
+                let entry = fields.entry(index).or_default();
+                match part {
+                    "name" => entry.0 = field.text().await?,
+                    "value" => entry.1 = field.text().await?,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let media_service = registry.media_service();
+    let mut req = UpdateProfileRequest {
+        account_id: auth.account.id().clone(),
+        display_name,
+        summary: note,
+        avatar: None,
+        header: None,
+        fields: has_fields.then(|| fields.into_values().collect()),
+    };
+    let images = [
+        (ProfileImageKind::Avatar, avatar),
+        (ProfileImageKind::Header, header),
+    ];
+    let mut uploaded = Vec::new();
+    let mut result = Ok(());
+    for (kind, file) in images {
+        let Some(file) = file else {
+            continue;
+        };
+        match media_service.upload_profile_image(kind, file).await {
+            Ok(image) => {
+                uploaded.push(image.clone());
+                match kind {
+                    ProfileImageKind::Avatar => req.avatar = Some(image),
+                    ProfileImageKind::Header => req.header = Some(image),
+                }
+            }
+            Err(e) => {
+                result = Err(ApiError::from(e));
+                break;
+            }
+        }
+    }
+    let result = match result {
+        Ok(()) => registry
+            .ap_service()
+            .update_local_profile(req)
+            .await
+            .map_err(ApiError::from),
+        Err(e) => Err(e),
+    };
+    let (profile, orphaned) = match result {
+        Ok(profile) => {
+            let orphaned = profile.replaced_images.clone();
+            (Ok(profile), orphaned)
+        }
+        Err(e) => (Err(e), uploaded),
+    };
+    for image in &orphaned {
+        if let Err(e) = media_service.delete_profile_image(image).await {
+            tracing::warn!(error = %e, url = %image.url, "Failed to delete profile image");
+        }
+    }
+    let profile = profile?;
+
+    let ap_service = registry.ap_service();
+    let account_id = auth.account.id().clone();
+    tokio::spawn(async move {
+        match ap_service.verify_profile_links(&account_id).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, %account_id, "Verified profile links"),
+            Err(e) => tracing::error!(error = %e, %account_id, "Failed to verify profile links"),
+        }
+    });
+
+    let host_url = registry.host_url_service();
+    Ok(Json(
+        AccountJson::new(profile.view, &*host_url).with_source(),
+    ))
+}
+
+#[tracing::instrument(skip(registry))]
+pub async fn get_account(
+    State(registry): State<AppRegistry>,
+    Path(actor_id): Path<ActorId>,
+) -> Result<Json<AccountJson>, ApiError> {
+    let view = registry.ap_service().find_actor_view(&actor_id).await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(AccountJson::new(view, &*host_url)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusesParams {
+    limit: Option<u32>,
+}
+
+impl StatusesParams {
+    const DEFAULT_LIMIT: u32 = 20;
+    const MAX_LIMIT: u32 = 40;
+
+    fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+/// Latest statuses of the account, newest first
+#[tracing::instrument(skip(registry, auth))]
+pub async fn account_statuses(
+    State(registry): State<AppRegistry>,
+    auth: Option<Authenticated<scope::ReadStatuses>>,
+    Path(actor_id): Path<ActorId>,
+    Query(params): Query<StatusesParams>,
+) -> Result<Json<Vec<StatusJson>>, ApiError> {
+    let ap_service = registry.ap_service();
+    // unknown accounts are 404 rather than an empty list
+    ap_service.find_actor_view(&actor_id).await?;
+
+    let viewer = auth.as_ref().and_then(|auth| auth.account.as_ref());
+    let views = ap_service
+        .find_actor_note_views(
+            &actor_id,
+            viewer.map(|account| account.id()),
+            params.limit(),
+        )
+        .await?;
+
+    let host_url = registry.host_url_service();
+    let statuses = views
+        .into_iter()
+        .map(|view| StatusJson::new(view, &*host_url))
+        .collect();
+    Ok(Json(statuses))
+}
+
+/// Relationships of the authenticated account to `id[]` accounts
+#[tracing::instrument(skip(registry, auth))]
+pub async fn relationships(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadFollows>,
+    RawQuery(query): RawQuery,
+) -> Result<Json<Vec<RelationshipJson>>, ApiError> {
+    // `id[]=1&id[]=2` does not deserialize into a `Vec`
+    let actor_ids = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .filter(|(key, _)| key == "id" || key == "id[]")
+        .filter_map(|(_, value)| value.parse::<ActorId>().ok())
+        .collect::<Vec<_>>();
+
+    let ap_service = registry.ap_service();
+    let mut relationships = Vec::new();
+    for actor_id in actor_ids {
+        let req = FollowActorRequest {
+            account_id: auth.account.id().clone(),
+            actor_id,
+        };
+        let relationship = ap_service.find_relationship(req).await?;
+        relationships.push(relationship.into());
+    }
+    Ok(Json(relationships))
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn follow(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteFollows>,
+    Path(actor_id): Path<ActorId>,
+) -> Result<Json<RelationshipJson>, ApiError> {
+    let req = FollowActorRequest {
+        account_id: auth.account.id().clone(),
+        actor_id,
+    };
+    let relationship = registry.ap_service().follow(req).await?;
+
+    Ok(Json(relationship.into()))
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn unfollow(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteFollows>,
+    Path(actor_id): Path<ActorId>,
+) -> Result<Json<RelationshipJson>, ApiError> {
+    let req = FollowActorRequest {
+        account_id: auth.account.id().clone(),
+        actor_id,
+    };
+    let relationship = registry.ap_service().unfollow(req).await?;
+
+    Ok(Json(relationship.into()))
+}
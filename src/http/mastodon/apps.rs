@@ -0,0 +1,179 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::auth::model::{
+        Scopes,
+        oauth::{App, FindAppError, RegisterAppError, RegisterAppRequest, RegisteredApp},
+    },
+    http::{
+        auth::Authenticated,
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::FormOrJson,
+    },
+};
+
+use super::error_response;
+
+pub enum ApiError {
+    UnprocessableEntity(String),
+    Unauthorized,
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::Unauthorized => {
+                error_response(StatusCode::UNAUTHORIZED, "The access token is invalid")
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<RegisterAppError> for ApiError {
+    fn from(err: RegisterAppError) -> Self {
+        match err {
+            RegisterAppError::InvalidRedirectUri(_) => {
+                ApiError::UnprocessableEntity(err.to_string())
+            }
+            RegisterAppError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<FindAppError> for ApiError {
+    fn from(err: FindAppError) -> Self {
+        match err {
+            FindAppError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// `redirect_uris` may be a newline separated string or an array
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RedirectUris {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RedirectUris {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            RedirectUris::One(uris) => uris.lines().map(|uri| uri.trim().to_string()).collect(),
+            RedirectUris::Many(uris) => uris,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAppParams {
+    client_name: String,
+    redirect_uris: RedirectUris,
+    scopes: Option<String>,
+    website: Option<String>,
+}
+
+/// Application entity
+///
+/// See https://docs.joinmastodon.org/entities/Application/
+#[derive(Debug, Serialize)]
+pub struct AppJson {
+    id: String,
+    name: String,
+    website: Option<String>,
+    scopes: Vec<&'static str>,
+    /// deprecated, `redirect_uris` joined with newlines
+    redirect_uri: String,
+    redirect_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<String>,
+}
+
+impl From<App> for AppJson {
+    fn from(app: App) -> Self {
+        AppJson {
+            id: app.id.to_string(),
+            name: app.name,
+            website: app.website,
+            scopes: app.scopes.iter().map(|scope| scope.as_str()).collect(),
+            redirect_uri: app.redirect_uris.join("\n"),
+            redirect_uris: app.redirect_uris,
+            client_id: None,
+            client_secret: None,
+        }
+    }
+}
+
+impl From<RegisteredApp> for AppJson {
+    fn from(registered: RegisteredApp) -> Self {
+        let client_id = registered.app.client_id.clone();
+        AppJson {
+            client_id: Some(client_id),
+            client_secret: Some(registered.client_secret.as_str().to_string()),
+            ..registered.app.into()
+        }
+    }
+}
+
+/// Register a client
+#[tracing::instrument(skip_all)]
+pub async fn create_app(
+    State(registry): State<AppRegistry>,
+    FormOrJson(params): FormOrJson<CreateAppParams>,
+) -> Result<Json<AppJson>, ApiError> {
+    let redirect_uris = params.redirect_uris.into_vec();
+    if redirect_uris.is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "redirect_uris can't be blank".to_string(),
+        ));
+    }
+    let scopes = match params.scopes.filter(|scopes| !scopes.trim().is_empty()) {
+        Some(scopes) => scopes
+            .parse::<Scopes>()
+            .map_err(|e| ApiError::UnprocessableEntity(e.to_string()))?,
+        None => Scopes::default_request(),
+    };
+
+    let req = RegisterAppRequest {
+        name: params.client_name,
+        redirect_uris,
+        scopes,
+        website: params.website.filter(|website| !website.is_empty()),
+    };
+    let registered = registry.auth_service().register_app(req).await?;
+
+    Ok(Json(registered.into()))
+}
+
+/// The app the token was issued to
+#[tracing::instrument(skip_all)]
+pub async fn verify_credentials(
+    State(registry): State<AppRegistry>,
+    auth: Authenticated,
+) -> Result<Json<AppJson>, ApiError> {
+    let Some(app_id) = auth.app_id else {
+        return Err(ApiError::Unauthorized);
+    };
+    let app = registry
+        .auth_service()
+        .find_app(&app_id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+    Ok(Json(app.into()))
+}
@@ -0,0 +1,52 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::media::model::FindEmojiError,
+    http::state::{AppRegistry, AppRegistryExt as _},
+};
+
+use super::{entities::CustomEmojiJson, error_response};
+
+pub enum ApiError {
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindEmojiError> for ApiError {
+    fn from(err: FindEmojiError) -> Self {
+        match err {
+            FindEmojiError::NotFound | FindEmojiError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Custom emoji of this instance, for clients to offer in their pickers
+///
+/// See https://docs.joinmastodon.org/methods/custom_emojis/#get
+#[tracing::instrument(skip(registry))]
+pub async fn custom_emojis(
+    State(registry): State<AppRegistry>,
+) -> Result<Json<Vec<CustomEmojiJson>>, ApiError> {
+    let emojis = registry.media_service().find_local_emojis().await?;
+    let host_url = registry.host_url_service();
+    Ok(Json(
+        emojis
+            .into_iter()
+            .map(|emoji| CustomEmojiJson::new(emoji, &*host_url))
+            .collect(),
+    ))
+}
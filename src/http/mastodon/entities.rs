@@ -0,0 +1,766 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    ap::ActorType,
+    domain::{
+        ap::model::{
+            ActorView,
+            follow::Relationship,
+            note::{NoteId, NoteView},
+            notification::{Marker, NotificationGroup, NotificationView},
+            poll::Poll,
+            reaction::{EmojiReactionCount, ReblogView},
+            search::{Hashtag, SearchResults},
+            timeline::TimelineItem,
+        },
+        hosturl::HostUrlService,
+        media::model::{Attachment, CustomEmoji, ProfileImage, RemoteMediaId},
+    },
+};
+
+/// Account entity
+///
+/// See https://docs.joinmastodon.org/entities/Account/
+#[derive(Debug, Serialize)]
+pub struct AccountJson {
+    id: String,
+    username: String,
+    /// `username` for local accounts, `username@host` for remote ones
+    acct: String,
+    display_name: String,
+    locked: bool,
+    bot: bool,
+    group: bool,
+    discoverable: bool,
+    created_at: DateTime<Utc>,
+    /// bio in HTML
+    note: String,
+    url: String,
+    uri: String,
+    avatar: String,
+    avatar_static: String,
+    header: String,
+    header_static: String,
+    followers_count: u64,
+    following_count: u64,
+    statuses_count: u64,
+    last_status_at: Option<String>,
+    emojis: Vec<CustomEmojiJson>,
+    fields: Vec<serde_json::Value>,
+    /// only for the authenticated account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<AccountSourceJson>,
+    /// `source` of local accounts, shown by [Self::with_source]
+    #[serde(skip)]
+    owner_source: Option<AccountSourceJson>,
+}
+
+/// Profile as the owner edits it
+///
+/// See https://docs.joinmastodon.org/entities/Account/#source
+#[derive(Debug, Serialize)]
+pub struct AccountSourceJson {
+    note: String,
+    fields: Vec<serde_json::Value>,
+    privacy: &'static str,
+    sensitive: bool,
+    language: Option<String>,
+    follow_requests_count: u64,
+}
+
+impl AccountJson {
+    pub fn new(view: ActorView, host_url: &dyn HostUrlService) -> Self {
+        let ActorView {
+            actor,
+            followers_count,
+            following_count,
+            statuses_count,
+        } = view;
+        let acct = match actor.account_id {
+            Some(_) => actor.name.clone(),
+            None => format!("{}@{}", actor.name, actor.host()),
+        };
+        // same placeholders Mastodon serves to accounts without images
+        let base_url = host_url.base_url();
+        let image_url = |image: Option<&ProfileImage>, placeholder: &str| match image {
+            Some(ProfileImage {
+                url,
+                storage_key: Some(_),
+            }) => url.to_string(),
+            Some(ProfileImage {
+                url,
+                storage_key: None,
+            }) => host_url
+                .media_proxy_url(RemoteMediaId::of(url).as_str())
+                .to_string(),
+            None => format!("{base_url}{placeholder}/original/missing.png"),
+        };
+        let avatar = image_url(actor.avatar.as_ref(), "avatars");
+        let header = image_url(actor.header.as_ref(), "headers");
+        let fields = actor
+            .fields
+            .iter()
+            .map(|field| {
+                serde_json::json!({
+                    "name": field.name,
+                    "value": field.value,
+                    "verified_at": field.verified_at,
+                })
+            })
+            .collect();
+        let owner_source = actor.account_id.is_some().then(|| AccountSourceJson {
+            note: actor.summary_source.clone().unwrap_or_default(),
+            fields: actor
+                .fields
+                .iter()
+                .map(|field| {
+                    serde_json::json!({
+                        "name": field.name,
+                        "value": field.source.as_deref().unwrap_or_default(),
+                        "verified_at": field.verified_at,
+                    })
+                })
+                .collect(),
+            privacy: "public",
+            sensitive: false,
+            language: None,
+            follow_requests_count: 0,
+        });
+        AccountJson {
+            id: actor.id.to_string(),
+            username: actor.name.clone(),
+            acct,
+            display_name: actor.display_name.unwrap_or(actor.name),
+            locked: false,
+            bot: matches!(
+                actor.actor_type,
+                ActorType::Application | ActorType::Service
+            ),
+            group: actor.actor_type == ActorType::Group,
+            discoverable: true,
+            created_at: actor.published.unwrap_or(actor.created_at),
+            note: actor.summary.unwrap_or_default(),
+            url: actor.actor_url.to_string(),
+            uri: actor.actor_url.to_string(),
+            avatar_static: avatar.clone(),
+            avatar,
+            header_static: header.clone(),
+            header,
+            followers_count,
+            following_count,
+            statuses_count,
+            last_status_at: None,
+            emojis: actor
+                .emojis
+                .into_iter()
+                .map(|emoji| CustomEmojiJson::new(emoji, host_url))
+                .collect(),
+            fields,
+            source: None,
+            owner_source,
+        }
+    }
+
+    /// Add `source` for the account's owner
+    pub fn with_source(mut self) -> Self {
+        self.source = self.owner_source.take();
+        self
+    }
+}
+
+/// MediaAttachment entity
+///
+/// See https://docs.joinmastodon.org/entities/MediaAttachment/
+#[derive(Debug, Serialize)]
+pub struct MediaAttachmentJson {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+    preview_url: Option<String>,
+    /// where a remote file is hosted
+    remote_url: Option<String>,
+    meta: Option<MediaMetaJson>,
+    /// alt text
+    description: Option<String>,
+    blurhash: Option<String>,
+}
+
+/// `meta` of MediaAttachment, for images
+#[derive(Debug, Serialize)]
+pub struct MediaMetaJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    focus: Option<FocusJson>,
+    original: MediaSizeJson,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    small: Option<MediaSizeJson>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FocusJson {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaSizeJson {
+    width: u32,
+    height: u32,
+    /// like `640x480`
+    size: String,
+    aspect: f64,
+}
+
+impl MediaSizeJson {
+    fn new((width, height): (u32, u32)) -> Self {
+        MediaSizeJson {
+            width,
+            height,
+            size: format!("{width}x{height}"),
+            aspect: if height == 0 {
+                0.0
+            } else {
+                f64::from(width) / f64::from(height)
+            },
+        }
+    }
+}
+
+impl MediaAttachmentJson {
+    /// Remote files are linked through the media proxy
+    pub fn new(attachment: Attachment, host_url: &dyn HostUrlService) -> Self {
+        let remote_url = attachment
+            .storage_key
+            .is_none()
+            .then(|| attachment.url.to_string());
+        let url = match &remote_url {
+            Some(_) => host_url.media_proxy_url(RemoteMediaId::of(&attachment.url).as_str()),
+            None => attachment.url.clone(),
+        };
+        let meta = attachment.size.map(|size| MediaMetaJson {
+            focus: attachment.focus.map(|focus| FocusJson {
+                x: focus.x,
+                y: focus.y,
+            }),
+            original: MediaSizeJson::new(size),
+            small: attachment
+                .preview
+                .as_ref()
+                .map(|preview| MediaSizeJson::new(preview.size)),
+        });
+        let preview_url = attachment
+            .preview
+            .as_ref()
+            .map_or(&url, |preview| &preview.url);
+        MediaAttachmentJson {
+            id: attachment.id.to_string(),
+            kind: attachment.kind.to_string(),
+            url: url.to_string(),
+            preview_url: Some(preview_url.to_string()),
+            remote_url,
+            meta,
+            description: attachment.description,
+            blurhash: attachment.blurhash,
+        }
+    }
+}
+
+/// CustomEmoji entity
+///
+/// See https://docs.joinmastodon.org/entities/CustomEmoji/
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomEmojiJson {
+    shortcode: String,
+    url: String,
+    static_url: String,
+    visible_in_picker: bool,
+    category: Option<String>,
+}
+
+/// Where the image of the emoji is served. Remote images are linked through the media proxy
+fn custom_emoji_url(emoji: &CustomEmoji, host_url: &dyn HostUrlService) -> String {
+    match emoji.storage_key {
+        Some(_) => emoji.url.to_string(),
+        None => host_url
+            .media_proxy_url(RemoteMediaId::of(&emoji.url).as_str())
+            .to_string(),
+    }
+}
+
+impl CustomEmojiJson {
+    pub fn new(emoji: CustomEmoji, host_url: &dyn HostUrlService) -> Self {
+        let url = custom_emoji_url(&emoji, host_url);
+        CustomEmojiJson {
+            shortcode: emoji.shortcode,
+            static_url: url.clone(),
+            url,
+            visible_in_picker: emoji.host.is_none(),
+            category: None,
+        }
+    }
+}
+
+/// EmojiReaction entity of Pleroma
+///
+/// See https://docs.pleroma.social/backend/development/API/pleroma_api/#emoji-reactions
+#[derive(Debug, Serialize)]
+pub struct EmojiReactionJson {
+    /// unicode emoji, or shortcode of a custom one with `@host` for remote ones
+    name: String,
+    count: u64,
+    me: bool,
+    /// image of a custom emoji
+    url: Option<String>,
+}
+
+impl EmojiReactionJson {
+    pub fn new(reaction: EmojiReactionCount, host_url: &dyn HostUrlService) -> Self {
+        let (name, url) = match &reaction.emoji {
+            Some(emoji) => {
+                let name = match &emoji.host {
+                    Some(host) => format!("{}@{host}", emoji.shortcode),
+                    None => emoji.shortcode.clone(),
+                };
+                (name, Some(custom_emoji_url(emoji, host_url)))
+            }
+            None => (reaction.content, None),
+        };
+        EmojiReactionJson {
+            name,
+            count: reaction.count,
+            me: reaction.me,
+            url,
+        }
+    }
+}
+
+/// Poll entity
+///
+/// See https://docs.joinmastodon.org/entities/Poll/
+#[derive(Debug, Serialize)]
+pub struct PollJson {
+    /// same as the id of the status
+    id: String,
+    expires_at: Option<DateTime<Utc>>,
+    expired: bool,
+    multiple: bool,
+    votes_count: u64,
+    voters_count: u64,
+    options: Vec<PollOptionJson>,
+    emojis: Vec<CustomEmojiJson>,
+    voted: bool,
+    own_votes: Vec<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PollOptionJson {
+    title: String,
+    votes_count: u64,
+}
+
+impl PollJson {
+    pub fn new(
+        note_id: &NoteId,
+        poll: Poll,
+        own_votes: Vec<u32>,
+        emojis: Vec<CustomEmojiJson>,
+    ) -> Self {
+        PollJson {
+            id: note_id.to_string(),
+            expires_at: poll.expires_at,
+            expired: poll.is_expired(),
+            multiple: poll.multiple,
+            votes_count: poll.options.iter().map(|option| option.votes_count).sum(),
+            voters_count: poll.voters_count,
+            options: poll
+                .options
+                .into_iter()
+                .map(|option| PollOptionJson {
+                    title: option.title,
+                    votes_count: option.votes_count,
+                })
+                .collect(),
+            emojis,
+            voted: !own_votes.is_empty(),
+            own_votes,
+        }
+    }
+
+    pub fn from_view(view: NoteView, host_url: &dyn HostUrlService) -> Option<Self> {
+        let poll = view.poll?;
+        let emojis = view
+            .emojis
+            .into_iter()
+            .map(|emoji| CustomEmojiJson::new(emoji, host_url))
+            .collect();
+        Some(PollJson::new(&view.id, poll, view.own_votes, emojis))
+    }
+}
+
+/// Pleroma's additions to the Status entity
+#[derive(Debug, Serialize)]
+pub struct PleromaStatusJson {
+    emoji_reactions: Vec<EmojiReactionJson>,
+}
+
+/// Status entity
+///
+/// See https://docs.joinmastodon.org/entities/Status/
+#[derive(Debug, Serialize)]
+pub struct StatusJson {
+    id: String,
+    uri: String,
+    url: Option<String>,
+    created_at: DateTime<Utc>,
+    edited_at: Option<DateTime<Utc>>,
+    account: AccountJson,
+    /// HTML content
+    content: String,
+    visibility: &'static str,
+    sensitive: bool,
+    spoiler_text: String,
+    in_reply_to_id: Option<String>,
+    in_reply_to_account_id: Option<String>,
+    reblog: Option<Box<StatusJson>>,
+    language: Option<String>,
+    replies_count: u64,
+    reblogs_count: u64,
+    favourites_count: u64,
+    favourited: bool,
+    reblogged: bool,
+    muted: bool,
+    bookmarked: bool,
+    media_attachments: Vec<MediaAttachmentJson>,
+    mentions: Vec<serde_json::Value>,
+    tags: Vec<serde_json::Value>,
+    emojis: Vec<CustomEmojiJson>,
+    card: Option<serde_json::Value>,
+    poll: Option<PollJson>,
+    pleroma: PleromaStatusJson,
+    /// source text, only in the response to deletion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+impl StatusJson {
+    pub fn new(view: NoteView, host_url: &dyn HostUrlService) -> Self {
+        let content = view.display_content();
+        let spoiler_text = view.content_warning().unwrap_or_default().to_string();
+        let emojis = view
+            .emojis
+            .into_iter()
+            .map(|emoji| CustomEmojiJson::new(emoji, host_url))
+            .collect::<Vec<_>>();
+        let poll = view
+            .poll
+            .map(|poll| PollJson::new(&view.id, poll, view.own_votes, emojis.clone()));
+        StatusJson {
+            id: view.id.to_string(),
+            uri: view.note_url.to_string(),
+            url: Some(view.url.as_ref().unwrap_or(&view.note_url).to_string()),
+            created_at: view.published,
+            edited_at: view.updated,
+            account: AccountJson::new(view.author, host_url),
+            content,
+            visibility: "public",
+            sensitive: view.sensitive,
+            spoiler_text,
+            in_reply_to_id: view.in_reply_to.as_ref().map(|(id, _)| id.to_string()),
+            in_reply_to_account_id: view.in_reply_to.as_ref().map(|(_, id)| id.to_string()),
+            reblog: None,
+            language: None,
+            replies_count: 0,
+            reblogs_count: view.reblogs_count,
+            favourites_count: view.favourites_count,
+            favourited: view.favourited,
+            reblogged: view.reblogged,
+            muted: false,
+            bookmarked: false,
+            media_attachments: view
+                .attachments
+                .into_iter()
+                .map(|attachment| MediaAttachmentJson::new(attachment, host_url))
+                .collect(),
+            mentions: Vec::new(),
+            tags: Vec::new(),
+            emojis,
+            card: None,
+            poll,
+            pleroma: PleromaStatusJson {
+                emoji_reactions: view
+                    .reactions
+                    .into_iter()
+                    .map(|reaction| EmojiReactionJson::new(reaction, host_url))
+                    .collect(),
+            },
+            text: None,
+        }
+    }
+
+    /// The reblog as a status of its own wrapping the reblogged one
+    pub fn reblog(view: ReblogView, host_url: &dyn HostUrlService) -> Self {
+        let reblog = StatusJson::new(view.note, host_url);
+        StatusJson {
+            id: view.id.to_string(),
+            uri: view.announce_url.to_string(),
+            url: None,
+            created_at: view.id.timestamp().unwrap_or_default(),
+            edited_at: None,
+            account: AccountJson::new(view.actor, host_url),
+            content: String::new(),
+            visibility: reblog.visibility,
+            sensitive: false,
+            spoiler_text: String::new(),
+            in_reply_to_id: None,
+            in_reply_to_account_id: None,
+            language: None,
+            replies_count: 0,
+            reblogs_count: 0,
+            favourites_count: 0,
+            favourited: reblog.favourited,
+            reblogged: reblog.reblogged,
+            muted: false,
+            bookmarked: false,
+            media_attachments: Vec::new(),
+            mentions: Vec::new(),
+            tags: Vec::new(),
+            emojis: Vec::new(),
+            card: None,
+            poll: None,
+            pleroma: PleromaStatusJson {
+                emoji_reactions: Vec::new(),
+            },
+            text: None,
+            reblog: Some(Box::new(reblog)),
+        }
+    }
+
+    pub fn from_timeline_item(item: TimelineItem, host_url: &dyn HostUrlService) -> Self {
+        match item {
+            TimelineItem::Note(view) => StatusJson::new(*view, host_url),
+            TimelineItem::Reblog(view) => StatusJson::reblog(*view, host_url),
+        }
+    }
+
+    /// Add the source text of a deleted status so it can be redrafted
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+}
+
+/// Relationship entity
+///
+/// See https://docs.joinmastodon.org/entities/Relationship/
+#[derive(Debug, Serialize)]
+pub struct RelationshipJson {
+    id: String,
+    following: bool,
+    showing_reblogs: bool,
+    notifying: bool,
+    followed_by: bool,
+    blocking: bool,
+    blocked_by: bool,
+    muting: bool,
+    muting_notifications: bool,
+    requested: bool,
+    requested_by: bool,
+    domain_blocking: bool,
+    endorsed: bool,
+    note: String,
+}
+
+impl From<Relationship> for RelationshipJson {
+    fn from(relationship: Relationship) -> Self {
+        RelationshipJson {
+            id: relationship.actor_id.to_string(),
+            following: relationship.following,
+            showing_reblogs: relationship.following,
+            notifying: false,
+            followed_by: relationship.followed_by,
+            blocking: false,
+            blocked_by: false,
+            muting: false,
+            muting_notifications: false,
+            requested: relationship.requested,
+            requested_by: false,
+            domain_blocking: false,
+            endorsed: false,
+            note: String::new(),
+        }
+    }
+}
+
+/// Notification entity
+///
+/// See https://docs.joinmastodon.org/entities/Notification/
+#[derive(Debug, Serialize)]
+pub struct NotificationJson {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    created_at: DateTime<Utc>,
+    account: AccountJson,
+    status: Option<StatusJson>,
+}
+
+impl NotificationJson {
+    pub fn new(view: NotificationView, host_url: &dyn HostUrlService) -> Self {
+        NotificationJson {
+            id: view.id.to_string(),
+            kind: view.kind.to_string(),
+            created_at: view.id.timestamp().unwrap_or_default(),
+            account: AccountJson::new(view.actor, host_url),
+            status: view.note.map(|note| StatusJson::new(note, host_url)),
+        }
+    }
+}
+
+/// Notifications of a page merged by group, with the accounts and statuses they refer to
+///
+/// See https://docs.joinmastodon.org/entities/GroupedNotificationsResults/
+#[derive(Debug, Serialize)]
+pub struct GroupedNotificationsJson {
+    accounts: Vec<AccountJson>,
+    statuses: Vec<StatusJson>,
+    notification_groups: Vec<NotificationGroupJson>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationGroupJson {
+    group_key: String,
+    notifications_count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    most_recent_notification_id: String,
+    page_min_id: String,
+    page_max_id: String,
+    latest_page_notification_at: DateTime<Utc>,
+    sample_account_ids: Vec<String>,
+    status_id: Option<String>,
+}
+
+impl GroupedNotificationsJson {
+    /// Accounts shown as samples of each group
+    const SAMPLE_ACCOUNTS: usize = 8;
+
+    pub fn new(groups: Vec<NotificationGroup>, host_url: &dyn HostUrlService) -> Self {
+        let mut json = GroupedNotificationsJson {
+            accounts: Vec::new(),
+            statuses: Vec::new(),
+            notification_groups: Vec::new(),
+        };
+        let mut account_ids = HashSet::new();
+        let mut status_ids = HashSet::new();
+        for group in groups {
+            // groups are never empty
+            let (Some(newest), Some(oldest)) =
+                (group.notifications.first(), group.notifications.last())
+            else {
+                continue;
+            };
+            let mut group_json = NotificationGroupJson {
+                group_key: group.key.to_string(),
+                notifications_count: group.notifications.len(),
+                kind: newest.kind.to_string(),
+                most_recent_notification_id: newest.id.to_string(),
+                page_min_id: oldest.id.to_string(),
+                page_max_id: newest.id.to_string(),
+                latest_page_notification_at: newest.id.timestamp().unwrap_or_default(),
+                sample_account_ids: Vec::new(),
+                status_id: newest.note.as_ref().map(|note| note.id.to_string()),
+            };
+            for view in group.notifications {
+                let actor_id = view.actor.actor.id.to_string();
+                if group_json.sample_account_ids.len() < Self::SAMPLE_ACCOUNTS
+                    && !group_json.sample_account_ids.contains(&actor_id)
+                {
+                    group_json.sample_account_ids.push(actor_id.clone());
+                }
+                if account_ids.insert(actor_id) {
+                    json.accounts.push(AccountJson::new(view.actor, host_url));
+                }
+                if let Some(note) = view.note
+                    && status_ids.insert(note.id.clone())
+                {
+                    json.statuses.push(StatusJson::new(note, host_url));
+                }
+            }
+            json.notification_groups.push(group_json);
+        }
+        json
+    }
+}
+
+/// Reading position in a timeline
+///
+/// See https://docs.joinmastodon.org/entities/Marker/
+#[derive(Debug, Serialize)]
+pub struct MarkerJson {
+    last_read_id: String,
+    version: i64,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<Marker> for MarkerJson {
+    fn from(marker: Marker) -> Self {
+        MarkerJson {
+            last_read_id: marker.last_read_id.to_string(),
+            version: marker.version,
+            updated_at: marker.updated_at,
+        }
+    }
+}
+
+/// Hashtag entity. Usage history is not tracked
+///
+/// See https://docs.joinmastodon.org/entities/Tag/
+#[derive(Debug, Serialize)]
+pub struct TagJson {
+    name: String,
+    url: String,
+    history: Vec<serde_json::Value>,
+}
+
+impl TagJson {
+    pub fn new(hashtag: Hashtag, host_url: &dyn HostUrlService) -> Self {
+        TagJson {
+            url: format!("{}tags/{}", host_url.base_url(), hashtag.name),
+            name: hashtag.name,
+            history: Vec::new(),
+        }
+    }
+}
+
+/// See https://docs.joinmastodon.org/entities/Search/
+#[derive(Debug, Serialize)]
+pub struct SearchJson {
+    accounts: Vec<AccountJson>,
+    statuses: Vec<StatusJson>,
+    hashtags: Vec<TagJson>,
+}
+
+impl SearchJson {
+    pub fn new(results: SearchResults, host_url: &dyn HostUrlService) -> Self {
+        SearchJson {
+            accounts: results
+                .actors
+                .into_iter()
+                .map(|actor| AccountJson::new(actor, host_url))
+                .collect(),
+            statuses: results
+                .notes
+                .into_iter()
+                .map(|note| StatusJson::new(note, host_url))
+                .collect(),
+            hashtags: results
+                .hashtags
+                .into_iter()
+                .map(|hashtag| TagJson::new(hashtag, host_url))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    Json,
+    extract::{RawQuery, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::ap::model::notification::{MarkerError, MarkerTimeline},
+    http::{
+        auth::{AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::FormOrJson,
+    },
+};
+
+use super::{entities::MarkerJson, error_response};
+
+pub enum ApiError {
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<MarkerError> for ApiError {
+    fn from(err: MarkerError) -> Self {
+        match err {
+            MarkerError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Markers keyed by the name of their timeline
+type MarkersJson = BTreeMap<String, MarkerJson>;
+
+/// Saved reading positions of the `timeline[]` timelines
+///
+/// See https://docs.joinmastodon.org/methods/markers/#get
+#[tracing::instrument(skip(registry, auth))]
+pub async fn get_markers(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadStatuses>,
+    RawQuery(query): RawQuery,
+) -> Result<Json<MarkersJson>, ApiError> {
+    // `timeline[]=home` does not deserialize into a `Vec`
+    let timelines = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .filter(|(key, _)| key == "timeline" || key == "timeline[]")
+        .filter_map(|(_, value)| value.parse::<MarkerTimeline>().ok())
+        .collect::<Vec<_>>();
+
+    let markers = registry
+        .ap_service()
+        .find_markers(auth.account.id())
+        .await?;
+    let markers = markers
+        .into_iter()
+        .filter(|marker| timelines.contains(&marker.timeline))
+        .map(|marker| (marker.timeline.to_string(), marker.into()))
+        .collect();
+    Ok(Json(markers))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LastReadParams {
+    last_read_id: uuid::Uuid,
+}
+
+/// Markers to save, as `{"home": {"last_read_id": ..}}` in JSON or `home[last_read_id]=..` in forms
+#[derive(Debug, Deserialize)]
+pub struct UpdateMarkersParams {
+    home: Option<LastReadParams>,
+    notifications: Option<LastReadParams>,
+    #[serde(rename = "home[last_read_id]")]
+    home_last_read_id: Option<uuid::Uuid>,
+    #[serde(rename = "notifications[last_read_id]")]
+    notifications_last_read_id: Option<uuid::Uuid>,
+}
+
+/// Save the reading position of the home and notifications timelines
+///
+/// See https://docs.joinmastodon.org/methods/markers/#create
+#[tracing::instrument(skip(registry, auth))]
+pub async fn update_markers(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    FormOrJson(params): FormOrJson<UpdateMarkersParams>,
+) -> Result<Json<MarkersJson>, ApiError> {
+    let last_read_ids = [
+        (
+            MarkerTimeline::Home,
+            params
+                .home
+                .map(|p| p.last_read_id)
+                .or(params.home_last_read_id),
+        ),
+        (
+            MarkerTimeline::Notifications,
+            params
+                .notifications
+                .map(|p| p.last_read_id)
+                .or(params.notifications_last_read_id),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(timeline, id)| Some((timeline, id?)))
+    .collect::<Vec<_>>();
+
+    let markers = registry
+        .ap_service()
+        .update_markers(auth.account.id(), &last_read_ids)
+        .await?;
+    let markers = markers
+        .into_iter()
+        .filter(|marker| last_read_ids.iter().any(|(t, _)| *t == marker.timeline))
+        .map(|marker| (marker.timeline.to_string(), marker.into()))
+        .collect();
+    Ok(Json(markers))
+}
@@ -0,0 +1,125 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, State, multipart::MultipartError},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::media::model::{
+        AttachmentId, FindAttachmentError, Focus, MediaFile, UploadMediaError, UploadMediaRequest,
+    },
+    http::{
+        auth::{AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{entities::MediaAttachmentJson, error_response};
+
+pub enum ApiError {
+    NotFound,
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => error_response(StatusCode::NOT_FOUND, "Record not found"),
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<UploadMediaError> for ApiError {
+    fn from(err: UploadMediaError) -> Self {
+        match err {
+            UploadMediaError::InvalidFile(message) => ApiError::UnprocessableEntity(message),
+            UploadMediaError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<FindAttachmentError> for ApiError {
+    fn from(err: FindAttachmentError) -> Self {
+        match err {
+            FindAttachmentError::NotFound => ApiError::NotFound,
+            FindAttachmentError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(err: MultipartError) -> Self {
+        ApiError::UnprocessableEntity(err.body_text())
+    }
+}
+
+/// Upload a file to attach to a status, sent as `multipart/form-data` with `file`,
+/// `description` and `focus`
+///
+/// The file is processed and stored before responding, so the attachment is ready at once.
+/// See https://docs.joinmastodon.org/methods/media/#v2
+#[tracing::instrument(skip(registry, auth, multipart))]
+pub async fn upload(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteMedia>,
+    mut multipart: Multipart,
+) -> Result<Json<MediaAttachmentJson>, ApiError> {
+    let mut file = None;
+    let mut description = None;
+    let mut focus = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("file") => {
+                let media_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field.bytes().await?.to_vec();
+                file = Some(MediaFile { media_type, data });
+            }
+            Some("description") => description = Some(field.text().await?),
+            Some("focus") => focus = Some(field.text().await?.parse::<Focus>()?),
+            _ => {}
+        }
+    }
+    let Some(file) = file else {
+        return Err(ApiError::UnprocessableEntity(
+            "Validation failed: File can't be blank".to_string(),
+        ));
+    };
+
+    let req = UploadMediaRequest {
+        account_id: auth.account.id().clone(),
+        file,
+        description,
+        focus,
+    };
+    let attachment = registry.media_service().upload(req).await?;
+    let host_url = registry.host_url_service();
+    Ok(Json(MediaAttachmentJson::new(attachment, &*host_url)))
+}
+
+/// An upload of the authenticated account
+///
+/// See https://docs.joinmastodon.org/methods/media/#get
+#[tracing::instrument(skip(registry, auth))]
+pub async fn get_media(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteMedia>,
+    Path(id): Path<AttachmentId>,
+) -> Result<Json<MediaAttachmentJson>, ApiError> {
+    let attachment = registry
+        .media_service()
+        .find_attachment(auth.account.id(), &id)
+        .await?;
+    let host_url = registry.host_url_service();
+    Ok(Json(MediaAttachmentJson::new(attachment, &*host_url)))
+}
@@ -0,0 +1,253 @@
+use axum::{
+    Json,
+    extract::{Path, Query, RawQuery, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        HttpUrl,
+        account::model::AccountId,
+        ap::model::{
+            ActorId,
+            notification::{
+                DeleteNotificationError, FindNotificationError, Notification, NotificationGroup,
+                NotificationGroupKey, NotificationId, NotificationKind, NotificationsRequest,
+            },
+        },
+        hosturl::HostUrlService,
+    },
+    http::{
+        auth::{AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{
+    entities::{GroupedNotificationsJson, NotificationJson},
+    error_response,
+    pagination::{PageParams, link_header},
+};
+
+pub enum ApiError {
+    NotFound,
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => error_response(StatusCode::NOT_FOUND, "Record not found"),
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindNotificationError> for ApiError {
+    fn from(err: FindNotificationError) -> Self {
+        match err {
+            FindNotificationError::NotFound => ApiError::NotFound,
+            FindNotificationError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<DeleteNotificationError> for ApiError {
+    fn from(err: DeleteNotificationError) -> Self {
+        match err {
+            DeleteNotificationError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Filters of a notifications page
+///
+/// `types[]=a&types[]=b` does not deserialize into a `Vec`, so they are parsed from the raw query.
+/// Unknown types are ignored, like Mastodon does.
+fn notifications_request(
+    account_id: AccountId,
+    query: Option<&str>,
+    params: PageParams<Notification>,
+) -> NotificationsRequest {
+    let mut req = NotificationsRequest {
+        account_id,
+        kinds: Vec::new(),
+        exclude_kinds: Vec::new(),
+        actor_id: None,
+        page: params.into_page(),
+    };
+    for (key, value) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        match key.as_ref() {
+            "types" | "types[]" => req.kinds.extend(value.parse::<NotificationKind>().ok()),
+            "exclude_types" | "exclude_types[]" => {
+                req.exclude_kinds
+                    .extend(value.parse::<NotificationKind>().ok());
+            }
+            "account_id" => req.actor_id = value.parse::<ActorId>().ok(),
+            _ => {}
+        }
+    }
+    req
+}
+
+/// Endpoint url with the filters of `query`, to build the `Link` header from
+fn page_url(
+    host_url: &dyn HostUrlService,
+    path: &str,
+    query: Option<&str>,
+) -> Result<HttpUrl, ApiError> {
+    let mut url = host_url.base_url().join(path).map_err(|e| {
+        tracing::error!(error = %e, "Failed to build notifications url");
+        ApiError::InternalServerError
+    })?;
+    let filters = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .filter(|(key, _)| !matches!(key.as_ref(), "max_id" | "since_id" | "min_id"))
+        .collect::<Vec<_>>();
+    if !filters.is_empty() {
+        url.query_pairs_mut().extend_pairs(filters);
+    }
+    HttpUrl::new(url).map_err(|_| ApiError::InternalServerError)
+}
+
+/// Notifications of the authenticated account, newest first
+///
+/// See https://docs.joinmastodon.org/methods/notifications/#get
+#[tracing::instrument(skip(registry, auth))]
+pub async fn notifications(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadNotifications>,
+    RawQuery(query): RawQuery,
+    Query(params): Query<PageParams<Notification>>,
+) -> Result<Response, ApiError> {
+    let req = notifications_request(auth.account.id().clone(), query.as_deref(), params);
+    let views = registry.ap_service().notifications(req).await?;
+
+    let host_url = registry.host_url_service();
+    let url = page_url(&*host_url, "api/v1/notifications", query.as_deref())?;
+    let ids = views.iter().map(|view| view.id.clone()).collect::<Vec<_>>();
+    let notifications = views
+        .into_iter()
+        .map(|view| NotificationJson::new(view, &*host_url))
+        .collect::<Vec<_>>();
+    Ok(match link_header(&url, &ids) {
+        Some(link) => ([(header::LINK, link)], Json(notifications)).into_response(),
+        None => Json(notifications).into_response(),
+    })
+}
+
+/// Notifications of the authenticated account, favourites and reblogs of a note grouped together
+///
+/// See https://docs.joinmastodon.org/methods/grouped_notifications/#get-grouped
+#[tracing::instrument(skip(registry, auth))]
+pub async fn grouped_notifications(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadNotifications>,
+    RawQuery(query): RawQuery,
+    Query(params): Query<PageParams<Notification>>,
+) -> Result<Response, ApiError> {
+    let req = notifications_request(auth.account.id().clone(), query.as_deref(), params);
+    let views = registry.ap_service().notifications(req).await?;
+
+    let host_url = registry.host_url_service();
+    let url = page_url(&*host_url, "api/v2/notifications", query.as_deref())?;
+    let ids = views.iter().map(|view| view.id.clone()).collect::<Vec<_>>();
+    let json = GroupedNotificationsJson::new(NotificationGroup::group(views), &*host_url);
+    Ok(match link_header(&url, &ids) {
+        Some(link) => ([(header::LINK, link)], Json(json)).into_response(),
+        None => Json(json).into_response(),
+    })
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn get_notification(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadNotifications>,
+    Path(id): Path<NotificationId>,
+) -> Result<Json<NotificationJson>, ApiError> {
+    let view = registry
+        .ap_service()
+        .find_notification(auth.account.id(), &id)
+        .await?;
+    let host_url = registry.host_url_service();
+    Ok(Json(NotificationJson::new(view, &*host_url)))
+}
+
+/// Empty object Mastodon answers dismissals with
+#[derive(Debug, Serialize)]
+pub struct EmptyJson {}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn dismiss(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteNotifications>,
+    Path(id): Path<NotificationId>,
+) -> Result<Json<EmptyJson>, ApiError> {
+    registry
+        .ap_service()
+        .dismiss_notification(auth.account.id(), &id)
+        .await?;
+    Ok(Json(EmptyJson {}))
+}
+
+/// Dismiss every notification of a group, like `favourite-{status_id}`
+#[tracing::instrument(skip(registry, auth))]
+pub async fn dismiss_group(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteNotifications>,
+    Path(group_key): Path<String>,
+) -> Result<Json<EmptyJson>, ApiError> {
+    let key = group_key
+        .parse::<NotificationGroupKey>()
+        .map_err(|_| ApiError::NotFound)?;
+    registry
+        .ap_service()
+        .dismiss_notification_group(auth.account.id(), &key)
+        .await?;
+    Ok(Json(EmptyJson {}))
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn clear(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteNotifications>,
+) -> Result<Json<EmptyJson>, ApiError> {
+    registry
+        .ap_service()
+        .clear_notifications(auth.account.id())
+        .await?;
+    Ok(Json(EmptyJson {}))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnreadCountParams {
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadCountJson {
+    count: u64,
+}
+
+/// Notifications newer than the `notifications` marker
+///
+/// See https://docs.joinmastodon.org/methods/notifications/#unread-count
+#[tracing::instrument(skip(registry, auth))]
+pub async fn unread_count(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadNotifications>,
+    Query(params): Query<UnreadCountParams>,
+) -> Result<Json<UnreadCountJson>, ApiError> {
+    const DEFAULT_LIMIT: u32 = 100;
+    const MAX_LIMIT: u32 = 1000;
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let count = registry
+        .ap_service()
+        .unread_notification_count(auth.account.id(), limit)
+        .await?;
+    Ok(Json(UnreadCountJson { count }))
+}
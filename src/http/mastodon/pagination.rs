@@ -0,0 +1,54 @@
+use axum::http::HeaderValue;
+use serde::Deserialize;
+
+use crate::domain::{
+    HttpUrl, Id,
+    ap::model::timeline::{TimelineItem, TimelinePage},
+};
+
+/// Cursor parameters shared by paginated endpoints, over ids of `T`
+///
+/// See https://docs.joinmastodon.org/api/guidelines/#pagination
+#[derive(Debug, Deserialize)]
+#[serde(bound = "")]
+pub struct PageParams<T = TimelineItem> {
+    max_id: Option<Id<T>>,
+    since_id: Option<Id<T>>,
+    min_id: Option<Id<T>>,
+    limit: Option<u32>,
+}
+
+impl<T> PageParams<T> {
+    const DEFAULT_LIMIT: u32 = 20;
+    const MAX_LIMIT: u32 = 40;
+
+    pub fn into_page(self) -> TimelinePage<T> {
+        TimelinePage {
+            max_id: self.max_id,
+            since_id: self.since_id,
+            min_id: self.min_id,
+            limit: self
+                .limit
+                .unwrap_or(Self::DEFAULT_LIMIT)
+                .clamp(1, Self::MAX_LIMIT),
+        }
+    }
+}
+
+/// `Link` header pointing to older (`next`) and newer (`prev`) pages
+///
+/// `url` is the endpoint with its non-cursor parameters. `ids` are newest first.
+pub fn link_header<T>(url: &HttpUrl, ids: &[Id<T>]) -> Option<HeaderValue> {
+    let (newest, oldest) = (ids.first()?, ids.last()?);
+    let page_url = |key: &str, id: &Id<T>| {
+        let mut url = url::Url::from(url.clone());
+        url.query_pairs_mut().append_pair(key, &id.to_string());
+        url
+    };
+    let link = format!(
+        r#"<{}>; rel="next", <{}>; rel="prev""#,
+        page_url("max_id", oldest),
+        page_url("min_id", newest)
+    );
+    HeaderValue::from_str(&link).ok()
+}
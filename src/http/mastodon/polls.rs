@@ -0,0 +1,135 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::ap::model::{
+        note::{FindNoteError, NoteId},
+        poll::{VoteError, VoteRequest},
+    },
+    http::{
+        auth::{Authenticated, AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::FormOrJson,
+    },
+};
+
+use super::{entities::PollJson, error_response};
+
+pub enum ApiError {
+    NotFound,
+    Forbidden,
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => error_response(StatusCode::NOT_FOUND, "Record not found"),
+            ApiError::Forbidden => {
+                error_response(StatusCode::FORBIDDEN, "This action is not allowed")
+            }
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindNoteError> for ApiError {
+    fn from(err: FindNoteError) -> Self {
+        match err {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => ApiError::NotFound,
+            FindNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<VoteError> for ApiError {
+    fn from(err: VoteError) -> Self {
+        match err {
+            VoteError::NotFound => ApiError::NotFound,
+            VoteError::Expired
+            | VoteError::AlreadyVoted
+            | VoteError::OwnPoll
+            | VoteError::InvalidChoice(_) => ApiError::UnprocessableEntity(err.to_string()),
+            VoteError::ActorNotFound => ApiError::Forbidden,
+            VoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Poll of the status with the id
+///
+/// See https://docs.joinmastodon.org/methods/polls/#get
+#[tracing::instrument(skip(registry, auth))]
+pub async fn get_poll(
+    State(registry): State<AppRegistry>,
+    auth: Option<Authenticated<scope::ReadStatuses>>,
+    Path(note_id): Path<NoteId>,
+) -> Result<Json<PollJson>, ApiError> {
+    let viewer = auth.as_ref().and_then(|auth| auth.account.as_ref());
+    let view = registry
+        .ap_service()
+        .find_note_view(&note_id, viewer.map(|account| account.id()))
+        .await?;
+
+    let host_url = registry.host_url_service();
+    PollJson::from_view(view, &*host_url)
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
+
+/// Index of an option, which some clients send as a string
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Choice {
+    Index(u32),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VoteParams {
+    #[serde(rename = "choices[]", alias = "choices", default)]
+    choices: Vec<Choice>,
+}
+
+/// Vote for the options at the indices of `choices[]`
+///
+/// See https://docs.joinmastodon.org/methods/polls/#vote
+#[tracing::instrument(skip(registry, auth))]
+pub async fn vote(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    Path(note_id): Path<NoteId>,
+    FormOrJson(params): FormOrJson<VoteParams>,
+) -> Result<Json<PollJson>, ApiError> {
+    let choices = params
+        .choices
+        .into_iter()
+        .map(|choice| match choice {
+            Choice::Index(index) => Ok(index),
+            Choice::Text(text) => text.parse(),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::UnprocessableEntity("invalid choice".to_string()))?;
+    let req = VoteRequest {
+        account_id: auth.account.id().clone(),
+        note_id,
+        choices,
+    };
+    let view = registry.ap_service().vote(req).await?;
+
+    let host_url = registry.host_url_service();
+    PollJson::from_view(view, &*host_url)
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
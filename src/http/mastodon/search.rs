@@ -0,0 +1,98 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::ap::model::{
+        ActorId,
+        search::{SearchError, SearchKind, SearchRequest},
+    },
+    http::{
+        auth::{Authenticated, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{entities::SearchJson, error_response};
+
+pub enum ApiError {
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<SearchError> for ApiError {
+    fn from(err: SearchError) -> Self {
+        match err {
+            SearchError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    q: String,
+    /// `accounts`, `statuses` or `hashtags`
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    /// look the handle or URL up on its server, for authenticated requests only
+    #[serde(default)]
+    resolve: bool,
+    /// only statuses of this account
+    account_id: Option<ActorId>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+/// Accounts, statuses and hashtags matching the query
+///
+/// See https://docs.joinmastodon.org/methods/search/#v2
+#[tracing::instrument(skip(registry, auth))]
+pub async fn search(
+    State(registry): State<AppRegistry>,
+    auth: Option<Authenticated<scope::ReadSearch>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<SearchJson>, ApiError> {
+    const DEFAULT_LIMIT: u32 = 20;
+    const MAX_LIMIT: u32 = 40;
+
+    let kind = params
+        .kind
+        .filter(|kind| !kind.is_empty())
+        .map(|kind| {
+            kind.parse::<SearchKind>()
+                .map_err(|_| ApiError::UnprocessableEntity(format!("Unknown type {kind}")))
+        })
+        .transpose()?;
+    let req = SearchRequest {
+        viewer: auth
+            .and_then(|auth| auth.account)
+            .map(|account| account.id().clone()),
+        query: params.q,
+        kind,
+        resolve: params.resolve,
+        actor_id: params.account_id,
+        limit: params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        offset: params.offset.unwrap_or_default(),
+    };
+    let results = registry.ap_service().search(req).await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(SearchJson::new(results, &*host_url)))
+}
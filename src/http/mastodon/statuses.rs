@@ -0,0 +1,332 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::{
+        HttpUrl,
+        ap::model::{
+            note::{
+                CreateLocalNoteError, CreateLocalNoteRequest, DeleteLocalNoteError,
+                DeleteLocalNoteRequest, FindNoteError, NoteId, ResolveNoteError,
+                ResolveNoteRequest,
+            },
+            poll::CreatePollRequest,
+            reaction::{EmojiReactRequest, ReactError, ReactRequest},
+        },
+        media::model::AttachmentId,
+        text::SourceMediaType,
+    },
+    http::{
+        auth::{Authenticated, AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::FormOrJson,
+    },
+};
+
+use super::{entities::StatusJson, error_response};
+
+pub enum ApiError {
+    NotFound,
+    Forbidden,
+    UnprocessableEntity(String),
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => error_response(StatusCode::NOT_FOUND, "Record not found"),
+            ApiError::Forbidden => {
+                error_response(StatusCode::FORBIDDEN, "This action is not allowed")
+            }
+            ApiError::UnprocessableEntity(message) => {
+                error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindNoteError> for ApiError {
+    fn from(err: FindNoteError) -> Self {
+        match err {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => ApiError::NotFound,
+            FindNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<CreateLocalNoteError> for ApiError {
+    fn from(err: CreateLocalNoteError) -> Self {
+        match err {
+            CreateLocalNoteError::ActorNotFound => ApiError::Forbidden,
+            CreateLocalNoteError::InvalidMedia(message)
+            | CreateLocalNoteError::InvalidPoll(message) => ApiError::UnprocessableEntity(message),
+            CreateLocalNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<DeleteLocalNoteError> for ApiError {
+    fn from(err: DeleteLocalNoteError) -> Self {
+        match err {
+            DeleteLocalNoteError::NotFound => ApiError::NotFound,
+            DeleteLocalNoteError::Forbidden => ApiError::Forbidden,
+            DeleteLocalNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<ResolveNoteError> for ApiError {
+    fn from(err: ResolveNoteError) -> Self {
+        match err {
+            ResolveNoteError::NotFound => ApiError::NotFound,
+            ResolveNoteError::InvalidNote(message) => ApiError::UnprocessableEntity(message),
+            ResolveNoteError::ActorNotFound => ApiError::Forbidden,
+            ResolveNoteError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+impl From<ReactError> for ApiError {
+    fn from(err: ReactError) -> Self {
+        match err {
+            ReactError::NoteNotFound | ReactError::EmojiNotFound => ApiError::NotFound,
+            ReactError::ActorNotFound => ApiError::Forbidden,
+            ReactError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStatusParams {
+    status: String,
+    /// `text/plain` or `text/markdown`, as in Pleroma. Defaults to `text/plain`
+    content_type: Option<String>,
+    /// content warning
+    spoiler_text: Option<String>,
+    /// the media is not safe for work
+    #[serde(default)]
+    sensitive: bool,
+    /// uploads to attach
+    #[serde(rename = "media_ids[]", alias = "media_ids", default)]
+    media_ids: Vec<AttachmentId>,
+    /// poll to attach, in JSON bodies
+    poll: Option<CreatePollParams>,
+    /// poll to attach, in form bodies
+    #[serde(rename = "poll[options][]", default)]
+    poll_options: Vec<String>,
+    #[serde(rename = "poll[expires_in]")]
+    poll_expires_in: Option<i64>,
+    #[serde(rename = "poll[multiple]", default)]
+    poll_multiple: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePollParams {
+    options: Vec<String>,
+    /// seconds the poll is open for
+    expires_in: i64,
+    #[serde(default)]
+    multiple: bool,
+}
+
+impl CreateStatusParams {
+    fn poll(&mut self) -> Result<Option<CreatePollRequest>, ApiError> {
+        let poll = match self.poll.take() {
+            Some(poll) => poll,
+            None if self.poll_options.is_empty() => return Ok(None),
+            None => CreatePollParams {
+                options: std::mem::take(&mut self.poll_options),
+                expires_in: self.poll_expires_in.ok_or_else(|| {
+                    ApiError::UnprocessableEntity("poll[expires_in] is missing".to_string())
+                })?,
+                multiple: self.poll_multiple,
+            },
+        };
+        Ok(Some(CreatePollRequest {
+            options: poll.options,
+            expires_in: poll.expires_in,
+            multiple: poll.multiple,
+        }))
+    }
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn create_status(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    FormOrJson(mut params): FormOrJson<CreateStatusParams>,
+) -> Result<Json<StatusJson>, ApiError> {
+    if params.status.trim().is_empty() && params.media_ids.is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "Validation failed: Text can't be blank".to_string(),
+        ));
+    }
+    let poll = params.poll()?;
+    let media_type = params
+        .content_type
+        .map(|s| SourceMediaType::from_local_str(&s))
+        .transpose()
+        .map_err(|e| ApiError::UnprocessableEntity(e.to_string()))?
+        .unwrap_or_default();
+
+    let account_id = auth.account.id();
+    let req = CreateLocalNoteRequest {
+        account_id: account_id.clone(),
+        content: params.status,
+        media_type,
+        summary: params.spoiler_text,
+        sensitive: params.sensitive,
+        media_ids: params.media_ids,
+        poll,
+    };
+    let ap_service = registry.ap_service();
+    let note = ap_service.create_local_note(req).await?;
+    let view = ap_service
+        .find_note_view(&note.id, Some(account_id))
+        .await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(StatusJson::new(view, &*host_url)))
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn get_status(
+    State(registry): State<AppRegistry>,
+    auth: Option<Authenticated<scope::ReadStatuses>>,
+    Path(note_id): Path<NoteId>,
+) -> Result<Json<StatusJson>, ApiError> {
+    let viewer = auth.as_ref().and_then(|auth| auth.account.as_ref());
+    let view = registry
+        .ap_service()
+        .find_note_view(&note_id, viewer.map(|account| account.id()))
+        .await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(StatusJson::new(view, &*host_url)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveStatusParams {
+    url: HttpUrl,
+}
+
+/// Fetch the remote status at `url` with the statuses it replies to, so that it can be
+/// interacted with
+#[tracing::instrument(skip(registry, auth))]
+pub async fn resolve_status(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadSearch>,
+    Query(params): Query<ResolveStatusParams>,
+) -> Result<Json<StatusJson>, ApiError> {
+    let req = ResolveNoteRequest {
+        account_id: auth.account.id().clone(),
+        url: params.url,
+    };
+    let view = registry.ap_service().resolve_remote_note(req).await?;
+
+    let host_url = registry.host_url_service();
+    Ok(Json(StatusJson::new(view, &*host_url)))
+}
+
+/// Delete the status and return it with its source text
+#[tracing::instrument(skip(registry, auth))]
+pub async fn delete_status(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    Path(note_id): Path<NoteId>,
+) -> Result<Json<StatusJson>, ApiError> {
+    let account_id = auth.account.id();
+    let ap_service = registry.ap_service();
+    let view = ap_service
+        .find_note_view(&note_id, Some(account_id))
+        .await?;
+
+    let req = DeleteLocalNoteRequest {
+        note_id,
+        account_id: account_id.clone(),
+    };
+    ap_service.delete_local_note(req).await?;
+
+    let text = view.source.content.clone();
+    let host_url = registry.host_url_service();
+    Ok(Json(StatusJson::new(view, &*host_url).with_text(text)))
+}
+
+/// Define a handler reacting to a status with `ApService::$method`
+macro_rules! reaction_handler {
+    ($(#[$meta:meta])* $name:ident, $scope:ty, $method:ident, $json:path) => {
+        $(#[$meta])*
+        #[tracing::instrument(skip(registry, auth))]
+        pub async fn $name(
+            State(registry): State<AppRegistry>,
+            auth: AuthenticatedAccount<$scope>,
+            Path(note_id): Path<NoteId>,
+        ) -> Result<Json<StatusJson>, ApiError> {
+            let req = ReactRequest {
+                account_id: auth.account.id().clone(),
+                note_id,
+            };
+            let view = registry.ap_service().$method(req).await?;
+
+            let host_url = registry.host_url_service();
+            Ok(Json($json(view, &*host_url)))
+        }
+    };
+}
+
+reaction_handler!(
+    favourite,
+    scope::WriteFavourites,
+    favourite,
+    StatusJson::new
+);
+reaction_handler!(
+    unfavourite,
+    scope::WriteFavourites,
+    unfavourite,
+    StatusJson::new
+);
+reaction_handler!(
+    /// Return the reblog wrapping the status
+    reblog,
+    scope::WriteStatuses,
+    reblog,
+    StatusJson::reblog
+);
+reaction_handler!(unreblog, scope::WriteStatuses, unreblog, StatusJson::new);
+
+/// Define a handler of Pleroma's emoji reaction API calling `ApService::$method`
+///
+/// See https://docs.pleroma.social/backend/development/API/pleroma_api/#emoji-reactions
+macro_rules! emoji_reaction_handler {
+    ($name:ident, $method:ident) => {
+        #[tracing::instrument(skip(registry, auth))]
+        pub async fn $name(
+            State(registry): State<AppRegistry>,
+            auth: AuthenticatedAccount<scope::WriteStatuses>,
+            Path((note_id, emoji)): Path<(NoteId, String)>,
+        ) -> Result<Json<StatusJson>, ApiError> {
+            let req = EmojiReactRequest {
+                account_id: auth.account.id().clone(),
+                note_id,
+                emoji,
+            };
+            let view = registry.ap_service().$method(req).await?;
+
+            let host_url = registry.host_url_service();
+            Ok(Json(StatusJson::new(view, &*host_url)))
+        }
+    };
+}
+
+emoji_reaction_handler!(react, react);
+emoji_reaction_handler!(unreact, unreact);
@@ -0,0 +1,377 @@
+use std::{convert::Infallible, str::FromStr};
+
+use axum::{
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade, rejection::WebSocketUpgradeRejection},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures_util::{StreamExt as _, stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        account::model::AccountId,
+        auth::model::SessionToken,
+        hosturl::HostUrlService,
+        stream::model::{Stream, StreamEvent, StreamMessage, StreamPayload, Subscription},
+    },
+    http::{
+        auth::{AuthError, Authenticated, bearer_token, scope},
+        shutdown_signal,
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{
+    entities::{NotificationJson, StatusJson},
+    error_response,
+};
+
+/// Header browsers can set on WebSockets, unlike `Authorization`
+const WEBSOCKET_PROTOCOL: &str = "sec-websocket-protocol";
+
+pub enum ApiError {
+    Auth(AuthError),
+    UnknownStream,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Auth(err) => err.into_response(),
+            ApiError::UnknownStream => {
+                error_response(StatusCode::BAD_REQUEST, "Unknown stream type")
+            }
+        }
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(err: AuthError) -> Self {
+        ApiError::Auth(err)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamParams {
+    stream: Option<String>,
+    /// for clients that cannot set headers, like browsers opening an `EventSource`
+    access_token: Option<String>,
+}
+
+/// Token from `Authorization`, the `access_token` parameter or the WebSocket protocol
+fn request_token(headers: &HeaderMap, params: &StreamParams) -> Option<SessionToken> {
+    bearer_token(headers)
+        .or_else(|| params.access_token.as_deref().map(SessionToken::from_raw))
+        .or_else(|| {
+            headers
+                .get(WEBSOCKET_PROTOCOL)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| SessionToken::from_raw(v.trim()))
+        })
+}
+
+fn parse_stream(name: &str) -> Result<Stream, ApiError> {
+    Stream::from_str(name).map_err(|_| ApiError::UnknownStream)
+}
+
+/// Account reading `stream`, `None` for anonymous readers of public streams
+async fn authorize(
+    registry: &AppRegistry,
+    stream: Stream,
+    token: Option<&SessionToken>,
+) -> Result<Option<AccountId>, AuthError> {
+    let Some(token) = token.cloned() else {
+        return match stream.is_user() {
+            true => Err(AuthError::Unauthorized),
+            false => Ok(None),
+        };
+    };
+    let account = match stream {
+        Stream::UserNotification => {
+            Authenticated::<scope::ReadNotifications>::from_token(registry, token)
+                .await?
+                .account
+        }
+        _ => {
+            Authenticated::<scope::ReadStatuses>::from_token(registry, token)
+                .await?
+                .account
+        }
+    };
+    match account {
+        Some(account) => Ok(Some(account.id().clone())),
+        None if stream.is_user() => Err(AuthError::AccountRequired),
+        None => Ok(None),
+    }
+}
+
+/// Name of the event and its payload as Mastodon sends them
+fn event_payload(message: StreamMessage, host_url: &dyn HostUrlService) -> (&'static str, String) {
+    let json = match message.payload {
+        StreamPayload::Update(item) => {
+            serde_json::to_string(&StatusJson::from_timeline_item(item, host_url))
+                .map(|json| ("update", json))
+        }
+        StreamPayload::Delete(id) => Ok(("delete", id.to_string())),
+        StreamPayload::Notification(view) => {
+            serde_json::to_string(&NotificationJson::new(*view, host_url))
+                .map(|json| ("notification", json))
+        }
+    };
+    // entities always serialize
+    json.unwrap_or_default()
+}
+
+/// Events of the next published event concerning `stream`, waiting until there is one
+///
+/// Return `None` once the subscription ends.
+async fn next_events(
+    registry: &AppRegistry,
+    subscription: &mut Subscription,
+    stream: Stream,
+    viewer: Option<&AccountId>,
+) -> Option<Vec<Event>> {
+    let service = registry.stream_service();
+    let host_url = registry.host_url_service();
+    loop {
+        let event = subscription.recv().await?;
+        let messages = match service.messages(&event, &[stream], viewer).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to prepare stream event");
+                continue;
+            }
+        };
+        if messages.is_empty() {
+            continue;
+        }
+        let events = messages
+            .into_iter()
+            .map(|message| {
+                let (event, payload) = event_payload(message, &*host_url);
+                Event::default().event(event).data(payload)
+            })
+            .collect();
+        return Some(events);
+    }
+}
+
+/// Server-Sent Events of `stream` until the client leaves or the server stops
+async fn sse(
+    registry: AppRegistry,
+    stream: Stream,
+    token: Option<SessionToken>,
+) -> Result<Response, ApiError> {
+    let viewer = authorize(&registry, stream, token.as_ref()).await?;
+    let subscription = registry.stream_service().subscribe();
+
+    let events = stream::unfold(subscription, move |mut subscription| {
+        let registry = registry.clone();
+        let viewer = viewer.clone();
+        async move {
+            let events = next_events(&registry, &mut subscription, stream, viewer.as_ref()).await?;
+            Some((events, subscription))
+        }
+    })
+    .flat_map(|events| stream::iter(events.into_iter().map(Ok::<_, Infallible>)))
+    .take_until(shutdown_signal());
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Whether the streaming API is up
+#[tracing::instrument]
+pub async fn health() -> &'static str {
+    "OK"
+}
+
+/// WebSocket multiplexing streams, or Server-Sent Events of the `stream` parameter
+///
+/// See https://docs.joinmastodon.org/methods/streaming/#websocket
+#[tracing::instrument(skip(registry, headers, params, ws))]
+pub async fn streaming(
+    State(registry): State<AppRegistry>,
+    headers: HeaderMap,
+    Query(params): Query<StreamParams>,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response, ApiError> {
+    let token = request_token(&headers, &params);
+    let Ok(ws) = ws else {
+        let stream = parse_stream(params.stream.as_deref().unwrap_or_default())?;
+        return sse(registry, stream, token).await;
+    };
+
+    // reject invalid tokens before upgrading, like Mastodon
+    if let Some(token) = &token {
+        Authenticated::<scope::Any>::from_token(&registry, token.clone()).await?;
+    }
+    let mut subscriptions = Vec::new();
+    if let Some(name) = &params.stream {
+        let stream = parse_stream(name)?;
+        let viewer = authorize(&registry, stream, token.as_ref()).await?;
+        subscriptions.push((stream, viewer));
+    }
+
+    // echo the token when it came as the protocol, or browsers drop the connection
+    let ws = match &token {
+        Some(token) => ws.protocols([token.as_str().to_string()]),
+        None => ws,
+    };
+    Ok(ws.on_upgrade(move |socket| websocket(socket, registry, token, subscriptions)))
+}
+
+/// Server-Sent Events of the stream in the path, like `/user/notification` for `user:notification`
+#[tracing::instrument(skip(registry, headers, params))]
+pub async fn streaming_path(
+    State(registry): State<AppRegistry>,
+    Path(path): Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<StreamParams>,
+) -> Result<Response, ApiError> {
+    let stream = parse_stream(&path.replace('/', ":"))?;
+    let token = request_token(&headers, &params);
+    sse(registry, stream, token).await
+}
+
+/// Message of the client over WebSocket
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { stream: String },
+    Unsubscribe { stream: String },
+}
+
+/// Event sent over WebSocket. `payload` is JSON in a string, as in Mastodon
+#[derive(Debug, Serialize)]
+struct EventJson<'a> {
+    stream: [&'a str; 1],
+    event: &'a str,
+    payload: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorJson<'a> {
+    error: &'a str,
+    status: u16,
+}
+
+async fn send_json(socket: &mut WebSocket, json: &impl Serialize) -> bool {
+    let Ok(text) = serde_json::to_string(json) else {
+        return true;
+    };
+    socket.send(Message::Text(text.into())).await.is_ok()
+}
+
+/// Error of a client message, the connection stays open
+async fn send_error(socket: &mut WebSocket, status: StatusCode, error: &str) -> bool {
+    send_json(
+        socket,
+        &ErrorJson {
+            error,
+            status: status.as_u16(),
+        },
+    )
+    .await
+}
+
+async fn websocket(
+    mut socket: WebSocket,
+    registry: AppRegistry,
+    token: Option<SessionToken>,
+    mut subscriptions: Vec<(Stream, Option<AccountId>)>,
+) {
+    let mut events = registry.stream_service().subscribe();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let sent = match serde_json::from_str::<ClientMessage>(text.as_str()) {
+                    Ok(ClientMessage::Subscribe { stream }) => {
+                        let Ok(stream) = Stream::from_str(&stream) else {
+                            send_error(&mut socket, StatusCode::BAD_REQUEST, "Unknown stream type").await;
+                            continue;
+                        };
+                        match authorize(&registry, stream, token.as_ref()).await {
+                            Ok(viewer) => {
+                                if !subscriptions.iter().any(|(s, _)| *s == stream) {
+                                    subscriptions.push((stream, viewer));
+                                }
+                                true
+                            }
+                            Err(e) => {
+                                let status = e.into_response().status();
+                                let error = status.canonical_reason().unwrap_or_default();
+                                send_error(&mut socket, status, error).await
+                            }
+                        }
+                    }
+                    Ok(ClientMessage::Unsubscribe { stream }) => {
+                        subscriptions.retain(|(s, _)| s.as_ref() != stream);
+                        true
+                    }
+                    Err(_) => send_error(&mut socket, StatusCode::BAD_REQUEST, "Invalid message").await,
+                };
+                if !sent {
+                    break;
+                }
+            }
+            event = events.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                if !send_event(&mut socket, &registry, &event, &subscriptions).await {
+                    break;
+                }
+            }
+            () = &mut shutdown => break,
+        }
+    }
+}
+
+/// Send the event on every subscribed stream it concerns. Return false once the client is gone
+async fn send_event(
+    socket: &mut WebSocket,
+    registry: &AppRegistry,
+    event: &StreamEvent,
+    subscriptions: &[(Stream, Option<AccountId>)],
+) -> bool {
+    let service = registry.stream_service();
+    let host_url = registry.host_url_service();
+    for (stream, viewer) in subscriptions {
+        let messages = match service.messages(event, &[*stream], viewer.as_ref()).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to prepare stream event");
+                continue;
+            }
+        };
+        for message in messages {
+            let stream = message.stream;
+            let (event, payload) = event_payload(message, &*host_url);
+            let json = EventJson {
+                stream: [stream.as_ref()],
+                event,
+                payload: &payload,
+            };
+            if !send_json(socket, &json).await {
+                return false;
+            }
+        }
+    }
+    true
+}
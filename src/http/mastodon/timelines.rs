@@ -0,0 +1,137 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::{
+        HttpUrl,
+        ap::model::timeline::{
+            FindTimelineError, HomeTimelineRequest, PublicTimelineRequest, TimelineItem,
+        },
+        hosturl::HostUrlService,
+    },
+    http::{
+        auth::{Authenticated, AuthenticatedAccount, scope},
+        state::{AppRegistry, AppRegistryExt as _},
+    },
+};
+
+use super::{
+    entities::StatusJson,
+    error_response,
+    pagination::{PageParams, link_header},
+};
+
+pub enum ApiError {
+    InternalServerError,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::InternalServerError => {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+}
+
+impl From<FindTimelineError> for ApiError {
+    fn from(err: FindTimelineError) -> Self {
+        match err {
+            FindTimelineError::Unknown(_) => ApiError::InternalServerError,
+        }
+    }
+}
+
+/// Statuses of a timeline page with the `Link` header to the pages around it
+fn timeline_response(
+    items: Vec<TimelineItem>,
+    url: HttpUrl,
+    host_url: &dyn HostUrlService,
+) -> Response {
+    let ids = items.iter().map(TimelineItem::id).collect::<Vec<_>>();
+    let statuses = items
+        .into_iter()
+        .map(|item| StatusJson::from_timeline_item(item, host_url))
+        .collect::<Vec<_>>();
+    match link_header(&url, &ids) {
+        Some(link) => ([(header::LINK, link)], Json(statuses)).into_response(),
+        None => Json(statuses).into_response(),
+    }
+}
+
+/// Own statuses and those of followed accounts, reblogs included
+#[tracing::instrument(skip(registry, auth))]
+pub async fn home(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::ReadStatuses>,
+    Query(params): Query<PageParams>,
+) -> Result<Response, ApiError> {
+    let req = HomeTimelineRequest {
+        account_id: auth.account.id().clone(),
+        page: params.into_page(),
+    };
+    let items = registry.ap_service().home_timeline(req).await?;
+
+    let host_url = registry.host_url_service();
+    let url = host_url
+        .base_url()
+        .join("api/v1/timelines/home")
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to build timeline url");
+            ApiError::InternalServerError
+        })?;
+    let url = HttpUrl::new(url).map_err(|_| ApiError::InternalServerError)?;
+    Ok(timeline_response(items, url, &*host_url))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicParams {
+    /// only local statuses
+    #[serde(default)]
+    local: bool,
+    /// only remote statuses
+    #[serde(default)]
+    remote: bool,
+}
+
+/// Every known status. `local=true` makes it the local timeline
+#[tracing::instrument(skip(registry, auth))]
+pub async fn public(
+    State(registry): State<AppRegistry>,
+    auth: Option<Authenticated<scope::ReadStatuses>>,
+    Query(public_params): Query<PublicParams>,
+    Query(params): Query<PageParams>,
+) -> Result<Response, ApiError> {
+    let req = PublicTimelineRequest {
+        viewer: auth
+            .and_then(|auth| auth.account)
+            .map(|account| account.id().clone()),
+        local: public_params.local,
+        remote: public_params.remote,
+        page: params.into_page(),
+    };
+    let items = registry.ap_service().public_timeline(req).await?;
+
+    let host_url = registry.host_url_service();
+    let mut url: url::Url = host_url
+        .base_url()
+        .join("api/v1/timelines/public")
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to build timeline url");
+            ApiError::InternalServerError
+        })?;
+    if public_params.local {
+        url.query_pairs_mut().append_pair("local", "true");
+    }
+    if public_params.remote {
+        url.query_pairs_mut().append_pair("remote", "true");
+    }
+    let url = HttpUrl::new(url).map_err(|_| ApiError::InternalServerError)?;
+    Ok(timeline_response(items, url, &*host_url))
+}
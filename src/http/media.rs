@@ -0,0 +1,107 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing,
+};
+
+use crate::domain::media::model::{FindAttachmentError, ProxyMediaError, RemoteMediaId};
+
+use super::state::{AppRegistry, AppRegistryExt as _};
+
+pub enum MediaError {
+    NotFound,
+    BadGateway,
+    InternalServerError,
+}
+
+impl IntoResponse for MediaError {
+    fn into_response(self) -> Response {
+        match self {
+            MediaError::NotFound => (StatusCode::NOT_FOUND, "File not found").into_response(),
+            MediaError::BadGateway => {
+                (StatusCode::BAD_GATEWAY, "Remote file unavailable").into_response()
+            }
+            MediaError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<FindAttachmentError> for MediaError {
+    fn from(err: FindAttachmentError) -> Self {
+        match err {
+            FindAttachmentError::NotFound => MediaError::NotFound,
+            FindAttachmentError::Unknown(_) => MediaError::InternalServerError,
+        }
+    }
+}
+
+impl From<ProxyMediaError> for MediaError {
+    fn from(err: ProxyMediaError) -> Self {
+        match err {
+            ProxyMediaError::NotFound => MediaError::NotFound,
+            ProxyMediaError::TooLarge
+            | ProxyMediaError::Unsupported(_)
+            | ProxyMediaError::Unavailable(_) => MediaError::BadGateway,
+            ProxyMediaError::Unknown(_) => MediaError::InternalServerError,
+        }
+    }
+}
+
+/// Serve an uploaded file. Files never change under their key, so they can be cached forever
+#[tracing::instrument(skip(registry))]
+pub async fn get_file(
+    State(registry): State<AppRegistry>,
+    Path(key): Path<String>,
+) -> Result<Response, MediaError> {
+    let file = registry.media_service().find_file(&key).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.media_type),
+            (
+                header::CACHE_CONTROL,
+                "public, max-age=31536000, immutable".to_string(),
+            ),
+        ],
+        file.data,
+    )
+        .into_response())
+}
+
+/// Serve a remote file through the media proxy, so that clients do not load it from its server
+#[tracing::instrument(skip(registry))]
+pub async fn get_remote_file(
+    State(registry): State<AppRegistry>,
+    Path(id): Path<String>,
+) -> Result<Response, MediaError> {
+    let id = id.parse::<RemoteMediaId>()?;
+    let file = registry
+        .media_service()
+        .proxy(&id)
+        .await
+        .inspect_err(|e| tracing::info!(error = %e, "Failed to proxy remote file"))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.media_type),
+            (header::CACHE_CONTROL, "public, max-age=86400".to_string()),
+            // remote files could be anything their server says they are
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (
+                header::CONTENT_SECURITY_POLICY,
+                "default-src 'none'; sandbox".to_string(),
+            ),
+        ],
+        file.data,
+    )
+        .into_response())
+}
+
+pub fn router(registry: AppRegistry) -> Router {
+    Router::new()
+        .route("/{key}", routing::get(get_file))
+        .route("/proxy/{id}", routing::get(get_remote_file))
+        .with_state(registry)
+}
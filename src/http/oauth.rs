@@ -0,0 +1,421 @@
+use axum::{
+    Form, Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        account::model::{AccountName, Password},
+        auth::model::{
+            Scopes, Session, SessionToken,
+            oauth::{
+                AuthorizationCode, AuthorizationRequest, AuthorizeError, AuthorizeRequest,
+                ClientCredentials, ClientSecret, CodeChallenge, OOB_REDIRECT_URI, RevokeRequest,
+                TokenError, TokenRequest,
+            },
+        },
+        text::render::escape_html,
+    },
+    http::{
+        state::{AppRegistry, AppRegistryExt as _},
+        utils::FormOrJson,
+    },
+};
+
+/// Errors of `/oauth/authorize`, shown to the user instead of redirecting to a client that
+/// could not be verified
+pub enum AuthorizePageError {
+    BadRequest(String),
+    InternalServerError,
+}
+
+impl IntoResponse for AuthorizePageError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthorizePageError::BadRequest(message) => (
+                StatusCode::BAD_REQUEST,
+                Html(page("Authorization failed", &escape_html(&message))),
+            )
+                .into_response(),
+            AuthorizePageError::InternalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<AuthorizeError> for AuthorizePageError {
+    fn from(err: AuthorizeError) -> Self {
+        match err {
+            AuthorizeError::InvalidClient
+            | AuthorizeError::InvalidRedirectUri
+            | AuthorizeError::InvalidScope
+            | AuthorizeError::InvalidCredentials => AuthorizePageError::BadRequest(err.to_string()),
+            AuthorizeError::Unknown(_) => AuthorizePageError::InternalServerError,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorizeParams {
+    response_type: String,
+    client_id: String,
+    redirect_uri: String,
+    scope: Option<String>,
+    state: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+}
+
+impl TryFrom<AuthorizeParams> for AuthorizationRequest {
+    type Error = AuthorizePageError;
+
+    fn try_from(params: AuthorizeParams) -> Result<Self, Self::Error> {
+        if params.response_type != "code" {
+            return Err(AuthorizePageError::BadRequest(format!(
+                "response_type {} is not supported",
+                params.response_type
+            )));
+        }
+        let scopes = params
+            .scope
+            .filter(|scope| !scope.trim().is_empty())
+            .map(|scope| scope.parse::<Scopes>())
+            .transpose()
+            .map_err(|e| AuthorizePageError::BadRequest(e.to_string()))?;
+        let code_challenge = match params.code_challenge {
+            Some(challenge) => {
+                // RFC 7636 section 4.3
+                let method = params.code_challenge_method.as_deref().unwrap_or("plain");
+                let method = method.parse().map_err(|_| {
+                    AuthorizePageError::BadRequest(format!(
+                        "code_challenge_method {method} is not supported"
+                    ))
+                })?;
+                Some(CodeChallenge { challenge, method })
+            }
+            None => None,
+        };
+
+        Ok(AuthorizationRequest {
+            client_id: params.client_id,
+            redirect_uri: params.redirect_uri,
+            scopes,
+            code_challenge,
+        })
+    }
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+fn hidden_input(name: &str, value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!(
+            r#"<input type="hidden" name="{name}" value="{}">"#,
+            escape_html(value)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Ask the user to log in and allow the client
+#[tracing::instrument(skip(registry))]
+pub async fn authorize_page(
+    State(registry): State<AppRegistry>,
+    Query(params): Query<AuthorizeParams>,
+) -> Result<Html<String>, AuthorizePageError> {
+    let req = AuthorizationRequest::try_from(params.clone())?;
+    let app = registry.auth_service().check_authorization(&req).await?;
+    let scopes = req.scopes.unwrap_or_else(Scopes::default_request);
+
+    let hidden = [
+        hidden_input("response_type", Some(&params.response_type)),
+        hidden_input("client_id", Some(&params.client_id)),
+        hidden_input("redirect_uri", Some(&params.redirect_uri)),
+        hidden_input("scope", Some(&scopes.to_string())),
+        hidden_input("state", params.state.as_deref()),
+        hidden_input("code_challenge", params.code_challenge.as_deref()),
+        hidden_input(
+            "code_challenge_method",
+            params.code_challenge_method.as_deref(),
+        ),
+    ]
+    .concat();
+    let body = format!(
+        r#"<p><strong>{app}</strong> wants to access your account with the scopes <code>{scopes}</code>.</p>
+<form method="post" action="/oauth/authorize">
+{hidden}
+<label>Username <input name="username" autocomplete="username" required></label>
+<label>Password <input name="password" type="password" autocomplete="current-password" required></label>
+<button type="submit">Authorize</button>
+</form>"#,
+        app = escape_html(&app.name),
+        scopes = escape_html(&scopes.to_string()),
+    );
+
+    Ok(Html(page("Authorize", &body)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeForm {
+    #[serde(flatten)]
+    params: AuthorizeParams,
+    username: String,
+    password: String,
+}
+
+/// Issue a code and send the user back to the client
+#[tracing::instrument(skip_all)]
+pub async fn authorize(
+    State(registry): State<AppRegistry>,
+    Form(form): Form<AuthorizeForm>,
+) -> Result<Response, AuthorizePageError> {
+    let state = form.params.state.clone();
+    let authorization = AuthorizationRequest::try_from(form.params)?;
+    let (Ok(name), Ok(password)) = (
+        AccountName::new(&form.username),
+        Password::new(&form.password),
+    ) else {
+        return Err(AuthorizeError::InvalidCredentials.into());
+    };
+
+    let redirect_uri = authorization.redirect_uri.clone();
+    let code = registry
+        .auth_service()
+        .authorize(AuthorizeRequest {
+            authorization,
+            name,
+            password,
+        })
+        .await?;
+
+    if redirect_uri == OOB_REDIRECT_URI {
+        let body = format!(
+            "<p>Copy this code to the app</p>\n<p><code>{}</code></p>",
+            escape_html(code.as_str())
+        );
+        return Ok(Html(page("Authorized", &body)).into_response());
+    }
+
+    // the URI was checked against the registered ones by `authorize`
+    let mut location = url::Url::parse(&redirect_uri)
+        .map_err(|e| AuthorizePageError::BadRequest(e.to_string()))?;
+    {
+        let mut query = location.query_pairs_mut();
+        query.append_pair("code", code.as_str());
+        if let Some(state) = &state {
+            query.append_pair("state", state);
+        }
+    }
+    Ok(Redirect::to(location.as_str()).into_response())
+}
+
+/// Error response of the token endpoint, RFC 6749 section 5.2
+pub enum TokenApiError {
+    InvalidRequest(String),
+    InvalidClient,
+    InvalidGrant,
+    InvalidScope,
+    UnsupportedGrantType,
+    InternalServerError,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenErrorJson {
+    error: &'static str,
+    error_description: String,
+}
+
+impl IntoResponse for TokenApiError {
+    fn into_response(self) -> Response {
+        let (status, error, description) = match self {
+            TokenApiError::InvalidRequest(message) => {
+                (StatusCode::BAD_REQUEST, "invalid_request", message)
+            }
+            TokenApiError::InvalidClient => (
+                StatusCode::UNAUTHORIZED,
+                "invalid_client",
+                TokenError::InvalidClient.to_string(),
+            ),
+            TokenApiError::InvalidGrant => (
+                StatusCode::BAD_REQUEST,
+                "invalid_grant",
+                TokenError::InvalidGrant.to_string(),
+            ),
+            TokenApiError::InvalidScope => (
+                StatusCode::BAD_REQUEST,
+                "invalid_scope",
+                TokenError::InvalidScope.to_string(),
+            ),
+            TokenApiError::UnsupportedGrantType => (
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+                "grant_type must be authorization_code or client_credentials".to_string(),
+            ),
+            TokenApiError::InternalServerError => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                    .into_response();
+            }
+        };
+        let json = TokenErrorJson {
+            error,
+            error_description: description,
+        };
+        (status, [(header::CACHE_CONTROL, "no-store")], Json(json)).into_response()
+    }
+}
+
+impl From<TokenError> for TokenApiError {
+    fn from(err: TokenError) -> Self {
+        match err {
+            TokenError::InvalidClient => TokenApiError::InvalidClient,
+            TokenError::InvalidGrant => TokenApiError::InvalidGrant,
+            TokenError::InvalidScope => TokenApiError::InvalidScope,
+            TokenError::Unknown(_) => TokenApiError::InternalServerError,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenParams {
+    grant_type: String,
+    code: Option<String>,
+    redirect_uri: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    code_verifier: Option<String>,
+    scope: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenJson {
+    access_token: String,
+    token_type: &'static str,
+    scope: String,
+    /// Unix time
+    created_at: i64,
+}
+
+impl From<Session> for TokenJson {
+    fn from(session: Session) -> Self {
+        TokenJson {
+            access_token: session.token.as_str().to_string(),
+            token_type: "Bearer",
+            scope: session.scopes.to_string(),
+            created_at: session.created.timestamp(),
+        }
+    }
+}
+
+/// Client credentials from `Authorization: Basic`, or else from the body
+fn client_credentials(
+    headers: &HeaderMap,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+) -> Result<ClientCredentials, TokenApiError> {
+    let basic = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Basic "))
+        .and_then(|v| STANDARD.decode(v).ok())
+        .and_then(|v| String::from_utf8(v).ok());
+    if let Some((id, secret)) = basic.as_deref().and_then(|v| v.split_once(':')) {
+        return Ok(ClientCredentials {
+            client_id: id.to_string(),
+            client_secret: Some(ClientSecret::from_raw(secret)),
+        });
+    }
+
+    let client_id = client_id.ok_or(TokenApiError::InvalidClient)?;
+    Ok(ClientCredentials {
+        client_id,
+        client_secret: client_secret.as_deref().map(ClientSecret::from_raw),
+    })
+}
+
+fn required(value: Option<String>, name: &str) -> Result<String, TokenApiError> {
+    value.ok_or_else(|| TokenApiError::InvalidRequest(format!("{name} is required")))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn token(
+    State(registry): State<AppRegistry>,
+    headers: HeaderMap,
+    FormOrJson(params): FormOrJson<TokenParams>,
+) -> Result<impl IntoResponse, TokenApiError> {
+    let client = client_credentials(&headers, params.client_id, params.client_secret)?;
+    let req = match params.grant_type.as_str() {
+        "authorization_code" => TokenRequest::AuthorizationCode {
+            client,
+            code: AuthorizationCode::from_raw(&required(params.code, "code")?),
+            redirect_uri: required(params.redirect_uri, "redirect_uri")?,
+            code_verifier: params.code_verifier,
+        },
+        "client_credentials" => {
+            let scopes = params
+                .scope
+                .filter(|scope| !scope.trim().is_empty())
+                .map(|scope| scope.parse())
+                .transpose()
+                .map_err(|_| TokenApiError::InvalidScope)?;
+            TokenRequest::ClientCredentials { client, scopes }
+        }
+        _ => return Err(TokenApiError::UnsupportedGrantType),
+    };
+
+    let session = registry.auth_service().issue_token(req).await?;
+
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(TokenJson::from(session)),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeParams {
+    token: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+/// Revoke a token, RFC 7009
+#[tracing::instrument(skip_all)]
+pub async fn revoke(
+    State(registry): State<AppRegistry>,
+    headers: HeaderMap,
+    FormOrJson(params): FormOrJson<RevokeParams>,
+) -> Result<Json<serde_json::Value>, TokenApiError> {
+    let client = client_credentials(&headers, params.client_id, params.client_secret)?;
+    let req = RevokeRequest {
+        client,
+        token: SessionToken::from_raw(&params.token),
+    };
+    registry.auth_service().revoke_token(req).await?;
+
+    Ok(Json(serde_json::json!({})))
+}
+
+pub fn router(registry: AppRegistry) -> Router {
+    Router::new()
+        .route("/authorize", routing::get(authorize_page).post(authorize))
+        .route("/token", routing::post(token))
+        .route("/revoke", routing::post(revoke))
+        .with_state(registry)
+}
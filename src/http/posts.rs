@@ -1,12 +1,25 @@
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::domain::{
-    account::model::AccountId,
-    ap::model::note::{CreateLocalNoteError, CreateLocalNoteRequest, LocalNote, NoteId},
+    ap::model::note::{
+        CreateLocalNoteError, CreateLocalNoteRequest, DeleteLocalNoteError, DeleteLocalNoteRequest,
+        FindNoteError, LocalNote, NoteId, NoteRevision, NoteSourceId, UpdateLocalNoteError,
+        UpdateLocalNoteRequest,
+    },
+    text::SourceMediaType,
 };
 
-use super::state::{AppRegistry, AppRegistryExt};
+use super::{
+    auth::{AuthenticatedAccount, scope},
+    state::{AppRegistry, AppRegistryExt},
+};
 
 #[derive(Debug, Serialize)]
 pub struct CreatePostSuccess {
@@ -30,6 +43,7 @@ impl From<LocalNote> for CreatePostSuccess {
 }
 
 pub enum CreatePostError {
+    BadRequest(String),
     ActorNotFound,
     InteranalServerError,
 }
@@ -37,6 +51,9 @@ pub enum CreatePostError {
 impl IntoResponse for CreatePostError {
     fn into_response(self) -> axum::response::Response {
         match self {
+            CreatePostError::BadRequest(message) => {
+                (StatusCode::BAD_REQUEST, message).into_response()
+            }
             CreatePostError::ActorNotFound => {
                 (StatusCode::FORBIDDEN, "actor not found").into_response()
             }
@@ -51,6 +68,8 @@ impl From<CreateLocalNoteError> for CreatePostError {
     fn from(value: CreateLocalNoteError) -> Self {
         match value {
             CreateLocalNoteError::ActorNotFound => CreatePostError::ActorNotFound,
+            CreateLocalNoteError::InvalidMedia(message)
+            | CreateLocalNoteError::InvalidPoll(message) => CreatePostError::BadRequest(message),
             CreateLocalNoteError::Unknown(_) => CreatePostError::InteranalServerError,
         }
     }
@@ -58,26 +77,40 @@ impl From<CreateLocalNoteError> for CreatePostError {
 
 #[derive(Debug, Deserialize)]
 pub struct CreatePostRequest {
-    account_id: AccountId,
     content: String,
+    /// `text/plain` or `text/markdown`. Defaults to `text/plain`
+    media_type: Option<String>,
+    /// content warning
+    summary: Option<String>,
+    #[serde(default)]
+    sensitive: bool,
 }
 
-impl TryFrom<CreatePostRequest> for CreateLocalNoteRequest {
-    type Error = std::convert::Infallible;
-    fn try_from(value: CreatePostRequest) -> Result<Self, Self::Error> {
-        Ok(CreateLocalNoteRequest {
-            account_id: value.account_id,
-            content: value.content,
-        })
-    }
+fn parse_media_type(media_type: Option<String>) -> Result<SourceMediaType, String> {
+    media_type
+        .map(|s| SourceMediaType::from_local_str(&s))
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(|e| e.to_string())
 }
 
-#[tracing::instrument(skip(registry))]
+/// Post as the authenticated account
+#[tracing::instrument(skip(registry, auth))]
 pub async fn create_post(
     State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
     Json(payload): Json<CreatePostRequest>,
 ) -> Result<CreatePostSuccess, CreatePostError> {
-    let req = payload.try_into().unwrap();
+    let media_type = parse_media_type(payload.media_type).map_err(CreatePostError::BadRequest)?;
+    let req = CreateLocalNoteRequest {
+        account_id: auth.account.id().clone(),
+        content: payload.content,
+        media_type,
+        summary: payload.summary,
+        sensitive: payload.sensitive,
+        media_ids: Vec::new(),
+        poll: None,
+    };
 
     let ap_service = registry.ap_service();
     let note = ap_service.create_local_note(req).await?;
@@ -85,8 +118,209 @@ pub async fn create_post(
     Ok(note.into())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PostParams {
+    note_id: NoteId,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdatePostSuccess {
+    note_id: NoteId,
+    content: String,
+}
+
+impl IntoResponse for UpdatePostSuccess {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+impl From<LocalNote> for UpdatePostSuccess {
+    fn from(value: LocalNote) -> Self {
+        UpdatePostSuccess {
+            note_id: value.id,
+            content: value.content,
+        }
+    }
+}
+
+pub enum UpdatePostError {
+    BadRequest(String),
+    NotFound,
+    Forbidden,
+    InteranalServerError,
+}
+
+impl IntoResponse for UpdatePostError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            UpdatePostError::BadRequest(message) => {
+                (StatusCode::BAD_REQUEST, message).into_response()
+            }
+            UpdatePostError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            UpdatePostError::Forbidden => {
+                (StatusCode::FORBIDDEN, "only the author can edit the post").into_response()
+            }
+            UpdatePostError::InteranalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<UpdateLocalNoteError> for UpdatePostError {
+    fn from(value: UpdateLocalNoteError) -> Self {
+        match value {
+            UpdateLocalNoteError::NotFound => UpdatePostError::NotFound,
+            UpdateLocalNoteError::Forbidden => UpdatePostError::Forbidden,
+            UpdateLocalNoteError::Unknown(_) => UpdatePostError::InteranalServerError,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePostRequest {
+    content: String,
+    /// `text/plain` or `text/markdown`. Defaults to `text/plain`
+    media_type: Option<String>,
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn update_post(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    Path(params): Path<PostParams>,
+    Json(payload): Json<UpdatePostRequest>,
+) -> Result<UpdatePostSuccess, UpdatePostError> {
+    let media_type = parse_media_type(payload.media_type).map_err(UpdatePostError::BadRequest)?;
+    let req = UpdateLocalNoteRequest {
+        note_id: params.note_id,
+        account_id: auth.account.id().clone(),
+        content: payload.content,
+        media_type,
+    };
+
+    let ap_service = registry.ap_service();
+    let note = ap_service.update_local_note(req).await?;
+
+    Ok(note.into())
+}
+
+pub enum DeletePostError {
+    NotFound,
+    Forbidden,
+    InteranalServerError,
+}
+
+impl IntoResponse for DeletePostError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            DeletePostError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            DeletePostError::Forbidden => {
+                (StatusCode::FORBIDDEN, "only the author can delete the post").into_response()
+            }
+            DeletePostError::InteranalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<DeleteLocalNoteError> for DeletePostError {
+    fn from(value: DeleteLocalNoteError) -> Self {
+        match value {
+            DeleteLocalNoteError::NotFound => DeletePostError::NotFound,
+            DeleteLocalNoteError::Forbidden => DeletePostError::Forbidden,
+            DeleteLocalNoteError::Unknown(_) => DeletePostError::InteranalServerError,
+        }
+    }
+}
+
+#[tracing::instrument(skip(registry, auth))]
+pub async fn delete_post(
+    State(registry): State<AppRegistry>,
+    auth: AuthenticatedAccount<scope::WriteStatuses>,
+    Path(params): Path<PostParams>,
+) -> Result<StatusCode, DeletePostError> {
+    let req = DeleteLocalNoteRequest {
+        note_id: params.note_id,
+        account_id: auth.account.id().clone(),
+    };
+
+    let ap_service = registry.ap_service();
+    ap_service.delete_local_note(req).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostRevision {
+    id: NoteSourceId,
+    /// rendered HTML
+    content: String,
+    source: PostRevisionSource,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostRevisionSource {
+    content: String,
+    media_type: &'static str,
+}
+
+impl From<NoteRevision> for PostRevision {
+    fn from(value: NoteRevision) -> Self {
+        PostRevision {
+            id: value.source.id,
+            content: value.content,
+            source: PostRevisionSource {
+                content: value.source.content,
+                media_type: value.source.media_type.as_str(),
+            },
+        }
+    }
+}
+
+pub enum PostRevisionsError {
+    NotFound,
+    InteranalServerError,
+}
+
+impl IntoResponse for PostRevisionsError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            PostRevisionsError::NotFound => (StatusCode::NOT_FOUND, "Not found").into_response(),
+            PostRevisionsError::InteranalServerError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+}
+
+impl From<FindNoteError> for PostRevisionsError {
+    fn from(value: FindNoteError) -> Self {
+        match value {
+            FindNoteError::NotFound | FindNoteError::Gone(_) => PostRevisionsError::NotFound,
+            FindNoteError::Unknown(_) => PostRevisionsError::InteranalServerError,
+        }
+    }
+}
+
+/// List revisions of a post, oldest first
+#[tracing::instrument(skip(registry))]
+pub async fn post_revisions(
+    State(registry): State<AppRegistry>,
+    Path(params): Path<PostParams>,
+) -> Result<Json<Vec<PostRevision>>, PostRevisionsError> {
+    let ap_service = registry.ap_service();
+    let revisions = ap_service.find_note_revisions(&params.note_id).await?;
+
+    Ok(Json(revisions.into_iter().map(Into::into).collect()))
+}
+
 pub fn router(registry: AppRegistry) -> Router {
     Router::new()
         .route("/", routing::post(create_post))
+        .route("/{note_id}", routing::put(update_post).delete(delete_post))
+        .route("/{note_id}/revisions", routing::get(post_revisions))
         .with_state(registry)
 }
@@ -5,7 +5,17 @@ use crate::{
     domain::{
         account::{self, adapter::AccountService},
         ap::{self, adapter::ApService},
+        auth::{self, adapter::AuthService},
         hosturl::{HostUrl, HostUrlService},
+        media::{
+            self,
+            adapter::{MediaService, MediaStorage},
+            model::CachePolicy,
+        },
+        stream::{self, adapter::StreamService},
+    },
+    infrastructure::{
+        ap_client::ApHttpClient, event_bus::PgEventBus, media_client::MediaHttpClient,
     },
 };
 
@@ -13,6 +23,9 @@ pub trait AppRegistryExt: Send + Sync {
     fn account_service(&self) -> Arc<dyn AccountService>;
     fn host_url_service(&self) -> Arc<dyn HostUrlService>;
     fn ap_service(&self) -> Arc<dyn ApService>;
+    fn auth_service(&self) -> Arc<dyn AuthService>;
+    fn media_service(&self) -> Arc<dyn MediaService>;
+    fn stream_service(&self) -> Arc<dyn StreamService>;
 }
 
 #[derive(Clone)]
@@ -20,19 +33,49 @@ pub struct AppRegistry {
     account_service: Arc<dyn AccountService>,
     host_url_service: Arc<dyn HostUrlService>,
     ap_service: Arc<dyn ApService>,
+    auth_service: Arc<dyn AuthService>,
+    media_service: Arc<dyn MediaService>,
+    stream_service: Arc<dyn StreamService>,
 }
 
 impl AppRegistry {
-    pub fn from_pg_host_url(pg: Postgres, host_url: HostUrl) -> Self {
+    pub fn from_pg_host_url(
+        pg: Postgres,
+        host_url: HostUrl,
+        media_storage: Arc<dyn MediaStorage>,
+        cache_policy: CachePolicy,
+        allow_private_addresses: bool,
+    ) -> Self {
         let host_url = Arc::new(host_url);
+        let bus = PgEventBus::new(pg.clone());
 
-        let ap_service = ap::service::Service::new(pg.clone(), pg.clone(), host_url.clone());
+        let ap_service = ap::service::Service::new(
+            pg.clone(),
+            pg.clone(),
+            pg.clone(),
+            pg.clone(),
+            host_url.clone(),
+            ApHttpClient::new(allow_private_addresses),
+            bus.clone(),
+        );
 
         let account_service = account::service::Service::new(pg.clone(), ap_service.clone());
+        let auth_service = auth::service::Service::new(pg.clone(), pg.clone());
+        let stream_service = stream::service::Service::new(bus, ap_service.clone());
+        let media_service = media::service::Service::new(
+            pg.clone(),
+            media_storage,
+            MediaHttpClient::new(allow_private_addresses),
+            host_url.clone(),
+            cache_policy,
+        );
 
         Self {
             account_service: Arc::new(account_service),
             ap_service: Arc::new(ap_service),
+            auth_service: Arc::new(auth_service),
+            media_service: Arc::new(media_service),
+            stream_service: Arc::new(stream_service),
             host_url_service: host_url,
         }
     }
@@ -50,4 +93,16 @@ impl AppRegistryExt for AppRegistry {
     fn ap_service(&self) -> Arc<dyn ApService> {
         self.ap_service.clone()
     }
+
+    fn auth_service(&self) -> Arc<dyn AuthService> {
+        self.auth_service.clone()
+    }
+
+    fn media_service(&self) -> Arc<dyn MediaService> {
+        self.media_service.clone()
+    }
+
+    fn stream_service(&self) -> Arc<dyn StreamService> {
+        self.stream_service.clone()
+    }
 }
@@ -1,7 +1,11 @@
 use crate::ap::constants;
 use axum::{
     Json,
-    extract::{FromRequest, Request, rejection::JsonRejection},
+    body::Bytes,
+    extract::{
+        FromRequest, Request,
+        rejection::{BytesRejection, JsonRejection},
+    },
     http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
 };
@@ -78,3 +82,59 @@ where
         }
     }
 }
+
+/// Request body sent either as JSON or as a form, like Mastodon clients do
+///
+/// Forms may repeat keys, like `media_ids[]=1&media_ids[]=2`, for sequences.
+pub struct FormOrJson<T>(pub T);
+
+pub enum FormOrJsonRejection {
+    Json(JsonRejection),
+    Bytes(BytesRejection),
+    Form(serde_html_form::de::Error),
+}
+
+impl IntoResponse for FormOrJsonRejection {
+    fn into_response(self) -> Response {
+        match self {
+            FormOrJsonRejection::Json(rejection) => rejection.into_response(),
+            FormOrJsonRejection::Bytes(rejection) => rejection.into_response(),
+            FormOrJsonRejection::Form(e) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Failed to deserialize form body: {e}"),
+            )
+                .into_response(),
+        }
+    }
+}
+
+fn is_json_type(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<mime::Mime>().ok())
+        .is_some_and(|mime| mime.essence_str() == mime::APPLICATION_JSON.essence_str())
+}
+
+impl<T, S> FromRequest<S> for FormOrJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = FormOrJsonRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if is_json_type(req.headers()) {
+            let Json(v) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(FormOrJsonRejection::Json)?;
+            Ok(FormOrJson(v))
+        } else {
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(FormOrJsonRejection::Bytes)?;
+            let v = serde_html_form::from_bytes(&body).map_err(FormOrJsonRejection::Form)?;
+            Ok(FormOrJson(v))
+        }
+    }
+}
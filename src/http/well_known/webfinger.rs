@@ -39,6 +39,7 @@ pub enum ApiError {
     InternalServerError,
     BadRequest(Cow<'static, str>),
     NotFound(Cow<'static, str>),
+    Gone(Cow<'static, str>),
 }
 
 impl IntoResponse for ApiError {
@@ -49,6 +50,7 @@ impl IntoResponse for ApiError {
             }
             ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
             ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            ApiError::Gone(message) => (StatusCode::GONE, message).into_response(),
         }
     }
 }
@@ -57,6 +59,7 @@ impl From<FindAccountError> for ApiError {
     fn from(err: FindAccountError) -> Self {
         match err {
             FindAccountError::InvalidName(_) => ApiError::BadRequest("invalid name".into()),
+            FindAccountError::Gone(_) => ApiError::Gone("account was deleted".into()),
             FindAccountError::Unknown(_) => ApiError::InternalServerError,
         }
     }
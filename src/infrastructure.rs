@@ -1 +1,6 @@
+pub(crate) mod ap_client;
+pub(crate) mod event_bus;
+pub(crate) mod media_client;
+pub(crate) mod media_storage;
+pub(crate) mod outbound;
 pub(crate) mod postgres;
@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::{StatusCode, header};
+
+use crate::{
+    ap::{
+        WebFinger, signature,
+        webfinger::{AcctUri, WEBFINGER_MEDIA_TYPE},
+    },
+    domain::{
+        HttpUrl,
+        ap::{
+            adapter::ApClient,
+            model::{
+                federation::{DeliverRequest, FetchError, HtmlPage},
+                key::SigningKey,
+            },
+        },
+    },
+};
+
+use super::outbound;
+
+const ACTIVITY_JSON: &str = "application/activity+json";
+const ACCEPT: &str = r#"application/activity+json, application/ld+json; profile="https://www.w3.org/ns/activitystreams""#;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Redirects followed to HTML pages
+const MAX_PAGE_REDIRECTS: usize = 3;
+/// Redirects followed to JSON documents, which are not expected to redirect much
+const MAX_DOCUMENT_REDIRECTS: usize = 1;
+/// Largest JSON document read, far above any actor or object
+const MAX_DOCUMENT_SIZE: usize = 1024 * 1024;
+/// How many times a delivery is attempted before giving up
+const DELIVERY_ATTEMPTS: u32 = 3;
+const RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+/// [ApClient] over HTTP
+#[derive(Debug, Clone)]
+pub struct ApHttpClient {
+    client: reqwest::Client,
+    page_client: reqwest::Client,
+    allow_private: bool,
+}
+
+impl ApHttpClient {
+    /// Client reaching only public addresses, unless `allow_private`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like [reqwest::Client::new]
+    pub fn new(allow_private: bool) -> Self {
+        let client = outbound::client_builder(TIMEOUT, MAX_DOCUMENT_REDIRECTS, allow_private)
+            .build()
+            .expect("failed to build HTTP client");
+        let page_client = outbound::client_builder(TIMEOUT, MAX_PAGE_REDIRECTS, allow_private)
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            client,
+            page_client,
+            allow_private,
+        }
+    }
+
+    /// Refuse `url` if it is not public and private addresses are not allowed
+    fn check_public(&self, url: &HttpUrl) -> Result<(), FetchError> {
+        if self.allow_private || outbound::is_public_url(url) {
+            Ok(())
+        } else {
+            Err(FetchError::NotPublic(url.clone()))
+        }
+    }
+
+    /// GET a JSON document, accepting `accept` and signed with `key` when given
+    async fn get_json(
+        &self,
+        url: &HttpUrl,
+        accept: &str,
+        key: Option<&SigningKey>,
+    ) -> Result<serde_json::Value, FetchError> {
+        self.check_public(url)?;
+        let mut request = self.client.get(url.as_str()).header(header::ACCEPT, accept);
+        if let Some(key) = key {
+            let private_key = signature::parse_private_key(&key.private_key_pem)
+                .map_err(|e| anyhow::anyhow!("invalid signing key {}: {e}", key.key_id))?;
+            let headers =
+                signature::signed_headers(&key.key_id, &private_key, "get", url, None, Utc::now());
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+        }
+        let mut res = request.send().await.map_err(|e| anyhow::anyhow!(e))?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND => return Err(FetchError::NotFound(url.clone())),
+            StatusCode::GONE => return Err(FetchError::Gone(url.clone())),
+            status if !status.is_success() => {
+                tracing::info!(%status, "Unexpected status");
+                return Err(FetchError::InvalidResponse(url.clone()));
+            }
+            _ => {}
+        }
+
+        let data = read_body(&mut res, url, MAX_DOCUMENT_SIZE).await?;
+        serde_json::from_slice(&data).map_err(|e| {
+            tracing::info!(error = %e, "Response is not JSON");
+            FetchError::InvalidResponse(url.clone())
+        })
+    }
+}
+
+/// Read the body of `res` for `url`, failing once it grows past `max_size`
+async fn read_body(
+    res: &mut reqwest::Response,
+    url: &HttpUrl,
+    max_size: usize,
+) -> Result<Vec<u8>, FetchError> {
+    // the length may be missing or lie, so the body is counted as it comes
+    let mut data = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|e| anyhow::anyhow!(e))? {
+        if data.len() + chunk.len() > max_size {
+            tracing::info!(max_size, "Response is too large");
+            return Err(FetchError::InvalidResponse(url.clone()));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+#[async_trait::async_trait]
+impl ApClient for ApHttpClient {
+    #[tracing::instrument(skip(self))]
+    async fn fetch(
+        &self,
+        url: &HttpUrl,
+        key: Option<&SigningKey>,
+    ) -> Result<serde_json::Value, FetchError> {
+        self.get_json(url, ACCEPT, key).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn webfinger(&self, acct: &AcctUri) -> Result<WebFinger, FetchError> {
+        let mut url = url::Url::parse(&format!("https://{}/.well-known/webfinger", acct.host))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        url.query_pairs_mut()
+            .append_pair("resource", &acct.to_string());
+        let url = HttpUrl::new(url).map_err(|e| anyhow::anyhow!(e))?;
+
+        let json = self.get_json(&url, WEBFINGER_MEDIA_TYPE, None).await?;
+        serde_json::from_value(json).map_err(|e| {
+            tracing::info!(error = %e, "Invalid WebFinger response");
+            FetchError::InvalidResponse(url)
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn fetch_html(&self, url: &HttpUrl, max_size: usize) -> Result<HtmlPage, FetchError> {
+        self.check_public(url)?;
+        let mut res = self
+            .page_client
+            .get(url.as_str())
+            .header(header::ACCEPT, "text/html")
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND => return Err(FetchError::NotFound(url.clone())),
+            StatusCode::GONE => return Err(FetchError::Gone(url.clone())),
+            status if !status.is_success() => {
+                tracing::info!(%status, "Unexpected status");
+                return Err(FetchError::InvalidResponse(url.clone()));
+            }
+            _ => {}
+        }
+        let is_html = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|value| {
+                let value = value.trim();
+                value.eq_ignore_ascii_case("text/html")
+                    || value.eq_ignore_ascii_case("application/xhtml+xml")
+            });
+        if !is_html {
+            tracing::info!("Response is not HTML");
+            return Err(FetchError::InvalidResponse(url.clone()));
+        }
+        let page_url = HttpUrl::new(res.url().clone()).map_err(|e| anyhow::anyhow!(e))?;
+
+        let data = read_body(&mut res, url, max_size).await?;
+        Ok(HtmlPage {
+            url: page_url,
+            html: String::from_utf8_lossy(&data).into_owned(),
+        })
+    }
+
+    fn deliver(&self, req: DeliverRequest) {
+        let private_key = match signature::parse_private_key(&req.key.private_key_pem) {
+            Ok(key) => key,
+            Err(e) => {
+                tracing::error!(error = %e, key_id = %req.key.key_id, "Invalid signing key");
+                return;
+            }
+        };
+        let body = match serde_json::to_vec(&req.activity) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to serialize activity");
+                return;
+            }
+        };
+
+        for inbox in req.inboxes {
+            if let Err(e) = self.check_public(&inbox) {
+                tracing::info!(error = %e, "Delivery refused");
+                continue;
+            }
+            let client = self.client.clone();
+            let key_id = req.key.key_id.clone();
+            let private_key = private_key.clone();
+            let body = body.clone();
+            tokio::spawn(async move {
+                for attempt in 1..=DELIVERY_ATTEMPTS {
+                    let headers = signature::signed_headers(
+                        &key_id,
+                        &private_key,
+                        "post",
+                        &inbox,
+                        Some(&body),
+                        Utc::now(),
+                    );
+                    let mut request = client
+                        .post(inbox.as_str())
+                        .header(header::CONTENT_TYPE, ACTIVITY_JSON)
+                        .body(body.clone());
+                    for (name, value) in headers {
+                        request = request.header(name, value);
+                    }
+
+                    match request.send().await {
+                        Ok(res) if res.status().is_success() => {
+                            tracing::debug!(%inbox, "Delivered activity");
+                            return;
+                        }
+                        // the inbox rejected the activity, retrying won't help
+                        Ok(res) if res.status().is_client_error() => {
+                            tracing::info!(%inbox, status = %res.status(), "Delivery rejected");
+                            return;
+                        }
+                        Ok(res) => {
+                            tracing::info!(%inbox, attempt, status = %res.status(), "Delivery failed");
+                        }
+                        Err(e) => {
+                            tracing::info!(%inbox, attempt, error = %e, "Delivery failed");
+                        }
+                    }
+                    if attempt < DELIVERY_ATTEMPTS {
+                        tokio::time::sleep(RETRY_BACKOFF * attempt).await;
+                    }
+                }
+                tracing::warn!(%inbox, "Gave up delivering activity");
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_loopback() {
+        let url: HttpUrl = "http://127.0.0.1:3000/ap/actors/alice".parse().unwrap();
+        let result = ApHttpClient::new(false).fetch(&url, None).await;
+        assert!(
+            matches!(result, Err(FetchError::NotPublic(_))),
+            "{result:?}"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{
+    domain::stream::{
+        adapter::EventBus,
+        model::{PublishEventError, StreamEvent, Subscription},
+    },
+    infrastructure::postgres::Postgres,
+};
+
+/// Events a slow subscriber may fall behind before missing some
+const CAPACITY: usize = 1024;
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// [EventBus] broadcasting in process, and to other processes with Postgres `LISTEN/NOTIFY`
+#[derive(Clone)]
+pub struct PgEventBus {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    sender: broadcast::Sender<StreamEvent>,
+    /// id of this process, to skip its own notifications
+    origin: Uuid,
+    pg: Postgres,
+}
+
+/// Event as sent through `NOTIFY`
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    origin: Uuid,
+    event: StreamEvent,
+}
+
+impl PgEventBus {
+    /// Start listening to events of other processes in the background
+    pub fn new(pg: Postgres) -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        let inner = Arc::new(Inner {
+            sender,
+            origin: Uuid::now_v7(),
+            pg,
+        });
+        tokio::spawn(listen(Arc::downgrade(&inner)));
+        Self { inner }
+    }
+}
+
+/// Rebroadcast events of other processes until the bus is dropped
+async fn listen(inner: std::sync::Weak<Inner>) {
+    loop {
+        let Some(pg) = inner.upgrade().map(|inner| inner.pg.clone()) else {
+            return;
+        };
+        let mut listener = match pg.listen_stream().await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to listen to stream events");
+                tokio::time::sleep(RECONNECT_BACKOFF).await;
+                continue;
+            }
+        };
+        drop(pg);
+        tracing::info!("Listening to stream events");
+
+        while let Some(payload) = listener.recv().await {
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            match serde_json::from_str::<Envelope>(&payload) {
+                Ok(envelope) if envelope.origin == inner.origin => {}
+                // no subscriber is fine
+                Ok(envelope) => _ = inner.sender.send(envelope.event),
+                Err(e) => tracing::warn!(error = %e, payload, "Ignore invalid stream event"),
+            }
+        }
+
+        // events published meanwhile by other processes are lost
+        tracing::warn!("Stream events connection lost, reconnecting");
+        tokio::time::sleep(RECONNECT_BACKOFF).await;
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBus for PgEventBus {
+    async fn publish(&self, event: StreamEvent) -> Result<(), PublishEventError> {
+        let envelope = Envelope {
+            origin: self.inner.origin,
+            event,
+        };
+        let payload = serde_json::to_string(&envelope).map_err(|e| anyhow::anyhow!(e))?;
+        _ = self.inner.sender.send(envelope.event);
+        self.inner.pg.notify_stream(&payload).await?;
+        Ok(())
+    }
+
+    fn subscribe(&self) -> Subscription {
+        Subscription::new(self.inner.sender.subscribe())
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use reqwest::{StatusCode, header};
+
+use crate::domain::{
+    HttpUrl,
+    media::{
+        adapter::MediaClient,
+        model::{MediaFile, ProxyMediaError},
+    },
+};
+
+use super::outbound;
+
+/// Files are larger than documents, so they get longer than [super::ap_client] requests
+const TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 3;
+
+/// [MediaClient] over HTTP
+#[derive(Debug, Clone)]
+pub struct MediaHttpClient {
+    client: reqwest::Client,
+    allow_private: bool,
+}
+
+impl MediaHttpClient {
+    /// Client reaching only public addresses, unless `allow_private`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like [reqwest::Client::new]
+    pub fn new(allow_private: bool) -> Self {
+        let client = outbound::client_builder(TIMEOUT, MAX_REDIRECTS, allow_private)
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            client,
+            allow_private,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MediaClient for MediaHttpClient {
+    #[tracing::instrument(skip(self))]
+    async fn fetch(&self, url: &HttpUrl, max_size: usize) -> Result<MediaFile, ProxyMediaError> {
+        if !self.allow_private && !outbound::is_public_url(url) {
+            return Err(ProxyMediaError::Unavailable(format!("{url} is not public")));
+        }
+        let mut res = self
+            .client
+            .get(url.as_str())
+            .send()
+            .await
+            .map_err(|e| ProxyMediaError::Unavailable(e.to_string()))?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND | StatusCode::GONE => return Err(ProxyMediaError::NotFound),
+            status if !status.is_success() => {
+                return Err(ProxyMediaError::Unavailable(format!("status {status}")));
+            }
+            _ => {}
+        }
+        if res
+            .content_length()
+            .is_some_and(|length| length > max_size as u64)
+        {
+            return Err(ProxyMediaError::TooLarge);
+        }
+        // without parameters like `; charset=binary`
+        let media_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        // the length may be missing or lie, so the body is counted as it comes
+        let mut data = Vec::new();
+        while let Some(chunk) = res
+            .chunk()
+            .await
+            .map_err(|e| ProxyMediaError::Unavailable(e.to_string()))?
+        {
+            if data.len() + chunk.len() > max_size {
+                return Err(ProxyMediaError::TooLarge);
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(MediaFile { media_type, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, http::header, routing};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    async fn serve_image() -> u16 {
+        let app = Router::new().route(
+            "/a.png",
+            routing::get(([(header::CONTENT_TYPE, "image/png")], "png")),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        port
+    }
+
+    #[rstest]
+    #[case::loopback_ip("127.0.0.1")]
+    #[case::loopback_name("localhost")]
+    #[tokio::test]
+    async fn test_fetch_loopback(#[case] host: &str) {
+        let port = serve_image().await;
+        let url: HttpUrl = format!("http://{host}:{port}/a.png").parse().unwrap();
+
+        let refused = MediaHttpClient::new(false).fetch(&url, 1024).await;
+        assert!(
+            matches!(refused, Err(ProxyMediaError::Unavailable(_))),
+            "{refused:?}"
+        );
+
+        let file = MediaHttpClient::new(true).fetch(&url, 1024).await.unwrap();
+        assert_eq!(file.media_type, "image/png");
+    }
+}
@@ -0,0 +1,160 @@
+mod local;
+mod s3;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::domain::media::{adapter::MediaStorage, model::CachePolicy};
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Where uploaded files are stored
+#[derive(Debug, Clone)]
+pub enum MediaStorageConfig {
+    /// files in a directory
+    Local { dir: PathBuf },
+    /// objects in a bucket of an S3-compatible service, like MinIO
+    S3 {
+        /// like `http://localhost:9000`. Buckets are addressed by path
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        secret_access_key: String,
+    },
+}
+
+impl Default for MediaStorageConfig {
+    fn default() -> Self {
+        Self::Local {
+            dir: PathBuf::from("media"),
+        }
+    }
+}
+
+impl MediaStorageConfig {
+    /// Read the config from `MEDIA_STORAGE`, `local` by default, and the variables it needs
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let storage = std::env::var("MEDIA_STORAGE").unwrap_or_else(|_| "local".to_string());
+        match storage.as_str() {
+            "local" => Ok(Self::Local {
+                dir: std::env::var("MEDIA_DIR")
+                    .unwrap_or_else(|_| "media".to_string())
+                    .into(),
+            }),
+            "s3" => Ok(Self::S3 {
+                endpoint: std::env::var("S3_ENDPOINT")?,
+                region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                bucket: std::env::var("S3_BUCKET")?,
+                access_key_id: std::env::var("S3_ACCESS_KEY_ID")?,
+                secret_access_key: std::env::var("S3_SECRET_ACCESS_KEY")?,
+            }),
+            _ => anyhow::bail!("unknown MEDIA_STORAGE {storage}, expected local or s3"),
+        }
+    }
+
+    pub(crate) fn build(self) -> Arc<dyn MediaStorage> {
+        match self {
+            Self::Local { dir } => Arc::new(LocalStorage::new(dir)),
+            Self::S3 {
+                endpoint,
+                region,
+                bucket,
+                access_key_id,
+                secret_access_key,
+            } => Arc::new(S3Storage::new(
+                endpoint,
+                region,
+                bucket,
+                access_key_id,
+                secret_access_key,
+            )),
+        }
+    }
+}
+
+/// How many copies of remote files fetched by the media proxy are kept in the media storage
+#[derive(Debug, Clone)]
+pub struct MediaCacheConfig {
+    /// total size in bytes, beyond which the least recently used files are evicted
+    pub max_size: u64,
+    /// files not used for this long are evicted
+    pub max_age: Duration,
+}
+
+impl Default for MediaCacheConfig {
+    /// 1 GiB, for a week
+    fn default() -> Self {
+        Self {
+            max_size: 1024 * 1024 * 1024,
+            max_age: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl MediaCacheConfig {
+    /// Read `MEDIA_CACHE_MAX_SIZE` in bytes and `MEDIA_CACHE_MAX_AGE` in seconds, both optional
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let mut config = Self::default();
+        if let Ok(max_size) = std::env::var("MEDIA_CACHE_MAX_SIZE") {
+            config.max_size = max_size.parse()?;
+        }
+        if let Ok(max_age) = std::env::var("MEDIA_CACHE_MAX_AGE") {
+            config.max_age = Duration::from_secs(max_age.parse()?);
+        }
+        Ok(config)
+    }
+
+    pub(crate) fn policy(&self) -> CachePolicy {
+        CachePolicy {
+            max_size: self.max_size,
+            max_age: chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX),
+        }
+    }
+}
+
+/// Keys name a single file, so that they cannot reach outside of the storage
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::domain::media::model::{MediaFile, StorageError};
+
+    use super::*;
+
+    /// Run against MinIO or another S3-compatible service with
+    /// `MEDIA_STORAGE=s3 S3_ENDPOINT=… S3_BUCKET=… S3_ACCESS_KEY_ID=… S3_SECRET_ACCESS_KEY=…
+    /// cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "needs an S3-compatible service"]
+    async fn test_s3_storage() {
+        let config = MediaStorageConfig::from_env().unwrap();
+        assert!(
+            matches!(config, MediaStorageConfig::S3 { .. }),
+            "MEDIA_STORAGE must be s3"
+        );
+        let storage = config.build();
+        let key = format!("test-{}.txt", uuid::Uuid::now_v7());
+        let file = MediaFile {
+            media_type: "text/plain".to_string(),
+            data: b"hello".to_vec(),
+        };
+
+        storage.put(&key, &file).await.unwrap();
+        assert_eq!(storage.get(&key).await.unwrap(), b"hello");
+
+        storage.delete(&key).await.unwrap();
+        assert!(matches!(
+            storage.get(&key).await,
+            Err(StorageError::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use crate::domain::media::{
+    adapter::MediaStorage,
+    model::{MediaFile, StorageError},
+};
+
+use super::is_valid_key;
+
+/// [MediaStorage] in a directory of the local filesystem
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if !is_valid_key(key) {
+            return Err(StorageError::NotFound);
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl MediaStorage for LocalStorage {
+    async fn put(&self, key: &str, file: &MediaFile) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to create media directory"))?;
+        // written aside first so that a file is never served half written
+        let partial = self.dir.join(format!(".{key}.partial"));
+        tokio::fs::write(&partial, &file.data)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, key, "Failed to write file"))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, key, "Failed to move file"))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::NotFound),
+            Err(e) => {
+                tracing::error!(error = %e, key, "Failed to read file");
+                Err(StorageError::Unknown(e.into()))
+            }
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::NotFound),
+            Err(e) => {
+                tracing::error!(error = %e, key, "Failed to delete file");
+                Err(StorageError::Unknown(e.into()))
+            }
+        }
+    }
+}
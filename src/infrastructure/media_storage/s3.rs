@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac as _};
+use reqwest::{Method, StatusCode, header};
+use sha2::{Digest as _, Sha256};
+
+use crate::domain::media::{
+    adapter::MediaStorage,
+    model::{MediaFile, StorageError},
+};
+
+use super::is_valid_key;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// [MediaStorage] in a bucket of an S3-compatible service
+///
+/// Requests are signed with AWS Signature Version 4 and address the bucket by path, which
+/// MinIO and most other implementations accept.
+/// See https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+#[derive(Clone)]
+pub struct S3Storage {
+    client: reqwest::Client,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl std::fmt::Debug for S3Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Storage")
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Storage {
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like [reqwest::Client::new]
+    pub fn new(
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        secret_access_key: String,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            client,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            region,
+            bucket,
+            access_key_id,
+            secret_access_key,
+        }
+    }
+
+    /// Send a request for the object at `key`, signed for `now`
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        body: Option<(&str, &[u8])>,
+        now: DateTime<Utc>,
+    ) -> Result<reqwest::Response, StorageError> {
+        if !is_valid_key(key) {
+            return Err(StorageError::NotFound);
+        }
+        let path = format!("/{}/{}", uri_encode(&self.bucket), uri_encode(key));
+        let url = url::Url::parse(&format!("{}{}", self.endpoint, path))
+            .map_err(|e| anyhow::anyhow!("invalid S3 endpoint {}: {e}", self.endpoint))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let payload_hash = hex(&Sha256::digest(body.map_or(&[][..], |(_, data)| data)));
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload_hash}"
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+            self.access_key_id
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(header::AUTHORIZATION, authorization);
+        if let Some((content_type, data)) = body {
+            request = request
+                .header(header::CONTENT_TYPE, content_type)
+                .body(data.to_vec());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, key, "Failed to send S3 request"))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::NOT_FOUND => Err(StorageError::NotFound),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                tracing::error!(%status, body, key, "S3 request failed");
+                Err(anyhow::anyhow!("S3 request failed with {status}").into())
+            }
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Percent-encode all but unreserved characters, as signatures expect
+fn uri_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl MediaStorage for S3Storage {
+    async fn put(&self, key: &str, file: &MediaFile) -> Result<(), StorageError> {
+        self.send(
+            Method::PUT,
+            key,
+            Some((&file.media_type, &file.data)),
+            Utc::now(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let response = self.send(Method::GET, key, None, Utc::now()).await?;
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, key, "Failed to read S3 object"))?;
+        Ok(data.to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.send(Method::DELETE, key, None, Utc::now()).await?;
+        Ok(())
+    }
+}
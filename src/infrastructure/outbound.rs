@@ -0,0 +1,177 @@
+//! Requests to URLs taken from federated content
+//!
+//! Remote actors choose these URLs, so unless private addresses are allowed, requests only reach
+//! public IPs. Names are checked when resolved and IP hosts before each request and redirect.
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+pub(crate) const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Start building a client following up to `max_redirects`, which reaches loopback, private and
+/// other non-public addresses only if `allow_private`
+pub(crate) fn client_builder(
+    timeout: Duration,
+    max_redirects: usize,
+    allow_private: bool,
+) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .redirect(redirect_policy(max_redirects, allow_private));
+    if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver))
+    }
+}
+
+/// Follow up to `max` redirects, only to public hosts unless `allow_private`
+///
+/// [reqwest::redirect::Policy::limited] of the locked reqwest stops one redirect short.
+pub(crate) fn redirect_policy(max: usize, allow_private: bool) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        // `previous` starts with the requested URL, not a redirect
+        if attempt.previous().len() > max {
+            attempt.error(format!("more than {max} redirects"))
+        } else if !allow_private && !is_public_url(attempt.url()) {
+            let error = format!("redirect to {} which is not public", attempt.url());
+            attempt.error(error)
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// Whether the host of `url` may be public: a name, checked when resolved, or a public IP
+///
+/// Requests to IP hosts are not resolved, so they must be checked with this first.
+pub(crate) fn is_public_url(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(_)) => true,
+        Some(url::Host::Ipv4(ip)) => is_public_ip(ip.into()),
+        Some(url::Host::Ipv6(ip)) => is_public_ip(ip.into()),
+        None => false,
+    }
+}
+
+/// Whether `ip` is reachable over the internet, unlike loopback, private, link-local (with cloud
+/// metadata services), unique local and other special-purpose addresses
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(ip);
+            }
+            let [first, second, ..] = ip.segments();
+            // NAT64 addresses embed an IPv4 one
+            if first == 0x64 && second == 0xff9b {
+                let [.., a, b, c, d] = ip.octets();
+                return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+            }
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // unique local fc00::/7
+                || first & 0xfe00 == 0xfc00
+                // link-local fe80::/10
+                || first & 0xffc0 == 0xfe80
+                // documentation 2001:db8::/32
+                || (first == 0x2001 && second == 0x0db8))
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "this network" 0.0.0.0/8
+        || a == 0
+        // shared address space 100.64.0.0/10
+        || (a == 100 && b & 0xc0 == 64)
+        // IETF protocol assignments 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking 198.18.0.0/15
+        || (a == 198 && b & 0xfe == 18)
+        // reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+/// Resolver dropping the addresses that are not public
+#[derive(Debug)]
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::public_v4("93.184.215.14", true)]
+    #[case::loopback("127.0.0.1", false)]
+    #[case::loopback_range("127.1.2.3", false)]
+    #[case::unspecified("0.0.0.0", false)]
+    #[case::private_10("10.1.2.3", false)]
+    #[case::private_172("172.16.0.1", false)]
+    #[case::public_172("172.32.0.1", true)]
+    #[case::private_192("192.168.1.1", false)]
+    #[case::cloud_metadata("169.254.169.254", false)]
+    #[case::shared("100.64.0.1", false)]
+    #[case::public_100("100.128.0.1", true)]
+    #[case::broadcast("255.255.255.255", false)]
+    #[case::public_v6("2606:4700:4700::1111", true)]
+    #[case::loopback_v6("::1", false)]
+    #[case::unspecified_v6("::", false)]
+    #[case::unique_local("fd00::1", false)]
+    #[case::link_local_v6("fe80::1", false)]
+    #[case::mapped_loopback("::ffff:127.0.0.1", false)]
+    #[case::mapped_public("::ffff:93.184.215.14", true)]
+    #[case::nat64_private("64:ff9b::a00:1", false)]
+    fn test_is_public_ip(#[case] ip: IpAddr, #[case] expected: bool) {
+        assert_eq!(is_public_ip(ip), expected);
+    }
+
+    #[rstest]
+    #[case::name("https://example.com/a.png", true)]
+    #[case::public_ip("https://93.184.215.14/a.png", true)]
+    #[case::loopback("http://127.0.0.1:3000/a.png", false)]
+    #[case::loopback_v6("http://[::1]/a.png", false)]
+    #[case::metadata("http://169.254.169.254/latest/meta-data/", false)]
+    fn test_is_public_url(#[case] url: url::Url, #[case] expected: bool) {
+        assert_eq!(is_public_url(&url), expected);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_localhost() {
+        let name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
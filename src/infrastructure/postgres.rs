@@ -1,3 +1,4 @@
+#[allow(clippy::too_many_arguments)]
 mod queries;
 use std::str::FromStr;
 
@@ -6,6 +7,8 @@ use deadpool_postgres::{Manager, ManagerConfig, Object, Pool, RecyclingMethod, T
 #[derive(Clone)]
 pub struct Postgres {
     pool: Pool,
+    /// for connections outside the pool, like `LISTEN`
+    config: tokio_postgres::Config,
 }
 
 impl Postgres {
@@ -15,7 +18,7 @@ impl Postgres {
         let mgr_config = ManagerConfig {
             recycling_method: RecyclingMethod::Verified,
         };
-        let mgr = Manager::from_config(pg_config, tokio_postgres::NoTls, mgr_config);
+        let mgr = Manager::from_config(pg_config.clone(), tokio_postgres::NoTls, mgr_config);
         let pool = Pool::builder(mgr).max_size(4).build()?;
 
         // Check connection
@@ -27,7 +30,10 @@ impl Postgres {
 
         tracing::info!("Connected to postgres");
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            config: pg_config,
+        })
     }
 
     #[tracing::instrument(skip_all)]
@@ -65,7 +71,7 @@ impl Postgres {
     }
 }
 
-async fn get_transaction(client: &mut Object) -> Result<Transaction, anyhow::Error> {
+async fn get_transaction(client: &mut Object) -> Result<Transaction<'_>, anyhow::Error> {
     client
         .transaction()
         .await
@@ -73,20 +79,102 @@ async fn get_transaction(client: &mut Object) -> Result<Transaction, anyhow::Err
         .inspect_err(|e| tracing::error!(error = %e, "Failed to get transaction"))
 }
 
+mod stream_impl {
+    use futures_util::{StreamExt, stream};
+    use tokio::sync::mpsc;
+    use tokio_postgres::AsyncMessage;
+
+    use super::*;
+
+    /// Channel of stream events between mozu processes, as in `queries/stream.sql`
+    const STREAM_CHANNEL: &str = "mozu_stream";
+
+    /// Dedicated connection listening to stream events of every process
+    pub(crate) struct StreamListener {
+        _client: tokio_postgres::Client,
+        payloads: mpsc::UnboundedReceiver<String>,
+    }
+
+    impl StreamListener {
+        /// Wait for the next payload. Return `None` once the connection is lost
+        pub(crate) async fn recv(&mut self) -> Option<String> {
+            self.payloads.recv().await
+        }
+    }
+
+    impl Postgres {
+        /// Send `payload` to every listener of stream events, this process included
+        pub(crate) async fn notify_stream(&self, payload: &str) -> Result<(), anyhow::Error> {
+            let client = self.get_client().await?;
+            queries::notify_stream(&client, payload)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to notify stream"))?;
+            Ok(())
+        }
+
+        /// Open a connection outside the pool and `LISTEN` to stream events
+        pub(crate) async fn listen_stream(&self) -> Result<StreamListener, anyhow::Error> {
+            let (client, mut connection) = self.config.connect(tokio_postgres::NoTls).await?;
+            let (tx, payloads) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+                while let Some(message) = messages.next().await {
+                    match message {
+                        Ok(AsyncMessage::Notification(notification)) => {
+                            if tx.send(notification.payload().to_string()).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            tracing::error!(error = %e, "Stream listener connection failed");
+                            break;
+                        }
+                    }
+                }
+            });
+            client
+                .batch_execute(&format!("LISTEN {STREAM_CHANNEL}"))
+                .await?;
+            Ok(StreamListener {
+                _client: client,
+                payloads,
+            })
+        }
+    }
+}
+
 mod account_repository_impl {
     use crate::domain::account::{
         adapter::AccountRepository,
-        model::{Account, AccountId, AccountName, CreateAccountError, FindAccountError},
+        model::{
+            Account, AccountId, AccountName, CreateAccountError, DeleteAccountError,
+            FindAccountError, PasswordHash,
+        },
     };
+    use chrono::{DateTime, Utc};
 
     use super::*;
     #[async_trait::async_trait]
     impl AccountRepository for Postgres {
         #[tracing::instrument(skip(self))]
-        async fn create(&self, account: Account) -> Result<Account, CreateAccountError> {
+        async fn create(
+            &self,
+            account: Account,
+            password_hash: &PasswordHash,
+        ) -> Result<Account, CreateAccountError> {
             let client = self.get_client().await?;
-            let res = queries::create_account(&client, account.id(), account.name().as_str()).await;
+            let res = queries::create_account(
+                &client,
+                account.id(),
+                account.name().as_str(),
+                Some(password_hash.as_str()),
+            )
+            .await;
             match res {
+                // the name belongs to a deleted account
+                Ok(0) => Err(CreateAccountError::AlreadyExists),
                 Ok(_) => Ok(account),
                 Err(e) if e.is_closed() => Err(CreateAccountError::Unknown(e.into())),
                 Err(_) => Err(CreateAccountError::AlreadyExists),
@@ -128,20 +216,76 @@ mod account_repository_impl {
                 return Ok(None);
             }
         }
+        #[tracing::instrument(skip(self))]
+        async fn delete(
+            &self,
+            account: &Account,
+            deleted: DateTime<Utc>,
+        ) -> Result<(), DeleteAccountError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::insert_account_tombstone(&transaction, account.name().as_str(), &deleted)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to insert tombstone"))?;
+            queries::insert_note_tombstones(&transaction, account.id(), &deleted)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to insert note tombstones"))?;
+
+            // actors, keys and notes cascade
+            let deleted_accounts = queries::delete_account(&transaction, account.id())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete account"))?;
+            if deleted_accounts == 0 {
+                return Err(DeleteAccountError::NotFound);
+            }
+
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+        #[tracing::instrument(skip(self))]
+        async fn find_tombstone(
+            &self,
+            name: &AccountName,
+        ) -> Result<Option<DateTime<Utc>>, FindAccountError> {
+            let client = self.get_client().await?;
+            let result = queries::find_account_tombstone(&client, name.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| {
+                    tracing::error!(error = %e, "Failed to find account tombstone");
+                })?;
+
+            Ok(result.map(|row| row.account_tombstones_deleted))
+        }
     }
 }
 
+/// Whether the query failed because of a unique constraint
+fn is_unique_violation(e: &tokio_postgres::Error) -> bool {
+    e.code() == Some(&tokio_postgres::error::SqlState::UNIQUE_VIOLATION)
+}
+
 mod ap_repository_impl {
     use crate::domain::{
-        HttpUrlError,
+        HttpUrl, HttpUrlError,
         account::model::AccountId,
         ap::{
             adapter::ActorRepository,
             model::{
-                ActorRow, CreateActorError,
-                actor::{FindActorError, FindRemoteActorRequest},
+                ActorId, ActorRow, ActorView, CreateActorError,
+                actor::{FindActorError, FindRemoteActorRequest, ProfileField, UpdateActorError},
+                key::{ActorKeyPair, CreateKeyError, FindKeyError, PublicKey, RSA_KEY_TYPE},
+                search::SearchError,
             },
         },
+        media::model::ProfileImage,
     };
 
     use super::*;
@@ -176,6 +320,145 @@ mod ap_repository_impl {
         }
     }
 
+    /// Element of the `fields` column of `actors`
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct ProfileFieldRow {
+        name: String,
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        verified_at: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    fn parse_fields(value: serde_json::Value) -> Result<Vec<ProfileField>, FindActorError> {
+        let rows = serde_json::from_value::<Vec<ProfileFieldRow>>(value).map_err(|e| {
+            tracing::error!(error = %e, "expected database profile fields to be valid");
+            FindActorError::Unknown(e.into())
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ProfileField {
+                name: row.name,
+                value: row.value,
+                source: row.source,
+                verified_at: row.verified_at,
+            })
+            .collect())
+    }
+
+    fn fields_value(fields: &[ProfileField]) -> serde_json::Value {
+        let rows = fields
+            .iter()
+            .map(|field| ProfileFieldRow {
+                name: field.name.clone(),
+                value: field.value.clone(),
+                source: field.source.clone(),
+                verified_at: field.verified_at,
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_value(rows).unwrap_or_default()
+    }
+
+    fn parse_profile_image(
+        url: Option<String>,
+        storage_key: Option<String>,
+    ) -> Result<Option<ProfileImage>, FindActorError> {
+        url.map(|url| {
+            Ok(ProfileImage {
+                url: url.parse()?,
+                storage_key,
+            })
+        })
+        .transpose()
+    }
+
+    /// Every actor query selects the same columns
+    macro_rules! impl_actor_row_from {
+        ($($row:ty),*) => {$(
+            impl TryFrom<$row> for ActorRow {
+                type Error = FindActorError;
+
+                fn try_from(row: $row) -> Result<Self, Self::Error> {
+                    let public_key = match (row.actors_public_key_id, row.actors_public_key_pem) {
+                        (Some(id), Some(pem)) => Some(PublicKey { id, pem }),
+                        _ => None,
+                    };
+                    Ok(ActorRow {
+                        id: row.actors_id.into(),
+                        actor_type: row.actors_type.into(),
+                        name: row.actors_name,
+                        inbox_url: row.actors_inbox_url.parse()?,
+                        outbox_url: row.actors_outbox_url.parse()?,
+                        actor_url: row.actors_actor_url.parse()?,
+                        account_id: row.actors_account_id.map(Into::into),
+                        shared_inbox_url: row
+                            .actors_shared_inbox_url
+                            .map(|s| s.parse())
+                            .transpose()?,
+                        summary: row.actors_summary,
+                        public_key,
+                        published: row.actors_published,
+                        created_at: row.actors_created_at,
+                        updated_at: row.actors_updated_at,
+                        display_name: row.actors_display_name,
+                        summary_source: row.actors_summary_source,
+                        avatar: parse_profile_image(
+                            row.actors_avatar_url,
+                            row.actors_avatar_storage_key,
+                        )?,
+                        header: parse_profile_image(
+                            row.actors_header_url,
+                            row.actors_header_storage_key,
+                        )?,
+                        fields: parse_fields(row.actors_fields)?,
+                        emojis: media_repository_impl::parse_emojis(row.actors_emojis)?,
+                    })
+                }
+            }
+        )*};
+    }
+
+    impl_actor_row_from!(
+        queries::GetAccountActorRow,
+        queries::GetActorByNameAndHostRow,
+        queries::GetActorByUrlRow,
+        queries::GetActorByKeyIdRow,
+        queries::GetActorViewRow,
+        queries::GetNoteViewRow,
+        queries::GetActorNoteViewsRow,
+        queries::GetHomeTimelineRow,
+        queries::GetHomeFeedEntryRow,
+        queries::GetPublicTimelineRow
+    );
+
+    /// Actor views select the actor columns plus the same counts
+    macro_rules! impl_actor_view_from {
+        ($($row:ty),*) => {$(
+            impl TryFrom<&$row> for ActorView {
+                type Error = FindActorError;
+
+                fn try_from(row: &$row) -> Result<Self, Self::Error> {
+                    Ok(ActorView {
+                        actor: row.clone().try_into()?,
+                        followers_count: u64::try_from(row.followers_count).unwrap_or_default(),
+                        following_count: u64::try_from(row.following_count).unwrap_or_default(),
+                        statuses_count: u64::try_from(row.statuses_count).unwrap_or_default(),
+                    })
+                }
+            }
+        )*};
+    }
+
+    impl_actor_view_from!(
+        queries::GetActorViewRow,
+        queries::GetNoteViewRow,
+        queries::GetActorNoteViewsRow,
+        queries::GetHomeTimelineRow,
+        queries::GetHomeFeedEntryRow,
+        queries::GetPublicTimelineRow
+    );
+
     #[async_trait::async_trait]
     impl ActorRepository for Postgres {
         async fn upsert_actor(&self, mut actor: ActorRow) -> Result<ActorRow, CreateActorError> {
@@ -184,6 +467,21 @@ mod ap_repository_impl {
 
             let shared_inbox_url = actor.shared_inbox_url.as_ref().map(|s| s.as_str());
             let account_id = actor.account_id.as_ref().map(|s| s.as_ref());
+            let summary = actor.summary.as_deref();
+            let public_key_id = actor.public_key.as_ref().map(|k| k.id.as_str());
+            let public_key_pem = actor.public_key.as_ref().map(|k| k.pem.as_str());
+            let avatar = actor.avatar.as_ref();
+            let header = actor.header.as_ref();
+            let fields = fields_value(&actor.fields);
+
+            // images of remote actors are shown through the media proxy
+            if actor.account_id.is_none() {
+                for image in avatar.into_iter().chain(header) {
+                    media_repository_impl::register_remote_media(&client, &image.url)
+                        .await
+                        .map_err(|e| CreateActorError::Unknown(e.into()))?;
+                }
+            }
 
             let res = queries::upsert_actor(
                 &client,
@@ -196,12 +494,32 @@ mod ap_repository_impl {
                 actor.outbox_url.as_str(),
                 shared_inbox_url,
                 account_id,
+                summary,
+                public_key_id,
+                public_key_pem,
+                actor.published.as_ref(),
+                &actor.created_at,
+                &actor.updated_at,
+                actor.display_name.as_deref(),
+                actor.summary_source.as_deref(),
+                avatar.map(|image| image.url.as_str()),
+                avatar.and_then(|image| image.storage_key.as_deref()),
+                header.map(|image| image.url.as_str()),
+                header.and_then(|image| image.storage_key.as_deref()),
+                &fields,
             )
             .await;
 
             match res {
                 Ok(Some(row)) => {
                     actor.id = row.actors_id.into();
+                    actor.created_at = row.actors_created_at;
+                    media_repository_impl::set_actor_emojis(&client, &actor.id, &actor.emojis)
+                        .await
+                        .map_err(|e| anyhow::anyhow!(e))
+                        .inspect_err(
+                            |e| tracing::error!(error = %e, "Failed to set actor emojis"),
+                        )?;
                     Ok(actor)
                 }
                 Ok(None) => {
@@ -213,6 +531,25 @@ mod ap_repository_impl {
             }
         }
 
+        async fn update_actor_fields(
+            &self,
+            id: &ActorId,
+            current: &[ProfileField],
+            fields: &[ProfileField],
+        ) -> Result<bool, UpdateActorError> {
+            let client = self.get_client().await?;
+            let count = queries::update_actor_fields(
+                &client,
+                id,
+                &fields_value(fields),
+                &fields_value(current),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to update actor fields"))?;
+            Ok(count > 0)
+        }
+
         async fn find_local_actor(
             &self,
             account_id: &AccountId,
@@ -220,23 +557,7 @@ mod ap_repository_impl {
             let client = self.get_client().await?;
             let result = queries::get_account_actor(&client, Some(account_id)).await;
             match result {
-                Ok(Some(row)) => {
-                    let actor_row = ActorRow {
-                        id: row.actors_id.into(),
-                        actor_type: row.actors_type.into(),
-                        name: row.actors_name,
-                        inbox_url: row.actors_inbox_url.parse()?,
-                        outbox_url: row.actors_outbox_url.parse()?,
-                        actor_url: row.actors_actor_url.parse()?,
-                        account_id: Some(account_id.clone()),
-                        shared_inbox_url: row
-                            .actors_shared_inbox_url
-                            .map(|s| s.parse())
-                            .transpose()?,
-                    };
-
-                    Ok(actor_row)
-                }
+                Ok(Some(row)) => row.try_into(),
                 Ok(None) => {
                     tracing::info!(account_id = %account_id, "Actor not found");
                     return Err(FindActorError::NotFound);
@@ -254,20 +575,7 @@ mod ap_repository_impl {
             let client = self.get_client().await?;
             let result = queries::get_actor_by_name_and_host(&client, &req.name, &req.host).await;
             match result {
-                Ok(Some(row)) => {
-                    let actor_row = ActorRow {
-                        id: row.actors_id.into(),
-                        actor_type: row.actors_type.into(),
-                        name: row.actors_name,
-                        inbox_url: row.actors_inbox_url.parse()?,
-                        outbox_url: row.actors_outbox_url.parse()?,
-                        actor_url: row.actors_actor_url.parse()?,
-                        account_id: None,
-                        shared_inbox_url: None,
-                    };
-
-                    Ok(actor_row)
-                }
+                Ok(Some(row)) => row.try_into(),
                 Ok(None) => {
                     tracing::info!(name = %req.name, host = %req.host, "Actor not found");
                     return Err(FindActorError::NotFound);
@@ -278,86 +586,2871 @@ mod ap_repository_impl {
                 }
             }
         }
-    }
-}
 
-mod note_repository_impl {
-    use super::*;
-    use crate::domain::ap::{
-        adapter::NoteRepository,
-        model::note::{CreateLocalNoteError, CreateRemoteNoteError, LocalNote, RemoteNote},
-    };
+        async fn find_actor_by_url(&self, actor_url: &HttpUrl) -> Result<ActorRow, FindActorError> {
+            let client = self.get_client().await?;
+            let result = queries::get_actor_by_url(&client, actor_url.as_str()).await;
+            match result {
+                Ok(Some(row)) => row.try_into(),
+                Ok(None) => {
+                    tracing::info!(actor_url = %actor_url, "Actor not found");
+                    Err(FindActorError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find actor by url");
+                    Err(FindActorError::Unknown(e.into()))
+                }
+            }
+        }
 
-    #[async_trait::async_trait]
-    impl NoteRepository for Postgres {
-        async fn create_local_note(
-            &self,
-            req: LocalNote,
-        ) -> Result<LocalNote, CreateLocalNoteError> {
-            let mut client = self.get_client().await?;
-            let transaction = get_transaction(&mut client).await?;
-            let note_source =
-                queries::insert_note_source(&transaction, &req.id, &req.account_id, &req.content)
-                    .await;
-            let note_source = match note_source {
-                Ok(Some(row)) => row,
+        async fn find_actor_by_key_id(&self, key_id: &str) -> Result<ActorRow, FindActorError> {
+            let client = self.get_client().await?;
+            let result = queries::get_actor_by_key_id(&client, Some(key_id)).await;
+            match result {
+                Ok(Some(row)) => row.try_into(),
                 Ok(None) => {
-                    let e = anyhow::anyhow!("Insert success but no row returned");
-                    tracing::error!(e = %e,"Failed to insert note source");
-                    return Err(CreateLocalNoteError::Unknown(e));
+                    tracing::info!(key_id = %key_id, "Actor not found");
+                    Err(FindActorError::NotFound)
                 }
                 Err(e) => {
-                    tracing::error!(error = %e, "Failed to insert note source");
-                    return Err(CreateLocalNoteError::Unknown(e.into()));
+                    tracing::error!(error = %e, "Failed to find actor by key id");
+                    Err(FindActorError::Unknown(e.into()))
                 }
-            };
+            }
+        }
 
-            let result = queries::insert_note(
-                &transaction,
-                &req.id,
-                &req.actor_id,
-                Some(&note_source.note_sources_id),
-                &req.content,
-                req.note_url.as_str(),
+        async fn find_actor_view(&self, id: &ActorId) -> Result<ActorView, FindActorError> {
+            let client = self.get_client().await?;
+            let result = queries::get_actor_view(&client, id).await;
+            match result {
+                Ok(Some(row)) => ActorView::try_from(&row),
+                Ok(None) => {
+                    tracing::info!(actor_id = %id, "Actor not found");
+                    Err(FindActorError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find actor view");
+                    Err(FindActorError::Unknown(e.into()))
+                }
+            }
+        }
+
+        async fn create_account_key(
+            &self,
+            account_id: &AccountId,
+            key: &ActorKeyPair,
+        ) -> Result<(), CreateKeyError> {
+            let client = self.get_client().await?;
+            let result = queries::insert_account_key(
+                &client,
+                account_id,
+                RSA_KEY_TYPE,
+                &key.public_key_pem,
+                &key.private_key_pem,
             )
             .await;
-            if let Err(e) = result {
-                tracing::error!(error = %e, "Failed to insert note");
-                return Err(CreateLocalNoteError::Unknown(e.into()));
-            };
-
-            match transaction.commit().await {
-                Ok(_) => Ok(req),
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) if is_unique_violation(&e) => Err(CreateKeyError::AlreadyExists),
                 Err(e) => {
-                    tracing::error!(error = %e, "Failed to commit transaction");
-                    return Err(CreateLocalNoteError::Unknown(e.into()));
+                    tracing::error!(error = %e, "Failed to insert account key");
+                    Err(CreateKeyError::Unknown(e.into()))
                 }
             }
         }
 
-        async fn create_remote_note(
+        async fn find_remote_inboxes(&self) -> Result<Vec<HttpUrl>, FindActorError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_remote_inboxes(&client)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find remote inboxes"))?;
+            let mut inboxes = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                inboxes.push(row.inbox_url.parse()?);
+            }
+            Ok(inboxes)
+        }
+
+        async fn search_actors(
             &self,
-            req: RemoteNote,
-        ) -> Result<RemoteNote, CreateRemoteNoteError> {
+            query: &str,
+            limit: u32,
+            offset: u32,
+        ) -> Result<Vec<ActorId>, SearchError> {
+            let (limit, offset) = (i64::from(limit), i64::from(offset));
             let client = self.get_client().await?;
+            let rows = queries::search_actors(&client, query, &limit, &offset)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to search actors"))?;
+            let mut ids = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                ids.push(ActorId::from(row.id));
+            }
+            Ok(ids)
+        }
 
-            let result = queries::insert_note(
+        async fn find_account_key(
+            &self,
+            account_id: &AccountId,
+        ) -> Result<ActorKeyPair, FindKeyError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_account_keys(&client, account_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find account keys"))?;
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                if row.account_keys_key_type == RSA_KEY_TYPE {
+                    return Ok(ActorKeyPair {
+                        public_key_pem: row.account_keys_public_key,
+                        private_key_pem: row.account_keys_private_key,
+                    });
+                }
+            }
+            Err(FindKeyError::NotFound)
+        }
+    }
+}
+
+mod follow_repository_impl {
+    use super::*;
+    use crate::domain::{
+        HttpUrl, HttpUrlError,
+        ap::{
+            adapter::FollowRepository,
+            model::{
+                ActorId,
+                follow::{
+                    CreateFollowError, DeleteFollowError, FindFollowError, Follow,
+                    UpdateFollowError,
+                },
+            },
+        },
+    };
+
+    #[async_trait::async_trait]
+    impl FollowRepository for Postgres {
+        async fn upsert_follow(&self, mut follow: Follow) -> Result<Follow, CreateFollowError> {
+            let client = self.get_client().await?;
+            let result = queries::upsert_follow(
                 &client,
-                &req.id,
-                &req.actor_id,
-                None,
-                &req.content,
-                req.note_url.as_str(),
+                &follow.id,
+                &follow.follower_id,
+                &follow.followee_id,
+                follow.follow_url.as_str(),
+                &follow.accepted,
             )
             .await;
-
             match result {
-                Ok(_) => Ok(req),
+                Ok(Some(row)) => {
+                    follow.id = row.follows_id.into();
+                    Ok(follow)
+                }
+                Ok(None) => Err(anyhow::anyhow!("Upsert success but no row returned").into()),
                 Err(e) => {
-                    tracing::error!(error = %e, "Failed to insert remote note");
-                    Err(CreateRemoteNoteError::Unknown(e.into()))
+                    tracing::error!(error = %e, "Failed to upsert follow");
+                    Err(CreateFollowError::Unknown(e.into()))
                 }
             }
         }
+
+        async fn find_follow(
+            &self,
+            follower_id: &ActorId,
+            followee_id: &ActorId,
+        ) -> Result<Option<Follow>, FindFollowError> {
+            let client = self.get_client().await?;
+            let row = queries::get_follow(&client, follower_id, followee_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find follow"))?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            Ok(Some(Follow {
+                id: row.follows_id.into(),
+                follower_id: row.follows_follower_id.into(),
+                followee_id: row.follows_followee_id.into(),
+                follow_url: row
+                    .follows_follow_url
+                    .parse()
+                    .map_err(|e: HttpUrlError| anyhow::anyhow!(e))?,
+                accepted: row.follows_accepted,
+            }))
+        }
+
+        async fn accept_follow(
+            &self,
+            followee_id: &ActorId,
+            follow_url: &HttpUrl,
+        ) -> Result<Option<ActorId>, UpdateFollowError> {
+            let client = self.get_client().await?;
+            let row = queries::accept_follow(&client, followee_id, follow_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to accept follow"))?;
+            Ok(row.map(|row| row.follows_follower_id.into()))
+        }
+
+        async fn reject_follow(
+            &self,
+            followee_id: &ActorId,
+            follow_url: &HttpUrl,
+        ) -> Result<(), DeleteFollowError> {
+            let client = self.get_client().await?;
+            queries::reject_follow(&client, followee_id, follow_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to reject follow"))?;
+            Ok(())
+        }
+
+        async fn delete_follow(
+            &self,
+            follower_id: &ActorId,
+            followee_id: &ActorId,
+        ) -> Result<(), DeleteFollowError> {
+            let client = self.get_client().await?;
+            queries::delete_follow(&client, follower_id, followee_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete follow"))?;
+            Ok(())
+        }
+
+        async fn delete_follow_by_url(
+            &self,
+            follower_id: &ActorId,
+            follow_url: &HttpUrl,
+        ) -> Result<(), DeleteFollowError> {
+            let client = self.get_client().await?;
+            queries::delete_follow_by_url(&client, follower_id, follow_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete follow"))?;
+            Ok(())
+        }
+
+        async fn find_follower_inboxes(
+            &self,
+            followee_id: &ActorId,
+        ) -> Result<Vec<HttpUrl>, FindFollowError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_follower_inboxes(&client, followee_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find follower inboxes"))?;
+            let mut inboxes = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                let inbox = row
+                    .inbox_url
+                    .parse()
+                    .map_err(|e: HttpUrlError| anyhow::anyhow!(e))?;
+                inboxes.push(inbox);
+            }
+            Ok(inboxes)
+        }
+    }
+}
+
+mod notification_repository_impl {
+    use super::*;
+    use crate::domain::{
+        account::model::AccountId,
+        ap::{
+            adapter::NotificationRepository,
+            model::{
+                note::NoteId,
+                notification::{
+                    CreateNotificationError, DeleteNotificationError, FindNotificationError,
+                    Marker, MarkerError, MarkerTimeline, Notification, NotificationId,
+                    NotificationKind, NotificationsRequest,
+                },
+            },
+        },
+    };
+
+    /// Notification queries select the same columns
+    macro_rules! impl_notification_from {
+        ($($row:ty),*) => {$(
+            impl TryFrom<($row, &AccountId)> for Notification {
+                type Error = FindNotificationError;
+
+                fn try_from((row, account_id): ($row, &AccountId)) -> Result<Self, Self::Error> {
+                    let kind = row.notifications_type.parse::<NotificationKind>().map_err(|e| {
+                        tracing::error!(error = %e, "expected database notification type to be valid");
+                        FindNotificationError::Unknown(anyhow::anyhow!(e))
+                    })?;
+                    Ok(Notification {
+                        id: row.notifications_id.into(),
+                        account_id: account_id.clone(),
+                        kind,
+                        actor_id: row.notifications_actor_id.into(),
+                        note_id: row.notifications_note_id.map(NoteId::from),
+                    })
+                }
+            }
+        )*};
+    }
+
+    impl_notification_from!(queries::GetNotificationsRow, queries::GetNotificationRow);
+
+    #[async_trait::async_trait]
+    impl NotificationRepository for Postgres {
+        async fn create_notification(
+            &self,
+            notification: &Notification,
+        ) -> Result<(), CreateNotificationError> {
+            let client = self.get_client().await?;
+            queries::insert_notification(
+                &client,
+                &notification.id,
+                &notification.account_id,
+                notification.kind.as_ref(),
+                &notification.actor_id,
+                notification.note_id.as_deref(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert notification"))?;
+            Ok(())
+        }
+
+        async fn find_notifications(
+            &self,
+            req: &NotificationsRequest,
+        ) -> Result<Vec<Notification>, FindNotificationError> {
+            let client = self.get_client().await?;
+            let kinds = req.kinds.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+            let exclude_kinds = req
+                .exclude_kinds
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>();
+            let page = &req.page;
+            let limit = i64::from(page.limit);
+            let ascending = page.ascending();
+            let rows = queries::get_notifications(
+                &client,
+                &req.account_id,
+                &kinds,
+                &exclude_kinds,
+                req.actor_id.as_deref(),
+                page.max_id.as_deref(),
+                page.lower_bound().map(|id| &**id),
+                &ascending,
+                &limit,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to find notifications"))?;
+
+            let mut notifications = rows
+                .map(|row| {
+                    let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                    Notification::try_from((row, &req.account_id))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if ascending {
+                notifications.reverse();
+            }
+            Ok(notifications)
+        }
+
+        async fn find_notification(
+            &self,
+            account_id: &AccountId,
+            id: &NotificationId,
+        ) -> Result<Notification, FindNotificationError> {
+            let client = self.get_client().await?;
+            let row = queries::get_notification(&client, account_id, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find notification"))?
+                .ok_or(FindNotificationError::NotFound)?;
+            Notification::try_from((row, account_id))
+        }
+
+        async fn delete_notification(
+            &self,
+            account_id: &AccountId,
+            id: &NotificationId,
+        ) -> Result<(), DeleteNotificationError> {
+            let client = self.get_client().await?;
+            queries::delete_notification(&client, account_id, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete notification"))?;
+            Ok(())
+        }
+
+        async fn delete_note_notifications(
+            &self,
+            account_id: &AccountId,
+            kind: NotificationKind,
+            note_id: &NoteId,
+        ) -> Result<(), DeleteNotificationError> {
+            let client = self.get_client().await?;
+            queries::delete_note_notifications(&client, account_id, kind.as_ref(), note_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete notifications"))?;
+            Ok(())
+        }
+
+        async fn clear_notifications(
+            &self,
+            account_id: &AccountId,
+        ) -> Result<(), DeleteNotificationError> {
+            let client = self.get_client().await?;
+            queries::clear_notifications(&client, account_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to clear notifications"))?;
+            Ok(())
+        }
+
+        async fn count_unread_notifications(
+            &self,
+            account_id: &AccountId,
+            limit: u32,
+        ) -> Result<u64, FindNotificationError> {
+            let client = self.get_client().await?;
+            let row = queries::count_unread_notifications(&client, account_id, &i64::from(limit))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to count notifications"))?;
+            Ok(row.map_or(0, |row| u64::try_from(row.count).unwrap_or_default()))
+        }
+
+        async fn find_markers(&self, account_id: &AccountId) -> Result<Vec<Marker>, MarkerError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_markers(&client, account_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find markers"))?;
+            rows.map(|row| {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                let timeline = row.markers_timeline.parse::<MarkerTimeline>().map_err(|e| {
+                    tracing::error!(error = %e, "expected database marker timeline to be valid");
+                    anyhow::anyhow!(e)
+                })?;
+                Ok(Marker {
+                    timeline,
+                    last_read_id: row.markers_last_read_id,
+                    version: row.markers_version,
+                    updated_at: row.markers_updated_at,
+                })
+            })
+            .collect()
+        }
+
+        async fn upsert_marker(
+            &self,
+            account_id: &AccountId,
+            timeline: MarkerTimeline,
+            last_read_id: &uuid::Uuid,
+        ) -> Result<(), MarkerError> {
+            let client = self.get_client().await?;
+            queries::upsert_marker(&client, account_id, timeline.as_ref(), last_read_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to upsert marker"))?;
+            Ok(())
+        }
+    }
+}
+
+mod session_repository_impl {
+    use super::*;
+    use crate::domain::{
+        account::model::{Account, AccountName, PasswordHash},
+        auth::{
+            adapter::SessionRepository,
+            model::{
+                AppId, AuthenticateError, CreateSessionError, LoginError, LogoutError, Principal,
+                Session, SessionToken, oauth::TokenError,
+            },
+        },
+    };
+    use chrono::{DateTime, Utc};
+
+    #[async_trait::async_trait]
+    impl SessionRepository for Postgres {
+        #[tracing::instrument(skip(self))]
+        async fn find_credentials(
+            &self,
+            name: &AccountName,
+        ) -> Result<Option<(Account, Option<PasswordHash>)>, LoginError> {
+            let client = self.get_client().await?;
+            let result = queries::find_account_credentials(&client, name.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find credentials"))?;
+
+            Ok(result.map(|row| {
+                let account = Account::from_id_name(row.accounts_id.into(), name.clone());
+                let hash = row
+                    .accounts_password_hash
+                    .map(PasswordHash::from_phc_string);
+                (account, hash)
+            }))
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn create_session(&self, session: &Session) -> Result<(), CreateSessionError> {
+            let client = self.get_client().await?;
+            queries::insert_session(
+                &client,
+                &session.id,
+                session.account.as_ref().map(|account| &**account.id()),
+                &session.token.hash(),
+                &session.created,
+                &session.expires,
+                session.app_id.as_deref(),
+                &session.scopes.to_string(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert session"))?;
+            Ok(())
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn find_session(
+            &self,
+            token: &SessionToken,
+            now: DateTime<Utc>,
+        ) -> Result<Option<Principal>, AuthenticateError> {
+            let client = self.get_client().await?;
+            let result = queries::find_session(&client, &token.hash(), &now)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find session"))?;
+
+            let Some(row) = result else {
+                return Ok(None);
+            };
+            let account = match (row.accounts_id, row.accounts_name) {
+                (Some(id), Some(name)) => {
+                    let name = AccountName::new(&name).map_err(|e| anyhow::anyhow!(e))?;
+                    Some(Account::from_id_name(id.into(), name))
+                }
+                _ => None,
+            };
+            let scopes = row
+                .sessions_scopes
+                .parse()
+                .map_err(|e| anyhow::anyhow!("{e}"))?;
+            Ok(Some(Principal {
+                account,
+                app_id: row.sessions_app_id.map(Into::into),
+                scopes,
+            }))
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn delete_session(&self, token: &SessionToken) -> Result<(), LogoutError> {
+            let client = self.get_client().await?;
+            queries::delete_session(&client, &token.hash())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete session"))?;
+            Ok(())
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn delete_app_session(
+            &self,
+            token: &SessionToken,
+            app_id: &AppId,
+        ) -> Result<(), TokenError> {
+            let client = self.get_client().await?;
+            queries::delete_app_session(&client, &token.hash(), Some(app_id))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete session"))?;
+            Ok(())
+        }
+    }
+}
+
+mod oauth_repository_impl {
+    use super::*;
+    use crate::domain::{
+        account::model::{Account, AccountName},
+        auth::{
+            adapter::OAuthRepository,
+            model::{
+                AppId, Scopes,
+                oauth::{
+                    App, AuthorizationCode, AuthorizationGrant, AuthorizeError, ClientSecret,
+                    CodeChallenge, FindAppError, RegisterAppError, TokenError,
+                },
+            },
+        },
+    };
+    use chrono::{DateTime, Utc};
+
+    /// Columns of `oauth_apps` as selected by the app queries
+    struct AppRow {
+        id: uuid::Uuid,
+        client_id: String,
+        client_secret_hash: String,
+        name: String,
+        redirect_uris: String,
+        scopes: String,
+        website: Option<String>,
+    }
+
+    macro_rules! impl_app_row_from {
+        ($($row:ty),*) => {$(
+            impl From<$row> for AppRow {
+                fn from(row: $row) -> Self {
+                    Self {
+                        id: row.oauth_apps_id,
+                        client_id: row.oauth_apps_client_id,
+                        client_secret_hash: row.oauth_apps_client_secret_hash,
+                        name: row.oauth_apps_name,
+                        redirect_uris: row.oauth_apps_redirect_uris,
+                        scopes: row.oauth_apps_scopes,
+                        website: row.oauth_apps_website,
+                    }
+                }
+            }
+        )*};
+    }
+    impl_app_row_from!(queries::FindAppByClientIdRow, queries::FindAppByIdRow);
+
+    impl TryFrom<AppRow> for (App, String) {
+        type Error = anyhow::Error;
+
+        fn try_from(row: AppRow) -> Result<Self, Self::Error> {
+            let scopes: Scopes = row.scopes.parse()?;
+            let app = App {
+                id: row.id.into(),
+                client_id: row.client_id,
+                name: row.name,
+                redirect_uris: row.redirect_uris.lines().map(str::to_string).collect(),
+                scopes,
+                website: row.website,
+            };
+            Ok((app, row.client_secret_hash))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OAuthRepository for Postgres {
+        #[tracing::instrument(skip(self))]
+        async fn create_app(
+            &self,
+            app: &App,
+            client_secret: &ClientSecret,
+            created: DateTime<Utc>,
+        ) -> Result<(), RegisterAppError> {
+            let client = self.get_client().await?;
+            queries::insert_app(
+                &client,
+                &app.id,
+                &app.client_id,
+                &client_secret.hash(),
+                &app.name,
+                &app.redirect_uris.join("\n"),
+                &app.scopes.to_string(),
+                app.website.as_deref(),
+                &created,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert app"))?;
+            Ok(())
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn find_app(&self, id: &AppId) -> Result<Option<App>, FindAppError> {
+            let client = self.get_client().await?;
+            let result = queries::find_app_by_id(&client, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find app"))?;
+
+            let Some(row) = result else {
+                return Ok(None);
+            };
+            let (app, _) = AppRow::from(row).try_into()?;
+            Ok(Some(app))
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn find_client(
+            &self,
+            client_id: &str,
+        ) -> Result<Option<(App, String)>, FindAppError> {
+            let client = self.get_client().await?;
+            let result = queries::find_app_by_client_id(&client, client_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find app"))?;
+
+            let Some(row) = result else {
+                return Ok(None);
+            };
+            Ok(Some(AppRow::from(row).try_into()?))
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn create_authorization_code(
+            &self,
+            code: &AuthorizationCode,
+            grant: &AuthorizationGrant,
+        ) -> Result<(), AuthorizeError> {
+            let client = self.get_client().await?;
+            let (code_challenge, code_challenge_method) = match &grant.code_challenge {
+                Some(challenge) => (
+                    Some(challenge.challenge.as_str()),
+                    Some(<&str>::from(challenge.method)),
+                ),
+                None => (None, None),
+            };
+            queries::insert_authorization_code(
+                &client,
+                &code.hash(),
+                &grant.app_id,
+                grant.account.id(),
+                &grant.redirect_uri,
+                &grant.scopes.to_string(),
+                code_challenge,
+                code_challenge_method,
+                &grant.expires,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert authorization code"))?;
+            Ok(())
+        }
+
+        #[tracing::instrument(skip(self))]
+        async fn take_authorization_code(
+            &self,
+            code: &AuthorizationCode,
+        ) -> Result<Option<AuthorizationGrant>, TokenError> {
+            let client = self.get_client().await?;
+            let result = queries::take_authorization_code(&client, &code.hash())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(
+                    |e| tracing::error!(error = %e, "Failed to take authorization code"),
+                )?;
+
+            let Some(row) = result else {
+                return Ok(None);
+            };
+            let code_challenge = match (row.taken_code_challenge, row.taken_code_challenge_method) {
+                (Some(challenge), Some(method)) => Some(CodeChallenge {
+                    challenge,
+                    method: method.parse().map_err(|e| anyhow::anyhow!("{e}"))?,
+                }),
+                _ => None,
+            };
+            let name = AccountName::new(&row.account_name).map_err(|e| anyhow::anyhow!(e))?;
+            Ok(Some(AuthorizationGrant {
+                app_id: row.taken_app_id.into(),
+                account: Account::from_id_name(row.account_id.into(), name),
+                redirect_uri: row.taken_redirect_uri,
+                scopes: row
+                    .taken_scopes
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("{e}"))?,
+                code_challenge,
+                expires: row.taken_expires,
+            }))
+        }
+    }
+}
+
+mod note_repository_impl {
+    use super::*;
+    use crate::domain::{
+        HttpUrl, HttpUrlError,
+        account::model::AccountId,
+        ap::{
+            adapter::{ActorRepository, NoteRepository},
+            model::{
+                ActorId, ActorView,
+                note::{
+                    CreateLocalNoteError, CreateRemoteNoteError, DeleteNoteError, FindNoteError,
+                    LocalNote, NoteId, NoteRecipientsError, NoteSource, NoteView, RemoteNote,
+                    UpdateNoteError,
+                },
+                poll::{
+                    ClosePollError, CreateVoteError, FindVoteError, Poll, PollOption, PollVote,
+                },
+                reaction::{
+                    CreateReactionError, DeleteReactionError, EmojiReaction, EmojiReactionCount,
+                    Favourite, FindReactionError, Reblog, ReblogId, ReblogView,
+                },
+                search::{Hashtag, SearchError},
+                timeline::{
+                    FindTimelineError, HOME_FEED_SIZE, HomeTimelineRequest, PublicTimelineRequest,
+                    TimelineItem, TimelineItemId, UpdateHomeFeedError,
+                },
+            },
+        },
+        media::model::{Attachment, AttachmentId, CustomEmoji, FindEmojiError},
+        text::{SourceMediaType, SourceMediaTypeError},
+    };
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+
+    impl From<HttpUrlError> for FindNoteError {
+        fn from(e: HttpUrlError) -> Self {
+            tracing::error!(error = %e, "expected database url to be valid but got invalid url");
+            FindNoteError::Unknown(e.into())
+        }
+    }
+
+    fn parse_media_type(media_type: &str) -> Result<SourceMediaType, FindNoteError> {
+        media_type.parse().map_err(|e: SourceMediaTypeError| {
+            tracing::error!(error = %e, "expected database media type to be valid");
+            FindNoteError::Unknown(anyhow::anyhow!(e))
+        })
+    }
+
+    /// Note views select the same columns
+    macro_rules! impl_note_view_from {
+        ($($row:ty),*) => {$(
+            impl TryFrom<$row> for NoteView {
+                type Error = FindNoteError;
+
+                fn try_from(row: $row) -> Result<Self, Self::Error> {
+                    let author = ActorView::try_from(&row)
+                        .map_err(|e| FindNoteError::Unknown(e.into()))?;
+                    let (poll, own_votes) = parse_poll(row.poll)?;
+                    Ok(NoteView {
+                        id: row.notes_id.into(),
+                        author,
+                        content: row.notes_content,
+                        summary: row.notes_summary,
+                        sensitive: row.notes_sensitive,
+                        source: NoteSource {
+                            id: row.source_id.into(),
+                            content: row.source_content,
+                            media_type: parse_media_type(&row.source_media_type)?,
+                        },
+                        note_type: row.notes_object_type.parse()?,
+                        title: row.notes_name,
+                        url: row.notes_url.map(|url| url.parse()).transpose()?,
+                        note_url: row.notes_note_url.parse()?,
+                        updated: row.notes_updated,
+                        published: row.notes_published,
+                        in_reply_to: row
+                            .notes_in_reply_to_id
+                            .zip(row.in_reply_to_actor_id)
+                            .map(|(note_id, actor_id)| (note_id.into(), actor_id.into())),
+                        favourites_count: u64::try_from(row.favourites_count).unwrap_or_default(),
+                        reblogs_count: u64::try_from(row.reblogs_count).unwrap_or_default(),
+                        favourited: row.favourited,
+                        reblogged: row.reblogged,
+                        reactions: parse_reactions(row.reactions)?,
+                        attachments: media_repository_impl::parse_attachments(row.attachments)?,
+                        emojis: media_repository_impl::parse_emojis(row.emojis)?,
+                        poll,
+                        own_votes,
+                    })
+                }
+            }
+        )*};
+    }
+
+    impl_note_view_from!(
+        queries::GetNoteViewRow,
+        queries::GetActorNoteViewsRow,
+        queries::GetHomeTimelineRow,
+        queries::GetHomeFeedEntryRow,
+        queries::GetPublicTimelineRow
+    );
+
+    /// Home feed entry: its id and, for reblogs, the reblogging actor and `Announce` url
+    type HomeFeedEntry = (uuid::Uuid, Option<(uuid::Uuid, String)>);
+
+    impl Postgres {
+        /// Turn home feed entries into timeline items, looking up reblogging actors
+        async fn home_feed_items(
+            &self,
+            entries: Vec<(HomeFeedEntry, NoteView)>,
+        ) -> Result<Vec<TimelineItem>, FindTimelineError> {
+            // a page is short, so reblogging actors are looked up one by one
+            let mut reblog_actors = HashMap::<ActorId, ActorView>::new();
+            let mut items = Vec::new();
+            for ((entry_id, reblog), note) in entries {
+                let Some((actor_id, announce_url)) = reblog else {
+                    items.push(TimelineItem::Note(Box::new(note)));
+                    continue;
+                };
+
+                let actor_id = ActorId::from(actor_id);
+                let actor = match reblog_actors.get(&actor_id) {
+                    Some(actor) => actor.clone(),
+                    None => {
+                        let actor = self
+                            .find_actor_view(&actor_id)
+                            .await
+                            .map_err(|e| anyhow::anyhow!(e))?;
+                        reblog_actors.insert(actor_id, actor.clone());
+                        actor
+                    }
+                };
+                items.push(TimelineItem::Reblog(Box::new(ReblogView {
+                    id: entry_id.into(),
+                    announce_url: parse_reaction_url(announce_url)?,
+                    actor,
+                    note,
+                })));
+            }
+            Ok(items)
+        }
+    }
+
+    impl Postgres {
+        /// Regenerate the home feed of the named account, or of every account when `None`,
+        /// returning how many were rebuilt
+        #[tracing::instrument(skip(self))]
+        pub async fn rebuild_home_feeds(&self, name: Option<&str>) -> Result<usize, anyhow::Error> {
+            let client = self.get_client().await?;
+            let account_ids = match name {
+                Some(name) => {
+                    let row = queries::find_account_by_name(&client, name)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("account {name} not found"))?;
+                    vec![row.accounts_id]
+                }
+                None => queries::list_account_ids(&client)
+                    .await?
+                    .map(|row| row.map(|row| row.accounts_id))
+                    .collect::<Result<Vec<_>, _>>()?,
+            };
+            drop(client);
+
+            for account_id in &account_ids {
+                let account_id = AccountId::from(*account_id);
+                self.rebuild_home_feed(&account_id, HOME_FEED_SIZE).await?;
+                tracing::info!(%account_id, "Rebuilt home feed");
+            }
+            Ok(account_ids.len())
+        }
+    }
+
+    /// Reactions to a note as aggregated by the note view queries
+    #[derive(serde::Deserialize)]
+    struct EmojiReactionCountRow {
+        content: String,
+        emoji: Option<media_repository_impl::CustomEmojiRow>,
+        count: i64,
+        me: bool,
+    }
+
+    fn parse_reactions(value: serde_json::Value) -> Result<Vec<EmojiReactionCount>, anyhow::Error> {
+        serde_json::from_value::<Vec<EmojiReactionCountRow>>(value)?
+            .into_iter()
+            .map(|row| {
+                Ok(EmojiReactionCount {
+                    content: row.content,
+                    emoji: row.emoji.map(CustomEmoji::try_from).transpose()?,
+                    count: u64::try_from(row.count).unwrap_or_default(),
+                    me: row.me,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()
+            .inspect_err(|e| tracing::error!(error = %e, "expected database reaction to be valid"))
+    }
+
+    /// Poll of the note queries, aggregated to JSON
+    #[derive(serde::Deserialize)]
+    struct PollRow {
+        multiple: bool,
+        expires_at: Option<DateTime<Utc>>,
+        closed_at: Option<DateTime<Utc>>,
+        voters_count: i64,
+        options: Option<Vec<PollOptionRow>>,
+        own_votes: Vec<i32>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct PollOptionRow {
+        title: String,
+        votes_count: i64,
+    }
+
+    /// Poll of a note and the options the viewer chose
+    fn parse_poll(
+        value: Option<serde_json::Value>,
+    ) -> Result<(Option<Poll>, Vec<u32>), anyhow::Error> {
+        let Some(value) = value else {
+            return Ok((None, Vec::new()));
+        };
+        let row = serde_json::from_value::<PollRow>(value)
+            .inspect_err(|e| tracing::error!(error = %e, "expected database poll to be valid"))?;
+        let poll = Poll {
+            options: row
+                .options
+                .unwrap_or_default()
+                .into_iter()
+                .map(|option| PollOption {
+                    title: option.title,
+                    votes_count: u64::try_from(option.votes_count).unwrap_or_default(),
+                })
+                .collect(),
+            multiple: row.multiple,
+            expires_at: row.expires_at,
+            closed_at: row.closed_at,
+            voters_count: u64::try_from(row.voters_count).unwrap_or_default(),
+        };
+        let own_votes = row
+            .own_votes
+            .into_iter()
+            .filter_map(|choice| u32::try_from(choice).ok())
+            .collect();
+        Ok((Some(poll), own_votes))
+    }
+
+    /// Store the poll of a note, or remove it, dropping the options that changed with their
+    /// votes. Counts are stored for remote polls only, local ones are counted from their votes
+    async fn set_poll(
+        client: &impl deadpool_postgres::GenericClient,
+        note_id: &uuid::Uuid,
+        poll: Option<&Poll>,
+        remote: bool,
+    ) -> Result<(), tokio_postgres::Error> {
+        let Some(poll) = poll else {
+            queries::delete_poll(client, note_id).await?;
+            return Ok(());
+        };
+        let count = |count: u64| remote.then(|| i64::try_from(count).unwrap_or(i64::MAX));
+        queries::upsert_poll(
+            client,
+            note_id,
+            &poll.multiple,
+            poll.expires_at.as_ref(),
+            poll.closed_at.as_ref(),
+            count(poll.voters_count).as_ref(),
+        )
+        .await?;
+        let titles = poll
+            .options
+            .iter()
+            .map(|option| option.title.clone())
+            .collect::<Vec<_>>();
+        queries::delete_changed_poll_options(client, note_id, &titles).await?;
+        for (position, option) in (0..).zip(&poll.options) {
+            queries::upsert_poll_option(
+                client,
+                note_id,
+                &position,
+                &option.title,
+                count(option.votes_count).as_ref(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    fn parse_reaction_url(url: String) -> Result<HttpUrl, anyhow::Error> {
+        url.parse().map_err(|e: HttpUrlError| {
+            tracing::error!(error = %e, "expected database url to be valid but got invalid url");
+            anyhow::anyhow!(e)
+        })
+    }
+
+    #[async_trait::async_trait]
+    impl NoteRepository for Postgres {
+        async fn create_local_note(
+            &self,
+            mut req: LocalNote,
+            media_ids: &[AttachmentId],
+        ) -> Result<LocalNote, CreateLocalNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            let note_source = queries::insert_note_source(
+                &transaction,
+                &req.source.id,
+                &req.id,
+                Some(&req.account_id),
+                &req.source.content,
+                req.source.media_type.as_str(),
+            )
+            .await;
+            let note_source = match note_source {
+                Ok(Some(row)) => row,
+                Ok(None) => {
+                    let e = anyhow::anyhow!("Insert success but no row returned");
+                    tracing::error!(e = %e,"Failed to insert note source");
+                    return Err(CreateLocalNoteError::Unknown(e));
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to insert note source");
+                    return Err(CreateLocalNoteError::Unknown(e.into()));
+                }
+            };
+
+            let result = queries::insert_note(
+                &transaction,
+                &req.id,
+                &req.actor_id,
+                &note_source.note_sources_id,
+                &req.content,
+                req.note_url.as_str(),
+                None,
+                &req.published,
+                &req.created_at,
+                &req.updated_at,
+                req.summary.as_deref(),
+                &req.sensitive,
+                req.note_type().as_str(),
+                None,
+                None,
+            )
+            .await;
+            if let Err(e) = result {
+                tracing::error!(error = %e, "Failed to insert note");
+                return Err(CreateLocalNoteError::Unknown(e.into()));
+            };
+
+            if !media_ids.is_empty() {
+                req.attachments = media_repository_impl::attach_note_media(
+                    &transaction,
+                    &req.id,
+                    &req.account_id,
+                    media_ids,
+                )
+                .await
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to attach media"))?;
+                // uploads of others and those already posted are not attached
+                if req.attachments.len() != media_ids.len() {
+                    return Err(CreateLocalNoteError::InvalidMedia(
+                        "media not found".to_string(),
+                    ));
+                }
+            }
+
+            media_repository_impl::set_note_emojis(&transaction, &req.id, &req.emojis)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set note emojis"))?;
+            set_poll(&transaction, &req.id, req.poll.as_ref(), false)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set poll"))?;
+
+            match transaction.commit().await {
+                Ok(_) => Ok(req),
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to commit transaction");
+                    return Err(CreateLocalNoteError::Unknown(e.into()));
+                }
+            }
+        }
+
+        async fn find_local_note(&self, id: &NoteId) -> Result<LocalNote, FindNoteError> {
+            let client = self.get_client().await?;
+            let result = queries::get_local_note(&client, id).await;
+            match result {
+                Ok(Some(row)) => {
+                    if let Some(deleted) = row.notes_deleted {
+                        tracing::info!(note_id = %id, "Note was deleted");
+                        return Err(FindNoteError::Gone(deleted));
+                    }
+                    let Some(account_id) = row.note_sources_account_id else {
+                        tracing::info!(note_id = %id, "Note is not local");
+                        return Err(FindNoteError::NotFound);
+                    };
+                    let note = LocalNote {
+                        id: row.notes_id.into(),
+                        account_id: account_id.into(),
+                        actor_id: row.notes_actor_id.into(),
+                        actor_url: row.actors_actor_url.parse()?,
+                        content: row.notes_content,
+                        summary: row.notes_summary,
+                        sensitive: row.notes_sensitive,
+                        source: NoteSource {
+                            id: row.source_id.into(),
+                            content: row.source_content,
+                            media_type: parse_media_type(&row.source_media_type)?,
+                        },
+                        note_url: row.notes_note_url.parse()?,
+                        updated: row.notes_updated,
+                        published: row.notes_published,
+                        created_at: row.notes_created_at,
+                        updated_at: row.notes_updated_at,
+                        attachments: media_repository_impl::parse_attachments(row.attachments)?,
+                        emojis: media_repository_impl::parse_emojis(row.emojis)?,
+                        poll: parse_poll(row.poll)?.0,
+                    };
+                    Ok(note)
+                }
+                Ok(None) => {
+                    let tombstone = queries::get_note_tombstone(&client, id)
+                        .await
+                        .map_err(|e| anyhow::anyhow!(e))
+                        .inspect_err(|e| tracing::error!(error = %e, "Failed to find tombstone"))?;
+                    if let Some(row) = tombstone {
+                        tracing::info!(note_id = %id, "Note was deleted with its account");
+                        return Err(FindNoteError::Gone(row.note_tombstones_deleted));
+                    }
+                    tracing::info!(note_id = %id, "Note not found");
+                    Err(FindNoteError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find local note");
+                    Err(FindNoteError::Unknown(e.into()))
+                }
+            }
+        }
+
+        async fn update_local_note(&self, note: &LocalNote) -> Result<(), UpdateNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::insert_note_source(
+                &transaction,
+                &note.source.id,
+                &note.id,
+                Some(&note.account_id),
+                &note.source.content,
+                note.source.media_type.as_str(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert note source"))?;
+
+            let updated = queries::update_note(
+                &transaction,
+                &note.id,
+                &note.source.id,
+                &note.content,
+                note.updated.as_ref(),
+                &note.updated_at,
+                note.summary.as_deref(),
+                &note.sensitive,
+                note.note_type().as_str(),
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to update note"))?;
+            if updated == 0 {
+                return Err(UpdateNoteError::NotFound);
+            }
+
+            media_repository_impl::set_note_emojis(&transaction, &note.id, &note.emojis)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set note emojis"))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn delete_note(
+            &self,
+            id: &NoteId,
+            deleted: DateTime<Utc>,
+        ) -> Result<(), DeleteNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            let deleted_notes = queries::delete_note(&transaction, id, Some(&deleted))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete note"))?;
+            if deleted_notes == 0 {
+                return Err(DeleteNoteError::NotFound);
+            }
+
+            queries::delete_note_sources(&transaction, id, Some(&deleted))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete note sources"))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn create_remote_note(
+            &self,
+            mut req: RemoteNote,
+        ) -> Result<RemoteNote, CreateRemoteNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+
+            // remote notes keep their HTML as the source
+            queries::insert_note_source(
+                &transaction,
+                &req.source_id,
+                &req.id,
+                None,
+                &req.content,
+                SourceMediaType::Html.as_str(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert note source"))?;
+
+            let result = queries::insert_note(
+                &transaction,
+                &req.id,
+                &req.actor_id,
+                &req.source_id,
+                &req.content,
+                req.note_url.as_str(),
+                req.in_reply_to_id.as_deref(),
+                &req.published,
+                &req.created_at,
+                &req.updated_at,
+                req.summary.as_deref(),
+                &req.sensitive,
+                req.note_type.as_str(),
+                req.title.as_deref(),
+                req.url.as_ref().map(|url| url.as_str()),
+            )
+            .await;
+            match result {
+                Ok(_) => {}
+                Err(e) if is_unique_violation(&e) => {
+                    return Err(CreateRemoteNoteError::AlreadyExists);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to insert remote note");
+                    return Err(CreateRemoteNoteError::Unknown(e.into()));
+                }
+            }
+
+            for attachment in &mut req.attachments {
+                attachment.note_id = Some(req.id.clone());
+                media_repository_impl::insert_attachment(&transaction, attachment)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to insert attachment"))?;
+            }
+            media_repository_impl::set_note_emojis(&transaction, &req.id, &req.emojis)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set note emojis"))?;
+            set_poll(&transaction, &req.id, req.poll.as_ref(), true)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set poll"))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(req)
+        }
+
+        async fn find_remote_note(&self, note_url: &HttpUrl) -> Result<RemoteNote, FindNoteError> {
+            let client = self.get_client().await?;
+            let result = queries::get_remote_note_by_url(&client, note_url.as_str()).await;
+            match result {
+                Ok(Some(row)) => Ok(RemoteNote {
+                    id: row.notes_id.into(),
+                    actor_id: row.notes_actor_id.into(),
+                    content: row.notes_content,
+                    summary: row.notes_summary,
+                    sensitive: row.notes_sensitive,
+                    source_id: row.notes_source_id.into(),
+                    note_type: row.notes_object_type.parse()?,
+                    title: row.notes_name,
+                    url: row.notes_url.map(|url| url.parse()).transpose()?,
+                    note_url: row.notes_note_url.parse()?,
+                    updated: row.notes_updated,
+                    in_reply_to_id: row.notes_in_reply_to_id.map(NoteId::from),
+                    published: row.notes_published,
+                    created_at: row.notes_created_at,
+                    updated_at: row.notes_updated_at,
+                    attachments: media_repository_impl::parse_attachments(row.attachments)?,
+                    emojis: media_repository_impl::parse_emojis(row.emojis)?,
+                    poll: parse_poll(row.poll)?.0,
+                }),
+                Ok(None) => {
+                    tracing::info!(note_url = %note_url, "Note not found");
+                    Err(FindNoteError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find remote note");
+                    Err(FindNoteError::Unknown(e.into()))
+                }
+            }
+        }
+
+        async fn update_remote_note(&self, note: &RemoteNote) -> Result<(), UpdateNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::insert_note_source(
+                &transaction,
+                &note.source_id,
+                &note.id,
+                None,
+                &note.content,
+                SourceMediaType::Html.as_str(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert note source"))?;
+
+            let updated = queries::update_note(
+                &transaction,
+                &note.id,
+                &note.source_id,
+                &note.content,
+                note.updated.as_ref(),
+                &note.updated_at,
+                note.summary.as_deref(),
+                &note.sensitive,
+                note.note_type.as_str(),
+                note.title.as_deref(),
+                note.url.as_ref().map(|url| url.as_str()),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to update note"))?;
+            if updated == 0 {
+                return Err(UpdateNoteError::NotFound);
+            }
+
+            queries::delete_note_attachments(&transaction, Some(&note.id))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete attachments"))?;
+            for attachment in &note.attachments {
+                let attachment = Attachment {
+                    note_id: Some(note.id.clone()),
+                    ..attachment.clone()
+                };
+                media_repository_impl::insert_attachment(&transaction, &attachment)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to insert attachment"))?;
+            }
+            media_repository_impl::set_note_emojis(&transaction, &note.id, &note.emojis)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set note emojis"))?;
+            set_poll(&transaction, &note.id, note.poll.as_ref(), true)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set poll"))?;
+
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn set_note_hashtags(
+            &self,
+            id: &NoteId,
+            names: &[String],
+        ) -> Result<(), UpdateNoteError> {
+            let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::insert_tags(&transaction, &names)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to insert tags"))?;
+            queries::delete_note_tags(&transaction, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete note tags"))?;
+            queries::insert_note_tags(&transaction, id, &names)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to insert note tags"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn find_local_emojis(
+            &self,
+            shortcodes: &[String],
+        ) -> Result<Vec<CustomEmoji>, FindEmojiError> {
+            let client = self.get_client().await?;
+            Ok(
+                media_repository_impl::find_local_emojis(&client, shortcodes)
+                    .await
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to find custom emojis"))?,
+            )
+        }
+
+        async fn find_emoji_by_shortcode(
+            &self,
+            shortcode: &str,
+            host: Option<&str>,
+        ) -> Result<CustomEmoji, FindEmojiError> {
+            let client = self.get_client().await?;
+            media_repository_impl::find_emoji_by_shortcode(&client, shortcode, host)
+                .await
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find custom emoji"))?
+                .ok_or(FindEmojiError::NotFound)
+        }
+
+        async fn search_notes(
+            &self,
+            query: &str,
+            actor_id: Option<&ActorId>,
+            limit: u32,
+            offset: u32,
+        ) -> Result<Vec<NoteId>, SearchError> {
+            let (limit, offset) = (i64::from(limit), i64::from(offset));
+            let client = self.get_client().await?;
+            let rows =
+                queries::search_notes(&client, query, actor_id.map(|id| &**id), &limit, &offset)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to search notes"))?;
+            let mut ids = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                ids.push(NoteId::from(row.id));
+            }
+            Ok(ids)
+        }
+
+        async fn search_hashtags(
+            &self,
+            query: &str,
+            limit: u32,
+            offset: u32,
+        ) -> Result<Vec<Hashtag>, SearchError> {
+            let (limit, offset) = (i64::from(limit), i64::from(offset));
+            let client = self.get_client().await?;
+            let rows = queries::search_tags(&client, query, &limit, &offset)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to search hashtags"))?;
+            let mut hashtags = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                hashtags.push(Hashtag { name: row.name });
+            }
+            Ok(hashtags)
+        }
+
+        async fn find_note_sources(&self, id: &NoteId) -> Result<Vec<NoteSource>, FindNoteError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_note_sources(&client, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find note sources"))?;
+            let mut sources = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                sources.push(NoteSource {
+                    id: row.note_sources_id.into(),
+                    content: row.note_sources_content,
+                    media_type: parse_media_type(&row.note_sources_media_type)?,
+                });
+            }
+            Ok(sources)
+        }
+
+        async fn add_note_recipients(
+            &self,
+            id: &NoteId,
+            inboxes: &[HttpUrl],
+        ) -> Result<(), NoteRecipientsError> {
+            if inboxes.is_empty() {
+                return Ok(());
+            }
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            for inbox in inboxes {
+                queries::insert_note_recipient(&transaction, id, inbox.as_str())
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to insert recipient"))?;
+            }
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn find_note_recipients(
+            &self,
+            id: &NoteId,
+        ) -> Result<Vec<HttpUrl>, NoteRecipientsError> {
+            let client = self.get_client().await?;
+            let rows = queries::get_note_recipients(&client, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find note recipients"))?;
+            let mut inboxes = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                let inbox = row
+                    .note_recipients_inbox_url
+                    .parse()
+                    .map_err(|e: HttpUrlError| anyhow::anyhow!(e))?;
+                inboxes.push(inbox);
+            }
+            Ok(inboxes)
+        }
+
+        async fn find_note_view(
+            &self,
+            id: &NoteId,
+            viewer: Option<&AccountId>,
+        ) -> Result<NoteView, FindNoteError> {
+            let client = self.get_client().await?;
+            let result = queries::get_note_view(&client, id, viewer.map(|id| &**id)).await;
+            match result {
+                Ok(Some(row)) => row.try_into(),
+                Ok(None) => {
+                    tracing::info!(note_id = %id, "Note not found");
+                    Err(FindNoteError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find note view");
+                    Err(FindNoteError::Unknown(e.into()))
+                }
+            }
+        }
+
+        async fn find_actor_note_views(
+            &self,
+            actor_id: &ActorId,
+            viewer: Option<&AccountId>,
+            limit: u32,
+        ) -> Result<Vec<NoteView>, FindNoteError> {
+            let client = self.get_client().await?;
+            let limit = i64::from(limit);
+            let rows =
+                queries::get_actor_note_views(&client, actor_id, viewer.map(|id| &**id), &limit)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+                    .inspect_err(|e| tracing::error!(error = %e, "Failed to find actor notes"))?;
+            let mut notes = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                notes.push(row.try_into()?);
+            }
+            Ok(notes)
+        }
+
+        async fn find_note_by_url(
+            &self,
+            note_url: &HttpUrl,
+        ) -> Result<(NoteId, ActorId), FindNoteError> {
+            let client = self.get_client().await?;
+            let result = queries::get_note_by_url(&client, note_url.as_str()).await;
+            match result {
+                Ok(Some(row)) => Ok((row.notes_id.into(), row.notes_actor_id.into())),
+                Ok(None) => {
+                    tracing::info!(note_url = %note_url, "Note not found");
+                    Err(FindNoteError::NotFound)
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to find note by url");
+                    Err(FindNoteError::Unknown(e.into()))
+                }
+            }
+        }
+
+        async fn create_favourite(
+            &self,
+            favourite: &Favourite,
+        ) -> Result<bool, CreateReactionError> {
+            let client = self.get_client().await?;
+            let inserted = queries::insert_favourite(
+                &client,
+                &favourite.id,
+                &favourite.actor_id,
+                &favourite.note_id,
+                favourite.like_url.as_str(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert favourite"))?;
+            Ok(inserted > 0)
+        }
+
+        async fn delete_favourite(
+            &self,
+            actor_id: &ActorId,
+            note_id: &NoteId,
+        ) -> Result<Option<HttpUrl>, DeleteReactionError> {
+            let client = self.get_client().await?;
+            let row = queries::delete_favourite(&client, actor_id, note_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete favourite"))?;
+            let like_url = row
+                .map(|row| parse_reaction_url(row.favourites_like_url))
+                .transpose()?;
+            Ok(like_url)
+        }
+
+        async fn delete_favourite_by_url(
+            &self,
+            actor_id: &ActorId,
+            like_url: &HttpUrl,
+        ) -> Result<(), DeleteReactionError> {
+            let client = self.get_client().await?;
+            queries::delete_favourite_by_url(&client, actor_id, like_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete favourite"))?;
+            Ok(())
+        }
+
+        async fn create_emoji_reaction(
+            &self,
+            reaction: &EmojiReaction,
+        ) -> Result<bool, CreateReactionError> {
+            let mut client = self.get_client().await?;
+            let result = async {
+                let tx = client.transaction().await?;
+                let emoji_id = match &reaction.emoji {
+                    Some(emoji) => {
+                        media_repository_impl::store_emojis(&tx, std::slice::from_ref(emoji))
+                            .await?
+                            .pop()
+                    }
+                    None => None,
+                };
+                let inserted = queries::insert_emoji_reaction(
+                    &tx,
+                    &reaction.id,
+                    &reaction.actor_id,
+                    &reaction.note_id,
+                    reaction.reaction_url.as_str(),
+                    &reaction.content,
+                    emoji_id.as_ref(),
+                )
+                .await?;
+                tx.commit().await?;
+                Ok::<_, tokio_postgres::Error>(inserted)
+            }
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert emoji reaction"))?;
+            Ok(result > 0)
+        }
+
+        async fn delete_emoji_reaction(
+            &self,
+            actor_id: &ActorId,
+            note_id: &NoteId,
+            content: &str,
+        ) -> Result<Option<HttpUrl>, DeleteReactionError> {
+            let client = self.get_client().await?;
+            let row = queries::delete_emoji_reaction(&client, actor_id, note_id, content)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete emoji reaction"))?;
+            let reaction_url = row
+                .map(|row| parse_reaction_url(row.emoji_reactions_reaction_url))
+                .transpose()?;
+            Ok(reaction_url)
+        }
+
+        async fn delete_emoji_reaction_by_url(
+            &self,
+            actor_id: &ActorId,
+            reaction_url: &HttpUrl,
+        ) -> Result<(), DeleteReactionError> {
+            let client = self.get_client().await?;
+            queries::delete_emoji_reaction_by_url(&client, actor_id, reaction_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete emoji reaction"))?;
+            Ok(())
+        }
+
+        async fn update_poll(&self, note_id: &NoteId, poll: &Poll) -> Result<(), UpdateNoteError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            set_poll(&transaction, note_id, Some(poll), true)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to set poll"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn create_poll_votes(
+            &self,
+            votes: &[PollVote],
+            once: bool,
+        ) -> Result<u64, CreateVoteError> {
+            let Some(first) = votes.first() else {
+                return Ok(0);
+            };
+            let mut client = self.get_client().await?;
+            let result = async {
+                let transaction = client.transaction().await?;
+                if queries::lock_poll(&transaction, &first.note_id)
+                    .await?
+                    .is_none()
+                {
+                    return Ok(Some(0));
+                }
+                let voted =
+                    queries::get_poll_choices(&transaction, &first.note_id, &first.actor_id)
+                        .await?
+                        .next()
+                        .is_some();
+                if once && voted {
+                    return Ok(None);
+                }
+                let mut choices = Vec::new();
+                for vote in votes {
+                    let choice = i32::try_from(vote.choice).unwrap_or(i32::MAX);
+                    let inserted = queries::insert_poll_vote(
+                        &transaction,
+                        &vote.id,
+                        &vote.note_id,
+                        &vote.actor_id,
+                        &choice,
+                        vote.vote_url.as_str(),
+                    )
+                    .await?;
+                    if inserted > 0 {
+                        choices.push(choice);
+                    }
+                }
+                if !choices.is_empty() {
+                    queries::increment_poll_votes(&transaction, &first.note_id, &choices).await?;
+                    if !voted {
+                        queries::increment_poll_voters(&transaction, &first.note_id).await?;
+                    }
+                }
+                transaction.commit().await?;
+                Ok::<_, tokio_postgres::Error>(Some(choices.len() as u64))
+            }
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert poll votes"))?;
+            result.ok_or(CreateVoteError::AlreadyVoted)
+        }
+
+        async fn find_poll_voter_inboxes(
+            &self,
+            note_id: &NoteId,
+        ) -> Result<Vec<HttpUrl>, FindVoteError> {
+            let client = self.get_client().await?;
+            let inboxes = queries::get_poll_voter_inboxes(&client, note_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find voter inboxes"))?
+                .map(|row| Ok(row?.inbox_url.parse()?))
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            Ok(inboxes)
+        }
+
+        async fn close_expired_polls(
+            &self,
+            now: DateTime<Utc>,
+        ) -> Result<Vec<NoteId>, ClosePollError> {
+            let client = self.get_client().await?;
+            let note_ids = queries::close_expired_polls(&client, &now)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to close expired polls"))?
+                .map(|row| Ok(NoteId::from(row?.polls_note_id)))
+                .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            Ok(note_ids)
+        }
+
+        async fn create_reblog(&self, reblog: &Reblog) -> Result<bool, CreateReactionError> {
+            let client = self.get_client().await?;
+            let inserted = queries::insert_reblog(
+                &client,
+                &reblog.id,
+                &reblog.actor_id,
+                &reblog.note_id,
+                reblog.announce_url.as_str(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert reblog"))?;
+            Ok(inserted > 0)
+        }
+
+        async fn find_reblog(
+            &self,
+            actor_id: &ActorId,
+            note_id: &NoteId,
+        ) -> Result<Option<Reblog>, FindReactionError> {
+            let client = self.get_client().await?;
+            let row = queries::get_reblog(&client, actor_id, note_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find reblog"))?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            Ok(Some(Reblog {
+                id: row.reblogs_id.into(),
+                actor_id: actor_id.clone(),
+                note_id: note_id.clone(),
+                announce_url: parse_reaction_url(row.reblogs_announce_url)?,
+            }))
+        }
+
+        async fn delete_reblog(
+            &self,
+            actor_id: &ActorId,
+            note_id: &NoteId,
+        ) -> Result<Option<Reblog>, DeleteReactionError> {
+            let client = self.get_client().await?;
+            let row = queries::delete_reblog(&client, actor_id, note_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete reblog"))?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            Ok(Some(Reblog {
+                id: row.reblogs_id.into(),
+                actor_id: actor_id.clone(),
+                note_id: note_id.clone(),
+                announce_url: parse_reaction_url(row.reblogs_announce_url)?,
+            }))
+        }
+
+        async fn delete_reblog_by_url(
+            &self,
+            actor_id: &ActorId,
+            announce_url: &HttpUrl,
+        ) -> Result<Option<ReblogId>, DeleteReactionError> {
+            let client = self.get_client().await?;
+            let row = queries::delete_reblog_by_url(&client, actor_id, announce_url.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete reblog"))?;
+            Ok(row.map(|row| row.reblogs_id.into()))
+        }
+
+        async fn find_home_timeline(
+            &self,
+            req: &HomeTimelineRequest,
+        ) -> Result<Vec<TimelineItem>, FindTimelineError> {
+            let client = self.get_client().await?;
+            let page = &req.page;
+            let limit = i64::from(page.limit);
+            let ascending = page.ascending();
+            let rows = queries::get_home_timeline(
+                &client,
+                &req.account_id,
+                page.max_id.as_deref(),
+                page.lower_bound().map(|id| &**id),
+                &ascending,
+                &limit,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to find home timeline"))?;
+
+            let mut items = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                let reblog = row.reblog_actor_id.zip(row.reblog_announce_url.clone());
+                let entry = (row.home_feed_entry_id, reblog);
+                items.push((
+                    entry,
+                    NoteView::try_from(row).map_err(|e| anyhow::anyhow!(e))?,
+                ));
+            }
+            let mut items = self.home_feed_items(items).await?;
+            if ascending {
+                items.reverse();
+            }
+            Ok(items)
+        }
+
+        async fn find_public_timeline(
+            &self,
+            req: &PublicTimelineRequest,
+        ) -> Result<Vec<TimelineItem>, FindTimelineError> {
+            let client = self.get_client().await?;
+            let page = &req.page;
+            let limit = i64::from(page.limit);
+            let ascending = page.ascending();
+            let rows = queries::get_public_timeline(
+                &client,
+                &req.local,
+                req.viewer.as_deref(),
+                &req.remote,
+                page.max_id.as_deref(),
+                page.lower_bound().map(|id| &**id),
+                &ascending,
+                &limit,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to find public timeline"))?;
+
+            let mut items = Vec::new();
+            for row in rows {
+                let row = row.map_err(|e| anyhow::anyhow!(e))?;
+                let note = NoteView::try_from(row).map_err(|e| anyhow::anyhow!(e))?;
+                items.push(TimelineItem::Note(Box::new(note)));
+            }
+            if ascending {
+                items.reverse();
+            }
+            Ok(items)
+        }
+
+        async fn find_home_feed_entry(
+            &self,
+            account_id: &AccountId,
+            entry_id: &TimelineItemId,
+        ) -> Result<Option<TimelineItem>, FindTimelineError> {
+            let client = self.get_client().await?;
+            let row = queries::get_home_feed_entry(&client, account_id, entry_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find home feed entry"))?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            let reblog = row.reblog_actor_id.zip(row.reblog_announce_url.clone());
+            let entry = (row.home_feed_entry_id, reblog);
+            let note = NoteView::try_from(row).map_err(|e| anyhow::anyhow!(e))?;
+            Ok(self.home_feed_items(vec![(entry, note)]).await?.pop())
+        }
+
+        async fn fan_out_note(
+            &self,
+            note_id: &NoteId,
+            author_id: &ActorId,
+            keep: u32,
+        ) -> Result<(), UpdateHomeFeedError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::fan_out_note(&transaction, note_id, author_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to fan out note"))?;
+            queries::trim_home_feeds(&transaction, author_id, &i64::from(keep))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to trim home feeds"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn fan_out_reblog(
+            &self,
+            reblog: &Reblog,
+            keep: u32,
+        ) -> Result<(), UpdateHomeFeedError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::fan_out_reblog(&transaction, &reblog.id, &reblog.note_id, &reblog.actor_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to fan out reblog"))?;
+            queries::trim_home_feeds(&transaction, &reblog.actor_id, &i64::from(keep))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to trim home feeds"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn merge_home_feed(
+            &self,
+            follower_id: &ActorId,
+            followee_id: &ActorId,
+            keep: u32,
+        ) -> Result<(), UpdateHomeFeedError> {
+            let keep = i64::from(keep);
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::merge_home_feed(&transaction, follower_id, followee_id, &keep)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to merge home feed"))?;
+            queries::trim_home_feed(&transaction, follower_id, &keep)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to trim home feed"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+
+        async fn unmerge_home_feed(
+            &self,
+            follower_id: &ActorId,
+            followee_id: &ActorId,
+        ) -> Result<(), UpdateHomeFeedError> {
+            let client = self.get_client().await?;
+            queries::unmerge_home_feed(&client, follower_id, followee_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to unmerge home feed"))?;
+            Ok(())
+        }
+
+        async fn rebuild_home_feed(
+            &self,
+            account_id: &AccountId,
+            keep: u32,
+        ) -> Result<(), UpdateHomeFeedError> {
+            let mut client = self.get_client().await?;
+            let transaction = get_transaction(&mut client).await?;
+            queries::clear_home_feed(&transaction, account_id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to clear home feed"))?;
+            queries::rebuild_home_feed(&transaction, Some(account_id), &i64::from(keep))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to rebuild home feed"))?;
+            transaction
+                .commit()
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to commit transaction"))?;
+            Ok(())
+        }
+    }
+}
+
+mod media_repository_impl {
+    use super::*;
+    use crate::domain::{
+        HttpUrl,
+        media::{
+            adapter::MediaRepository,
+            model::{
+                Attachment, AttachmentId, CreateAttachmentError, CreateEmojiError, CustomEmoji,
+                CustomEmojiId, DeleteEmojiError, FindAttachmentError, FindEmojiError, Focus,
+                MediaCacheError, Preview, ProxyMediaError, RemoteMedia, RemoteMediaId,
+            },
+        },
+    };
+    use chrono::{DateTime, Utc};
+
+    /// Columns of `attachments`, as selected by the attachment queries or aggregated to JSON
+    #[derive(serde::Deserialize)]
+    struct AttachmentRow {
+        id: uuid::Uuid,
+        account_id: Option<uuid::Uuid>,
+        note_id: Option<uuid::Uuid>,
+        #[serde(rename = "type")]
+        kind: String,
+        media_type: String,
+        url: String,
+        storage_key: Option<String>,
+        description: Option<String>,
+        created_at: DateTime<Utc>,
+        width: Option<i32>,
+        height: Option<i32>,
+        blurhash: Option<String>,
+        focus_x: Option<f32>,
+        focus_y: Option<f32>,
+        preview_url: Option<String>,
+        preview_storage_key: Option<String>,
+        preview_width: Option<i32>,
+        preview_height: Option<i32>,
+    }
+
+    macro_rules! impl_attachment_row_from {
+        ($($row:ty),*) => {$(
+            impl From<$row> for AttachmentRow {
+                fn from(row: $row) -> Self {
+                    Self {
+                        id: row.attachments_id,
+                        account_id: row.attachments_account_id,
+                        note_id: row.attachments_note_id,
+                        kind: row.attachments_type,
+                        media_type: row.attachments_media_type,
+                        url: row.attachments_url,
+                        storage_key: row.attachments_storage_key,
+                        description: row.attachments_description,
+                        created_at: row.attachments_created_at,
+                        width: row.attachments_width,
+                        height: row.attachments_height,
+                        blurhash: row.attachments_blurhash,
+                        focus_x: row.attachments_focus_x,
+                        focus_y: row.attachments_focus_y,
+                        preview_url: row.attachments_preview_url,
+                        preview_storage_key: row.attachments_preview_storage_key,
+                        preview_width: row.attachments_preview_width,
+                        preview_height: row.attachments_preview_height,
+                    }
+                }
+            }
+        )*};
+    }
+    impl_attachment_row_from!(
+        queries::GetAttachmentRow,
+        queries::GetAttachmentByStorageKeyRow,
+        queries::AttachNoteMediaRow
+    );
+
+    impl TryFrom<AttachmentRow> for Attachment {
+        type Error = anyhow::Error;
+
+        fn try_from(row: AttachmentRow) -> Result<Self, Self::Error> {
+            let size = |width: Option<i32>, height: Option<i32>| {
+                width
+                    .zip(height)
+                    .and_then(|(w, h)| Some((u32::try_from(w).ok()?, u32::try_from(h).ok()?)))
+            };
+            let preview = match (row.preview_url, row.preview_storage_key) {
+                (Some(url), Some(storage_key)) => Some(Preview {
+                    url: url.parse()?,
+                    storage_key,
+                    size: size(row.preview_width, row.preview_height).unwrap_or_default(),
+                }),
+                _ => None,
+            };
+            Ok(Attachment {
+                id: row.id.into(),
+                account_id: row.account_id.map(Into::into),
+                note_id: row.note_id.map(Into::into),
+                kind: row.kind.parse()?,
+                media_type: row.media_type,
+                url: row.url.parse::<HttpUrl>()?,
+                storage_key: row.storage_key,
+                description: row.description,
+                size: size(row.width, row.height),
+                blurhash: row.blurhash,
+                focus: row.focus_x.zip(row.focus_y).map(|(x, y)| Focus { x, y }),
+                preview,
+                created_at: row.created_at,
+            })
+        }
+    }
+
+    /// Parse the `attachments` column of note queries, a JSON array of attachment rows
+    pub(super) fn parse_attachments(
+        value: serde_json::Value,
+    ) -> Result<Vec<Attachment>, anyhow::Error> {
+        serde_json::from_value::<Vec<AttachmentRow>>(value)?
+            .into_iter()
+            .map(Attachment::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .inspect_err(
+                |e| tracing::error!(error = %e, "expected database attachment to be valid"),
+            )
+    }
+
+    /// Columns of `custom_emojis`, as selected by the emoji queries or aggregated to JSON
+    #[derive(serde::Deserialize)]
+    pub(super) struct CustomEmojiRow {
+        id: uuid::Uuid,
+        shortcode: String,
+        host: Option<String>,
+        uri: String,
+        url: String,
+        media_type: Option<String>,
+        storage_key: Option<String>,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+    }
+
+    macro_rules! impl_custom_emoji_row_from {
+        ($($row:ty),*) => {$(
+            impl From<$row> for CustomEmojiRow {
+                fn from(row: $row) -> Self {
+                    Self {
+                        id: row.custom_emojis_id,
+                        shortcode: row.custom_emojis_shortcode,
+                        host: row.custom_emojis_host,
+                        uri: row.custom_emojis_uri,
+                        url: row.custom_emojis_url,
+                        media_type: row.custom_emojis_media_type,
+                        storage_key: row.custom_emojis_storage_key,
+                        created_at: row.custom_emojis_created_at,
+                        updated_at: row.custom_emojis_updated_at,
+                    }
+                }
+            }
+        )*};
+    }
+    impl_custom_emoji_row_from!(
+        queries::GetLocalCustomEmojisRow,
+        queries::GetCustomEmojiRow,
+        queries::GetLocalCustomEmojisByShortcodeRow,
+        queries::GetCustomEmojiByShortcodeRow
+    );
+
+    impl TryFrom<CustomEmojiRow> for CustomEmoji {
+        type Error = anyhow::Error;
+
+        fn try_from(row: CustomEmojiRow) -> Result<Self, Self::Error> {
+            Ok(CustomEmoji {
+                id: row.id.into(),
+                shortcode: row.shortcode,
+                host: row.host,
+                uri: row.uri.parse()?,
+                url: row.url.parse()?,
+                media_type: row.media_type,
+                storage_key: row.storage_key,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })
+        }
+    }
+
+    /// Parse the `emojis` column of note and actor queries, a JSON array of emoji rows
+    pub(super) fn parse_emojis(
+        value: serde_json::Value,
+    ) -> Result<Vec<CustomEmoji>, anyhow::Error> {
+        serde_json::from_value::<Vec<CustomEmojiRow>>(value)?
+            .into_iter()
+            .map(CustomEmoji::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .inspect_err(|e| tracing::error!(error = %e, "expected database emoji to be valid"))
+    }
+
+    /// Emoji named `shortcode` of `host`, of this instance if `None`
+    pub(super) async fn find_emoji_by_shortcode(
+        client: &impl deadpool_postgres::GenericClient,
+        shortcode: &str,
+        host: Option<&str>,
+    ) -> Result<Option<CustomEmoji>, anyhow::Error> {
+        queries::get_custom_emoji_by_shortcode(client, shortcode, host)
+            .await?
+            .map(|row| CustomEmoji::try_from(CustomEmojiRow::from(row)))
+            .transpose()
+    }
+
+    /// Emoji of this instance named by `shortcodes`
+    pub(super) async fn find_local_emojis(
+        client: &impl deadpool_postgres::GenericClient,
+        shortcodes: &[String],
+    ) -> Result<Vec<CustomEmoji>, anyhow::Error> {
+        queries::get_local_custom_emojis_by_shortcode(client, shortcodes)
+            .await?
+            .map(|row| CustomEmoji::try_from(CustomEmojiRow::from(row?)))
+            .collect()
+    }
+
+    /// Ids the emoji are stored under. Remote ones are stored or refreshed, and their images
+    /// registered with the media proxy
+    pub(super) async fn store_emojis(
+        client: &impl deadpool_postgres::GenericClient,
+        emojis: &[CustomEmoji],
+    ) -> Result<Vec<uuid::Uuid>, tokio_postgres::Error> {
+        let mut ids = Vec::new();
+        for emoji in emojis {
+            if emoji.host.is_none() {
+                ids.push(*emoji.id);
+                continue;
+            }
+            register_remote_media(client, &emoji.url).await?;
+            let row = queries::upsert_remote_custom_emoji(
+                client,
+                &emoji.id,
+                &emoji.shortcode,
+                emoji.host.as_deref(),
+                emoji.uri.as_str(),
+                emoji.url.as_str(),
+                emoji.media_type.as_deref(),
+                &emoji.updated_at,
+            )
+            .await?;
+            if let Some(row) = row {
+                ids.push(row.custom_emojis_id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Replace the emoji of the note
+    pub(super) async fn set_note_emojis(
+        client: &impl deadpool_postgres::GenericClient,
+        note_id: &uuid::Uuid,
+        emojis: &[CustomEmoji],
+    ) -> Result<(), tokio_postgres::Error> {
+        queries::delete_note_emojis(client, note_id).await?;
+        if !emojis.is_empty() {
+            let ids = store_emojis(client, emojis).await?;
+            queries::insert_note_emojis(client, note_id, &ids).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the emoji of the actor
+    pub(super) async fn set_actor_emojis(
+        client: &impl deadpool_postgres::GenericClient,
+        actor_id: &uuid::Uuid,
+        emojis: &[CustomEmoji],
+    ) -> Result<(), tokio_postgres::Error> {
+        queries::delete_actor_emojis(client, actor_id).await?;
+        if !emojis.is_empty() {
+            let ids = store_emojis(client, emojis).await?;
+            queries::insert_actor_emojis(client, actor_id, &ids).await?;
+        }
+        Ok(())
+    }
+
+    /// Let the media proxy serve the remote file at `url`
+    pub(super) async fn register_remote_media(
+        client: &impl deadpool_postgres::GenericClient,
+        url: &HttpUrl,
+    ) -> Result<u64, tokio_postgres::Error> {
+        queries::insert_remote_media(client, RemoteMediaId::of(url).as_str(), url.as_str()).await
+    }
+
+    /// Insert the attachment, registering the files of remote ones with the media proxy
+    pub(super) async fn insert_attachment(
+        client: &impl deadpool_postgres::GenericClient,
+        attachment: &Attachment,
+    ) -> Result<u64, tokio_postgres::Error> {
+        if attachment.storage_key.is_none() {
+            register_remote_media(client, &attachment.url).await?;
+        }
+        // sizes fit, as images larger than `i32::MAX` pixels wide are not decoded
+        let to_i32 = |size: (u32, u32)| (size.0 as i32, size.1 as i32);
+        let (width, height) = attachment.size.map(to_i32).unzip();
+        let preview = attachment.preview.as_ref();
+        let (preview_width, preview_height) = preview.map(|preview| to_i32(preview.size)).unzip();
+        queries::insert_attachment(
+            client,
+            &attachment.id,
+            attachment.account_id.as_deref(),
+            attachment.note_id.as_deref(),
+            attachment.kind.as_ref(),
+            &attachment.media_type,
+            attachment.url.as_str(),
+            attachment.storage_key.as_deref(),
+            attachment.description.as_deref(),
+            &attachment.created_at,
+            width.as_ref(),
+            height.as_ref(),
+            attachment.blurhash.as_deref(),
+            attachment.focus.as_ref().map(|focus| &focus.x),
+            attachment.focus.as_ref().map(|focus| &focus.y),
+            preview.map(|preview| preview.url.as_str()),
+            preview.map(|preview| preview.storage_key.as_str()),
+            preview_width.as_ref(),
+            preview_height.as_ref(),
+        )
+        .await
+    }
+
+    macro_rules! impl_remote_media_from {
+        ($($row:ty),*) => {$(
+            impl TryFrom<$row> for RemoteMedia {
+                type Error = anyhow::Error;
+
+                fn try_from(row: $row) -> Result<Self, Self::Error> {
+                    Ok(RemoteMedia {
+                        id: row.remote_media_id.parse()?,
+                        url: row.remote_media_url.parse()?,
+                        media_type: row.remote_media_media_type,
+                        storage_key: row.remote_media_storage_key,
+                    })
+                }
+            }
+        )*};
+    }
+    impl_remote_media_from!(
+        queries::GetRemoteMediaRow,
+        queries::GetEvictableRemoteMediaRow
+    );
+
+    /// Attach uploads of `account_id` to the note, returning them in the order of `ids`
+    pub(super) async fn attach_note_media(
+        client: &impl deadpool_postgres::GenericClient,
+        note_id: &uuid::Uuid,
+        account_id: &uuid::Uuid,
+        ids: &[AttachmentId],
+    ) -> Result<Vec<Attachment>, anyhow::Error> {
+        let ids = ids.iter().map(|id| **id).collect::<Vec<_>>();
+        let mut attachments =
+            queries::attach_note_media(client, Some(note_id), &ids, Some(account_id))
+                .await?
+                .map(|row| Attachment::try_from(AttachmentRow::from(row?)))
+                .collect::<Result<Vec<_>, _>>()?;
+        attachments.sort_by_key(|attachment| ids.iter().position(|id| *id == *attachment.id));
+        Ok(attachments)
+    }
+
+    #[async_trait::async_trait]
+    impl MediaRepository for Postgres {
+        async fn create_attachment(
+            &self,
+            attachment: &Attachment,
+        ) -> Result<(), CreateAttachmentError> {
+            let client = self.get_client().await?;
+            insert_attachment(&client, attachment)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to insert attachment"))?;
+            Ok(())
+        }
+
+        async fn find_attachment(
+            &self,
+            id: &AttachmentId,
+        ) -> Result<Attachment, FindAttachmentError> {
+            let client = self.get_client().await?;
+            let row = queries::get_attachment(&client, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find attachment"))?
+                .ok_or(FindAttachmentError::NotFound)?;
+            Ok(Attachment::try_from(AttachmentRow::from(row))?)
+        }
+
+        async fn find_attachment_by_storage_key(
+            &self,
+            key: &str,
+        ) -> Result<Attachment, FindAttachmentError> {
+            let client = self.get_client().await?;
+            let row = queries::get_attachment_by_storage_key(&client, Some(key))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find attachment"))?
+                .ok_or(FindAttachmentError::NotFound)?;
+            Ok(Attachment::try_from(AttachmentRow::from(row))?)
+        }
+
+        async fn is_stored_image(&self, key: &str) -> Result<bool, FindAttachmentError> {
+            let client = self.get_client().await?;
+            let row = queries::get_stored_image(&client, Some(key))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find stored image"))?;
+            Ok(row.is_some())
+        }
+
+        async fn create_emoji(&self, emoji: &CustomEmoji) -> Result<(), CreateEmojiError> {
+            let client = self.get_client().await?;
+            let inserted = queries::insert_custom_emoji(
+                &client,
+                &emoji.id,
+                &emoji.shortcode,
+                emoji.uri.as_str(),
+                emoji.url.as_str(),
+                emoji.media_type.as_deref(),
+                emoji.storage_key.as_deref(),
+                &emoji.created_at,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to insert custom emoji"))?;
+            if inserted == 0 {
+                return Err(CreateEmojiError::AlreadyExists);
+            }
+            Ok(())
+        }
+
+        async fn find_emoji(&self, id: &CustomEmojiId) -> Result<CustomEmoji, FindEmojiError> {
+            let client = self.get_client().await?;
+            let row = queries::get_custom_emoji(&client, id)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find custom emoji"))?
+                .ok_or(FindEmojiError::NotFound)?;
+            Ok(CustomEmoji::try_from(CustomEmojiRow::from(row))?)
+        }
+
+        async fn find_local_emojis(&self) -> Result<Vec<CustomEmoji>, FindEmojiError> {
+            let client = self.get_client().await?;
+            let emojis = queries::get_local_custom_emojis(&client)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find custom emojis"))?
+                .map(|row| CustomEmoji::try_from(CustomEmojiRow::from(row?)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(emojis)
+        }
+
+        async fn delete_local_emoji(
+            &self,
+            shortcode: &str,
+        ) -> Result<Option<String>, DeleteEmojiError> {
+            let client = self.get_client().await?;
+            let row = queries::delete_local_custom_emoji(&client, shortcode)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to delete custom emoji"))?
+                .ok_or(DeleteEmojiError::NotFound)?;
+            Ok(row.custom_emojis_storage_key)
+        }
+
+        async fn find_remote_media(
+            &self,
+            id: &RemoteMediaId,
+        ) -> Result<RemoteMedia, ProxyMediaError> {
+            let client = self.get_client().await?;
+            let row = queries::get_remote_media(&client, id.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to find remote media"))?
+                .ok_or(ProxyMediaError::NotFound)?;
+            Ok(RemoteMedia::try_from(row)?)
+        }
+
+        async fn set_remote_media_cached(
+            &self,
+            id: &RemoteMediaId,
+            media_type: &str,
+            storage_key: &str,
+            size: u64,
+        ) -> Result<(), MediaCacheError> {
+            let client = self.get_client().await?;
+            let size = i64::try_from(size).map_err(|e| anyhow::anyhow!(e))?;
+            queries::set_remote_media_cached(
+                &client,
+                id.as_str(),
+                Some(media_type),
+                Some(storage_key),
+                Some(&size),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to set remote media cached"))?;
+            Ok(())
+        }
+
+        async fn touch_remote_media(&self, id: &RemoteMediaId) -> Result<(), MediaCacheError> {
+            let client = self.get_client().await?;
+            queries::touch_remote_media(&client, id.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+                .inspect_err(|e| tracing::error!(error = %e, "Failed to touch remote media"))?;
+            Ok(())
+        }
+
+        async fn find_evictable_remote_media(
+            &self,
+            accessed_before: &DateTime<Utc>,
+            max_size: u64,
+            limit: u32,
+        ) -> Result<Vec<RemoteMedia>, MediaCacheError> {
+            let client = self.get_client().await?;
+            let max_size = i64::try_from(max_size).unwrap_or(i64::MAX);
+            let limit = i64::from(limit);
+            let media = queries::get_evictable_remote_media(
+                &client,
+                Some(accessed_before),
+                &max_size,
+                &limit,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to find evictable remote media"))?
+            .map(|row| RemoteMedia::try_from(row?))
+            .collect::<Result<Vec<_>, _>>()?;
+            Ok(media)
+        }
+
+        async fn unset_remote_media_cached(
+            &self,
+            media: &RemoteMedia,
+        ) -> Result<(), MediaCacheError> {
+            let client = self.get_client().await?;
+            queries::unset_remote_media_cached(
+                &client,
+                media.id.as_str(),
+                media.storage_key.as_deref(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .inspect_err(|e| tracing::error!(error = %e, "Failed to unset remote media cached"))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        ap::ActorType,
+        domain::{
+            HttpUrl,
+            ap::{
+                adapter::{ActorRepository, NoteRepository},
+                model::{
+                    ActorId, ActorRow,
+                    note::{NoteId, NoteSourceId, NoteType, RemoteNote},
+                    poll::{CreateVoteError, Poll, PollOption, PollVote, PollVoteId},
+                },
+            },
+        },
+    };
+
+    use super::*;
+
+    async fn create_actor(pg: &Postgres, name: &str) -> ActorId {
+        let now = Utc::now();
+        let actor_url: HttpUrl = format!("https://remote.test/{name}-{}", ActorId::new())
+            .parse()
+            .unwrap();
+        let row = ActorRow {
+            id: ActorId::new(),
+            actor_type: ActorType::Person,
+            name: name.to_string(),
+            inbox_url: format!("{actor_url}/inbox").parse().unwrap(),
+            outbox_url: format!("{actor_url}/outbox").parse().unwrap(),
+            actor_url,
+            shared_inbox_url: None,
+            summary: None,
+            public_key: None,
+            account_id: None,
+            published: None,
+            created_at: now,
+            updated_at: now,
+            display_name: None,
+            summary_source: None,
+            avatar: None,
+            header: None,
+            fields: Vec::new(),
+            emojis: Vec::new(),
+        };
+        pg.upsert_actor(row).await.unwrap().id
+    }
+
+    fn vote(note: &RemoteNote, actor_id: &ActorId, choice: u32) -> PollVote {
+        let id = PollVoteId::new();
+        PollVote {
+            note_id: note.id.clone(),
+            actor_id: actor_id.clone(),
+            choice,
+            vote_url: format!("{}/votes/{id}", note.note_url).parse().unwrap(),
+            id,
+        }
+    }
+
+    async fn counts(pg: &Postgres, note: &RemoteNote) -> (Vec<u64>, u64) {
+        let note = pg.find_remote_note(&note.note_url).await.unwrap();
+        let poll = note.poll.unwrap();
+        let votes = poll.options.iter().map(|option| option.votes_count);
+        (votes.collect(), poll.voters_count)
+    }
+
+    /// Run against a migrated database with
+    /// `DATABASE_HOST=… DATABASE_PORT=… DATABASE_USER=… DATABASE_PASSWORD=… DATABASE_NAME=…
+    /// cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL database"]
+    async fn test_create_poll_votes() {
+        let pg = Postgres::from_env().await.unwrap();
+        let author_id = create_actor(&pg, "author").await;
+        let voter_id = create_actor(&pg, "voter").await;
+        let now = Utc::now();
+        let note_id = NoteId::new();
+        let note = RemoteNote {
+            note_url: format!("https://remote.test/notes/{note_id}")
+                .parse()
+                .unwrap(),
+            id: note_id,
+            actor_id: author_id,
+            content: "<p>a or b?</p>".to_string(),
+            summary: None,
+            sensitive: false,
+            source_id: NoteSourceId::new(),
+            note_type: NoteType::Question,
+            title: None,
+            url: None,
+            updated: None,
+            in_reply_to_id: None,
+            published: now,
+            created_at: now,
+            updated_at: now,
+            attachments: Vec::new(),
+            emojis: Vec::new(),
+            poll: Some(Poll {
+                options: ["a", "b"]
+                    .into_iter()
+                    .map(|title| PollOption {
+                        title: title.to_string(),
+                        votes_count: 0,
+                    })
+                    .collect(),
+                multiple: false,
+                expires_at: None,
+                closed_at: None,
+                voters_count: 0,
+            }),
+        };
+        let note = pg.create_remote_note(note).await.unwrap();
+
+        let first = vote(&note, &voter_id, 0);
+        assert_eq!(
+            pg.create_poll_votes(std::slice::from_ref(&first), true)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(counts(&pg, &note).await, (vec![1, 0], 1));
+
+        // a voter who already voted is refused once
+        let result = pg
+            .create_poll_votes(&[vote(&note, &voter_id, 1)], true)
+            .await;
+        assert!(matches!(result, Err(CreateVoteError::AlreadyVoted)));
+        assert_eq!(counts(&pg, &note).await, (vec![1, 0], 1));
+
+        // and can add options otherwise, still as one voter
+        let second = vote(&note, &voter_id, 1);
+        assert_eq!(pg.create_poll_votes(&[second], false).await.unwrap(), 1);
+        assert_eq!(counts(&pg, &note).await, (vec![1, 1], 1));
+
+        // an option is counted once per voter, and a vote once however often it is received
+        let repeated = vote(&note, &voter_id, 0);
+        assert_eq!(pg.create_poll_votes(&[repeated], false).await.unwrap(), 0);
+        let redelivered = PollVote {
+            id: PollVoteId::new(),
+            ..first
+        };
+        assert_eq!(
+            pg.create_poll_votes(&[redelivered], false).await.unwrap(),
+            0
+        );
+        assert_eq!(counts(&pg, &note).await, (vec![1, 1], 1));
     }
 }
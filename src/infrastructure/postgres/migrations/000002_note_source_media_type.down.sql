@@ -0,0 +1,2 @@
+ALTER TABLE note_sources
+DROP COLUMN IF EXISTS media_type;
@@ -0,0 +1,3 @@
+-- Media type of the text the note was rendered from
+ALTER TABLE note_sources
+ADD COLUMN media_type TEXT NOT NULL DEFAULT 'text/plain';
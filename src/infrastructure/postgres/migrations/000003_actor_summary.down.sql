@@ -0,0 +1,2 @@
+ALTER TABLE actors
+DROP COLUMN IF EXISTS summary;
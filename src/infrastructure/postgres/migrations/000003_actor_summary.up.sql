@@ -0,0 +1,3 @@
+-- Actor bio in HTML
+ALTER TABLE actors
+ADD COLUMN summary TEXT;
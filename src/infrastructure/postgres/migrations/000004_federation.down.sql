@@ -0,0 +1,12 @@
+DROP TABLE IF EXISTS note_recipients;
+
+DROP INDEX IF EXISTS notes_note_url_key;
+
+DROP TABLE IF EXISTS follows;
+
+DROP INDEX IF EXISTS actors_public_key_id_key;
+DROP INDEX IF EXISTS actors_actor_url_key;
+
+ALTER TABLE actors
+DROP COLUMN IF EXISTS public_key_pem,
+DROP COLUMN IF EXISTS public_key_id;
@@ -0,0 +1,42 @@
+-- Public key of remote actors. Local actor keys live in account_keys
+ALTER TABLE actors
+ADD COLUMN public_key_id TEXT,
+ADD COLUMN public_key_pem TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS actors_actor_url_key ON actors (actor_url);
+CREATE UNIQUE INDEX IF NOT EXISTS actors_public_key_id_key ON actors (
+    public_key_id
+);
+
+-- follower follows followee
+CREATE TABLE IF NOT EXISTS follows (
+    id UUID PRIMARY KEY,
+    follower_id UUID NOT NULL,
+    followee_id UUID NOT NULL,
+    -- ActivityPub Follow activity id
+    follow_url TEXT NOT NULL,
+
+    FOREIGN KEY (follower_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    FOREIGN KEY (followee_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    CONSTRAINT unique_follow UNIQUE (follower_id, followee_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS notes_note_url_key ON notes (note_url);
+
+-- Inboxes a local note was delivered to
+CREATE TABLE IF NOT EXISTS note_recipients (
+    note_id UUID NOT NULL,
+    inbox_url TEXT NOT NULL,
+
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    PRIMARY KEY (note_id, inbox_url)
+);
@@ -0,0 +1,20 @@
+ALTER TABLE notes
+DROP COLUMN IF EXISTS updated,
+ALTER COLUMN source_id DROP NOT NULL;
+
+-- keep only the current revision of local notes
+UPDATE notes
+SET source_id = NULL
+FROM note_sources
+WHERE
+    notes.source_id = note_sources.id
+    AND note_sources.account_id IS NULL;
+
+DELETE FROM note_sources
+WHERE
+    account_id IS NULL
+    OR id NOT IN (SELECT notes.source_id FROM notes WHERE notes.source_id IS NOT NULL);
+
+ALTER TABLE note_sources
+DROP COLUMN IF EXISTS note_id,
+ALTER COLUMN account_id SET NOT NULL;
@@ -0,0 +1,42 @@
+-- Every revision of a note is a row in note_sources.
+-- notes.source_id points to the current one.
+-- Remote notes have no account and keep their HTML as source.
+ALTER TABLE note_sources
+ALTER COLUMN account_id DROP NOT NULL,
+ADD COLUMN note_id UUID;
+
+UPDATE note_sources
+SET note_id = notes.id
+FROM notes
+WHERE notes.source_id = note_sources.id;
+
+INSERT INTO note_sources (id, account_id, content, media_type, note_id)
+SELECT
+    notes.id,
+    NULL,
+    notes.content,
+    'text/html',
+    notes.id
+FROM notes
+WHERE notes.source_id IS NULL;
+
+UPDATE notes
+SET source_id = id
+WHERE source_id IS NULL;
+
+DELETE FROM note_sources
+WHERE note_id IS NULL;
+
+ALTER TABLE note_sources
+ALTER COLUMN note_id SET NOT NULL,
+ADD FOREIGN KEY (note_id) REFERENCES notes (id)
+ON UPDATE CASCADE
+ON DELETE CASCADE
+DEFERRABLE INITIALLY DEFERRED;
+
+CREATE INDEX IF NOT EXISTS note_sources_note_id_idx ON note_sources (note_id);
+
+ALTER TABLE notes
+ALTER COLUMN source_id SET NOT NULL,
+-- when the note was last edited
+ADD COLUMN updated TIMESTAMPTZ;
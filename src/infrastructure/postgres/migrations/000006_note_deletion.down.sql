@@ -0,0 +1,5 @@
+ALTER TABLE note_sources
+DROP COLUMN IF EXISTS deleted;
+
+ALTER TABLE notes
+DROP COLUMN IF EXISTS deleted;
@@ -0,0 +1,6 @@
+-- Deleted notes are kept to answer with a Tombstone
+ALTER TABLE notes
+ADD COLUMN deleted TIMESTAMPTZ;
+
+ALTER TABLE note_sources
+ADD COLUMN deleted TIMESTAMPTZ;
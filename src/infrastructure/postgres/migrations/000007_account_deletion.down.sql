@@ -0,0 +1,5 @@
+ALTER TABLE note_sources
+DROP CONSTRAINT IF EXISTS note_sources_account_id_fkey,
+ADD FOREIGN KEY (account_id) REFERENCES accounts (id);
+
+DROP TABLE IF EXISTS account_tombstones;
@@ -0,0 +1,13 @@
+-- Names of deleted accounts.
+-- They cannot be registered again and their actors answer 410 Gone.
+CREATE TABLE IF NOT EXISTS account_tombstones (
+    name TEXT PRIMARY KEY,
+    deleted TIMESTAMPTZ NOT NULL
+);
+
+-- Sources go away with their account like notes go away with their actor
+ALTER TABLE note_sources
+DROP CONSTRAINT IF EXISTS note_sources_account_id_fkey,
+ADD FOREIGN KEY (account_id) REFERENCES accounts (id)
+ON UPDATE CASCADE
+ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS sessions;
+
+ALTER TABLE accounts
+DROP COLUMN IF EXISTS password_hash;
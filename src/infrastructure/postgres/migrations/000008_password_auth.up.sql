@@ -0,0 +1,20 @@
+-- argon2 PHC string.
+-- Accounts created before passwords existed cannot log in.
+ALTER TABLE accounts
+ADD COLUMN password_hash TEXT;
+
+-- Bearer tokens issued on login
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL,
+    -- SHA-256 of the token, which only the client knows
+    token_hash TEXT NOT NULL UNIQUE,
+    created TIMESTAMPTZ NOT NULL,
+    expires TIMESTAMPTZ NOT NULL,
+
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS sessions_account_id_idx ON sessions (account_id);
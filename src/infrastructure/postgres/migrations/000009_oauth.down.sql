@@ -0,0 +1,12 @@
+DELETE FROM sessions
+WHERE account_id IS NULL;
+
+DROP INDEX IF EXISTS sessions_app_id_idx;
+
+ALTER TABLE sessions
+DROP COLUMN IF EXISTS scopes,
+DROP COLUMN IF EXISTS app_id,
+ALTER COLUMN account_id SET NOT NULL;
+
+DROP TABLE IF EXISTS oauth_authorization_codes;
+DROP TABLE IF EXISTS oauth_apps;
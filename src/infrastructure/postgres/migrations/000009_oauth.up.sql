@@ -0,0 +1,44 @@
+-- Third-party clients registered with POST /api/v1/apps
+CREATE TABLE IF NOT EXISTS oauth_apps (
+    id UUID PRIMARY KEY,
+    client_id TEXT NOT NULL UNIQUE,
+    -- SHA-256 of the secret, which only the client knows
+    client_secret_hash TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- one URI per line
+    redirect_uris TEXT NOT NULL,
+    -- space separated
+    scopes TEXT NOT NULL,
+    website TEXT,
+    created TIMESTAMPTZ NOT NULL
+);
+
+-- Short lived codes of the authorization code flow
+CREATE TABLE IF NOT EXISTS oauth_authorization_codes (
+    -- SHA-256 of the code
+    code_hash TEXT PRIMARY KEY,
+    app_id UUID NOT NULL,
+    account_id UUID NOT NULL,
+    redirect_uri TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    -- PKCE, RFC 7636
+    code_challenge TEXT,
+    code_challenge_method TEXT,
+    expires TIMESTAMPTZ NOT NULL,
+
+    FOREIGN KEY (app_id) REFERENCES oauth_apps (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+-- Sessions double as OAuth access tokens.
+-- Tokens from client_credentials belong to an app only.
+ALTER TABLE sessions
+ALTER COLUMN account_id DROP NOT NULL,
+ADD COLUMN app_id UUID REFERENCES oauth_apps (id) ON UPDATE CASCADE ON DELETE CASCADE,
+ADD COLUMN scopes TEXT NOT NULL DEFAULT 'read write follow push';
+
+CREATE INDEX IF NOT EXISTS sessions_app_id_idx ON sessions (app_id);
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS notes_actor_id_idx;
+
+DROP TABLE IF EXISTS reblogs;
+DROP TABLE IF EXISTS favourites;
+
+DELETE FROM follows
+WHERE NOT accepted;
+
+ALTER TABLE follows
+DROP COLUMN IF EXISTS accepted;
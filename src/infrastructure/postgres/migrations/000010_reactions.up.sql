@@ -0,0 +1,45 @@
+-- Follows sent to remote actors wait for their Accept
+ALTER TABLE follows
+ADD COLUMN accepted BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Like activities
+CREATE TABLE IF NOT EXISTS favourites (
+    id UUID PRIMARY KEY,
+    actor_id UUID NOT NULL,
+    note_id UUID NOT NULL,
+    -- ActivityPub Like activity id
+    like_url TEXT NOT NULL UNIQUE,
+
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    CONSTRAINT unique_favourite UNIQUE (actor_id, note_id)
+);
+
+CREATE INDEX IF NOT EXISTS favourites_note_id_idx ON favourites (note_id);
+
+-- Announce activities
+CREATE TABLE IF NOT EXISTS reblogs (
+    id UUID PRIMARY KEY,
+    actor_id UUID NOT NULL,
+    note_id UUID NOT NULL,
+    -- ActivityPub Announce activity id
+    announce_url TEXT NOT NULL UNIQUE,
+
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    CONSTRAINT unique_reblog UNIQUE (actor_id, note_id)
+);
+
+CREATE INDEX IF NOT EXISTS reblogs_note_id_idx ON reblogs (note_id);
+
+CREATE INDEX IF NOT EXISTS notes_actor_id_idx ON notes (actor_id, id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS follows_followee_id_idx;
+DROP INDEX IF EXISTS home_feed_reblog_id_idx;
+DROP INDEX IF EXISTS home_feed_note_id_idx;
+
+DROP TABLE IF EXISTS home_feed;
@@ -0,0 +1,78 @@
+-- Home timelines materialized when notes and reblogs arrive
+CREATE TABLE IF NOT EXISTS home_feed (
+    account_id UUID NOT NULL,
+    -- id of the note, or of the reblog that brought it
+    entry_id UUID NOT NULL,
+    note_id UUID NOT NULL,
+    reblog_id UUID,
+
+    PRIMARY KEY (account_id, entry_id),
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (reblog_id) REFERENCES reblogs (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS home_feed_note_id_idx ON home_feed (note_id);
+CREATE INDEX IF NOT EXISTS home_feed_reblog_id_idx ON home_feed (reblog_id);
+
+-- Fan-out looks up followers of the author
+CREATE INDEX IF NOT EXISTS follows_followee_id_idx ON follows (followee_id);
+
+-- Fill the feeds of existing accounts with their latest 400 entries
+WITH sources AS (
+    SELECT
+        actors.account_id,
+        actors.id AS actor_id
+    FROM actors
+    WHERE actors.account_id IS NOT NULL
+    UNION
+    SELECT
+        actors.account_id,
+        follows.followee_id
+    FROM follows
+    INNER JOIN actors ON follows.follower_id = actors.id
+    WHERE actors.account_id IS NOT NULL AND follows.accepted
+),
+
+entries AS (
+    SELECT
+        sources.account_id,
+        notes.id AS entry_id,
+        notes.id AS note_id,
+        NULL::UUID AS reblog_id
+    FROM sources
+    INNER JOIN notes ON sources.actor_id = notes.actor_id
+    WHERE notes.deleted IS NULL
+    UNION ALL
+    SELECT
+        sources.account_id,
+        reblogs.id,
+        reblogs.note_id,
+        reblogs.id
+    FROM sources
+    INNER JOIN reblogs ON sources.actor_id = reblogs.actor_id
+),
+
+ranked AS (
+    SELECT
+        entries.*,
+        ROW_NUMBER()
+            OVER (PARTITION BY entries.account_id ORDER BY entries.entry_id DESC)
+        AS position
+    FROM entries
+)
+
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    ranked.account_id,
+    ranked.entry_id,
+    ranked.note_id,
+    ranked.reblog_id
+FROM ranked
+WHERE ranked.position <= 400;
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS markers;
+
+DROP INDEX IF EXISTS notifications_note_id_idx;
+DROP INDEX IF EXISTS notifications_actor_id_idx;
+DROP INDEX IF EXISTS notifications_account_id_idx;
+
+DROP TABLE IF EXISTS notifications;
@@ -0,0 +1,40 @@
+-- What other actors did to local accounts
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY,
+    -- notified account
+    account_id UUID NOT NULL,
+    -- follow, mention, favourite or reblog
+    type TEXT NOT NULL,
+    -- who did it
+    actor_id UUID NOT NULL,
+    note_id UUID,
+
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS notifications_account_id_idx ON notifications (account_id, id);
+CREATE INDEX IF NOT EXISTS notifications_actor_id_idx ON notifications (actor_id);
+CREATE INDEX IF NOT EXISTS notifications_note_id_idx ON notifications (note_id);
+
+-- Reading position of accounts in their timelines
+CREATE TABLE IF NOT EXISTS markers (
+    account_id UUID NOT NULL,
+    -- home or notifications
+    timeline TEXT NOT NULL,
+    last_read_id UUID NOT NULL,
+    version BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (account_id, timeline),
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS note_tags;
+DROP TABLE IF EXISTS tags;
+
+DROP INDEX IF EXISTS actors_search_tsv_idx;
+ALTER TABLE actors DROP COLUMN IF EXISTS search_tsv;
+
+DROP INDEX IF EXISTS notes_content_tsv_idx;
+ALTER TABLE notes DROP COLUMN IF EXISTS content_tsv;
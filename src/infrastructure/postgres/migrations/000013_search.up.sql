@@ -0,0 +1,43 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Words of the note content, markup removed
+ALTER TABLE notes
+ADD COLUMN content_tsv TSVECTOR GENERATED ALWAYS AS (
+    to_tsvector('simple', regexp_replace(content, '<[^>]*>', ' ', 'g'))
+) STORED;
+
+CREATE INDEX IF NOT EXISTS notes_content_tsv_idx ON notes USING GIN (content_tsv);
+
+-- Words of the actor name, ranked above those of its bio
+ALTER TABLE actors
+ADD COLUMN search_tsv TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', name), 'A')
+    || setweight(
+        to_tsvector('simple', regexp_replace(coalesce(summary, ''), '<[^>]*>', ' ', 'g')),
+        'B'
+    )
+) STORED;
+
+CREATE INDEX IF NOT EXISTS actors_search_tsv_idx ON actors USING GIN (search_tsv);
+
+-- Hashtags, lowercased and without `#`
+CREATE TABLE IF NOT EXISTS tags (
+    name TEXT PRIMARY KEY
+);
+
+CREATE INDEX IF NOT EXISTS tags_name_trgm_idx ON tags USING GIN (name gin_trgm_ops);
+
+CREATE TABLE IF NOT EXISTS note_tags (
+    note_id UUID NOT NULL,
+    tag_name TEXT NOT NULL,
+
+    PRIMARY KEY (note_id, tag_name),
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (tag_name) REFERENCES tags (name)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS note_tags_tag_name_idx ON note_tags (tag_name, note_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS notes_in_reply_to_id_idx;
+
+ALTER TABLE notes DROP COLUMN IF EXISTS in_reply_to_id;
@@ -0,0 +1,7 @@
+-- Note this one replies to, when it is known
+ALTER TABLE notes
+ADD COLUMN in_reply_to_id UUID REFERENCES notes (id)
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS notes_in_reply_to_id_idx ON notes (in_reply_to_id);
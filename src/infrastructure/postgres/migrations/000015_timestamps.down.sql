@@ -0,0 +1,17 @@
+ALTER TABLE note_sources
+DROP COLUMN IF EXISTS created_at,
+DROP COLUMN IF EXISTS updated_at;
+
+ALTER TABLE notes
+DROP COLUMN IF EXISTS published,
+DROP COLUMN IF EXISTS created_at,
+DROP COLUMN IF EXISTS updated_at;
+
+ALTER TABLE actors
+DROP COLUMN IF EXISTS published,
+DROP COLUMN IF EXISTS created_at,
+DROP COLUMN IF EXISTS updated_at;
+
+ALTER TABLE accounts
+DROP COLUMN IF EXISTS created_at,
+DROP COLUMN IF EXISTS updated_at;
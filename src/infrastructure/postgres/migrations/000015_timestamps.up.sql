@@ -0,0 +1,75 @@
+-- Millisecond timestamp embedded in a UUIDv7, to backfill rows created before these columns
+CREATE OR REPLACE FUNCTION uuid_v7_timestamp(id UUID) RETURNS TIMESTAMPTZ AS $$
+    SELECT to_timestamp(
+        ('x' || substr(replace(id::TEXT, '-', ''), 1, 12))::BIT(48)::BIGINT / 1000.0
+    );
+$$ LANGUAGE sql IMMUTABLE;
+
+ALTER TABLE accounts
+ADD COLUMN created_at TIMESTAMPTZ,
+ADD COLUMN updated_at TIMESTAMPTZ;
+
+UPDATE accounts
+SET
+    created_at = uuid_v7_timestamp(id),
+    updated_at = uuid_v7_timestamp(id);
+
+ALTER TABLE accounts
+ALTER COLUMN created_at SET NOT NULL,
+ALTER COLUMN created_at SET DEFAULT now(),
+ALTER COLUMN updated_at SET NOT NULL,
+ALTER COLUMN updated_at SET DEFAULT now();
+
+-- `published` of the actor document, as sent by remote servers that have it
+ALTER TABLE actors
+ADD COLUMN published TIMESTAMPTZ,
+ADD COLUMN created_at TIMESTAMPTZ,
+ADD COLUMN updated_at TIMESTAMPTZ;
+
+UPDATE actors
+SET
+    published = CASE WHEN account_id IS NOT NULL THEN uuid_v7_timestamp(id) END,
+    created_at = uuid_v7_timestamp(id),
+    updated_at = uuid_v7_timestamp(id);
+
+ALTER TABLE actors
+ALTER COLUMN created_at SET NOT NULL,
+ALTER COLUMN created_at SET DEFAULT now(),
+ALTER COLUMN updated_at SET NOT NULL,
+ALTER COLUMN updated_at SET DEFAULT now();
+
+-- `published` of the note, as sent for remote notes
+ALTER TABLE notes
+ADD COLUMN published TIMESTAMPTZ,
+ADD COLUMN created_at TIMESTAMPTZ,
+ADD COLUMN updated_at TIMESTAMPTZ;
+
+UPDATE notes
+SET
+    published = uuid_v7_timestamp(id),
+    created_at = uuid_v7_timestamp(id),
+    updated_at = coalesce(deleted, updated, uuid_v7_timestamp(id));
+
+ALTER TABLE notes
+ALTER COLUMN published SET NOT NULL,
+ALTER COLUMN created_at SET NOT NULL,
+ALTER COLUMN created_at SET DEFAULT now(),
+ALTER COLUMN updated_at SET NOT NULL,
+ALTER COLUMN updated_at SET DEFAULT now();
+
+ALTER TABLE note_sources
+ADD COLUMN created_at TIMESTAMPTZ,
+ADD COLUMN updated_at TIMESTAMPTZ;
+
+UPDATE note_sources
+SET
+    created_at = uuid_v7_timestamp(id),
+    updated_at = coalesce(deleted, uuid_v7_timestamp(id));
+
+ALTER TABLE note_sources
+ALTER COLUMN created_at SET NOT NULL,
+ALTER COLUMN created_at SET DEFAULT now(),
+ALTER COLUMN updated_at SET NOT NULL,
+ALTER COLUMN updated_at SET DEFAULT now();
+
+DROP FUNCTION uuid_v7_timestamp;
@@ -0,0 +1,19 @@
+-- Files attached to notes. Uploads have an account and no note until they are posted
+CREATE TABLE IF NOT EXISTS attachments (
+    id UUID PRIMARY KEY,
+    account_id UUID REFERENCES accounts (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    note_id UUID REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    type TEXT NOT NULL,
+    media_type TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- key of uploaded files in the media storage
+    storage_key TEXT UNIQUE,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS attachments_note_id_idx ON attachments (note_id, id);
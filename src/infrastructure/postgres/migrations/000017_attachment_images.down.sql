@@ -0,0 +1,10 @@
+ALTER TABLE attachments
+DROP COLUMN IF EXISTS width,
+DROP COLUMN IF EXISTS height,
+DROP COLUMN IF EXISTS blurhash,
+DROP COLUMN IF EXISTS focus_x,
+DROP COLUMN IF EXISTS focus_y,
+DROP COLUMN IF EXISTS preview_url,
+DROP COLUMN IF EXISTS preview_storage_key,
+DROP COLUMN IF EXISTS preview_width,
+DROP COLUMN IF EXISTS preview_height;
@@ -0,0 +1,11 @@
+-- What clients show of images before and while loading them
+ALTER TABLE attachments
+ADD COLUMN width INTEGER,
+ADD COLUMN height INTEGER,
+ADD COLUMN blurhash TEXT,
+ADD COLUMN focus_x REAL,
+ADD COLUMN focus_y REAL,
+ADD COLUMN preview_url TEXT,
+ADD COLUMN preview_storage_key TEXT UNIQUE,
+ADD COLUMN preview_width INTEGER,
+ADD COLUMN preview_height INTEGER;
@@ -0,0 +1,16 @@
+-- Remote files served through the media proxy, by the hex SHA-256 of their URL.
+-- Only URLs found in federated content are registered, so that the proxy cannot be pointed anywhere
+CREATE TABLE IF NOT EXISTS remote_media (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    -- set while a copy is cached in the media storage
+    media_type TEXT,
+    storage_key TEXT UNIQUE,
+    size BIGINT,
+    cached_at TIMESTAMPTZ,
+    accessed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS remote_media_accessed_at_idx ON remote_media (accessed_at)
+WHERE storage_key IS NOT NULL;
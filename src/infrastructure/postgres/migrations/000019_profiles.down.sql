@@ -0,0 +1,22 @@
+DROP INDEX IF EXISTS actors_search_tsv_idx;
+ALTER TABLE actors DROP COLUMN IF EXISTS search_tsv;
+
+ALTER TABLE actors
+DROP COLUMN IF EXISTS display_name,
+DROP COLUMN IF EXISTS summary_source,
+DROP COLUMN IF EXISTS avatar_url,
+DROP COLUMN IF EXISTS avatar_storage_key,
+DROP COLUMN IF EXISTS header_url,
+DROP COLUMN IF EXISTS header_storage_key,
+DROP COLUMN IF EXISTS fields;
+
+ALTER TABLE actors
+ADD COLUMN search_tsv TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', name), 'A')
+    || setweight(
+        to_tsvector('simple', regexp_replace(coalesce(summary, ''), '<[^>]*>', ' ', 'g')),
+        'B'
+    )
+) STORED;
+
+CREATE INDEX IF NOT EXISTS actors_search_tsv_idx ON actors USING GIN (search_tsv);
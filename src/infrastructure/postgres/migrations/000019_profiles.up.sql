@@ -0,0 +1,25 @@
+-- Profiles shown on accounts. Images of local actors are kept in the media storage
+ALTER TABLE actors
+ADD COLUMN IF NOT EXISTS display_name TEXT,
+-- bio as written by local actors, `summary` is rendered from it
+ADD COLUMN IF NOT EXISTS summary_source TEXT,
+ADD COLUMN IF NOT EXISTS avatar_url TEXT,
+ADD COLUMN IF NOT EXISTS avatar_storage_key TEXT UNIQUE,
+ADD COLUMN IF NOT EXISTS header_url TEXT,
+ADD COLUMN IF NOT EXISTS header_storage_key TEXT UNIQUE,
+-- PropertyValue fields, as [{"name": ..., "value": HTML, "source": text of local actors}]
+ADD COLUMN IF NOT EXISTS fields JSONB NOT NULL DEFAULT '[]';
+
+-- Display names are searched like names
+DROP INDEX IF EXISTS actors_search_tsv_idx;
+ALTER TABLE actors DROP COLUMN IF EXISTS search_tsv;
+ALTER TABLE actors
+ADD COLUMN search_tsv TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', name || ' ' || coalesce(display_name, '')), 'A')
+    || setweight(
+        to_tsvector('simple', regexp_replace(coalesce(summary, ''), '<[^>]*>', ' ', 'g')),
+        'B'
+    )
+) STORED;
+
+CREATE INDEX IF NOT EXISTS actors_search_tsv_idx ON actors USING GIN (search_tsv);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS actor_emojis;
+DROP TABLE IF EXISTS note_emojis;
+DROP TABLE IF EXISTS custom_emojis;
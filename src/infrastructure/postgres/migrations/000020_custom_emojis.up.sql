@@ -0,0 +1,43 @@
+-- Custom emoji shown in place of `:shortcode:`. Local ones have no host and are kept in the
+-- media storage, remote ones are linked to where they are hosted
+CREATE TABLE IF NOT EXISTS custom_emojis (
+    id UUID PRIMARY KEY,
+    shortcode TEXT NOT NULL,
+    host TEXT,
+    -- `id` of the `Emoji` object
+    uri TEXT NOT NULL,
+    url TEXT NOT NULL,
+    media_type TEXT,
+    storage_key TEXT UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    UNIQUE NULLS NOT DISTINCT (shortcode, host)
+);
+
+CREATE TABLE IF NOT EXISTS note_emojis (
+    note_id UUID NOT NULL,
+    emoji_id UUID NOT NULL,
+
+    PRIMARY KEY (note_id, emoji_id),
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (emoji_id) REFERENCES custom_emojis (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+-- Emoji of the display name, bio and fields of actors
+CREATE TABLE IF NOT EXISTS actor_emojis (
+    actor_id UUID NOT NULL,
+    emoji_id UUID NOT NULL,
+
+    PRIMARY KEY (actor_id, emoji_id),
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (emoji_id) REFERENCES custom_emojis (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,26 @@
+-- Emoji reactions of Misskey `Like` with `_misskey_reaction` and Pleroma `EmojiReact`
+CREATE TABLE IF NOT EXISTS emoji_reactions (
+    id UUID PRIMARY KEY,
+    actor_id UUID NOT NULL,
+    note_id UUID NOT NULL,
+    -- ActivityPub Like or EmojiReact activity id
+    reaction_url TEXT NOT NULL UNIQUE,
+    -- unicode emoji or `:shortcode:` of a custom emoji
+    content TEXT NOT NULL,
+    emoji_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (emoji_id) REFERENCES custom_emojis (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    CONSTRAINT unique_emoji_reaction UNIQUE (actor_id, note_id, content)
+);
+
+CREATE INDEX IF NOT EXISTS emoji_reactions_note_id_idx ON emoji_reactions (note_id);
@@ -0,0 +1,48 @@
+-- Polls of notes. Votes of local polls are counted from poll_votes, remote polls keep the
+-- counts their server sent in voters_count and votes_count
+CREATE TABLE IF NOT EXISTS polls (
+    note_id UUID PRIMARY KEY,
+    multiple BOOLEAN NOT NULL DEFAULT FALSE,
+    expires_at TIMESTAMPTZ,
+    -- when the final counts were sent, or received for remote polls
+    closed_at TIMESTAMPTZ,
+    voters_count BIGINT,
+
+    FOREIGN KEY (note_id) REFERENCES notes (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS polls_expires_at_idx ON polls (expires_at)
+WHERE closed_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS poll_options (
+    note_id UUID NOT NULL,
+    position INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    votes_count BIGINT,
+
+    PRIMARY KEY (note_id, position),
+    FOREIGN KEY (note_id) REFERENCES polls (note_id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+-- Votes of local actors, and of remote ones in local polls
+CREATE TABLE IF NOT EXISTS poll_votes (
+    id UUID PRIMARY KEY,
+    note_id UUID NOT NULL,
+    actor_id UUID NOT NULL,
+    choice INTEGER NOT NULL,
+    -- ActivityPub id of the Note naming the option
+    vote_url TEXT NOT NULL UNIQUE,
+
+    FOREIGN KEY (note_id, choice) REFERENCES poll_options (note_id, position)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES actors (id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+
+    CONSTRAINT unique_poll_vote UNIQUE (note_id, actor_id, choice)
+);
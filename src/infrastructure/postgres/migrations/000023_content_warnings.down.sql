@@ -0,0 +1,3 @@
+ALTER TABLE notes
+DROP COLUMN IF EXISTS sensitive,
+DROP COLUMN IF EXISTS summary;
@@ -0,0 +1,4 @@
+-- Content warning the note is folded behind, and whether its media is not safe for work
+ALTER TABLE notes
+ADD COLUMN summary TEXT,
+ADD COLUMN sensitive BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+ALTER TABLE notes
+DROP COLUMN IF EXISTS url,
+DROP COLUMN IF EXISTS name,
+DROP COLUMN IF EXISTS object_type;
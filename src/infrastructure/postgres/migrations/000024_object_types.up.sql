@@ -0,0 +1,6 @@
+-- Type of the object a note was received as, with the title and web page of long-form objects
+-- like `Article`s
+ALTER TABLE notes
+ADD COLUMN object_type TEXT NOT NULL DEFAULT 'Note',
+ADD COLUMN name TEXT,
+ADD COLUMN url TEXT;
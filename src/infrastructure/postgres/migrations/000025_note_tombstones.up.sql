@@ -0,0 +1,6 @@
+-- Notes that went away with their account.
+-- Their urls keep answering with a Tombstone like notes deleted one by one.
+CREATE TABLE IF NOT EXISTS note_tombstones (
+    id UUID PRIMARY KEY,
+    deleted TIMESTAMPTZ NOT NULL
+);
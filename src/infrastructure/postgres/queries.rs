@@ -16,15 +16,26 @@ pub enum ActorType {
     Organization,
 }
 pub const CREATE_ACCOUNT: &str = r#"-- name: CreateAccount :exec
-INSERT INTO accounts (id, name)
-VALUES ($1, $2)"#;
+INSERT INTO accounts (id, name, password_hash)
+SELECT
+    $1,
+    $2,
+    $3
+WHERE NOT EXISTS (
+    SELECT 1 FROM account_tombstones
+    WHERE account_tombstones.name = $2
+)"#;
 pub async fn create_account(
     client: &impl deadpool_postgres::GenericClient,
     accounts_id: &uuid::Uuid,
     accounts_name: &str,
+    accounts_password_hash: Option<&str>,
 ) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
     client
-        .execute(CREATE_ACCOUNT, &[&accounts_id, &accounts_name])
+        .execute(
+            CREATE_ACCOUNT,
+            &[&accounts_id, &accounts_name, &accounts_password_hash],
+        )
         .await
 }
 pub const FIND_ACCOUNT_BY_ID: &str = r#"-- name: FindAccountById :one
@@ -54,6 +65,27 @@ pub async fn find_account_by_id(
     };
     Ok(Some(v))
 }
+pub const LIST_ACCOUNT_IDS: &str = r#"-- name: ListAccountIds :many
+SELECT id
+FROM accounts
+ORDER BY id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct ListAccountIdsRow {
+    pub accounts_id: uuid::Uuid,
+}
+pub async fn list_account_ids(
+    client: &impl deadpool_postgres::GenericClient,
+) -> Result<
+    impl Iterator<Item = Result<ListAccountIdsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client.query(LIST_ACCOUNT_IDS, &[]).await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(ListAccountIdsRow {
+            accounts_id: r.try_get(0)?,
+        })
+    }))
+}
 pub const FIND_ACCOUNT_BY_NAME: &str = r#"-- name: FindAccountByName :one
 SELECT
     id,
@@ -81,6 +113,105 @@ pub async fn find_account_by_name(
     };
     Ok(Some(v))
 }
+pub const FIND_ACCOUNT_CREDENTIALS: &str = r#"-- name: FindAccountCredentials :one
+SELECT
+    id,
+    name,
+    password_hash
+FROM accounts
+WHERE name = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct FindAccountCredentialsRow {
+    pub accounts_id: uuid::Uuid,
+    pub accounts_name: String,
+    pub accounts_password_hash: Option<String>,
+}
+pub async fn find_account_credentials(
+    client: &impl deadpool_postgres::GenericClient,
+    accounts_name: &str,
+) -> Result<Option<FindAccountCredentialsRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(FIND_ACCOUNT_CREDENTIALS, &[&accounts_name])
+        .await?;
+    let v = match row {
+        Some(v) => FindAccountCredentialsRow {
+            accounts_id: v.try_get(0)?,
+            accounts_name: v.try_get(1)?,
+            accounts_password_hash: v.try_get(2)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_ACCOUNT: &str = r#"-- name: DeleteAccount :exec
+DELETE FROM accounts
+WHERE id = $1"#;
+pub async fn delete_account(
+    client: &impl deadpool_postgres::GenericClient,
+    accounts_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(DELETE_ACCOUNT, &[&accounts_id]).await
+}
+pub const INSERT_ACCOUNT_TOMBSTONE: &str = r#"-- name: InsertAccountTombstone :exec
+INSERT INTO account_tombstones (name, deleted)
+VALUES ($1, $2)"#;
+pub async fn insert_account_tombstone(
+    client: &impl deadpool_postgres::GenericClient,
+    account_tombstones_name: &str,
+    account_tombstones_deleted: &chrono::DateTime<chrono::Utc>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_ACCOUNT_TOMBSTONE,
+            &[&account_tombstones_name, &account_tombstones_deleted],
+        )
+        .await
+}
+pub const INSERT_NOTE_TOMBSTONES: &str = r#"-- name: InsertNoteTombstones :exec
+INSERT INTO note_tombstones (id, deleted)
+SELECT
+    notes.id,
+    COALESCE(notes.deleted, $2)
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE actors.account_id = $1
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_note_tombstones(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_account_id: &uuid::Uuid,
+    deleted: &chrono::DateTime<chrono::Utc>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INSERT_NOTE_TOMBSTONES, &[&actors_account_id, &deleted])
+        .await
+}
+pub const FIND_ACCOUNT_TOMBSTONE: &str = r#"-- name: FindAccountTombstone :one
+SELECT
+    name,
+    deleted
+FROM account_tombstones
+WHERE name = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct FindAccountTombstoneRow {
+    pub account_tombstones_name: String,
+    pub account_tombstones_deleted: chrono::DateTime<chrono::Utc>,
+}
+pub async fn find_account_tombstone(
+    client: &impl deadpool_postgres::GenericClient,
+    account_tombstones_name: &str,
+) -> Result<Option<FindAccountTombstoneRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(FIND_ACCOUNT_TOMBSTONE, &[&account_tombstones_name])
+        .await?;
+    let v = match row {
+        Some(v) => FindAccountTombstoneRow {
+            account_tombstones_name: v.try_get(0)?,
+            account_tombstones_deleted: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
 pub const GET_ACCOUNT_ACTOR: &str = r#"-- name: GetAccountActor :one
 SELECT
     id,
@@ -90,7 +221,27 @@ SELECT
     actor_url,
     inbox_url,
     outbox_url,
-    shared_inbox_url
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
 FROM actors
 WHERE account_id = $1"#;
 #[derive(PartialEq, Debug, Clone)]
@@ -103,6 +254,21 @@ pub struct GetAccountActorRow {
     pub actors_inbox_url: String,
     pub actors_outbox_url: String,
     pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
 }
 pub async fn get_account_actor(
     client: &impl deadpool_postgres::GenericClient,
@@ -121,6 +287,21 @@ pub async fn get_account_actor(
             actors_inbox_url: v.try_get(5)?,
             actors_outbox_url: v.try_get(6)?,
             actors_shared_inbox_url: v.try_get(7)?,
+            actors_summary: v.try_get(8)?,
+            actors_public_key_id: v.try_get(9)?,
+            actors_public_key_pem: v.try_get(10)?,
+            actors_account_id: v.try_get(11)?,
+            actors_published: v.try_get(12)?,
+            actors_created_at: v.try_get(13)?,
+            actors_updated_at: v.try_get(14)?,
+            actors_display_name: v.try_get(15)?,
+            actors_summary_source: v.try_get(16)?,
+            actors_avatar_url: v.try_get(17)?,
+            actors_avatar_storage_key: v.try_get(18)?,
+            actors_header_url: v.try_get(19)?,
+            actors_header_storage_key: v.try_get(20)?,
+            actors_fields: v.try_get(21)?,
+            actors_emojis: v.try_get(22)?,
         },
         None => return Ok(None),
     };
@@ -135,7 +316,27 @@ SELECT
     actor_url,
     inbox_url,
     outbox_url,
-    shared_inbox_url
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
 FROM actors
 WHERE name = $1 AND host = $2"#;
 #[derive(PartialEq, Debug, Clone)]
@@ -148,6 +349,21 @@ pub struct GetActorByNameAndHostRow {
     pub actors_inbox_url: String,
     pub actors_outbox_url: String,
     pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
 }
 pub async fn get_actor_by_name_and_host(
     client: &impl deadpool_postgres::GenericClient,
@@ -167,11 +383,237 @@ pub async fn get_actor_by_name_and_host(
             actors_inbox_url: v.try_get(5)?,
             actors_outbox_url: v.try_get(6)?,
             actors_shared_inbox_url: v.try_get(7)?,
+            actors_summary: v.try_get(8)?,
+            actors_public_key_id: v.try_get(9)?,
+            actors_public_key_pem: v.try_get(10)?,
+            actors_account_id: v.try_get(11)?,
+            actors_published: v.try_get(12)?,
+            actors_created_at: v.try_get(13)?,
+            actors_updated_at: v.try_get(14)?,
+            actors_display_name: v.try_get(15)?,
+            actors_summary_source: v.try_get(16)?,
+            actors_avatar_url: v.try_get(17)?,
+            actors_avatar_storage_key: v.try_get(18)?,
+            actors_header_url: v.try_get(19)?,
+            actors_header_storage_key: v.try_get(20)?,
+            actors_fields: v.try_get(21)?,
+            actors_emojis: v.try_get(22)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_ACTOR_BY_URL: &str = r#"-- name: GetActorByUrl :one
+SELECT
+    id,
+    type,
+    name,
+    host,
+    actor_url,
+    inbox_url,
+    outbox_url,
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
+FROM actors
+WHERE actor_url = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetActorByUrlRow {
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+}
+pub async fn get_actor_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_actor_url: &str,
+) -> Result<Option<GetActorByUrlRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_ACTOR_BY_URL, &[&actors_actor_url])
+        .await?;
+    let v = match row {
+        Some(v) => GetActorByUrlRow {
+            actors_id: v.try_get(0)?,
+            actors_type: v.try_get(1)?,
+            actors_name: v.try_get(2)?,
+            actors_host: v.try_get(3)?,
+            actors_actor_url: v.try_get(4)?,
+            actors_inbox_url: v.try_get(5)?,
+            actors_outbox_url: v.try_get(6)?,
+            actors_shared_inbox_url: v.try_get(7)?,
+            actors_summary: v.try_get(8)?,
+            actors_public_key_id: v.try_get(9)?,
+            actors_public_key_pem: v.try_get(10)?,
+            actors_account_id: v.try_get(11)?,
+            actors_published: v.try_get(12)?,
+            actors_created_at: v.try_get(13)?,
+            actors_updated_at: v.try_get(14)?,
+            actors_display_name: v.try_get(15)?,
+            actors_summary_source: v.try_get(16)?,
+            actors_avatar_url: v.try_get(17)?,
+            actors_avatar_storage_key: v.try_get(18)?,
+            actors_header_url: v.try_get(19)?,
+            actors_header_storage_key: v.try_get(20)?,
+            actors_fields: v.try_get(21)?,
+            actors_emojis: v.try_get(22)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_ACTOR_BY_KEY_ID: &str = r#"-- name: GetActorByKeyId :one
+SELECT
+    id,
+    type,
+    name,
+    host,
+    actor_url,
+    inbox_url,
+    outbox_url,
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
+FROM actors
+WHERE public_key_id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetActorByKeyIdRow {
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+}
+pub async fn get_actor_by_key_id(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_public_key_id: Option<&str>,
+) -> Result<Option<GetActorByKeyIdRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_ACTOR_BY_KEY_ID, &[&actors_public_key_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetActorByKeyIdRow {
+            actors_id: v.try_get(0)?,
+            actors_type: v.try_get(1)?,
+            actors_name: v.try_get(2)?,
+            actors_host: v.try_get(3)?,
+            actors_actor_url: v.try_get(4)?,
+            actors_inbox_url: v.try_get(5)?,
+            actors_outbox_url: v.try_get(6)?,
+            actors_shared_inbox_url: v.try_get(7)?,
+            actors_summary: v.try_get(8)?,
+            actors_public_key_id: v.try_get(9)?,
+            actors_public_key_pem: v.try_get(10)?,
+            actors_account_id: v.try_get(11)?,
+            actors_published: v.try_get(12)?,
+            actors_created_at: v.try_get(13)?,
+            actors_updated_at: v.try_get(14)?,
+            actors_display_name: v.try_get(15)?,
+            actors_summary_source: v.try_get(16)?,
+            actors_avatar_url: v.try_get(17)?,
+            actors_avatar_storage_key: v.try_get(18)?,
+            actors_header_url: v.try_get(19)?,
+            actors_header_storage_key: v.try_get(20)?,
+            actors_fields: v.try_get(21)?,
+            actors_emojis: v.try_get(22)?,
         },
         None => return Ok(None),
     };
     Ok(Some(v))
 }
+pub const GET_REMOTE_INBOXES: &str = r#"-- name: GetRemoteInboxes :many
+SELECT DISTINCT COALESCE(shared_inbox_url, inbox_url) AS inbox_url
+FROM actors
+WHERE account_id IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetRemoteInboxesRow {
+    pub inbox_url: String,
+}
+pub async fn get_remote_inboxes(
+    client: &impl deadpool_postgres::GenericClient,
+) -> Result<
+    impl Iterator<Item = Result<GetRemoteInboxesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client.query(GET_REMOTE_INBOXES, &[]).await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetRemoteInboxesRow {
+            inbox_url: r.try_get(0)?,
+        })
+    }))
+}
 pub const UPSERT_ACTOR: &str = r#"-- name: UpsertActor :one
 INSERT INTO actors (
     id,
@@ -182,9 +624,25 @@ INSERT INTO actors (
     inbox_url,
     outbox_url,
     shared_inbox_url,
-    account_id
+    account_id,
+    summary,
+    public_key_id,
+    public_key_pem,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields
+)
+VALUES (
+    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+    $21, $22
 )
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
 ON CONFLICT (name, host) DO UPDATE
 SET
 type = excluded.type,
@@ -192,11 +650,24 @@ actor_url = excluded.actor_url,
 inbox_url = excluded.inbox_url,
 outbox_url = excluded.outbox_url,
 shared_inbox_url = excluded.shared_inbox_url,
-account_id = excluded.account_id
-RETURNING id"#;
+account_id = excluded.account_id,
+summary = excluded.summary,
+public_key_id = excluded.public_key_id,
+public_key_pem = excluded.public_key_pem,
+published = excluded.published,
+updated_at = excluded.updated_at,
+display_name = excluded.display_name,
+summary_source = excluded.summary_source,
+avatar_url = excluded.avatar_url,
+avatar_storage_key = excluded.avatar_storage_key,
+header_url = excluded.header_url,
+header_storage_key = excluded.header_storage_key,
+fields = excluded.fields
+RETURNING id, created_at"#;
 #[derive(PartialEq, Debug, Clone)]
 pub struct UpsertActorRow {
     pub actors_id: uuid::Uuid,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
 }
 pub async fn upsert_actor(
     client: &impl deadpool_postgres::GenericClient,
@@ -209,6 +680,19 @@ pub async fn upsert_actor(
     actors_outbox_url: &str,
     actors_shared_inbox_url: Option<&str>,
     actors_account_id: Option<&uuid::Uuid>,
+    actors_summary: Option<&str>,
+    actors_public_key_id: Option<&str>,
+    actors_public_key_pem: Option<&str>,
+    actors_published: Option<&chrono::DateTime<chrono::Utc>>,
+    actors_created_at: &chrono::DateTime<chrono::Utc>,
+    actors_updated_at: &chrono::DateTime<chrono::Utc>,
+    actors_display_name: Option<&str>,
+    actors_summary_source: Option<&str>,
+    actors_avatar_url: Option<&str>,
+    actors_avatar_storage_key: Option<&str>,
+    actors_header_url: Option<&str>,
+    actors_header_storage_key: Option<&str>,
+    actors_fields: &serde_json::Value,
 ) -> Result<Option<UpsertActorRow>, deadpool_postgres::tokio_postgres::Error> {
     let row = client
         .query_opt(
@@ -223,17 +707,253 @@ pub async fn upsert_actor(
                 &actors_outbox_url,
                 &actors_shared_inbox_url,
                 &actors_account_id,
+                &actors_summary,
+                &actors_public_key_id,
+                &actors_public_key_pem,
+                &actors_published,
+                &actors_created_at,
+                &actors_updated_at,
+                &actors_display_name,
+                &actors_summary_source,
+                &actors_avatar_url,
+                &actors_avatar_storage_key,
+                &actors_header_url,
+                &actors_header_storage_key,
+                &actors_fields,
             ],
         )
         .await?;
     let v = match row {
         Some(v) => UpsertActorRow {
             actors_id: v.try_get(0)?,
+            actors_created_at: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const UPDATE_ACTOR_FIELDS: &str = r#"-- name: UpdateActorFields :exec
+UPDATE actors
+SET fields = $2
+WHERE id = $1 AND fields = $3"#;
+pub async fn update_actor_fields(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_id: &uuid::Uuid,
+    actors_fields: &serde_json::Value,
+    actors_fields_2: &serde_json::Value,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            UPDATE_ACTOR_FIELDS,
+            &[&actors_id, &actors_fields, &actors_fields_2],
+        )
+        .await
+}
+pub const GET_LOCAL_CUSTOM_EMOJIS_BY_SHORTCODE: &str = r#"-- name: GetLocalCustomEmojisByShortcode :many
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE shortcode = ANY($1::TEXT []) AND host IS NULL
+ORDER BY shortcode"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetLocalCustomEmojisByShortcodeRow {
+    pub custom_emojis_id: uuid::Uuid,
+    pub custom_emojis_shortcode: String,
+    pub custom_emojis_host: Option<String>,
+    pub custom_emojis_uri: String,
+    pub custom_emojis_url: String,
+    pub custom_emojis_media_type: Option<String>,
+    pub custom_emojis_storage_key: Option<String>,
+    pub custom_emojis_created_at: chrono::DateTime<chrono::Utc>,
+    pub custom_emojis_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_local_custom_emojis_by_shortcode(
+    client: &impl deadpool_postgres::GenericClient,
+    shortcodes: &[String],
+) -> Result<
+    impl Iterator<
+        Item = Result<GetLocalCustomEmojisByShortcodeRow, deadpool_postgres::tokio_postgres::Error>,
+    >,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(GET_LOCAL_CUSTOM_EMOJIS_BY_SHORTCODE, &[&shortcodes])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetLocalCustomEmojisByShortcodeRow {
+            custom_emojis_id: r.try_get(0)?,
+            custom_emojis_shortcode: r.try_get(1)?,
+            custom_emojis_host: r.try_get(2)?,
+            custom_emojis_uri: r.try_get(3)?,
+            custom_emojis_url: r.try_get(4)?,
+            custom_emojis_media_type: r.try_get(5)?,
+            custom_emojis_storage_key: r.try_get(6)?,
+            custom_emojis_created_at: r.try_get(7)?,
+            custom_emojis_updated_at: r.try_get(8)?,
+        })
+    }))
+}
+pub const GET_CUSTOM_EMOJI_BY_SHORTCODE: &str = r#"-- name: GetCustomEmojiByShortcode :one
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE shortcode = $1 AND host IS NOT DISTINCT FROM $2"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetCustomEmojiByShortcodeRow {
+    pub custom_emojis_id: uuid::Uuid,
+    pub custom_emojis_shortcode: String,
+    pub custom_emojis_host: Option<String>,
+    pub custom_emojis_uri: String,
+    pub custom_emojis_url: String,
+    pub custom_emojis_media_type: Option<String>,
+    pub custom_emojis_storage_key: Option<String>,
+    pub custom_emojis_created_at: chrono::DateTime<chrono::Utc>,
+    pub custom_emojis_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_custom_emoji_by_shortcode(
+    client: &impl deadpool_postgres::GenericClient,
+    custom_emojis_shortcode: &str,
+    custom_emojis_host: Option<&str>,
+) -> Result<Option<GetCustomEmojiByShortcodeRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            GET_CUSTOM_EMOJI_BY_SHORTCODE,
+            &[&custom_emojis_shortcode, &custom_emojis_host],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => GetCustomEmojiByShortcodeRow {
+            custom_emojis_id: v.try_get(0)?,
+            custom_emojis_shortcode: v.try_get(1)?,
+            custom_emojis_host: v.try_get(2)?,
+            custom_emojis_uri: v.try_get(3)?,
+            custom_emojis_url: v.try_get(4)?,
+            custom_emojis_media_type: v.try_get(5)?,
+            custom_emojis_storage_key: v.try_get(6)?,
+            custom_emojis_created_at: v.try_get(7)?,
+            custom_emojis_updated_at: v.try_get(8)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const UPSERT_REMOTE_CUSTOM_EMOJI: &str = r#"-- name: UpsertRemoteCustomEmoji :one
+INSERT INTO custom_emojis (id, shortcode, host, uri, url, media_type, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+ON CONFLICT (shortcode, host) DO UPDATE SET
+uri = excluded.uri,
+url = excluded.url,
+media_type = excluded.media_type,
+updated_at = excluded.updated_at
+RETURNING id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct UpsertRemoteCustomEmojiRow {
+    pub custom_emojis_id: uuid::Uuid,
+}
+pub async fn upsert_remote_custom_emoji(
+    client: &impl deadpool_postgres::GenericClient,
+    custom_emojis_id: &uuid::Uuid,
+    custom_emojis_shortcode: &str,
+    custom_emojis_host: Option<&str>,
+    custom_emojis_uri: &str,
+    custom_emojis_url: &str,
+    custom_emojis_media_type: Option<&str>,
+    custom_emojis_created_at: &chrono::DateTime<chrono::Utc>,
+) -> Result<Option<UpsertRemoteCustomEmojiRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            UPSERT_REMOTE_CUSTOM_EMOJI,
+            &[
+                &custom_emojis_id,
+                &custom_emojis_shortcode,
+                &custom_emojis_host,
+                &custom_emojis_uri,
+                &custom_emojis_url,
+                &custom_emojis_media_type,
+                &custom_emojis_created_at,
+            ],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => UpsertRemoteCustomEmojiRow {
+            custom_emojis_id: v.try_get(0)?,
         },
         None => return Ok(None),
     };
     Ok(Some(v))
 }
+pub const DELETE_ACTOR_EMOJIS: &str = r#"-- name: DeleteActorEmojis :exec
+DELETE FROM actor_emojis
+WHERE actor_id = $1"#;
+pub async fn delete_actor_emojis(
+    client: &impl deadpool_postgres::GenericClient,
+    actor_emojis_actor_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_ACTOR_EMOJIS, &[&actor_emojis_actor_id])
+        .await
+}
+pub const INSERT_ACTOR_EMOJIS: &str = r#"-- name: InsertActorEmojis :exec
+INSERT INTO actor_emojis (actor_id, emoji_id)
+SELECT
+    $1,
+    id
+FROM custom_emojis
+WHERE id = ANY($2::UUID [])
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_actor_emojis(
+    client: &impl deadpool_postgres::GenericClient,
+    actor_emojis_actor_id: &uuid::Uuid,
+    emoji_ids: &[uuid::Uuid],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INSERT_ACTOR_EMOJIS, &[&actor_emojis_actor_id, &emoji_ids])
+        .await
+}
+pub const DELETE_NOTE_EMOJIS: &str = r#"-- name: DeleteNoteEmojis :exec
+DELETE FROM note_emojis
+WHERE note_id = $1"#;
+pub async fn delete_note_emojis(
+    client: &impl deadpool_postgres::GenericClient,
+    note_emojis_note_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_NOTE_EMOJIS, &[&note_emojis_note_id])
+        .await
+}
+pub const INSERT_NOTE_EMOJIS: &str = r#"-- name: InsertNoteEmojis :exec
+INSERT INTO note_emojis (note_id, emoji_id)
+SELECT
+    $1,
+    id
+FROM custom_emojis
+WHERE id = ANY($2::UUID [])
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_note_emojis(
+    client: &impl deadpool_postgres::GenericClient,
+    note_emojis_note_id: &uuid::Uuid,
+    emoji_ids: &[uuid::Uuid],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INSERT_NOTE_EMOJIS, &[&note_emojis_note_id, &emoji_ids])
+        .await
+}
 pub const INSERT_ACCOUNT_KEY: &str = r#"-- name: InsertAccountKey :exec
 INSERT INTO account_keys (
     account_id,
@@ -295,9 +1015,11 @@ pub async fn get_account_keys(
 pub const INSERT_NOTE_SOURCE: &str = r#"-- name: InsertNoteSource :one
 INSERT INTO note_sources (
     id,
+    note_id,
     account_id,
-    content
-) VALUES ($1, $2, $3)
+    content,
+    media_type
+) VALUES ($1, $2, $3, $4, $5)
 RETURNING id"#;
 #[derive(PartialEq, Debug, Clone)]
 pub struct InsertNoteSourceRow {
@@ -306,16 +1028,20 @@ pub struct InsertNoteSourceRow {
 pub async fn insert_note_source(
     client: &impl deadpool_postgres::GenericClient,
     note_sources_id: &uuid::Uuid,
-    note_sources_account_id: &uuid::Uuid,
+    note_sources_note_id: &uuid::Uuid,
+    note_sources_account_id: Option<&uuid::Uuid>,
     note_sources_content: &str,
+    note_sources_media_type: &str,
 ) -> Result<Option<InsertNoteSourceRow>, deadpool_postgres::tokio_postgres::Error> {
     let row = client
         .query_opt(
             INSERT_NOTE_SOURCE,
             &[
                 &note_sources_id,
+                &note_sources_note_id,
                 &note_sources_account_id,
                 &note_sources_content,
+                &note_sources_media_type,
             ],
         )
         .await?;
@@ -333,15 +1059,33 @@ INSERT INTO notes (
     actor_id,
     source_id,
     content,
-    note_url
-) VALUES ($1, $2, $3, $4, $5)"#;
+    note_url,
+    in_reply_to_id,
+    published,
+    created_at,
+    updated_at,
+    summary,
+    sensitive,
+    object_type,
+    name,
+    url
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)"#;
 pub async fn insert_note(
     client: &impl deadpool_postgres::GenericClient,
     notes_id: &uuid::Uuid,
     notes_actor_id: &uuid::Uuid,
-    notes_source_id: Option<&uuid::Uuid>,
+    notes_source_id: &uuid::Uuid,
     notes_content: &str,
     notes_note_url: &str,
+    notes_in_reply_to_id: Option<&uuid::Uuid>,
+    notes_published: &chrono::DateTime<chrono::Utc>,
+    notes_created_at: &chrono::DateTime<chrono::Utc>,
+    notes_updated_at: &chrono::DateTime<chrono::Utc>,
+    notes_summary: Option<&str>,
+    notes_sensitive: &bool,
+    notes_object_type: &str,
+    notes_name: Option<&str>,
+    notes_url: Option<&str>,
 ) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
     client
         .execute(
@@ -352,7 +1096,4201 @@ pub async fn insert_note(
                 &notes_source_id,
                 &notes_content,
                 &notes_note_url,
+                &notes_in_reply_to_id,
+                &notes_published,
+                &notes_created_at,
+                &notes_updated_at,
+                &notes_summary,
+                &notes_sensitive,
+                &notes_object_type,
+                &notes_name,
+                &notes_url,
             ],
         )
         .await
 }
+pub const GET_LOCAL_NOTE: &str = r#"-- name: GetLocalNote :one
+SELECT
+    notes.id,
+    notes.actor_id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.note_url,
+    notes.updated,
+    notes.deleted,
+    notes.published,
+    notes.created_at,
+    notes.updated_at,
+    note_sources.id AS source_id,
+    note_sources.account_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.actor_url,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', '[]'::JSON
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetLocalNoteRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_actor_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_deleted: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_created_at: chrono::DateTime<chrono::Utc>,
+    pub notes_updated_at: chrono::DateTime<chrono::Utc>,
+    pub source_id: uuid::Uuid,
+    pub note_sources_account_id: Option<uuid::Uuid>,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_actor_url: String,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_local_note(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_id: &uuid::Uuid,
+) -> Result<Option<GetLocalNoteRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client.query_opt(GET_LOCAL_NOTE, &[&notes_id]).await?;
+    let v = match row {
+        Some(v) => GetLocalNoteRow {
+            notes_id: v.try_get(0)?,
+            notes_actor_id: v.try_get(1)?,
+            notes_content: v.try_get(2)?,
+            notes_summary: v.try_get(3)?,
+            notes_sensitive: v.try_get(4)?,
+            notes_note_url: v.try_get(5)?,
+            notes_updated: v.try_get(6)?,
+            notes_deleted: v.try_get(7)?,
+            notes_published: v.try_get(8)?,
+            notes_created_at: v.try_get(9)?,
+            notes_updated_at: v.try_get(10)?,
+            source_id: v.try_get(11)?,
+            note_sources_account_id: v.try_get(12)?,
+            source_content: v.try_get(13)?,
+            source_media_type: v.try_get(14)?,
+            actors_actor_url: v.try_get(15)?,
+            attachments: v.try_get(16)?,
+            emojis: v.try_get(17)?,
+            poll: v.try_get(18)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_NOTE_TOMBSTONE: &str = r#"-- name: GetNoteTombstone :one
+SELECT deleted
+FROM note_tombstones
+WHERE id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNoteTombstoneRow {
+    pub note_tombstones_deleted: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_note_tombstone(
+    client: &impl deadpool_postgres::GenericClient,
+    note_tombstones_id: &uuid::Uuid,
+) -> Result<Option<GetNoteTombstoneRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_NOTE_TOMBSTONE, &[&note_tombstones_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetNoteTombstoneRow {
+            note_tombstones_deleted: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const UPDATE_NOTE: &str = r#"-- name: UpdateNote :exec
+UPDATE notes
+SET
+    source_id = $2,
+    content = $3,
+    updated = $4,
+    updated_at = $5,
+    summary = $6,
+    sensitive = $7,
+    object_type = $8,
+    name = $9,
+    url = $10
+WHERE id = $1 AND deleted IS NULL"#;
+pub async fn update_note(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_id: &uuid::Uuid,
+    notes_source_id: &uuid::Uuid,
+    notes_content: &str,
+    notes_updated: Option<&chrono::DateTime<chrono::Utc>>,
+    notes_updated_at: &chrono::DateTime<chrono::Utc>,
+    notes_summary: Option<&str>,
+    notes_sensitive: &bool,
+    notes_object_type: &str,
+    notes_name: Option<&str>,
+    notes_url: Option<&str>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            UPDATE_NOTE,
+            &[
+                &notes_id,
+                &notes_source_id,
+                &notes_content,
+                &notes_updated,
+                &notes_updated_at,
+                &notes_summary,
+                &notes_sensitive,
+                &notes_object_type,
+                &notes_name,
+                &notes_url,
+            ],
+        )
+        .await
+}
+pub const GET_REMOTE_NOTE_BY_URL: &str = r#"-- name: GetRemoteNoteByUrl :one
+SELECT
+    notes.id,
+    notes.actor_id,
+    notes.source_id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.in_reply_to_id,
+    notes.published,
+    notes.created_at,
+    notes.updated_at,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', '[]'::JSON
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    notes.note_url = $1
+    AND notes.deleted IS NULL
+    AND actors.account_id IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetRemoteNoteByUrlRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_actor_id: uuid::Uuid,
+    pub notes_source_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_created_at: chrono::DateTime<chrono::Utc>,
+    pub notes_updated_at: chrono::DateTime<chrono::Utc>,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_remote_note_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_note_url: &str,
+) -> Result<Option<GetRemoteNoteByUrlRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_REMOTE_NOTE_BY_URL, &[&notes_note_url])
+        .await?;
+    let v = match row {
+        Some(v) => GetRemoteNoteByUrlRow {
+            notes_id: v.try_get(0)?,
+            notes_actor_id: v.try_get(1)?,
+            notes_source_id: v.try_get(2)?,
+            notes_content: v.try_get(3)?,
+            notes_summary: v.try_get(4)?,
+            notes_sensitive: v.try_get(5)?,
+            notes_object_type: v.try_get(6)?,
+            notes_name: v.try_get(7)?,
+            notes_url: v.try_get(8)?,
+            notes_note_url: v.try_get(9)?,
+            notes_updated: v.try_get(10)?,
+            notes_in_reply_to_id: v.try_get(11)?,
+            notes_published: v.try_get(12)?,
+            notes_created_at: v.try_get(13)?,
+            notes_updated_at: v.try_get(14)?,
+            attachments: v.try_get(15)?,
+            emojis: v.try_get(16)?,
+            poll: v.try_get(17)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_NOTE_SOURCES: &str = r#"-- name: GetNoteSources :many
+SELECT
+    id,
+    content,
+    media_type
+FROM note_sources
+WHERE note_id = $1 AND deleted IS NULL
+ORDER BY id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNoteSourcesRow {
+    pub note_sources_id: uuid::Uuid,
+    pub note_sources_content: String,
+    pub note_sources_media_type: String,
+}
+pub async fn get_note_sources(
+    client: &impl deadpool_postgres::GenericClient,
+    note_sources_note_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetNoteSourcesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(GET_NOTE_SOURCES, &[&note_sources_note_id])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetNoteSourcesRow {
+            note_sources_id: r.try_get(0)?,
+            note_sources_content: r.try_get(1)?,
+            note_sources_media_type: r.try_get(2)?,
+        })
+    }))
+}
+pub const DELETE_NOTE: &str = r#"-- name: DeleteNote :exec
+UPDATE notes
+SET
+    deleted = $2,
+    updated_at = now()
+WHERE id = $1 AND deleted IS NULL"#;
+pub async fn delete_note(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_id: &uuid::Uuid,
+    notes_deleted: Option<&chrono::DateTime<chrono::Utc>>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_NOTE, &[&notes_id, &notes_deleted])
+        .await
+}
+pub const DELETE_NOTE_SOURCES: &str = r#"-- name: DeleteNoteSources :exec
+UPDATE note_sources
+SET
+    deleted = $2,
+    updated_at = now()
+WHERE note_id = $1 AND deleted IS NULL"#;
+pub async fn delete_note_sources(
+    client: &impl deadpool_postgres::GenericClient,
+    note_sources_note_id: &uuid::Uuid,
+    note_sources_deleted: Option<&chrono::DateTime<chrono::Utc>>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_NOTE_SOURCES,
+            &[&note_sources_note_id, &note_sources_deleted],
+        )
+        .await
+}
+pub const INSERT_NOTE_RECIPIENT: &str = r#"-- name: InsertNoteRecipient :exec
+INSERT INTO note_recipients (
+    note_id,
+    inbox_url
+) VALUES ($1, $2)
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_note_recipient(
+    client: &impl deadpool_postgres::GenericClient,
+    note_recipients_note_id: &uuid::Uuid,
+    note_recipients_inbox_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_NOTE_RECIPIENT,
+            &[&note_recipients_note_id, &note_recipients_inbox_url],
+        )
+        .await
+}
+pub const GET_NOTE_RECIPIENTS: &str = r#"-- name: GetNoteRecipients :many
+SELECT inbox_url
+FROM note_recipients
+WHERE note_id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNoteRecipientsRow {
+    pub note_recipients_inbox_url: String,
+}
+pub async fn get_note_recipients(
+    client: &impl deadpool_postgres::GenericClient,
+    note_recipients_note_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetNoteRecipientsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(GET_NOTE_RECIPIENTS, &[&note_recipients_note_id])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetNoteRecipientsRow {
+            note_recipients_inbox_url: r.try_get(0)?,
+        })
+    }))
+}
+pub const UPSERT_FOLLOW: &str = r#"-- name: UpsertFollow :one
+INSERT INTO follows (
+    id,
+    follower_id,
+    followee_id,
+    follow_url,
+    accepted
+) VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (follower_id, followee_id) DO UPDATE
+SET
+    follow_url = excluded.follow_url,
+    accepted = excluded.accepted
+RETURNING id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct UpsertFollowRow {
+    pub follows_id: uuid::Uuid,
+}
+pub async fn upsert_follow(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_id: &uuid::Uuid,
+    follows_follower_id: &uuid::Uuid,
+    follows_followee_id: &uuid::Uuid,
+    follows_follow_url: &str,
+    follows_accepted: &bool,
+) -> Result<Option<UpsertFollowRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            UPSERT_FOLLOW,
+            &[
+                &follows_id,
+                &follows_follower_id,
+                &follows_followee_id,
+                &follows_follow_url,
+                &follows_accepted,
+            ],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => UpsertFollowRow {
+            follows_id: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_FOLLOW: &str = r#"-- name: GetFollow :one
+SELECT
+    id,
+    follower_id,
+    followee_id,
+    follow_url,
+    accepted
+FROM follows
+WHERE follower_id = $1 AND followee_id = $2"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetFollowRow {
+    pub follows_id: uuid::Uuid,
+    pub follows_follower_id: uuid::Uuid,
+    pub follows_followee_id: uuid::Uuid,
+    pub follows_follow_url: String,
+    pub follows_accepted: bool,
+}
+pub async fn get_follow(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_follower_id: &uuid::Uuid,
+    follows_followee_id: &uuid::Uuid,
+) -> Result<Option<GetFollowRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_FOLLOW, &[&follows_follower_id, &follows_followee_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetFollowRow {
+            follows_id: v.try_get(0)?,
+            follows_follower_id: v.try_get(1)?,
+            follows_followee_id: v.try_get(2)?,
+            follows_follow_url: v.try_get(3)?,
+            follows_accepted: v.try_get(4)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const ACCEPT_FOLLOW: &str = r#"-- name: AcceptFollow :one
+UPDATE follows
+SET accepted = TRUE
+WHERE followee_id = $1 AND follow_url = $2
+RETURNING follower_id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct AcceptFollowRow {
+    pub follows_follower_id: uuid::Uuid,
+}
+pub async fn accept_follow(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_followee_id: &uuid::Uuid,
+    follows_follow_url: &str,
+) -> Result<Option<AcceptFollowRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(ACCEPT_FOLLOW, &[&follows_followee_id, &follows_follow_url])
+        .await?;
+    let v = match row {
+        Some(v) => AcceptFollowRow {
+            follows_follower_id: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const REJECT_FOLLOW: &str = r#"-- name: RejectFollow :exec
+DELETE FROM follows
+WHERE followee_id = $1 AND follow_url = $2"#;
+pub async fn reject_follow(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_followee_id: &uuid::Uuid,
+    follows_follow_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(REJECT_FOLLOW, &[&follows_followee_id, &follows_follow_url])
+        .await
+}
+pub const DELETE_FOLLOW: &str = r#"-- name: DeleteFollow :exec
+DELETE FROM follows
+WHERE follower_id = $1 AND followee_id = $2"#;
+pub async fn delete_follow(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_follower_id: &uuid::Uuid,
+    follows_followee_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_FOLLOW, &[&follows_follower_id, &follows_followee_id])
+        .await
+}
+pub const DELETE_FOLLOW_BY_URL: &str = r#"-- name: DeleteFollowByUrl :exec
+DELETE FROM follows
+WHERE follower_id = $1 AND follow_url = $2"#;
+pub async fn delete_follow_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_follower_id: &uuid::Uuid,
+    follows_follow_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_FOLLOW_BY_URL,
+            &[&follows_follower_id, &follows_follow_url],
+        )
+        .await
+}
+pub const GET_FOLLOWER_INBOXES: &str = r#"-- name: GetFollowerInboxes :many
+SELECT DISTINCT COALESCE(actors.shared_inbox_url, actors.inbox_url) AS inbox_url
+FROM follows
+INNER JOIN actors ON follows.follower_id = actors.id
+WHERE
+    follows.followee_id = $1
+    AND follows.accepted
+    AND actors.account_id IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetFollowerInboxesRow {
+    pub inbox_url: String,
+}
+pub async fn get_follower_inboxes(
+    client: &impl deadpool_postgres::GenericClient,
+    follows_followee_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetFollowerInboxesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(GET_FOLLOWER_INBOXES, &[&follows_followee_id])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetFollowerInboxesRow {
+            inbox_url: r.try_get(0)?,
+        })
+    }))
+}
+pub const GET_ACTOR_VIEW: &str = r#"-- name: GetActorView :one
+SELECT
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count
+FROM actors
+WHERE actors.id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetActorViewRow {
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+}
+pub async fn get_actor_view(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_id: &uuid::Uuid,
+) -> Result<Option<GetActorViewRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client.query_opt(GET_ACTOR_VIEW, &[&actors_id]).await?;
+    let v = match row {
+        Some(v) => GetActorViewRow {
+            actors_id: v.try_get(0)?,
+            actors_type: v.try_get(1)?,
+            actors_name: v.try_get(2)?,
+            actors_host: v.try_get(3)?,
+            actors_actor_url: v.try_get(4)?,
+            actors_inbox_url: v.try_get(5)?,
+            actors_outbox_url: v.try_get(6)?,
+            actors_shared_inbox_url: v.try_get(7)?,
+            actors_summary: v.try_get(8)?,
+            actors_public_key_id: v.try_get(9)?,
+            actors_public_key_pem: v.try_get(10)?,
+            actors_account_id: v.try_get(11)?,
+            actors_published: v.try_get(12)?,
+            actors_created_at: v.try_get(13)?,
+            actors_updated_at: v.try_get(14)?,
+            actors_display_name: v.try_get(15)?,
+            actors_summary_source: v.try_get(16)?,
+            actors_avatar_url: v.try_get(17)?,
+            actors_avatar_storage_key: v.try_get(18)?,
+            actors_header_url: v.try_get(19)?,
+            actors_header_storage_key: v.try_get(20)?,
+            actors_fields: v.try_get(21)?,
+            actors_emojis: v.try_get(22)?,
+            followers_count: v.try_get(23)?,
+            following_count: v.try_get(24)?,
+            statuses_count: v.try_get(25)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_NOTE_VIEW: &str = r#"-- name: GetNoteView :one
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.id = $1 AND notes.deleted IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNoteViewRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub in_reply_to_actor_id: Option<uuid::Uuid>,
+    pub source_id: uuid::Uuid,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+    pub favourites_count: i64,
+    pub reblogs_count: i64,
+    pub favourited: bool,
+    pub reblogged: bool,
+    pub reactions: serde_json::Value,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_note_view(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_id: &uuid::Uuid,
+    viewers_account_id: Option<&uuid::Uuid>,
+) -> Result<Option<GetNoteViewRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_NOTE_VIEW, &[&notes_id, &viewers_account_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetNoteViewRow {
+            notes_id: v.try_get(0)?,
+            notes_content: v.try_get(1)?,
+            notes_summary: v.try_get(2)?,
+            notes_sensitive: v.try_get(3)?,
+            notes_object_type: v.try_get(4)?,
+            notes_name: v.try_get(5)?,
+            notes_url: v.try_get(6)?,
+            notes_note_url: v.try_get(7)?,
+            notes_updated: v.try_get(8)?,
+            notes_published: v.try_get(9)?,
+            notes_in_reply_to_id: v.try_get(10)?,
+            in_reply_to_actor_id: v.try_get(11)?,
+            source_id: v.try_get(12)?,
+            source_content: v.try_get(13)?,
+            source_media_type: v.try_get(14)?,
+            actors_id: v.try_get(15)?,
+            actors_type: v.try_get(16)?,
+            actors_name: v.try_get(17)?,
+            actors_host: v.try_get(18)?,
+            actors_actor_url: v.try_get(19)?,
+            actors_inbox_url: v.try_get(20)?,
+            actors_outbox_url: v.try_get(21)?,
+            actors_shared_inbox_url: v.try_get(22)?,
+            actors_summary: v.try_get(23)?,
+            actors_public_key_id: v.try_get(24)?,
+            actors_public_key_pem: v.try_get(25)?,
+            actors_account_id: v.try_get(26)?,
+            actors_published: v.try_get(27)?,
+            actors_created_at: v.try_get(28)?,
+            actors_updated_at: v.try_get(29)?,
+            actors_display_name: v.try_get(30)?,
+            actors_summary_source: v.try_get(31)?,
+            actors_avatar_url: v.try_get(32)?,
+            actors_avatar_storage_key: v.try_get(33)?,
+            actors_header_url: v.try_get(34)?,
+            actors_header_storage_key: v.try_get(35)?,
+            actors_fields: v.try_get(36)?,
+            actors_emojis: v.try_get(37)?,
+            followers_count: v.try_get(38)?,
+            following_count: v.try_get(39)?,
+            statuses_count: v.try_get(40)?,
+            favourites_count: v.try_get(41)?,
+            reblogs_count: v.try_get(42)?,
+            favourited: v.try_get(43)?,
+            reblogged: v.try_get(44)?,
+            reactions: v.try_get(45)?,
+            attachments: v.try_get(46)?,
+            emojis: v.try_get(47)?,
+            poll: v.try_get(48)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_ACTOR_NOTE_VIEWS: &str = r#"-- name: GetActorNoteViews :many
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.actor_id = $1 AND notes.deleted IS NULL
+ORDER BY notes.id DESC
+LIMIT $3"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetActorNoteViewsRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub in_reply_to_actor_id: Option<uuid::Uuid>,
+    pub source_id: uuid::Uuid,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+    pub favourites_count: i64,
+    pub reblogs_count: i64,
+    pub favourited: bool,
+    pub reblogged: bool,
+    pub reactions: serde_json::Value,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_actor_note_views(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_actor_id: &uuid::Uuid,
+    viewers_account_id: Option<&uuid::Uuid>,
+    limit: &i64,
+) -> Result<
+    impl Iterator<Item = Result<GetActorNoteViewsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_ACTOR_NOTE_VIEWS,
+            &[&notes_actor_id, &viewers_account_id, &limit],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetActorNoteViewsRow {
+            notes_id: r.try_get(0)?,
+            notes_content: r.try_get(1)?,
+            notes_summary: r.try_get(2)?,
+            notes_sensitive: r.try_get(3)?,
+            notes_object_type: r.try_get(4)?,
+            notes_name: r.try_get(5)?,
+            notes_url: r.try_get(6)?,
+            notes_note_url: r.try_get(7)?,
+            notes_updated: r.try_get(8)?,
+            notes_published: r.try_get(9)?,
+            notes_in_reply_to_id: r.try_get(10)?,
+            in_reply_to_actor_id: r.try_get(11)?,
+            source_id: r.try_get(12)?,
+            source_content: r.try_get(13)?,
+            source_media_type: r.try_get(14)?,
+            actors_id: r.try_get(15)?,
+            actors_type: r.try_get(16)?,
+            actors_name: r.try_get(17)?,
+            actors_host: r.try_get(18)?,
+            actors_actor_url: r.try_get(19)?,
+            actors_inbox_url: r.try_get(20)?,
+            actors_outbox_url: r.try_get(21)?,
+            actors_shared_inbox_url: r.try_get(22)?,
+            actors_summary: r.try_get(23)?,
+            actors_public_key_id: r.try_get(24)?,
+            actors_public_key_pem: r.try_get(25)?,
+            actors_account_id: r.try_get(26)?,
+            actors_published: r.try_get(27)?,
+            actors_created_at: r.try_get(28)?,
+            actors_updated_at: r.try_get(29)?,
+            actors_display_name: r.try_get(30)?,
+            actors_summary_source: r.try_get(31)?,
+            actors_avatar_url: r.try_get(32)?,
+            actors_avatar_storage_key: r.try_get(33)?,
+            actors_header_url: r.try_get(34)?,
+            actors_header_storage_key: r.try_get(35)?,
+            actors_fields: r.try_get(36)?,
+            actors_emojis: r.try_get(37)?,
+            followers_count: r.try_get(38)?,
+            following_count: r.try_get(39)?,
+            statuses_count: r.try_get(40)?,
+            favourites_count: r.try_get(41)?,
+            reblogs_count: r.try_get(42)?,
+            favourited: r.try_get(43)?,
+            reblogged: r.try_get(44)?,
+            reactions: r.try_get(45)?,
+            attachments: r.try_get(46)?,
+            emojis: r.try_get(47)?,
+            poll: r.try_get(48)?,
+        })
+    }))
+}
+pub const GET_NOTE_BY_URL: &str = r#"-- name: GetNoteByUrl :one
+SELECT
+    id,
+    actor_id
+FROM notes
+WHERE note_url = $1 AND deleted IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNoteByUrlRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_actor_id: uuid::Uuid,
+}
+pub async fn get_note_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_note_url: &str,
+) -> Result<Option<GetNoteByUrlRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_NOTE_BY_URL, &[&notes_note_url])
+        .await?;
+    let v = match row {
+        Some(v) => GetNoteByUrlRow {
+            notes_id: v.try_get(0)?,
+            notes_actor_id: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_FAVOURITE: &str = r#"-- name: InsertFavourite :exec
+INSERT INTO favourites (
+    id,
+    actor_id,
+    note_id,
+    like_url
+) VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_favourite(
+    client: &impl deadpool_postgres::GenericClient,
+    favourites_id: &uuid::Uuid,
+    favourites_actor_id: &uuid::Uuid,
+    favourites_note_id: &uuid::Uuid,
+    favourites_like_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_FAVOURITE,
+            &[
+                &favourites_id,
+                &favourites_actor_id,
+                &favourites_note_id,
+                &favourites_like_url,
+            ],
+        )
+        .await
+}
+pub const DELETE_FAVOURITE: &str = r#"-- name: DeleteFavourite :one
+DELETE FROM favourites
+WHERE actor_id = $1 AND note_id = $2
+RETURNING like_url"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct DeleteFavouriteRow {
+    pub favourites_like_url: String,
+}
+pub async fn delete_favourite(
+    client: &impl deadpool_postgres::GenericClient,
+    favourites_actor_id: &uuid::Uuid,
+    favourites_note_id: &uuid::Uuid,
+) -> Result<Option<DeleteFavouriteRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            DELETE_FAVOURITE,
+            &[&favourites_actor_id, &favourites_note_id],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => DeleteFavouriteRow {
+            favourites_like_url: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_FAVOURITE_BY_URL: &str = r#"-- name: DeleteFavouriteByUrl :exec
+DELETE FROM favourites
+WHERE actor_id = $1 AND like_url = $2"#;
+pub async fn delete_favourite_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    favourites_actor_id: &uuid::Uuid,
+    favourites_like_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_FAVOURITE_BY_URL,
+            &[&favourites_actor_id, &favourites_like_url],
+        )
+        .await
+}
+pub const INSERT_EMOJI_REACTION: &str = r#"-- name: InsertEmojiReaction :exec
+INSERT INTO emoji_reactions (
+    id,
+    actor_id,
+    note_id,
+    reaction_url,
+    content,
+    emoji_id
+) VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_emoji_reaction(
+    client: &impl deadpool_postgres::GenericClient,
+    emoji_reactions_id: &uuid::Uuid,
+    emoji_reactions_actor_id: &uuid::Uuid,
+    emoji_reactions_note_id: &uuid::Uuid,
+    emoji_reactions_reaction_url: &str,
+    emoji_reactions_content: &str,
+    emoji_reactions_emoji_id: Option<&uuid::Uuid>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_EMOJI_REACTION,
+            &[
+                &emoji_reactions_id,
+                &emoji_reactions_actor_id,
+                &emoji_reactions_note_id,
+                &emoji_reactions_reaction_url,
+                &emoji_reactions_content,
+                &emoji_reactions_emoji_id,
+            ],
+        )
+        .await
+}
+pub const DELETE_EMOJI_REACTION: &str = r#"-- name: DeleteEmojiReaction :one
+DELETE FROM emoji_reactions
+WHERE actor_id = $1 AND note_id = $2 AND content = $3
+RETURNING reaction_url"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct DeleteEmojiReactionRow {
+    pub emoji_reactions_reaction_url: String,
+}
+pub async fn delete_emoji_reaction(
+    client: &impl deadpool_postgres::GenericClient,
+    emoji_reactions_actor_id: &uuid::Uuid,
+    emoji_reactions_note_id: &uuid::Uuid,
+    emoji_reactions_content: &str,
+) -> Result<Option<DeleteEmojiReactionRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            DELETE_EMOJI_REACTION,
+            &[
+                &emoji_reactions_actor_id,
+                &emoji_reactions_note_id,
+                &emoji_reactions_content,
+            ],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => DeleteEmojiReactionRow {
+            emoji_reactions_reaction_url: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_EMOJI_REACTION_BY_URL: &str = r#"-- name: DeleteEmojiReactionByUrl :exec
+DELETE FROM emoji_reactions
+WHERE actor_id = $1 AND reaction_url = $2"#;
+pub async fn delete_emoji_reaction_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    emoji_reactions_actor_id: &uuid::Uuid,
+    emoji_reactions_reaction_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_EMOJI_REACTION_BY_URL,
+            &[&emoji_reactions_actor_id, &emoji_reactions_reaction_url],
+        )
+        .await
+}
+pub const UPSERT_POLL: &str = r#"-- name: UpsertPoll :exec
+INSERT INTO polls (note_id, multiple, expires_at, closed_at, voters_count)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (note_id) DO UPDATE SET
+multiple = excluded.multiple,
+expires_at = excluded.expires_at,
+closed_at = excluded.closed_at,
+voters_count = excluded.voters_count"#;
+pub async fn upsert_poll(
+    client: &impl deadpool_postgres::GenericClient,
+    polls_note_id: &uuid::Uuid,
+    polls_multiple: &bool,
+    polls_expires_at: Option<&chrono::DateTime<chrono::Utc>>,
+    polls_closed_at: Option<&chrono::DateTime<chrono::Utc>>,
+    polls_voters_count: Option<&i64>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            UPSERT_POLL,
+            &[
+                &polls_note_id,
+                &polls_multiple,
+                &polls_expires_at,
+                &polls_closed_at,
+                &polls_voters_count,
+            ],
+        )
+        .await
+}
+pub const DELETE_POLL: &str = r#"-- name: DeletePoll :exec
+DELETE FROM polls
+WHERE note_id = $1"#;
+pub async fn delete_poll(
+    client: &impl deadpool_postgres::GenericClient,
+    polls_note_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(DELETE_POLL, &[&polls_note_id]).await
+}
+pub const DELETE_CHANGED_POLL_OPTIONS: &str = r#"-- name: DeleteChangedPollOptions :exec
+-- options not at the same position with the same title any more, and their votes
+DELETE FROM poll_options
+WHERE
+    note_id = $1
+    AND (
+        position >= cardinality($2::TEXT [])
+        OR title IS DISTINCT FROM ($2::TEXT [])[position + 1]
+    )"#;
+pub async fn delete_changed_poll_options(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_options_note_id: &uuid::Uuid,
+    titles: &[String],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_CHANGED_POLL_OPTIONS,
+            &[&poll_options_note_id, &titles],
+        )
+        .await
+}
+pub const UPSERT_POLL_OPTION: &str = r#"-- name: UpsertPollOption :exec
+INSERT INTO poll_options (note_id, position, title, votes_count)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (note_id, position) DO UPDATE SET
+votes_count = excluded.votes_count"#;
+pub async fn upsert_poll_option(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_options_note_id: &uuid::Uuid,
+    poll_options_position: &i32,
+    poll_options_title: &str,
+    poll_options_votes_count: Option<&i64>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            UPSERT_POLL_OPTION,
+            &[
+                &poll_options_note_id,
+                &poll_options_position,
+                &poll_options_title,
+                &poll_options_votes_count,
+            ],
+        )
+        .await
+}
+pub const LOCK_POLL: &str = r#"-- name: LockPoll :one
+-- the votes of an actor are checked and recorded by one transaction at a time
+SELECT note_id
+FROM polls
+WHERE note_id = $1
+FOR UPDATE"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct LockPollRow {
+    pub polls_note_id: uuid::Uuid,
+}
+pub async fn lock_poll(
+    client: &impl deadpool_postgres::GenericClient,
+    polls_note_id: &uuid::Uuid,
+) -> Result<Option<LockPollRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client.query_opt(LOCK_POLL, &[&polls_note_id]).await?;
+    let v = match row {
+        Some(v) => LockPollRow {
+            polls_note_id: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_POLL_VOTE: &str = r#"-- name: InsertPollVote :exec
+INSERT INTO poll_votes (id, note_id, actor_id, choice, vote_url)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_poll_vote(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_votes_id: &uuid::Uuid,
+    poll_votes_note_id: &uuid::Uuid,
+    poll_votes_actor_id: &uuid::Uuid,
+    poll_votes_choice: &i32,
+    poll_votes_vote_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_POLL_VOTE,
+            &[
+                &poll_votes_id,
+                &poll_votes_note_id,
+                &poll_votes_actor_id,
+                &poll_votes_choice,
+                &poll_votes_vote_url,
+            ],
+        )
+        .await
+}
+pub const INCREMENT_POLL_VOTES: &str = r#"-- name: IncrementPollVotes :exec
+-- only remote polls have their counts stored
+UPDATE poll_options SET votes_count = votes_count + 1
+WHERE note_id = $1 AND position = ANY($2::INTEGER [])"#;
+pub async fn increment_poll_votes(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_options_note_id: &uuid::Uuid,
+    choices: &[i32],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INCREMENT_POLL_VOTES, &[&poll_options_note_id, &choices])
+        .await
+}
+pub const INCREMENT_POLL_VOTERS: &str = r#"-- name: IncrementPollVoters :exec
+UPDATE polls SET voters_count = voters_count + 1
+WHERE note_id = $1"#;
+pub async fn increment_poll_voters(
+    client: &impl deadpool_postgres::GenericClient,
+    polls_note_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INCREMENT_POLL_VOTERS, &[&polls_note_id])
+        .await
+}
+pub const GET_POLL_CHOICES: &str = r#"-- name: GetPollChoices :many
+SELECT choice
+FROM poll_votes
+WHERE note_id = $1 AND actor_id = $2
+ORDER BY choice"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetPollChoicesRow {
+    pub poll_votes_choice: i32,
+}
+pub async fn get_poll_choices(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_votes_note_id: &uuid::Uuid,
+    poll_votes_actor_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetPollChoicesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_POLL_CHOICES,
+            &[&poll_votes_note_id, &poll_votes_actor_id],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetPollChoicesRow {
+            poll_votes_choice: r.try_get(0)?,
+        })
+    }))
+}
+pub const CLOSE_EXPIRED_POLLS: &str = r#"-- name: CloseExpiredPolls :many
+UPDATE polls SET closed_at = $1
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    polls.note_id = notes.id
+    AND actors.account_id IS NOT NULL
+    AND polls.closed_at IS NULL
+    AND polls.expires_at <= $1
+RETURNING polls.note_id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct CloseExpiredPollsRow {
+    pub polls_note_id: uuid::Uuid,
+}
+pub async fn close_expired_polls(
+    client: &impl deadpool_postgres::GenericClient,
+    polls_closed_at: &chrono::DateTime<chrono::Utc>,
+) -> Result<
+    impl Iterator<Item = Result<CloseExpiredPollsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(CLOSE_EXPIRED_POLLS, &[&polls_closed_at])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(CloseExpiredPollsRow {
+            polls_note_id: r.try_get(0)?,
+        })
+    }))
+}
+pub const GET_POLL_VOTER_INBOXES: &str = r#"-- name: GetPollVoterInboxes :many
+SELECT DISTINCT COALESCE(actors.shared_inbox_url, actors.inbox_url) AS inbox_url
+FROM poll_votes
+INNER JOIN actors ON poll_votes.actor_id = actors.id
+WHERE
+    poll_votes.note_id = $1
+    AND actors.account_id IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetPollVoterInboxesRow {
+    pub inbox_url: String,
+}
+pub async fn get_poll_voter_inboxes(
+    client: &impl deadpool_postgres::GenericClient,
+    poll_votes_note_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetPollVoterInboxesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(GET_POLL_VOTER_INBOXES, &[&poll_votes_note_id])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetPollVoterInboxesRow {
+            inbox_url: r.try_get(0)?,
+        })
+    }))
+}
+pub const INSERT_REBLOG: &str = r#"-- name: InsertReblog :exec
+INSERT INTO reblogs (
+    id,
+    actor_id,
+    note_id,
+    announce_url
+) VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_reblog(
+    client: &impl deadpool_postgres::GenericClient,
+    reblogs_id: &uuid::Uuid,
+    reblogs_actor_id: &uuid::Uuid,
+    reblogs_note_id: &uuid::Uuid,
+    reblogs_announce_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_REBLOG,
+            &[
+                &reblogs_id,
+                &reblogs_actor_id,
+                &reblogs_note_id,
+                &reblogs_announce_url,
+            ],
+        )
+        .await
+}
+pub const GET_REBLOG: &str = r#"-- name: GetReblog :one
+SELECT
+    id,
+    announce_url
+FROM reblogs
+WHERE actor_id = $1 AND note_id = $2"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetReblogRow {
+    pub reblogs_id: uuid::Uuid,
+    pub reblogs_announce_url: String,
+}
+pub async fn get_reblog(
+    client: &impl deadpool_postgres::GenericClient,
+    reblogs_actor_id: &uuid::Uuid,
+    reblogs_note_id: &uuid::Uuid,
+) -> Result<Option<GetReblogRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_REBLOG, &[&reblogs_actor_id, &reblogs_note_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetReblogRow {
+            reblogs_id: v.try_get(0)?,
+            reblogs_announce_url: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_REBLOG: &str = r#"-- name: DeleteReblog :one
+DELETE FROM reblogs
+WHERE actor_id = $1 AND note_id = $2
+RETURNING id, announce_url"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct DeleteReblogRow {
+    pub reblogs_id: uuid::Uuid,
+    pub reblogs_announce_url: String,
+}
+pub async fn delete_reblog(
+    client: &impl deadpool_postgres::GenericClient,
+    reblogs_actor_id: &uuid::Uuid,
+    reblogs_note_id: &uuid::Uuid,
+) -> Result<Option<DeleteReblogRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(DELETE_REBLOG, &[&reblogs_actor_id, &reblogs_note_id])
+        .await?;
+    let v = match row {
+        Some(v) => DeleteReblogRow {
+            reblogs_id: v.try_get(0)?,
+            reblogs_announce_url: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_REBLOG_BY_URL: &str = r#"-- name: DeleteReblogByUrl :one
+DELETE FROM reblogs
+WHERE actor_id = $1 AND announce_url = $2
+RETURNING id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct DeleteReblogByUrlRow {
+    pub reblogs_id: uuid::Uuid,
+}
+pub async fn delete_reblog_by_url(
+    client: &impl deadpool_postgres::GenericClient,
+    reblogs_actor_id: &uuid::Uuid,
+    reblogs_announce_url: &str,
+) -> Result<Option<DeleteReblogByUrlRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            DELETE_REBLOG_BY_URL,
+            &[&reblogs_actor_id, &reblogs_announce_url],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => DeleteReblogByUrlRow {
+            reblogs_id: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_HOME_TIMELINE: &str = r#"-- name: GetHomeTimeline :many
+SELECT
+    home_feed.entry_id,
+    reblogs.actor_id AS reblog_actor_id,
+    reblogs.announce_url AS reblog_announce_url,
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $1
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $1
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $1
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $1
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM home_feed
+INNER JOIN notes ON home_feed.note_id = notes.id
+LEFT JOIN reblogs ON home_feed.reblog_id = reblogs.id
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    home_feed.account_id = $1
+    AND notes.deleted IS NULL
+    AND ($2::UUID IS NULL OR home_feed.entry_id < $2)
+    AND ($3::UUID IS NULL OR home_feed.entry_id > $3)
+ORDER BY
+    CASE WHEN $4 THEN home_feed.entry_id END ASC,
+    home_feed.entry_id DESC
+LIMIT $5"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetHomeTimelineRow {
+    pub home_feed_entry_id: uuid::Uuid,
+    pub reblog_actor_id: Option<uuid::Uuid>,
+    pub reblog_announce_url: Option<String>,
+    pub notes_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub in_reply_to_actor_id: Option<uuid::Uuid>,
+    pub source_id: uuid::Uuid,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+    pub favourites_count: i64,
+    pub reblogs_count: i64,
+    pub favourited: bool,
+    pub reblogged: bool,
+    pub reactions: serde_json::Value,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_home_timeline(
+    client: &impl deadpool_postgres::GenericClient,
+    home_feed_account_id: &uuid::Uuid,
+    max_id: Option<&uuid::Uuid>,
+    min_id: Option<&uuid::Uuid>,
+    ascending: &bool,
+    limit: &i64,
+) -> Result<
+    impl Iterator<Item = Result<GetHomeTimelineRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_HOME_TIMELINE,
+            &[&home_feed_account_id, &max_id, &min_id, &ascending, &limit],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetHomeTimelineRow {
+            home_feed_entry_id: r.try_get(0)?,
+            reblog_actor_id: r.try_get(1)?,
+            reblog_announce_url: r.try_get(2)?,
+            notes_id: r.try_get(3)?,
+            notes_content: r.try_get(4)?,
+            notes_summary: r.try_get(5)?,
+            notes_sensitive: r.try_get(6)?,
+            notes_object_type: r.try_get(7)?,
+            notes_name: r.try_get(8)?,
+            notes_url: r.try_get(9)?,
+            notes_note_url: r.try_get(10)?,
+            notes_updated: r.try_get(11)?,
+            notes_published: r.try_get(12)?,
+            notes_in_reply_to_id: r.try_get(13)?,
+            in_reply_to_actor_id: r.try_get(14)?,
+            source_id: r.try_get(15)?,
+            source_content: r.try_get(16)?,
+            source_media_type: r.try_get(17)?,
+            actors_id: r.try_get(18)?,
+            actors_type: r.try_get(19)?,
+            actors_name: r.try_get(20)?,
+            actors_host: r.try_get(21)?,
+            actors_actor_url: r.try_get(22)?,
+            actors_inbox_url: r.try_get(23)?,
+            actors_outbox_url: r.try_get(24)?,
+            actors_shared_inbox_url: r.try_get(25)?,
+            actors_summary: r.try_get(26)?,
+            actors_public_key_id: r.try_get(27)?,
+            actors_public_key_pem: r.try_get(28)?,
+            actors_account_id: r.try_get(29)?,
+            actors_published: r.try_get(30)?,
+            actors_created_at: r.try_get(31)?,
+            actors_updated_at: r.try_get(32)?,
+            actors_display_name: r.try_get(33)?,
+            actors_summary_source: r.try_get(34)?,
+            actors_avatar_url: r.try_get(35)?,
+            actors_avatar_storage_key: r.try_get(36)?,
+            actors_header_url: r.try_get(37)?,
+            actors_header_storage_key: r.try_get(38)?,
+            actors_fields: r.try_get(39)?,
+            actors_emojis: r.try_get(40)?,
+            followers_count: r.try_get(41)?,
+            following_count: r.try_get(42)?,
+            statuses_count: r.try_get(43)?,
+            favourites_count: r.try_get(44)?,
+            reblogs_count: r.try_get(45)?,
+            favourited: r.try_get(46)?,
+            reblogged: r.try_get(47)?,
+            reactions: r.try_get(48)?,
+            attachments: r.try_get(49)?,
+            emojis: r.try_get(50)?,
+            poll: r.try_get(51)?,
+        })
+    }))
+}
+pub const GET_HOME_FEED_ENTRY: &str = r#"-- name: GetHomeFeedEntry :one
+SELECT
+    home_feed.entry_id,
+    reblogs.actor_id AS reblog_actor_id,
+    reblogs.announce_url AS reblog_announce_url,
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $1
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $1
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $1
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $1
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM home_feed
+INNER JOIN notes ON home_feed.note_id = notes.id
+LEFT JOIN reblogs ON home_feed.reblog_id = reblogs.id
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    home_feed.account_id = $1
+    AND home_feed.entry_id = $2
+    AND notes.deleted IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetHomeFeedEntryRow {
+    pub home_feed_entry_id: uuid::Uuid,
+    pub reblog_actor_id: Option<uuid::Uuid>,
+    pub reblog_announce_url: Option<String>,
+    pub notes_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub in_reply_to_actor_id: Option<uuid::Uuid>,
+    pub source_id: uuid::Uuid,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+    pub favourites_count: i64,
+    pub reblogs_count: i64,
+    pub favourited: bool,
+    pub reblogged: bool,
+    pub reactions: serde_json::Value,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_home_feed_entry(
+    client: &impl deadpool_postgres::GenericClient,
+    home_feed_account_id: &uuid::Uuid,
+    home_feed_entry_id: &uuid::Uuid,
+) -> Result<Option<GetHomeFeedEntryRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            GET_HOME_FEED_ENTRY,
+            &[&home_feed_account_id, &home_feed_entry_id],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => GetHomeFeedEntryRow {
+            home_feed_entry_id: v.try_get(0)?,
+            reblog_actor_id: v.try_get(1)?,
+            reblog_announce_url: v.try_get(2)?,
+            notes_id: v.try_get(3)?,
+            notes_content: v.try_get(4)?,
+            notes_summary: v.try_get(5)?,
+            notes_sensitive: v.try_get(6)?,
+            notes_object_type: v.try_get(7)?,
+            notes_name: v.try_get(8)?,
+            notes_url: v.try_get(9)?,
+            notes_note_url: v.try_get(10)?,
+            notes_updated: v.try_get(11)?,
+            notes_published: v.try_get(12)?,
+            notes_in_reply_to_id: v.try_get(13)?,
+            in_reply_to_actor_id: v.try_get(14)?,
+            source_id: v.try_get(15)?,
+            source_content: v.try_get(16)?,
+            source_media_type: v.try_get(17)?,
+            actors_id: v.try_get(18)?,
+            actors_type: v.try_get(19)?,
+            actors_name: v.try_get(20)?,
+            actors_host: v.try_get(21)?,
+            actors_actor_url: v.try_get(22)?,
+            actors_inbox_url: v.try_get(23)?,
+            actors_outbox_url: v.try_get(24)?,
+            actors_shared_inbox_url: v.try_get(25)?,
+            actors_summary: v.try_get(26)?,
+            actors_public_key_id: v.try_get(27)?,
+            actors_public_key_pem: v.try_get(28)?,
+            actors_account_id: v.try_get(29)?,
+            actors_published: v.try_get(30)?,
+            actors_created_at: v.try_get(31)?,
+            actors_updated_at: v.try_get(32)?,
+            actors_display_name: v.try_get(33)?,
+            actors_summary_source: v.try_get(34)?,
+            actors_avatar_url: v.try_get(35)?,
+            actors_avatar_storage_key: v.try_get(36)?,
+            actors_header_url: v.try_get(37)?,
+            actors_header_storage_key: v.try_get(38)?,
+            actors_fields: v.try_get(39)?,
+            actors_emojis: v.try_get(40)?,
+            followers_count: v.try_get(41)?,
+            following_count: v.try_get(42)?,
+            statuses_count: v.try_get(43)?,
+            favourites_count: v.try_get(44)?,
+            reblogs_count: v.try_get(45)?,
+            favourited: v.try_get(46)?,
+            reblogged: v.try_get(47)?,
+            reactions: v.try_get(48)?,
+            attachments: v.try_get(49)?,
+            emojis: v.try_get(50)?,
+            poll: v.try_get(51)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const FAN_OUT_NOTE: &str = r#"-- name: FanOutNote :exec
+INSERT INTO home_feed (account_id, entry_id, note_id)
+SELECT
+    actors.account_id,
+    $1,
+    $1
+FROM actors
+WHERE
+    actors.account_id IS NOT NULL
+    AND (
+        actors.id = $2
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $2 AND follows.accepted
+        )
+    )
+ON CONFLICT DO NOTHING"#;
+pub async fn fan_out_note(
+    client: &impl deadpool_postgres::GenericClient,
+    notes_id: &uuid::Uuid,
+    actors_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(FAN_OUT_NOTE, &[&notes_id, &actors_id]).await
+}
+pub const FAN_OUT_REBLOG: &str = r#"-- name: FanOutReblog :exec
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    actors.account_id,
+    $1,
+    $2,
+    $1
+FROM actors
+WHERE
+    actors.account_id IS NOT NULL
+    AND (
+        actors.id = $3
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $3 AND follows.accepted
+        )
+    )
+ON CONFLICT DO NOTHING"#;
+pub async fn fan_out_reblog(
+    client: &impl deadpool_postgres::GenericClient,
+    reblogs_id: &uuid::Uuid,
+    reblogs_note_id: &uuid::Uuid,
+    actors_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(FAN_OUT_REBLOG, &[&reblogs_id, &reblogs_note_id, &actors_id])
+        .await
+}
+pub const TRIM_HOME_FEEDS: &str = r#"-- name: TrimHomeFeeds :exec
+DELETE FROM home_feed
+USING actors,
+    LATERAL (
+        SELECT kept.entry_id FROM home_feed AS kept
+        WHERE kept.account_id = actors.account_id
+        ORDER BY kept.entry_id DESC
+        OFFSET $2
+        LIMIT 1
+    ) AS oldest
+WHERE
+    (
+        actors.id = $1
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $1 AND follows.accepted
+        )
+    )
+    AND home_feed.account_id = actors.account_id
+    AND home_feed.entry_id <= oldest.entry_id"#;
+pub async fn trim_home_feeds(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_id: &uuid::Uuid,
+    keep: &i64,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(TRIM_HOME_FEEDS, &[&actors_id, &keep]).await
+}
+pub const MERGE_HOME_FEED: &str = r#"-- name: MergeHomeFeed :exec
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    followers.account_id,
+    entries.entry_id,
+    entries.note_id,
+    entries.reblog_id
+FROM actors AS followers
+CROSS JOIN (
+    (
+        SELECT
+            notes.id AS entry_id,
+            notes.id AS note_id,
+            NULL::UUID AS reblog_id
+        FROM notes
+        WHERE notes.actor_id = $2 AND notes.deleted IS NULL
+        ORDER BY notes.id DESC
+        LIMIT $3
+    )
+    UNION ALL
+    (
+        SELECT
+            reblogs.id,
+            reblogs.note_id,
+            reblogs.id
+        FROM reblogs
+        WHERE reblogs.actor_id = $2
+        ORDER BY reblogs.id DESC
+        LIMIT $3
+    )
+) AS entries
+WHERE followers.id = $1 AND followers.account_id IS NOT NULL
+ON CONFLICT DO NOTHING"#;
+pub async fn merge_home_feed(
+    client: &impl deadpool_postgres::GenericClient,
+    followers_id: &uuid::Uuid,
+    followee_id: &uuid::Uuid,
+    limit: &i64,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(MERGE_HOME_FEED, &[&followers_id, &followee_id, &limit])
+        .await
+}
+pub const UNMERGE_HOME_FEED: &str = r#"-- name: UnmergeHomeFeed :exec
+DELETE FROM home_feed
+USING actors AS followers, notes
+WHERE
+    followers.id = $1
+    AND home_feed.account_id = followers.account_id
+    AND home_feed.note_id = notes.id
+    AND (
+        (home_feed.reblog_id IS NULL AND notes.actor_id = $2)
+        OR home_feed.reblog_id IN (
+            SELECT reblogs.id FROM reblogs
+            WHERE reblogs.actor_id = $2
+        )
+    )"#;
+pub async fn unmerge_home_feed(
+    client: &impl deadpool_postgres::GenericClient,
+    followers_id: &uuid::Uuid,
+    followee_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(UNMERGE_HOME_FEED, &[&followers_id, &followee_id])
+        .await
+}
+pub const TRIM_HOME_FEED: &str = r#"-- name: TrimHomeFeed :exec
+DELETE FROM home_feed
+USING actors
+WHERE
+    actors.id = $1
+    AND home_feed.account_id = actors.account_id
+    AND home_feed.entry_id <= (
+        SELECT kept.entry_id FROM home_feed AS kept
+        WHERE kept.account_id = actors.account_id
+        ORDER BY kept.entry_id DESC
+        OFFSET $2
+        LIMIT 1
+    )"#;
+pub async fn trim_home_feed(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_id: &uuid::Uuid,
+    keep: &i64,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(TRIM_HOME_FEED, &[&actors_id, &keep]).await
+}
+pub const CLEAR_HOME_FEED: &str = r#"-- name: ClearHomeFeed :exec
+DELETE FROM home_feed
+WHERE home_feed.account_id = $1"#;
+pub async fn clear_home_feed(
+    client: &impl deadpool_postgres::GenericClient,
+    home_feed_account_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(CLEAR_HOME_FEED, &[&home_feed_account_id])
+        .await
+}
+pub const REBUILD_HOME_FEED: &str = r#"-- name: RebuildHomeFeed :exec
+WITH viewer_actors AS (
+    SELECT actors.id FROM actors WHERE actors.account_id = $1
+),
+
+sources AS (
+    SELECT viewer_actors.id FROM viewer_actors
+    UNION
+    SELECT follows.followee_id FROM follows
+    WHERE follows.follower_id IN (SELECT viewer_actors.id FROM viewer_actors) AND follows.accepted
+),
+
+entries AS (
+    SELECT
+        notes.id AS entry_id,
+        notes.id AS note_id,
+        NULL::UUID AS reblog_id
+    FROM notes
+    WHERE notes.actor_id IN (SELECT sources.id FROM sources) AND notes.deleted IS NULL
+    UNION ALL
+    SELECT
+        reblogs.id,
+        reblogs.note_id,
+        reblogs.id
+    FROM reblogs
+    WHERE reblogs.actor_id IN (SELECT sources.id FROM sources)
+)
+
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    $1,
+    entries.entry_id,
+    entries.note_id,
+    entries.reblog_id
+FROM entries
+ORDER BY entries.entry_id DESC
+LIMIT $2"#;
+pub async fn rebuild_home_feed(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_account_id: Option<&uuid::Uuid>,
+    limit: &i64,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(REBUILD_HOME_FEED, &[&actors_account_id, &limit])
+        .await
+}
+pub const GET_PUBLIC_TIMELINE: &str = r#"-- name: GetPublicTimeline :many
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    notes.deleted IS NULL
+    AND (NOT $1 OR actors.account_id IS NOT NULL)
+    AND (NOT $3 OR actors.account_id IS NULL)
+    AND ($4::UUID IS NULL OR notes.id < $4)
+    AND ($5::UUID IS NULL OR notes.id > $5)
+ORDER BY
+    CASE WHEN $6 THEN notes.id END ASC,
+    notes.id DESC
+LIMIT $7"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetPublicTimelineRow {
+    pub notes_id: uuid::Uuid,
+    pub notes_content: String,
+    pub notes_summary: Option<String>,
+    pub notes_sensitive: bool,
+    pub notes_object_type: String,
+    pub notes_name: Option<String>,
+    pub notes_url: Option<String>,
+    pub notes_note_url: String,
+    pub notes_updated: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes_published: chrono::DateTime<chrono::Utc>,
+    pub notes_in_reply_to_id: Option<uuid::Uuid>,
+    pub in_reply_to_actor_id: Option<uuid::Uuid>,
+    pub source_id: uuid::Uuid,
+    pub source_content: String,
+    pub source_media_type: String,
+    pub actors_id: uuid::Uuid,
+    pub actors_type: ActorType,
+    pub actors_name: String,
+    pub actors_host: String,
+    pub actors_actor_url: String,
+    pub actors_inbox_url: String,
+    pub actors_outbox_url: String,
+    pub actors_shared_inbox_url: Option<String>,
+    pub actors_summary: Option<String>,
+    pub actors_public_key_id: Option<String>,
+    pub actors_public_key_pem: Option<String>,
+    pub actors_account_id: Option<uuid::Uuid>,
+    pub actors_published: Option<chrono::DateTime<chrono::Utc>>,
+    pub actors_created_at: chrono::DateTime<chrono::Utc>,
+    pub actors_updated_at: chrono::DateTime<chrono::Utc>,
+    pub actors_display_name: Option<String>,
+    pub actors_summary_source: Option<String>,
+    pub actors_avatar_url: Option<String>,
+    pub actors_avatar_storage_key: Option<String>,
+    pub actors_header_url: Option<String>,
+    pub actors_header_storage_key: Option<String>,
+    pub actors_fields: serde_json::Value,
+    pub actors_emojis: serde_json::Value,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub statuses_count: i64,
+    pub favourites_count: i64,
+    pub reblogs_count: i64,
+    pub favourited: bool,
+    pub reblogged: bool,
+    pub reactions: serde_json::Value,
+    pub attachments: serde_json::Value,
+    pub emojis: serde_json::Value,
+    pub poll: Option<serde_json::Value>,
+}
+pub async fn get_public_timeline(
+    client: &impl deadpool_postgres::GenericClient,
+    local: &bool,
+    viewers_account_id: Option<&uuid::Uuid>,
+    remote: &bool,
+    max_id: Option<&uuid::Uuid>,
+    min_id: Option<&uuid::Uuid>,
+    ascending: &bool,
+    limit: &i64,
+) -> Result<
+    impl Iterator<Item = Result<GetPublicTimelineRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_PUBLIC_TIMELINE,
+            &[
+                &local,
+                &viewers_account_id,
+                &remote,
+                &max_id,
+                &min_id,
+                &ascending,
+                &limit,
+            ],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetPublicTimelineRow {
+            notes_id: r.try_get(0)?,
+            notes_content: r.try_get(1)?,
+            notes_summary: r.try_get(2)?,
+            notes_sensitive: r.try_get(3)?,
+            notes_object_type: r.try_get(4)?,
+            notes_name: r.try_get(5)?,
+            notes_url: r.try_get(6)?,
+            notes_note_url: r.try_get(7)?,
+            notes_updated: r.try_get(8)?,
+            notes_published: r.try_get(9)?,
+            notes_in_reply_to_id: r.try_get(10)?,
+            in_reply_to_actor_id: r.try_get(11)?,
+            source_id: r.try_get(12)?,
+            source_content: r.try_get(13)?,
+            source_media_type: r.try_get(14)?,
+            actors_id: r.try_get(15)?,
+            actors_type: r.try_get(16)?,
+            actors_name: r.try_get(17)?,
+            actors_host: r.try_get(18)?,
+            actors_actor_url: r.try_get(19)?,
+            actors_inbox_url: r.try_get(20)?,
+            actors_outbox_url: r.try_get(21)?,
+            actors_shared_inbox_url: r.try_get(22)?,
+            actors_summary: r.try_get(23)?,
+            actors_public_key_id: r.try_get(24)?,
+            actors_public_key_pem: r.try_get(25)?,
+            actors_account_id: r.try_get(26)?,
+            actors_published: r.try_get(27)?,
+            actors_created_at: r.try_get(28)?,
+            actors_updated_at: r.try_get(29)?,
+            actors_display_name: r.try_get(30)?,
+            actors_summary_source: r.try_get(31)?,
+            actors_avatar_url: r.try_get(32)?,
+            actors_avatar_storage_key: r.try_get(33)?,
+            actors_header_url: r.try_get(34)?,
+            actors_header_storage_key: r.try_get(35)?,
+            actors_fields: r.try_get(36)?,
+            actors_emojis: r.try_get(37)?,
+            followers_count: r.try_get(38)?,
+            following_count: r.try_get(39)?,
+            statuses_count: r.try_get(40)?,
+            favourites_count: r.try_get(41)?,
+            reblogs_count: r.try_get(42)?,
+            favourited: r.try_get(43)?,
+            reblogged: r.try_get(44)?,
+            reactions: r.try_get(45)?,
+            attachments: r.try_get(46)?,
+            emojis: r.try_get(47)?,
+            poll: r.try_get(48)?,
+        })
+    }))
+}
+pub const INSERT_SESSION: &str = r#"-- name: InsertSession :exec
+INSERT INTO sessions (
+    id,
+    account_id,
+    token_hash,
+    created,
+    expires,
+    app_id,
+    scopes
+) VALUES ($1, $2, $3, $4, $5, $6, $7)"#;
+pub async fn insert_session(
+    client: &impl deadpool_postgres::GenericClient,
+    sessions_id: &uuid::Uuid,
+    sessions_account_id: Option<&uuid::Uuid>,
+    sessions_token_hash: &str,
+    sessions_created: &chrono::DateTime<chrono::Utc>,
+    sessions_expires: &chrono::DateTime<chrono::Utc>,
+    sessions_app_id: Option<&uuid::Uuid>,
+    sessions_scopes: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_SESSION,
+            &[
+                &sessions_id,
+                &sessions_account_id,
+                &sessions_token_hash,
+                &sessions_created,
+                &sessions_expires,
+                &sessions_app_id,
+                &sessions_scopes,
+            ],
+        )
+        .await
+}
+pub const FIND_SESSION: &str = r#"-- name: FindSession :one
+SELECT
+    sessions.app_id,
+    sessions.scopes,
+    accounts.id,
+    accounts.name
+FROM sessions
+LEFT JOIN accounts ON sessions.account_id = accounts.id
+WHERE sessions.token_hash = $1 AND sessions.expires > $2"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct FindSessionRow {
+    pub sessions_app_id: Option<uuid::Uuid>,
+    pub sessions_scopes: String,
+    pub accounts_id: Option<uuid::Uuid>,
+    pub accounts_name: Option<String>,
+}
+pub async fn find_session(
+    client: &impl deadpool_postgres::GenericClient,
+    sessions_token_hash: &str,
+    sessions_expires: &chrono::DateTime<chrono::Utc>,
+) -> Result<Option<FindSessionRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(FIND_SESSION, &[&sessions_token_hash, &sessions_expires])
+        .await?;
+    let v = match row {
+        Some(v) => FindSessionRow {
+            sessions_app_id: v.try_get(0)?,
+            sessions_scopes: v.try_get(1)?,
+            accounts_id: v.try_get(2)?,
+            accounts_name: v.try_get(3)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_SESSION: &str = r#"-- name: DeleteSession :exec
+DELETE FROM sessions
+WHERE token_hash = $1"#;
+pub async fn delete_session(
+    client: &impl deadpool_postgres::GenericClient,
+    sessions_token_hash: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_SESSION, &[&sessions_token_hash])
+        .await
+}
+pub const DELETE_APP_SESSION: &str = r#"-- name: DeleteAppSession :exec
+DELETE FROM sessions
+WHERE token_hash = $1 AND app_id = $2"#;
+pub async fn delete_app_session(
+    client: &impl deadpool_postgres::GenericClient,
+    sessions_token_hash: &str,
+    sessions_app_id: Option<&uuid::Uuid>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_APP_SESSION,
+            &[&sessions_token_hash, &sessions_app_id],
+        )
+        .await
+}
+pub const INSERT_APP: &str = r#"-- name: InsertApp :exec
+INSERT INTO oauth_apps (
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website,
+    created
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#;
+pub async fn insert_app(
+    client: &impl deadpool_postgres::GenericClient,
+    oauth_apps_id: &uuid::Uuid,
+    oauth_apps_client_id: &str,
+    oauth_apps_client_secret_hash: &str,
+    oauth_apps_name: &str,
+    oauth_apps_redirect_uris: &str,
+    oauth_apps_scopes: &str,
+    oauth_apps_website: Option<&str>,
+    oauth_apps_created: &chrono::DateTime<chrono::Utc>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_APP,
+            &[
+                &oauth_apps_id,
+                &oauth_apps_client_id,
+                &oauth_apps_client_secret_hash,
+                &oauth_apps_name,
+                &oauth_apps_redirect_uris,
+                &oauth_apps_scopes,
+                &oauth_apps_website,
+                &oauth_apps_created,
+            ],
+        )
+        .await
+}
+pub const FIND_APP_BY_CLIENT_ID: &str = r#"-- name: FindAppByClientId :one
+SELECT
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website
+FROM oauth_apps
+WHERE client_id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct FindAppByClientIdRow {
+    pub oauth_apps_id: uuid::Uuid,
+    pub oauth_apps_client_id: String,
+    pub oauth_apps_client_secret_hash: String,
+    pub oauth_apps_name: String,
+    pub oauth_apps_redirect_uris: String,
+    pub oauth_apps_scopes: String,
+    pub oauth_apps_website: Option<String>,
+}
+pub async fn find_app_by_client_id(
+    client: &impl deadpool_postgres::GenericClient,
+    oauth_apps_client_id: &str,
+) -> Result<Option<FindAppByClientIdRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(FIND_APP_BY_CLIENT_ID, &[&oauth_apps_client_id])
+        .await?;
+    let v = match row {
+        Some(v) => FindAppByClientIdRow {
+            oauth_apps_id: v.try_get(0)?,
+            oauth_apps_client_id: v.try_get(1)?,
+            oauth_apps_client_secret_hash: v.try_get(2)?,
+            oauth_apps_name: v.try_get(3)?,
+            oauth_apps_redirect_uris: v.try_get(4)?,
+            oauth_apps_scopes: v.try_get(5)?,
+            oauth_apps_website: v.try_get(6)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const FIND_APP_BY_ID: &str = r#"-- name: FindAppById :one
+SELECT
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website
+FROM oauth_apps
+WHERE id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct FindAppByIdRow {
+    pub oauth_apps_id: uuid::Uuid,
+    pub oauth_apps_client_id: String,
+    pub oauth_apps_client_secret_hash: String,
+    pub oauth_apps_name: String,
+    pub oauth_apps_redirect_uris: String,
+    pub oauth_apps_scopes: String,
+    pub oauth_apps_website: Option<String>,
+}
+pub async fn find_app_by_id(
+    client: &impl deadpool_postgres::GenericClient,
+    oauth_apps_id: &uuid::Uuid,
+) -> Result<Option<FindAppByIdRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client.query_opt(FIND_APP_BY_ID, &[&oauth_apps_id]).await?;
+    let v = match row {
+        Some(v) => FindAppByIdRow {
+            oauth_apps_id: v.try_get(0)?,
+            oauth_apps_client_id: v.try_get(1)?,
+            oauth_apps_client_secret_hash: v.try_get(2)?,
+            oauth_apps_name: v.try_get(3)?,
+            oauth_apps_redirect_uris: v.try_get(4)?,
+            oauth_apps_scopes: v.try_get(5)?,
+            oauth_apps_website: v.try_get(6)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_AUTHORIZATION_CODE: &str = r#"-- name: InsertAuthorizationCode :exec
+INSERT INTO oauth_authorization_codes (
+    code_hash,
+    app_id,
+    account_id,
+    redirect_uri,
+    scopes,
+    code_challenge,
+    code_challenge_method,
+    expires
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#;
+pub async fn insert_authorization_code(
+    client: &impl deadpool_postgres::GenericClient,
+    oauth_authorization_codes_code_hash: &str,
+    oauth_authorization_codes_app_id: &uuid::Uuid,
+    oauth_authorization_codes_account_id: &uuid::Uuid,
+    oauth_authorization_codes_redirect_uri: &str,
+    oauth_authorization_codes_scopes: &str,
+    oauth_authorization_codes_code_challenge: Option<&str>,
+    oauth_authorization_codes_code_challenge_method: Option<&str>,
+    oauth_authorization_codes_expires: &chrono::DateTime<chrono::Utc>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_AUTHORIZATION_CODE,
+            &[
+                &oauth_authorization_codes_code_hash,
+                &oauth_authorization_codes_app_id,
+                &oauth_authorization_codes_account_id,
+                &oauth_authorization_codes_redirect_uri,
+                &oauth_authorization_codes_scopes,
+                &oauth_authorization_codes_code_challenge,
+                &oauth_authorization_codes_code_challenge_method,
+                &oauth_authorization_codes_expires,
+            ],
+        )
+        .await
+}
+pub const TAKE_AUTHORIZATION_CODE: &str = r#"-- name: TakeAuthorizationCode :one
+WITH taken AS (
+    DELETE FROM oauth_authorization_codes
+    WHERE code_hash = $1
+    RETURNING *
+)
+
+SELECT
+    taken.app_id,
+    taken.redirect_uri,
+    taken.scopes,
+    taken.code_challenge,
+    taken.code_challenge_method,
+    taken.expires,
+    accounts.id AS account_id,
+    accounts.name AS account_name
+FROM taken
+INNER JOIN accounts ON taken.account_id = accounts.id"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct TakeAuthorizationCodeRow {
+    pub taken_app_id: uuid::Uuid,
+    pub taken_redirect_uri: String,
+    pub taken_scopes: String,
+    pub taken_code_challenge: Option<String>,
+    pub taken_code_challenge_method: Option<String>,
+    pub taken_expires: chrono::DateTime<chrono::Utc>,
+    pub account_id: uuid::Uuid,
+    pub account_name: String,
+}
+pub async fn take_authorization_code(
+    client: &impl deadpool_postgres::GenericClient,
+    oauth_authorization_codes_code_hash: &str,
+) -> Result<Option<TakeAuthorizationCodeRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            TAKE_AUTHORIZATION_CODE,
+            &[&oauth_authorization_codes_code_hash],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => TakeAuthorizationCodeRow {
+            taken_app_id: v.try_get(0)?,
+            taken_redirect_uri: v.try_get(1)?,
+            taken_scopes: v.try_get(2)?,
+            taken_code_challenge: v.try_get(3)?,
+            taken_code_challenge_method: v.try_get(4)?,
+            taken_expires: v.try_get(5)?,
+            account_id: v.try_get(6)?,
+            account_name: v.try_get(7)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_ATTACHMENT: &str = r#"-- name: InsertAttachment :exec
+INSERT INTO attachments (
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)"#;
+pub async fn insert_attachment(
+    client: &impl deadpool_postgres::GenericClient,
+    attachments_id: &uuid::Uuid,
+    attachments_account_id: Option<&uuid::Uuid>,
+    attachments_note_id: Option<&uuid::Uuid>,
+    attachments_type: &str,
+    attachments_media_type: &str,
+    attachments_url: &str,
+    attachments_storage_key: Option<&str>,
+    attachments_description: Option<&str>,
+    attachments_created_at: &chrono::DateTime<chrono::Utc>,
+    attachments_width: Option<&i32>,
+    attachments_height: Option<&i32>,
+    attachments_blurhash: Option<&str>,
+    attachments_focus_x: Option<&f32>,
+    attachments_focus_y: Option<&f32>,
+    attachments_preview_url: Option<&str>,
+    attachments_preview_storage_key: Option<&str>,
+    attachments_preview_width: Option<&i32>,
+    attachments_preview_height: Option<&i32>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_ATTACHMENT,
+            &[
+                &attachments_id,
+                &attachments_account_id,
+                &attachments_note_id,
+                &attachments_type,
+                &attachments_media_type,
+                &attachments_url,
+                &attachments_storage_key,
+                &attachments_description,
+                &attachments_created_at,
+                &attachments_width,
+                &attachments_height,
+                &attachments_blurhash,
+                &attachments_focus_x,
+                &attachments_focus_y,
+                &attachments_preview_url,
+                &attachments_preview_storage_key,
+                &attachments_preview_width,
+                &attachments_preview_height,
+            ],
+        )
+        .await
+}
+pub const GET_ATTACHMENT: &str = r#"-- name: GetAttachment :one
+SELECT
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+FROM attachments
+WHERE id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetAttachmentRow {
+    pub attachments_id: uuid::Uuid,
+    pub attachments_account_id: Option<uuid::Uuid>,
+    pub attachments_note_id: Option<uuid::Uuid>,
+    pub attachments_type: String,
+    pub attachments_media_type: String,
+    pub attachments_url: String,
+    pub attachments_storage_key: Option<String>,
+    pub attachments_description: Option<String>,
+    pub attachments_created_at: chrono::DateTime<chrono::Utc>,
+    pub attachments_width: Option<i32>,
+    pub attachments_height: Option<i32>,
+    pub attachments_blurhash: Option<String>,
+    pub attachments_focus_x: Option<f32>,
+    pub attachments_focus_y: Option<f32>,
+    pub attachments_preview_url: Option<String>,
+    pub attachments_preview_storage_key: Option<String>,
+    pub attachments_preview_width: Option<i32>,
+    pub attachments_preview_height: Option<i32>,
+}
+pub async fn get_attachment(
+    client: &impl deadpool_postgres::GenericClient,
+    attachments_id: &uuid::Uuid,
+) -> Result<Option<GetAttachmentRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client.query_opt(GET_ATTACHMENT, &[&attachments_id]).await?;
+    let v = match row {
+        Some(v) => GetAttachmentRow {
+            attachments_id: v.try_get(0)?,
+            attachments_account_id: v.try_get(1)?,
+            attachments_note_id: v.try_get(2)?,
+            attachments_type: v.try_get(3)?,
+            attachments_media_type: v.try_get(4)?,
+            attachments_url: v.try_get(5)?,
+            attachments_storage_key: v.try_get(6)?,
+            attachments_description: v.try_get(7)?,
+            attachments_created_at: v.try_get(8)?,
+            attachments_width: v.try_get(9)?,
+            attachments_height: v.try_get(10)?,
+            attachments_blurhash: v.try_get(11)?,
+            attachments_focus_x: v.try_get(12)?,
+            attachments_focus_y: v.try_get(13)?,
+            attachments_preview_url: v.try_get(14)?,
+            attachments_preview_storage_key: v.try_get(15)?,
+            attachments_preview_width: v.try_get(16)?,
+            attachments_preview_height: v.try_get(17)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_ATTACHMENT_BY_STORAGE_KEY: &str = r#"-- name: GetAttachmentByStorageKey :one
+SELECT
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+FROM attachments
+WHERE storage_key = $1 OR preview_storage_key = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetAttachmentByStorageKeyRow {
+    pub attachments_id: uuid::Uuid,
+    pub attachments_account_id: Option<uuid::Uuid>,
+    pub attachments_note_id: Option<uuid::Uuid>,
+    pub attachments_type: String,
+    pub attachments_media_type: String,
+    pub attachments_url: String,
+    pub attachments_storage_key: Option<String>,
+    pub attachments_description: Option<String>,
+    pub attachments_created_at: chrono::DateTime<chrono::Utc>,
+    pub attachments_width: Option<i32>,
+    pub attachments_height: Option<i32>,
+    pub attachments_blurhash: Option<String>,
+    pub attachments_focus_x: Option<f32>,
+    pub attachments_focus_y: Option<f32>,
+    pub attachments_preview_url: Option<String>,
+    pub attachments_preview_storage_key: Option<String>,
+    pub attachments_preview_width: Option<i32>,
+    pub attachments_preview_height: Option<i32>,
+}
+pub async fn get_attachment_by_storage_key(
+    client: &impl deadpool_postgres::GenericClient,
+    attachments_storage_key: Option<&str>,
+) -> Result<Option<GetAttachmentByStorageKeyRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_ATTACHMENT_BY_STORAGE_KEY, &[&attachments_storage_key])
+        .await?;
+    let v = match row {
+        Some(v) => GetAttachmentByStorageKeyRow {
+            attachments_id: v.try_get(0)?,
+            attachments_account_id: v.try_get(1)?,
+            attachments_note_id: v.try_get(2)?,
+            attachments_type: v.try_get(3)?,
+            attachments_media_type: v.try_get(4)?,
+            attachments_url: v.try_get(5)?,
+            attachments_storage_key: v.try_get(6)?,
+            attachments_description: v.try_get(7)?,
+            attachments_created_at: v.try_get(8)?,
+            attachments_width: v.try_get(9)?,
+            attachments_height: v.try_get(10)?,
+            attachments_blurhash: v.try_get(11)?,
+            attachments_focus_x: v.try_get(12)?,
+            attachments_focus_y: v.try_get(13)?,
+            attachments_preview_url: v.try_get(14)?,
+            attachments_preview_storage_key: v.try_get(15)?,
+            attachments_preview_width: v.try_get(16)?,
+            attachments_preview_height: v.try_get(17)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const ATTACH_NOTE_MEDIA: &str = r#"-- name: AttachNoteMedia :many
+UPDATE attachments
+SET note_id = $1
+WHERE id = ANY($2::UUID []) AND account_id = $3 AND note_id IS NULL
+RETURNING
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct AttachNoteMediaRow {
+    pub attachments_id: uuid::Uuid,
+    pub attachments_account_id: Option<uuid::Uuid>,
+    pub attachments_note_id: Option<uuid::Uuid>,
+    pub attachments_type: String,
+    pub attachments_media_type: String,
+    pub attachments_url: String,
+    pub attachments_storage_key: Option<String>,
+    pub attachments_description: Option<String>,
+    pub attachments_created_at: chrono::DateTime<chrono::Utc>,
+    pub attachments_width: Option<i32>,
+    pub attachments_height: Option<i32>,
+    pub attachments_blurhash: Option<String>,
+    pub attachments_focus_x: Option<f32>,
+    pub attachments_focus_y: Option<f32>,
+    pub attachments_preview_url: Option<String>,
+    pub attachments_preview_storage_key: Option<String>,
+    pub attachments_preview_width: Option<i32>,
+    pub attachments_preview_height: Option<i32>,
+}
+pub async fn attach_note_media(
+    client: &impl deadpool_postgres::GenericClient,
+    attachments_note_id: Option<&uuid::Uuid>,
+    ids: &[uuid::Uuid],
+    attachments_account_id: Option<&uuid::Uuid>,
+) -> Result<
+    impl Iterator<Item = Result<AttachNoteMediaRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            ATTACH_NOTE_MEDIA,
+            &[&attachments_note_id, &ids, &attachments_account_id],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(AttachNoteMediaRow {
+            attachments_id: r.try_get(0)?,
+            attachments_account_id: r.try_get(1)?,
+            attachments_note_id: r.try_get(2)?,
+            attachments_type: r.try_get(3)?,
+            attachments_media_type: r.try_get(4)?,
+            attachments_url: r.try_get(5)?,
+            attachments_storage_key: r.try_get(6)?,
+            attachments_description: r.try_get(7)?,
+            attachments_created_at: r.try_get(8)?,
+            attachments_width: r.try_get(9)?,
+            attachments_height: r.try_get(10)?,
+            attachments_blurhash: r.try_get(11)?,
+            attachments_focus_x: r.try_get(12)?,
+            attachments_focus_y: r.try_get(13)?,
+            attachments_preview_url: r.try_get(14)?,
+            attachments_preview_storage_key: r.try_get(15)?,
+            attachments_preview_width: r.try_get(16)?,
+            attachments_preview_height: r.try_get(17)?,
+        })
+    }))
+}
+pub const DELETE_NOTE_ATTACHMENTS: &str = r#"-- name: DeleteNoteAttachments :exec
+DELETE FROM attachments
+WHERE note_id = $1"#;
+pub async fn delete_note_attachments(
+    client: &impl deadpool_postgres::GenericClient,
+    attachments_note_id: Option<&uuid::Uuid>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_NOTE_ATTACHMENTS, &[&attachments_note_id])
+        .await
+}
+pub const INSERT_REMOTE_MEDIA: &str = r#"-- name: InsertRemoteMedia :exec
+INSERT INTO remote_media (id, url)
+VALUES ($1, $2)
+ON CONFLICT (id) DO NOTHING"#;
+pub async fn insert_remote_media(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_id: &str,
+    remote_media_url: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INSERT_REMOTE_MEDIA, &[&remote_media_id, &remote_media_url])
+        .await
+}
+pub const GET_REMOTE_MEDIA: &str = r#"-- name: GetRemoteMedia :one
+SELECT
+    id,
+    url,
+    media_type,
+    storage_key
+FROM remote_media
+WHERE id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetRemoteMediaRow {
+    pub remote_media_id: String,
+    pub remote_media_url: String,
+    pub remote_media_media_type: Option<String>,
+    pub remote_media_storage_key: Option<String>,
+}
+pub async fn get_remote_media(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_id: &str,
+) -> Result<Option<GetRemoteMediaRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_REMOTE_MEDIA, &[&remote_media_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetRemoteMediaRow {
+            remote_media_id: v.try_get(0)?,
+            remote_media_url: v.try_get(1)?,
+            remote_media_media_type: v.try_get(2)?,
+            remote_media_storage_key: v.try_get(3)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const SET_REMOTE_MEDIA_CACHED: &str = r#"-- name: SetRemoteMediaCached :exec
+UPDATE remote_media
+SET media_type = $2, storage_key = $3, size = $4, cached_at = now(), accessed_at = now()
+WHERE id = $1;
+
+-- Access times are only kept to the minute, sparing a write on every request"#;
+pub async fn set_remote_media_cached(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_id: &str,
+    remote_media_media_type: Option<&str>,
+    remote_media_storage_key: Option<&str>,
+    remote_media_size: Option<&i64>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            SET_REMOTE_MEDIA_CACHED,
+            &[
+                &remote_media_id,
+                &remote_media_media_type,
+                &remote_media_storage_key,
+                &remote_media_size,
+            ],
+        )
+        .await
+}
+pub const TOUCH_REMOTE_MEDIA: &str = r#"-- name: TouchRemoteMedia :exec
+UPDATE remote_media
+SET accessed_at = now()
+WHERE id = $1 AND accessed_at < now() - INTERVAL '1 minute';
+
+-- Cached files not accessed since $1, then the least recently accessed ones
+-- beyond a total size of $2 bytes"#;
+pub async fn touch_remote_media(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_id: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(TOUCH_REMOTE_MEDIA, &[&remote_media_id])
+        .await
+}
+pub const GET_EVICTABLE_REMOTE_MEDIA: &str = r#"-- name: GetEvictableRemoteMedia :many
+SELECT
+    id,
+    url,
+    media_type,
+    storage_key
+FROM (
+    SELECT
+        remote_media.*,
+        (sum(size) OVER (ORDER BY accessed_at DESC, id DESC))::BIGINT AS retained_size
+    FROM remote_media
+    WHERE storage_key IS NOT NULL
+) AS cached
+WHERE accessed_at < $1 OR retained_size > $2
+ORDER BY accessed_at, id
+LIMIT $3"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetEvictableRemoteMediaRow {
+    pub remote_media_id: String,
+    pub remote_media_url: String,
+    pub remote_media_media_type: Option<String>,
+    pub remote_media_storage_key: Option<String>,
+}
+pub async fn get_evictable_remote_media(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_accessed_at: Option<&chrono::DateTime<chrono::Utc>>,
+    retained_size: &i64,
+    limit: &i64,
+) -> Result<
+    impl Iterator<Item = Result<GetEvictableRemoteMediaRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_EVICTABLE_REMOTE_MEDIA,
+            &[&remote_media_accessed_at, &retained_size, &limit],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetEvictableRemoteMediaRow {
+            remote_media_id: r.try_get(0)?,
+            remote_media_url: r.try_get(1)?,
+            remote_media_media_type: r.try_get(2)?,
+            remote_media_storage_key: r.try_get(3)?,
+        })
+    }))
+}
+pub const UNSET_REMOTE_MEDIA_CACHED: &str = r#"-- name: UnsetRemoteMediaCached :exec
+UPDATE remote_media
+SET media_type = NULL, storage_key = NULL, size = NULL, cached_at = NULL
+WHERE id = $1 AND storage_key = $2;
+
+-- Stored images served from the media backend outside of attachments"#;
+pub async fn unset_remote_media_cached(
+    client: &impl deadpool_postgres::GenericClient,
+    remote_media_id: &str,
+    remote_media_storage_key: Option<&str>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            UNSET_REMOTE_MEDIA_CACHED,
+            &[&remote_media_id, &remote_media_storage_key],
+        )
+        .await
+}
+pub const GET_STORED_IMAGE: &str = r#"-- name: GetStoredImage :one
+SELECT id
+FROM actors
+WHERE avatar_storage_key = $1 OR header_storage_key = $1
+UNION ALL
+SELECT id
+FROM custom_emojis
+WHERE storage_key = $1
+LIMIT 1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetStoredImageRow {
+    pub id: uuid::Uuid,
+}
+pub async fn get_stored_image(
+    client: &impl deadpool_postgres::GenericClient,
+    actors_avatar_storage_key: Option<&str>,
+) -> Result<Option<GetStoredImageRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_STORED_IMAGE, &[&actors_avatar_storage_key])
+        .await?;
+    let v = match row {
+        Some(v) => GetStoredImageRow { id: v.try_get(0)? },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_CUSTOM_EMOJI: &str = r#"-- name: InsertCustomEmoji :exec
+INSERT INTO custom_emojis (
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+)
+VALUES ($1, $2, NULL, $3, $4, $5, $6, $7, $7)
+ON CONFLICT (shortcode, host) DO NOTHING"#;
+pub async fn insert_custom_emoji(
+    client: &impl deadpool_postgres::GenericClient,
+    custom_emojis_id: &uuid::Uuid,
+    custom_emojis_shortcode: &str,
+    custom_emojis_uri: &str,
+    custom_emojis_url: &str,
+    custom_emojis_media_type: Option<&str>,
+    custom_emojis_storage_key: Option<&str>,
+    custom_emojis_created_at: &chrono::DateTime<chrono::Utc>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_CUSTOM_EMOJI,
+            &[
+                &custom_emojis_id,
+                &custom_emojis_shortcode,
+                &custom_emojis_uri,
+                &custom_emojis_url,
+                &custom_emojis_media_type,
+                &custom_emojis_storage_key,
+                &custom_emojis_created_at,
+            ],
+        )
+        .await
+}
+pub const GET_LOCAL_CUSTOM_EMOJIS: &str = r#"-- name: GetLocalCustomEmojis :many
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE host IS NULL
+ORDER BY shortcode"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetLocalCustomEmojisRow {
+    pub custom_emojis_id: uuid::Uuid,
+    pub custom_emojis_shortcode: String,
+    pub custom_emojis_host: Option<String>,
+    pub custom_emojis_uri: String,
+    pub custom_emojis_url: String,
+    pub custom_emojis_media_type: Option<String>,
+    pub custom_emojis_storage_key: Option<String>,
+    pub custom_emojis_created_at: chrono::DateTime<chrono::Utc>,
+    pub custom_emojis_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_local_custom_emojis(
+    client: &impl deadpool_postgres::GenericClient,
+) -> Result<
+    impl Iterator<Item = Result<GetLocalCustomEmojisRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client.query(GET_LOCAL_CUSTOM_EMOJIS, &[]).await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetLocalCustomEmojisRow {
+            custom_emojis_id: r.try_get(0)?,
+            custom_emojis_shortcode: r.try_get(1)?,
+            custom_emojis_host: r.try_get(2)?,
+            custom_emojis_uri: r.try_get(3)?,
+            custom_emojis_url: r.try_get(4)?,
+            custom_emojis_media_type: r.try_get(5)?,
+            custom_emojis_storage_key: r.try_get(6)?,
+            custom_emojis_created_at: r.try_get(7)?,
+            custom_emojis_updated_at: r.try_get(8)?,
+        })
+    }))
+}
+pub const GET_CUSTOM_EMOJI: &str = r#"-- name: GetCustomEmoji :one
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetCustomEmojiRow {
+    pub custom_emojis_id: uuid::Uuid,
+    pub custom_emojis_shortcode: String,
+    pub custom_emojis_host: Option<String>,
+    pub custom_emojis_uri: String,
+    pub custom_emojis_url: String,
+    pub custom_emojis_media_type: Option<String>,
+    pub custom_emojis_storage_key: Option<String>,
+    pub custom_emojis_created_at: chrono::DateTime<chrono::Utc>,
+    pub custom_emojis_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_custom_emoji(
+    client: &impl deadpool_postgres::GenericClient,
+    custom_emojis_id: &uuid::Uuid,
+) -> Result<Option<GetCustomEmojiRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(GET_CUSTOM_EMOJI, &[&custom_emojis_id])
+        .await?;
+    let v = match row {
+        Some(v) => GetCustomEmojiRow {
+            custom_emojis_id: v.try_get(0)?,
+            custom_emojis_shortcode: v.try_get(1)?,
+            custom_emojis_host: v.try_get(2)?,
+            custom_emojis_uri: v.try_get(3)?,
+            custom_emojis_url: v.try_get(4)?,
+            custom_emojis_media_type: v.try_get(5)?,
+            custom_emojis_storage_key: v.try_get(6)?,
+            custom_emojis_created_at: v.try_get(7)?,
+            custom_emojis_updated_at: v.try_get(8)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_LOCAL_CUSTOM_EMOJI: &str = r#"-- name: DeleteLocalCustomEmoji :one
+DELETE FROM custom_emojis
+WHERE shortcode = $1 AND host IS NULL
+RETURNING storage_key"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct DeleteLocalCustomEmojiRow {
+    pub custom_emojis_storage_key: Option<String>,
+}
+pub async fn delete_local_custom_emoji(
+    client: &impl deadpool_postgres::GenericClient,
+    custom_emojis_shortcode: &str,
+) -> Result<Option<DeleteLocalCustomEmojiRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(DELETE_LOCAL_CUSTOM_EMOJI, &[&custom_emojis_shortcode])
+        .await?;
+    let v = match row {
+        Some(v) => DeleteLocalCustomEmojiRow {
+            custom_emojis_storage_key: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const INSERT_NOTIFICATION: &str = r#"-- name: InsertNotification :exec
+INSERT INTO notifications (
+    id,
+    account_id,
+    type,
+    actor_id,
+    note_id
+) VALUES ($1, $2, $3, $4, $5);
+
+-- Page of notifications, skipping those of deleted notes"#;
+pub async fn insert_notification(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_id: &uuid::Uuid,
+    notifications_account_id: &uuid::Uuid,
+    notifications_type: &str,
+    notifications_actor_id: &uuid::Uuid,
+    notifications_note_id: Option<&uuid::Uuid>,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            INSERT_NOTIFICATION,
+            &[
+                &notifications_id,
+                &notifications_account_id,
+                &notifications_type,
+                &notifications_actor_id,
+                &notifications_note_id,
+            ],
+        )
+        .await
+}
+pub const GET_NOTIFICATIONS: &str = r#"-- name: GetNotifications :many
+SELECT
+    notifications.id,
+    notifications.type,
+    notifications.actor_id,
+    notifications.note_id
+FROM notifications
+LEFT JOIN notes ON notifications.note_id = notes.id
+WHERE
+    notifications.account_id = $1
+    AND notes.deleted IS NULL
+    AND (cardinality($2::TEXT []) = 0 OR notifications.type = ANY($2))
+    AND NOT (notifications.type = ANY($3::TEXT []))
+    AND ($4::UUID IS NULL OR notifications.actor_id = $4)
+    AND ($5::UUID IS NULL OR notifications.id < $5)
+    AND ($6::UUID IS NULL OR notifications.id > $6)
+ORDER BY
+    CASE WHEN $7 THEN notifications.id END ASC,
+    notifications.id DESC
+LIMIT $8"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNotificationsRow {
+    pub notifications_id: uuid::Uuid,
+    pub notifications_type: String,
+    pub notifications_actor_id: uuid::Uuid,
+    pub notifications_note_id: Option<uuid::Uuid>,
+}
+pub async fn get_notifications(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+    types: &[&str],
+    exclude_types: &[&str],
+    notifications_actor_id: Option<&uuid::Uuid>,
+    max_id: Option<&uuid::Uuid>,
+    min_id: Option<&uuid::Uuid>,
+    ascending: &bool,
+    limit: &i64,
+) -> Result<
+    impl Iterator<Item = Result<GetNotificationsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(
+            GET_NOTIFICATIONS,
+            &[
+                &notifications_account_id,
+                &types,
+                &exclude_types,
+                &notifications_actor_id,
+                &max_id,
+                &min_id,
+                &ascending,
+                &limit,
+            ],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetNotificationsRow {
+            notifications_id: r.try_get(0)?,
+            notifications_type: r.try_get(1)?,
+            notifications_actor_id: r.try_get(2)?,
+            notifications_note_id: r.try_get(3)?,
+        })
+    }))
+}
+pub const GET_NOTIFICATION: &str = r#"-- name: GetNotification :one
+SELECT
+    notifications.id,
+    notifications.type,
+    notifications.actor_id,
+    notifications.note_id
+FROM notifications
+LEFT JOIN notes ON notifications.note_id = notes.id
+WHERE
+    notifications.account_id = $1
+    AND notifications.id = $2
+    AND notes.deleted IS NULL"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetNotificationRow {
+    pub notifications_id: uuid::Uuid,
+    pub notifications_type: String,
+    pub notifications_actor_id: uuid::Uuid,
+    pub notifications_note_id: Option<uuid::Uuid>,
+}
+pub async fn get_notification(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+    notifications_id: &uuid::Uuid,
+) -> Result<Option<GetNotificationRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            GET_NOTIFICATION,
+            &[&notifications_account_id, &notifications_id],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => GetNotificationRow {
+            notifications_id: v.try_get(0)?,
+            notifications_type: v.try_get(1)?,
+            notifications_actor_id: v.try_get(2)?,
+            notifications_note_id: v.try_get(3)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const DELETE_NOTIFICATION: &str = r#"-- name: DeleteNotification :exec
+DELETE FROM notifications
+WHERE account_id = $1 AND id = $2"#;
+pub async fn delete_notification(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+    notifications_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_NOTIFICATION,
+            &[&notifications_account_id, &notifications_id],
+        )
+        .await
+}
+pub const DELETE_NOTE_NOTIFICATIONS: &str = r#"-- name: DeleteNoteNotifications :exec
+DELETE FROM notifications
+WHERE account_id = $1 AND type = $2 AND note_id = $3"#;
+pub async fn delete_note_notifications(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+    notifications_type: &str,
+    notifications_note_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(
+            DELETE_NOTE_NOTIFICATIONS,
+            &[
+                &notifications_account_id,
+                &notifications_type,
+                &notifications_note_id,
+            ],
+        )
+        .await
+}
+pub const CLEAR_NOTIFICATIONS: &str = r#"-- name: ClearNotifications :exec
+DELETE FROM notifications
+WHERE account_id = $1;
+
+-- Notifications newer than the notifications marker, up to a limit"#;
+pub async fn clear_notifications(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(CLEAR_NOTIFICATIONS, &[&notifications_account_id])
+        .await
+}
+pub const COUNT_UNREAD_NOTIFICATIONS: &str = r#"-- name: CountUnreadNotifications :one
+SELECT COUNT(*) AS count
+FROM (
+    SELECT 1
+    FROM notifications
+    LEFT JOIN notes ON notifications.note_id = notes.id
+    LEFT JOIN markers
+        ON
+            notifications.account_id = markers.account_id
+            AND markers.timeline = 'notifications'
+    WHERE
+        notifications.account_id = $1
+        AND notes.deleted IS NULL
+        AND (markers.last_read_id IS NULL OR notifications.id > markers.last_read_id)
+    LIMIT $2
+) AS unread"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct CountUnreadNotificationsRow {
+    pub count: i64,
+}
+pub async fn count_unread_notifications(
+    client: &impl deadpool_postgres::GenericClient,
+    notifications_account_id: &uuid::Uuid,
+    limit: &i64,
+) -> Result<Option<CountUnreadNotificationsRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            COUNT_UNREAD_NOTIFICATIONS,
+            &[&notifications_account_id, &limit],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => CountUnreadNotificationsRow {
+            count: v.try_get(0)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const GET_MARKERS: &str = r#"-- name: GetMarkers :many
+SELECT
+    timeline,
+    last_read_id,
+    version,
+    updated_at
+FROM markers
+WHERE account_id = $1"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct GetMarkersRow {
+    pub markers_timeline: String,
+    pub markers_last_read_id: uuid::Uuid,
+    pub markers_version: i64,
+    pub markers_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn get_markers(
+    client: &impl deadpool_postgres::GenericClient,
+    markers_account_id: &uuid::Uuid,
+) -> Result<
+    impl Iterator<Item = Result<GetMarkersRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client.query(GET_MARKERS, &[&markers_account_id]).await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(GetMarkersRow {
+            markers_timeline: r.try_get(0)?,
+            markers_last_read_id: r.try_get(1)?,
+            markers_version: r.try_get(2)?,
+            markers_updated_at: r.try_get(3)?,
+        })
+    }))
+}
+pub const UPSERT_MARKER: &str = r#"-- name: UpsertMarker :one
+INSERT INTO markers (
+    account_id,
+    timeline,
+    last_read_id
+) VALUES ($1, $2, $3)
+ON CONFLICT (account_id, timeline) DO UPDATE
+SET
+    last_read_id = excluded.last_read_id,
+    version = markers.version + 1,
+    updated_at = CURRENT_TIMESTAMP
+RETURNING version, updated_at"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct UpsertMarkerRow {
+    pub markers_version: i64,
+    pub markers_updated_at: chrono::DateTime<chrono::Utc>,
+}
+pub async fn upsert_marker(
+    client: &impl deadpool_postgres::GenericClient,
+    markers_account_id: &uuid::Uuid,
+    markers_timeline: &str,
+    markers_last_read_id: &uuid::Uuid,
+) -> Result<Option<UpsertMarkerRow>, deadpool_postgres::tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            UPSERT_MARKER,
+            &[
+                &markers_account_id,
+                &markers_timeline,
+                &markers_last_read_id,
+            ],
+        )
+        .await?;
+    let v = match row {
+        Some(v) => UpsertMarkerRow {
+            markers_version: v.try_get(0)?,
+            markers_updated_at: v.try_get(1)?,
+        },
+        None => return Ok(None),
+    };
+    Ok(Some(v))
+}
+pub const SEARCH_ACTORS: &str = r#"-- name: SearchActors :many
+SELECT id
+FROM actors
+WHERE
+    search_tsv @@ websearch_to_tsquery('simple', $1::TEXT)
+    OR starts_with(lower(name), lower($1::TEXT))
+ORDER BY
+    lower(name) = lower($1::TEXT) DESC,
+    ts_rank(search_tsv, websearch_to_tsquery('simple', $1::TEXT)) DESC,
+    id ASC
+LIMIT $2 OFFSET $3"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct SearchActorsRow {
+    pub id: uuid::Uuid,
+}
+pub async fn search_actors(
+    client: &impl deadpool_postgres::GenericClient,
+    query: &str,
+    limit: &i64,
+    offset: &i64,
+) -> Result<
+    impl Iterator<Item = Result<SearchActorsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(SEARCH_ACTORS, &[&query, &limit, &offset])
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Ok(SearchActorsRow { id: r.try_get(0)? })))
+}
+pub const SEARCH_NOTES: &str = r#"-- name: SearchNotes :many
+SELECT id
+FROM notes
+WHERE
+    deleted IS NULL
+    AND content_tsv @@ websearch_to_tsquery('simple', $1::TEXT)
+    AND ($2::UUID IS NULL OR actor_id = $2::UUID)
+ORDER BY id DESC
+LIMIT $3 OFFSET $4"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct SearchNotesRow {
+    pub id: uuid::Uuid,
+}
+pub async fn search_notes(
+    client: &impl deadpool_postgres::GenericClient,
+    query: &str,
+    actor_id: Option<&uuid::Uuid>,
+    limit: &i64,
+    offset: &i64,
+) -> Result<
+    impl Iterator<Item = Result<SearchNotesRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(SEARCH_NOTES, &[&query, &actor_id, &limit, &offset])
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Ok(SearchNotesRow { id: r.try_get(0)? })))
+}
+pub const SEARCH_TAGS: &str = r#"-- name: SearchTags :many
+SELECT name
+FROM tags
+WHERE name % $1::TEXT OR starts_with(name, $1::TEXT)
+ORDER BY
+    starts_with(name, $1::TEXT) DESC,
+    similarity(name, $1::TEXT) DESC,
+    name ASC
+LIMIT $2 OFFSET $3"#;
+#[derive(PartialEq, Debug, Clone)]
+pub struct SearchTagsRow {
+    pub name: String,
+}
+pub async fn search_tags(
+    client: &impl deadpool_postgres::GenericClient,
+    query: &str,
+    limit: &i64,
+    offset: &i64,
+) -> Result<
+    impl Iterator<Item = Result<SearchTagsRow, deadpool_postgres::tokio_postgres::Error>>,
+    deadpool_postgres::tokio_postgres::Error,
+> {
+    let rows = client
+        .query(SEARCH_TAGS, &[&query, &limit, &offset])
+        .await?;
+    Ok(rows.into_iter().map(|r| {
+        Ok(SearchTagsRow {
+            name: r.try_get(0)?,
+        })
+    }))
+}
+pub const INSERT_TAGS: &str = r#"-- name: InsertTags :exec
+INSERT INTO tags (name)
+SELECT unnest($1::TEXT [])
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_tags(
+    client: &impl deadpool_postgres::GenericClient,
+    names: &[&str],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(INSERT_TAGS, &[&names]).await
+}
+pub const DELETE_NOTE_TAGS: &str = r#"-- name: DeleteNoteTags :exec
+DELETE FROM note_tags
+WHERE note_id = $1"#;
+pub async fn delete_note_tags(
+    client: &impl deadpool_postgres::GenericClient,
+    note_tags_note_id: &uuid::Uuid,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(DELETE_NOTE_TAGS, &[&note_tags_note_id])
+        .await
+}
+pub const INSERT_NOTE_TAGS: &str = r#"-- name: InsertNoteTags :exec
+INSERT INTO note_tags (note_id, tag_name)
+SELECT
+    $1,
+    unnest($2::TEXT [])
+ON CONFLICT DO NOTHING"#;
+pub async fn insert_note_tags(
+    client: &impl deadpool_postgres::GenericClient,
+    note_tags_note_id: &uuid::Uuid,
+    names: &[&str],
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client
+        .execute(INSERT_NOTE_TAGS, &[&note_tags_note_id, &names])
+        .await
+}
+pub const NOTIFY_STREAM: &str = r#"-- name: NotifyStream :exec
+SELECT pg_notify('mozu_stream', $1)"#;
+pub async fn notify_stream(
+    client: &impl deadpool_postgres::GenericClient,
+    payload: &str,
+) -> Result<u64, deadpool_postgres::tokio_postgres::Error> {
+    client.execute(NOTIFY_STREAM, &[&payload]).await
+}
@@ -1,6 +1,13 @@
 -- name: CreateAccount :exec
-INSERT INTO accounts (id, name)
-VALUES ($1, $2);
+INSERT INTO accounts (id, name, password_hash)
+SELECT
+    $1,
+    $2,
+    $3
+WHERE NOT EXISTS (
+    SELECT 1 FROM account_tombstones
+    WHERE account_tombstones.name = $2
+);
 
 -- name: FindAccountById :one
 SELECT
@@ -9,9 +16,47 @@ SELECT
 FROM accounts
 WHERE id = $1;
 
+-- name: ListAccountIds :many
+SELECT id
+FROM accounts
+ORDER BY id;
+
 -- name: FindAccountByName :one
 SELECT
     id,
     name
 FROM accounts
 WHERE name = $1;
+
+-- name: FindAccountCredentials :one
+SELECT
+    id,
+    name,
+    password_hash
+FROM accounts
+WHERE name = $1;
+
+-- name: DeleteAccount :exec
+DELETE FROM accounts
+WHERE id = $1;
+
+-- name: InsertAccountTombstone :exec
+INSERT INTO account_tombstones (name, deleted)
+VALUES ($1, $2);
+
+-- name: InsertNoteTombstones :exec
+INSERT INTO note_tombstones (id, deleted)
+SELECT
+    notes.id,
+    COALESCE(notes.deleted, $2)
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE actors.account_id = $1
+ON CONFLICT DO NOTHING;
+
+-- name: FindAccountTombstone :one
+SELECT
+    name,
+    deleted
+FROM account_tombstones
+WHERE name = $1;
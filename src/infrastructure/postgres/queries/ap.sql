@@ -7,7 +7,27 @@ SELECT
     actor_url,
     inbox_url,
     outbox_url,
-    shared_inbox_url
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
 FROM actors
 WHERE account_id = $1;
 
@@ -20,10 +40,101 @@ SELECT
     actor_url,
     inbox_url,
     outbox_url,
-    shared_inbox_url
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
 FROM actors
 WHERE name = $1 AND host = $2;
 
+-- name: GetActorByUrl :one
+SELECT
+    id,
+    type,
+    name,
+    host,
+    actor_url,
+    inbox_url,
+    outbox_url,
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
+FROM actors
+WHERE actor_url = $1;
+
+-- name: GetActorByKeyId :one
+SELECT
+    id,
+    type,
+    name,
+    host,
+    actor_url,
+    inbox_url,
+    outbox_url,
+    shared_inbox_url,
+    summary,
+    public_key_id,
+    public_key_pem,
+    account_id,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis
+FROM actors
+WHERE public_key_id = $1;
+
+-- name: GetRemoteInboxes :many
+SELECT DISTINCT COALESCE(shared_inbox_url, inbox_url) AS inbox_url
+FROM actors
+WHERE account_id IS NULL;
+
 -- name: UpsertActor :one
 INSERT INTO actors (
     id,
@@ -34,9 +145,25 @@ INSERT INTO actors (
     inbox_url,
     outbox_url,
     shared_inbox_url,
-    account_id
+    account_id,
+    summary,
+    public_key_id,
+    public_key_pem,
+    published,
+    created_at,
+    updated_at,
+    display_name,
+    summary_source,
+    avatar_url,
+    avatar_storage_key,
+    header_url,
+    header_storage_key,
+    fields
+)
+VALUES (
+    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+    $21, $22
 )
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
 ON CONFLICT (name, host) DO UPDATE
 SET
 type = excluded.type,
@@ -44,9 +171,91 @@ actor_url = excluded.actor_url,
 inbox_url = excluded.inbox_url,
 outbox_url = excluded.outbox_url,
 shared_inbox_url = excluded.shared_inbox_url,
-account_id = excluded.account_id
+account_id = excluded.account_id,
+summary = excluded.summary,
+public_key_id = excluded.public_key_id,
+public_key_pem = excluded.public_key_pem,
+published = excluded.published,
+updated_at = excluded.updated_at,
+display_name = excluded.display_name,
+summary_source = excluded.summary_source,
+avatar_url = excluded.avatar_url,
+avatar_storage_key = excluded.avatar_storage_key,
+header_url = excluded.header_url,
+header_storage_key = excluded.header_storage_key,
+fields = excluded.fields
+RETURNING id, created_at;
+
+-- name: UpdateActorFields :exec
+UPDATE actors
+SET fields = $2
+WHERE id = $1 AND fields = $3;
+
+-- name: GetLocalCustomEmojisByShortcode :many
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE shortcode = ANY($1::TEXT []) AND host IS NULL
+ORDER BY shortcode;
+
+-- name: GetCustomEmojiByShortcode :one
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE shortcode = $1 AND host IS NOT DISTINCT FROM $2;
+
+-- name: UpsertRemoteCustomEmoji :one
+INSERT INTO custom_emojis (id, shortcode, host, uri, url, media_type, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+ON CONFLICT (shortcode, host) DO UPDATE SET
+uri = excluded.uri,
+url = excluded.url,
+media_type = excluded.media_type,
+updated_at = excluded.updated_at
 RETURNING id;
 
+-- name: DeleteActorEmojis :exec
+DELETE FROM actor_emojis
+WHERE actor_id = $1;
+
+-- name: InsertActorEmojis :exec
+INSERT INTO actor_emojis (actor_id, emoji_id)
+SELECT
+    $1,
+    id
+FROM custom_emojis
+WHERE id = ANY($2::UUID [])
+ON CONFLICT DO NOTHING;
+
+-- name: DeleteNoteEmojis :exec
+DELETE FROM note_emojis
+WHERE note_id = $1;
+
+-- name: InsertNoteEmojis :exec
+INSERT INTO note_emojis (note_id, emoji_id)
+SELECT
+    $1,
+    id
+FROM custom_emojis
+WHERE id = ANY($2::UUID [])
+ON CONFLICT DO NOTHING;
+
 -- name: InsertAccountKey :exec
 INSERT INTO account_keys (
     account_id,
@@ -67,9 +276,11 @@ WHERE account_id = $1;
 -- name: InsertNoteSource :one
 INSERT INTO note_sources (
     id,
+    note_id,
     account_id,
-    content
-) VALUES ($1, $2, $3)
+    content,
+    media_type
+) VALUES ($1, $2, $3, $4, $5)
 RETURNING id;
 
 -- name: InsertNote :exec
@@ -78,5 +289,1343 @@ INSERT INTO notes (
     actor_id,
     source_id,
     content,
-    note_url
-) VALUES ($1, $2, $3, $4, $5);
+    note_url,
+    in_reply_to_id,
+    published,
+    created_at,
+    updated_at,
+    summary,
+    sensitive,
+    object_type,
+    name,
+    url
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14);
+
+-- name: GetLocalNote :one
+SELECT
+    notes.id,
+    notes.actor_id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.note_url,
+    notes.updated,
+    notes.deleted,
+    notes.published,
+    notes.created_at,
+    notes.updated_at,
+    note_sources.id AS source_id,
+    note_sources.account_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.actor_url,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', '[]'::JSON
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.id = $1;
+
+-- name: GetNoteTombstone :one
+SELECT deleted
+FROM note_tombstones
+WHERE id = $1;
+
+-- name: UpdateNote :exec
+UPDATE notes
+SET
+    source_id = $2,
+    content = $3,
+    updated = $4,
+    updated_at = $5,
+    summary = $6,
+    sensitive = $7,
+    object_type = $8,
+    name = $9,
+    url = $10
+WHERE id = $1 AND deleted IS NULL;
+
+-- name: GetRemoteNoteByUrl :one
+SELECT
+    notes.id,
+    notes.actor_id,
+    notes.source_id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.in_reply_to_id,
+    notes.published,
+    notes.created_at,
+    notes.updated_at,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', '[]'::JSON
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    notes.note_url = $1
+    AND notes.deleted IS NULL
+    AND actors.account_id IS NULL;
+
+-- name: GetNoteSources :many
+SELECT
+    id,
+    content,
+    media_type
+FROM note_sources
+WHERE note_id = $1 AND deleted IS NULL
+ORDER BY id;
+
+-- name: DeleteNote :exec
+UPDATE notes
+SET
+    deleted = $2,
+    updated_at = now()
+WHERE id = $1 AND deleted IS NULL;
+
+-- name: DeleteNoteSources :exec
+UPDATE note_sources
+SET
+    deleted = $2,
+    updated_at = now()
+WHERE note_id = $1 AND deleted IS NULL;
+
+-- name: InsertNoteRecipient :exec
+INSERT INTO note_recipients (
+    note_id,
+    inbox_url
+) VALUES ($1, $2)
+ON CONFLICT DO NOTHING;
+
+-- name: GetNoteRecipients :many
+SELECT inbox_url
+FROM note_recipients
+WHERE note_id = $1;
+
+-- name: UpsertFollow :one
+INSERT INTO follows (
+    id,
+    follower_id,
+    followee_id,
+    follow_url,
+    accepted
+) VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (follower_id, followee_id) DO UPDATE
+SET
+    follow_url = excluded.follow_url,
+    accepted = excluded.accepted
+RETURNING id;
+
+-- name: GetFollow :one
+SELECT
+    id,
+    follower_id,
+    followee_id,
+    follow_url,
+    accepted
+FROM follows
+WHERE follower_id = $1 AND followee_id = $2;
+
+-- name: AcceptFollow :one
+UPDATE follows
+SET accepted = TRUE
+WHERE followee_id = $1 AND follow_url = $2
+RETURNING follower_id;
+
+-- name: RejectFollow :exec
+DELETE FROM follows
+WHERE followee_id = $1 AND follow_url = $2;
+
+-- name: DeleteFollow :exec
+DELETE FROM follows
+WHERE follower_id = $1 AND followee_id = $2;
+
+-- name: DeleteFollowByUrl :exec
+DELETE FROM follows
+WHERE follower_id = $1 AND follow_url = $2;
+
+-- name: GetFollowerInboxes :many
+SELECT DISTINCT COALESCE(actors.shared_inbox_url, actors.inbox_url) AS inbox_url
+FROM follows
+INNER JOIN actors ON follows.follower_id = actors.id
+WHERE
+    follows.followee_id = $1
+    AND follows.accepted
+    AND actors.account_id IS NULL;
+
+-- name: GetActorView :one
+SELECT
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count
+FROM actors
+WHERE actors.id = $1;
+
+-- name: GetNoteView :one
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.id = $1 AND notes.deleted IS NULL;
+
+-- name: GetActorNoteViews :many
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE notes.actor_id = $1 AND notes.deleted IS NULL
+ORDER BY notes.id DESC
+LIMIT $3;
+
+-- name: GetNoteByUrl :one
+SELECT
+    id,
+    actor_id
+FROM notes
+WHERE note_url = $1 AND deleted IS NULL;
+
+-- name: InsertFavourite :exec
+INSERT INTO favourites (
+    id,
+    actor_id,
+    note_id,
+    like_url
+) VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING;
+
+-- name: DeleteFavourite :one
+DELETE FROM favourites
+WHERE actor_id = $1 AND note_id = $2
+RETURNING like_url;
+
+-- name: DeleteFavouriteByUrl :exec
+DELETE FROM favourites
+WHERE actor_id = $1 AND like_url = $2;
+
+-- name: InsertEmojiReaction :exec
+INSERT INTO emoji_reactions (
+    id,
+    actor_id,
+    note_id,
+    reaction_url,
+    content,
+    emoji_id
+) VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT DO NOTHING;
+
+-- name: DeleteEmojiReaction :one
+DELETE FROM emoji_reactions
+WHERE actor_id = $1 AND note_id = $2 AND content = $3
+RETURNING reaction_url;
+
+-- name: DeleteEmojiReactionByUrl :exec
+DELETE FROM emoji_reactions
+WHERE actor_id = $1 AND reaction_url = $2;
+
+-- name: UpsertPoll :exec
+INSERT INTO polls (note_id, multiple, expires_at, closed_at, voters_count)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (note_id) DO UPDATE SET
+multiple = excluded.multiple,
+expires_at = excluded.expires_at,
+closed_at = excluded.closed_at,
+voters_count = excluded.voters_count;
+
+-- name: DeletePoll :exec
+DELETE FROM polls
+WHERE note_id = $1;
+
+-- name: DeleteChangedPollOptions :exec
+-- options not at the same position with the same title any more, and their votes
+DELETE FROM poll_options
+WHERE
+    note_id = $1
+    AND (
+        position >= cardinality($2::TEXT [])
+        OR title IS DISTINCT FROM ($2::TEXT [])[position + 1]
+    );
+
+-- name: UpsertPollOption :exec
+INSERT INTO poll_options (note_id, position, title, votes_count)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (note_id, position) DO UPDATE SET
+votes_count = excluded.votes_count;
+
+-- name: LockPoll :one
+-- the votes of an actor are checked and recorded by one transaction at a time
+SELECT note_id
+FROM polls
+WHERE note_id = $1
+FOR UPDATE;
+
+-- name: InsertPollVote :exec
+INSERT INTO poll_votes (id, note_id, actor_id, choice, vote_url)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT DO NOTHING;
+
+-- name: IncrementPollVotes :exec
+-- only remote polls have their counts stored
+UPDATE poll_options SET votes_count = votes_count + 1
+WHERE note_id = $1 AND position = ANY($2::INTEGER []);
+
+-- name: IncrementPollVoters :exec
+UPDATE polls SET voters_count = voters_count + 1
+WHERE note_id = $1;
+
+-- name: GetPollChoices :many
+SELECT choice
+FROM poll_votes
+WHERE note_id = $1 AND actor_id = $2
+ORDER BY choice;
+
+-- name: CloseExpiredPolls :many
+UPDATE polls SET closed_at = $1
+FROM notes
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    polls.note_id = notes.id
+    AND actors.account_id IS NOT NULL
+    AND polls.closed_at IS NULL
+    AND polls.expires_at <= $1
+RETURNING polls.note_id;
+
+-- name: GetPollVoterInboxes :many
+SELECT DISTINCT COALESCE(actors.shared_inbox_url, actors.inbox_url) AS inbox_url
+FROM poll_votes
+INNER JOIN actors ON poll_votes.actor_id = actors.id
+WHERE
+    poll_votes.note_id = $1
+    AND actors.account_id IS NULL;
+
+-- name: InsertReblog :exec
+INSERT INTO reblogs (
+    id,
+    actor_id,
+    note_id,
+    announce_url
+) VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING;
+
+-- name: GetReblog :one
+SELECT
+    id,
+    announce_url
+FROM reblogs
+WHERE actor_id = $1 AND note_id = $2;
+
+-- name: DeleteReblog :one
+DELETE FROM reblogs
+WHERE actor_id = $1 AND note_id = $2
+RETURNING id, announce_url;
+
+-- name: DeleteReblogByUrl :one
+DELETE FROM reblogs
+WHERE actor_id = $1 AND announce_url = $2
+RETURNING id;
+
+-- name: GetHomeTimeline :many
+SELECT
+    home_feed.entry_id,
+    reblogs.actor_id AS reblog_actor_id,
+    reblogs.announce_url AS reblog_announce_url,
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $1
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $1
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $1
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $1
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM home_feed
+INNER JOIN notes ON home_feed.note_id = notes.id
+LEFT JOIN reblogs ON home_feed.reblog_id = reblogs.id
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    home_feed.account_id = $1
+    AND notes.deleted IS NULL
+    AND ($2::UUID IS NULL OR home_feed.entry_id < $2)
+    AND ($3::UUID IS NULL OR home_feed.entry_id > $3)
+ORDER BY
+    CASE WHEN $4 THEN home_feed.entry_id END ASC,
+    home_feed.entry_id DESC
+LIMIT $5;
+
+-- name: GetHomeFeedEntry :one
+SELECT
+    home_feed.entry_id,
+    reblogs.actor_id AS reblog_actor_id,
+    reblogs.announce_url AS reblog_announce_url,
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $1
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $1
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $1
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $1
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM home_feed
+INNER JOIN notes ON home_feed.note_id = notes.id
+LEFT JOIN reblogs ON home_feed.reblog_id = reblogs.id
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    home_feed.account_id = $1
+    AND home_feed.entry_id = $2
+    AND notes.deleted IS NULL;
+
+-- name: FanOutNote :exec
+INSERT INTO home_feed (account_id, entry_id, note_id)
+SELECT
+    actors.account_id,
+    $1,
+    $1
+FROM actors
+WHERE
+    actors.account_id IS NOT NULL
+    AND (
+        actors.id = $2
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $2 AND follows.accepted
+        )
+    )
+ON CONFLICT DO NOTHING;
+
+-- name: FanOutReblog :exec
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    actors.account_id,
+    $1,
+    $2,
+    $1
+FROM actors
+WHERE
+    actors.account_id IS NOT NULL
+    AND (
+        actors.id = $3
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $3 AND follows.accepted
+        )
+    )
+ON CONFLICT DO NOTHING;
+
+-- name: TrimHomeFeeds :exec
+DELETE FROM home_feed
+USING actors,
+    LATERAL (
+        SELECT kept.entry_id FROM home_feed AS kept
+        WHERE kept.account_id = actors.account_id
+        ORDER BY kept.entry_id DESC
+        OFFSET $2
+        LIMIT 1
+    ) AS oldest
+WHERE
+    (
+        actors.id = $1
+        OR actors.id IN (
+            SELECT follows.follower_id FROM follows
+            WHERE follows.followee_id = $1 AND follows.accepted
+        )
+    )
+    AND home_feed.account_id = actors.account_id
+    AND home_feed.entry_id <= oldest.entry_id;
+
+-- name: MergeHomeFeed :exec
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    followers.account_id,
+    entries.entry_id,
+    entries.note_id,
+    entries.reblog_id
+FROM actors AS followers
+CROSS JOIN (
+    (
+        SELECT
+            notes.id AS entry_id,
+            notes.id AS note_id,
+            NULL::UUID AS reblog_id
+        FROM notes
+        WHERE notes.actor_id = $2 AND notes.deleted IS NULL
+        ORDER BY notes.id DESC
+        LIMIT $3
+    )
+    UNION ALL
+    (
+        SELECT
+            reblogs.id,
+            reblogs.note_id,
+            reblogs.id
+        FROM reblogs
+        WHERE reblogs.actor_id = $2
+        ORDER BY reblogs.id DESC
+        LIMIT $3
+    )
+) AS entries
+WHERE followers.id = $1 AND followers.account_id IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+-- name: UnmergeHomeFeed :exec
+DELETE FROM home_feed
+USING actors AS followers, notes
+WHERE
+    followers.id = $1
+    AND home_feed.account_id = followers.account_id
+    AND home_feed.note_id = notes.id
+    AND (
+        (home_feed.reblog_id IS NULL AND notes.actor_id = $2)
+        OR home_feed.reblog_id IN (
+            SELECT reblogs.id FROM reblogs
+            WHERE reblogs.actor_id = $2
+        )
+    );
+
+-- name: TrimHomeFeed :exec
+DELETE FROM home_feed
+USING actors
+WHERE
+    actors.id = $1
+    AND home_feed.account_id = actors.account_id
+    AND home_feed.entry_id <= (
+        SELECT kept.entry_id FROM home_feed AS kept
+        WHERE kept.account_id = actors.account_id
+        ORDER BY kept.entry_id DESC
+        OFFSET $2
+        LIMIT 1
+    );
+
+-- name: ClearHomeFeed :exec
+DELETE FROM home_feed
+WHERE home_feed.account_id = $1;
+
+-- name: RebuildHomeFeed :exec
+WITH viewer_actors AS (
+    SELECT actors.id FROM actors WHERE actors.account_id = $1
+),
+
+sources AS (
+    SELECT viewer_actors.id FROM viewer_actors
+    UNION
+    SELECT follows.followee_id FROM follows
+    WHERE follows.follower_id IN (SELECT viewer_actors.id FROM viewer_actors) AND follows.accepted
+),
+
+entries AS (
+    SELECT
+        notes.id AS entry_id,
+        notes.id AS note_id,
+        NULL::UUID AS reblog_id
+    FROM notes
+    WHERE notes.actor_id IN (SELECT sources.id FROM sources) AND notes.deleted IS NULL
+    UNION ALL
+    SELECT
+        reblogs.id,
+        reblogs.note_id,
+        reblogs.id
+    FROM reblogs
+    WHERE reblogs.actor_id IN (SELECT sources.id FROM sources)
+)
+
+INSERT INTO home_feed (account_id, entry_id, note_id, reblog_id)
+SELECT
+    $1,
+    entries.entry_id,
+    entries.note_id,
+    entries.reblog_id
+FROM entries
+ORDER BY entries.entry_id DESC
+LIMIT $2;
+
+-- name: GetPublicTimeline :many
+SELECT
+    notes.id,
+    notes.content,
+    notes.summary,
+    notes.sensitive,
+    notes.object_type,
+    notes.name,
+    notes.url,
+    notes.note_url,
+    notes.updated,
+    notes.published,
+    notes.in_reply_to_id,
+    (
+        SELECT parents.actor_id FROM notes AS parents
+        WHERE parents.id = notes.in_reply_to_id
+    ) AS in_reply_to_actor_id,
+    note_sources.id AS source_id,
+    note_sources.content AS source_content,
+    note_sources.media_type AS source_media_type,
+    actors.id,
+    actors.type,
+    actors.name,
+    actors.host,
+    actors.actor_url,
+    actors.inbox_url,
+    actors.outbox_url,
+    actors.shared_inbox_url,
+    actors.summary,
+    actors.public_key_id,
+    actors.public_key_pem,
+    actors.account_id,
+    actors.published,
+    actors.created_at,
+    actors.updated_at,
+    actors.display_name,
+    actors.summary_source,
+    actors.avatar_url,
+    actors.avatar_storage_key,
+    actors.header_url,
+    actors.header_storage_key,
+    actors.fields,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM actor_emojis
+        INNER JOIN custom_emojis ON actor_emojis.emoji_id = custom_emojis.id
+        WHERE actor_emojis.actor_id = actors.id
+    ) AS actors_emojis,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.followee_id = actors.id AND follows.accepted
+    ) AS followers_count,
+    (
+        SELECT COUNT(*) FROM follows
+        WHERE follows.follower_id = actors.id AND follows.accepted
+    ) AS following_count,
+    (
+        SELECT COUNT(*) FROM notes AS actor_notes
+        WHERE actor_notes.actor_id = actors.id AND actor_notes.deleted IS NULL
+    ) AS statuses_count,
+    (
+        SELECT COUNT(*) FROM favourites
+        WHERE favourites.note_id = notes.id
+    ) AS favourites_count,
+    (
+        SELECT COUNT(*) FROM reblogs
+        WHERE reblogs.note_id = notes.id
+    ) AS reblogs_count,
+    EXISTS (
+        SELECT 1 FROM favourites
+        INNER JOIN actors AS viewers ON favourites.actor_id = viewers.id
+        WHERE favourites.note_id = notes.id AND viewers.account_id = $2
+    ) AS favourited,
+    EXISTS (
+        SELECT 1 FROM reblogs
+        INNER JOIN actors AS viewers ON reblogs.actor_id = viewers.id
+        WHERE reblogs.note_id = notes.id AND viewers.account_id = $2
+    ) AS reblogged,
+    (
+        SELECT coalesce(json_agg(reactions ORDER BY reactions.count DESC, reactions.first_reacted), '[]')
+        FROM (
+            SELECT
+                emoji_reactions.content,
+                CASE WHEN custom_emojis.id IS NOT NULL THEN to_json(custom_emojis) END AS emoji,
+                COUNT(*) AS count,
+                bool_or(viewers.id IS NOT NULL) AS me,
+                min(emoji_reactions.created_at) AS first_reacted
+            FROM emoji_reactions
+            LEFT JOIN custom_emojis ON emoji_reactions.emoji_id = custom_emojis.id
+            LEFT JOIN actors AS viewers
+                ON emoji_reactions.actor_id = viewers.id AND viewers.account_id = $2
+            WHERE emoji_reactions.note_id = notes.id
+            GROUP BY emoji_reactions.content, custom_emojis.id
+        ) AS reactions
+    ) AS reactions,
+    (
+        SELECT coalesce(json_agg(attachments ORDER BY attachments.id), '[]')
+        FROM attachments
+        WHERE attachments.note_id = notes.id
+    ) AS attachments,
+    (
+        SELECT coalesce(json_agg(custom_emojis ORDER BY custom_emojis.shortcode), '[]')
+        FROM note_emojis
+        INNER JOIN custom_emojis ON note_emojis.emoji_id = custom_emojis.id
+        WHERE note_emojis.note_id = notes.id
+    ) AS emojis,
+    (
+        SELECT json_build_object(
+            'multiple', polls.multiple,
+            'expires_at', polls.expires_at,
+            'closed_at', polls.closed_at,
+            'voters_count', coalesce(polls.voters_count, (
+                SELECT COUNT(DISTINCT poll_votes.actor_id) FROM poll_votes
+                WHERE poll_votes.note_id = polls.note_id
+            )),
+            'options', (
+                SELECT json_agg(json_build_object(
+                    'title', poll_options.title,
+                    'votes_count', coalesce(poll_options.votes_count, (
+                        SELECT COUNT(*) FROM poll_votes
+                        WHERE
+                            poll_votes.note_id = poll_options.note_id
+                            AND poll_votes.choice = poll_options.position
+                    ))
+                ) ORDER BY poll_options.position)
+                FROM poll_options
+                WHERE poll_options.note_id = polls.note_id
+            ),
+            'own_votes', (
+                SELECT coalesce(json_agg(poll_votes.choice ORDER BY poll_votes.choice), '[]')
+                FROM poll_votes
+                INNER JOIN actors AS viewers ON poll_votes.actor_id = viewers.id
+                WHERE poll_votes.note_id = polls.note_id AND viewers.account_id = $2
+            )
+        )
+        FROM polls
+        WHERE polls.note_id = notes.id
+    ) AS poll
+FROM notes
+INNER JOIN note_sources ON notes.source_id = note_sources.id
+INNER JOIN actors ON notes.actor_id = actors.id
+WHERE
+    notes.deleted IS NULL
+    AND (NOT $1 OR actors.account_id IS NOT NULL)
+    AND (NOT $3 OR actors.account_id IS NULL)
+    AND ($4::UUID IS NULL OR notes.id < $4)
+    AND ($5::UUID IS NULL OR notes.id > $5)
+ORDER BY
+    CASE WHEN $6 THEN notes.id END ASC,
+    notes.id DESC
+LIMIT $7;
@@ -0,0 +1,95 @@
+-- name: InsertSession :exec
+INSERT INTO sessions (
+    id,
+    account_id,
+    token_hash,
+    created,
+    expires,
+    app_id,
+    scopes
+) VALUES ($1, $2, $3, $4, $5, $6, $7);
+
+-- name: FindSession :one
+SELECT
+    sessions.app_id,
+    sessions.scopes,
+    accounts.id,
+    accounts.name
+FROM sessions
+LEFT JOIN accounts ON sessions.account_id = accounts.id
+WHERE sessions.token_hash = $1 AND sessions.expires > $2;
+
+-- name: DeleteSession :exec
+DELETE FROM sessions
+WHERE token_hash = $1;
+
+-- name: DeleteAppSession :exec
+DELETE FROM sessions
+WHERE token_hash = $1 AND app_id = $2;
+
+-- name: InsertApp :exec
+INSERT INTO oauth_apps (
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website,
+    created
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+
+-- name: FindAppByClientId :one
+SELECT
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website
+FROM oauth_apps
+WHERE client_id = $1;
+
+-- name: FindAppById :one
+SELECT
+    id,
+    client_id,
+    client_secret_hash,
+    name,
+    redirect_uris,
+    scopes,
+    website
+FROM oauth_apps
+WHERE id = $1;
+
+-- name: InsertAuthorizationCode :exec
+INSERT INTO oauth_authorization_codes (
+    code_hash,
+    app_id,
+    account_id,
+    redirect_uri,
+    scopes,
+    code_challenge,
+    code_challenge_method,
+    expires
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+
+-- name: TakeAuthorizationCode :one
+WITH taken AS (
+    DELETE FROM oauth_authorization_codes
+    WHERE code_hash = $1
+    RETURNING *
+)
+
+SELECT
+    taken.app_id,
+    taken.redirect_uri,
+    taken.scopes,
+    taken.code_challenge,
+    taken.code_challenge_method,
+    taken.expires,
+    accounts.id AS account_id,
+    accounts.name AS account_name
+FROM taken
+INNER JOIN accounts ON taken.account_id = accounts.id;
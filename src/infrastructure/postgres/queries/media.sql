@@ -0,0 +1,205 @@
+-- name: InsertAttachment :exec
+INSERT INTO attachments (
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18);
+
+-- name: GetAttachment :one
+SELECT
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+FROM attachments
+WHERE id = $1;
+
+-- name: GetAttachmentByStorageKey :one
+SELECT
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height
+FROM attachments
+WHERE storage_key = $1 OR preview_storage_key = $1;
+
+-- name: AttachNoteMedia :many
+UPDATE attachments
+SET note_id = $1
+WHERE id = ANY($2::UUID []) AND account_id = $3 AND note_id IS NULL
+RETURNING
+    id,
+    account_id,
+    note_id,
+    type,
+    media_type,
+    url,
+    storage_key,
+    description,
+    created_at,
+    width,
+    height,
+    blurhash,
+    focus_x,
+    focus_y,
+    preview_url,
+    preview_storage_key,
+    preview_width,
+    preview_height;
+
+-- name: DeleteNoteAttachments :exec
+DELETE FROM attachments
+WHERE note_id = $1;
+
+-- name: InsertRemoteMedia :exec
+INSERT INTO remote_media (id, url)
+VALUES ($1, $2)
+ON CONFLICT (id) DO NOTHING;
+
+-- name: GetRemoteMedia :one
+SELECT
+    id,
+    url,
+    media_type,
+    storage_key
+FROM remote_media
+WHERE id = $1;
+
+-- name: SetRemoteMediaCached :exec
+UPDATE remote_media
+SET media_type = $2, storage_key = $3, size = $4, cached_at = now(), accessed_at = now()
+WHERE id = $1;
+
+-- Access times are only kept to the minute, sparing a write on every request
+-- name: TouchRemoteMedia :exec
+UPDATE remote_media
+SET accessed_at = now()
+WHERE id = $1 AND accessed_at < now() - INTERVAL '1 minute';
+
+-- Cached files not accessed since $1, then the least recently accessed ones
+-- beyond a total size of $2 bytes
+-- name: GetEvictableRemoteMedia :many
+SELECT
+    id,
+    url,
+    media_type,
+    storage_key
+FROM (
+    SELECT
+        remote_media.*,
+        (sum(size) OVER (ORDER BY accessed_at DESC, id DESC))::BIGINT AS retained_size
+    FROM remote_media
+    WHERE storage_key IS NOT NULL
+) AS cached
+WHERE accessed_at < $1 OR retained_size > $2
+ORDER BY accessed_at, id
+LIMIT $3;
+
+-- name: UnsetRemoteMediaCached :exec
+UPDATE remote_media
+SET media_type = NULL, storage_key = NULL, size = NULL, cached_at = NULL
+WHERE id = $1 AND storage_key = $2;
+
+-- Stored images served from the media backend outside of attachments
+-- name: GetStoredImage :one
+SELECT id
+FROM actors
+WHERE avatar_storage_key = $1 OR header_storage_key = $1
+UNION ALL
+SELECT id
+FROM custom_emojis
+WHERE storage_key = $1
+LIMIT 1;
+
+-- name: InsertCustomEmoji :exec
+INSERT INTO custom_emojis (
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+)
+VALUES ($1, $2, NULL, $3, $4, $5, $6, $7, $7)
+ON CONFLICT (shortcode, host) DO NOTHING;
+
+-- name: GetLocalCustomEmojis :many
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE host IS NULL
+ORDER BY shortcode;
+
+-- name: GetCustomEmoji :one
+SELECT
+    id,
+    shortcode,
+    host,
+    uri,
+    url,
+    media_type,
+    storage_key,
+    created_at,
+    updated_at
+FROM custom_emojis
+WHERE id = $1;
+
+-- name: DeleteLocalCustomEmoji :one
+DELETE FROM custom_emojis
+WHERE shortcode = $1 AND host IS NULL
+RETURNING storage_key;
@@ -0,0 +1,95 @@
+-- name: InsertNotification :exec
+INSERT INTO notifications (
+    id,
+    account_id,
+    type,
+    actor_id,
+    note_id
+) VALUES ($1, $2, $3, $4, $5);
+
+-- Page of notifications, skipping those of deleted notes
+-- name: GetNotifications :many
+SELECT
+    notifications.id,
+    notifications.type,
+    notifications.actor_id,
+    notifications.note_id
+FROM notifications
+LEFT JOIN notes ON notifications.note_id = notes.id
+WHERE
+    notifications.account_id = $1
+    AND notes.deleted IS NULL
+    AND (cardinality($2::TEXT []) = 0 OR notifications.type = ANY($2))
+    AND NOT (notifications.type = ANY($3::TEXT []))
+    AND ($4::UUID IS NULL OR notifications.actor_id = $4)
+    AND ($5::UUID IS NULL OR notifications.id < $5)
+    AND ($6::UUID IS NULL OR notifications.id > $6)
+ORDER BY
+    CASE WHEN $7 THEN notifications.id END ASC,
+    notifications.id DESC
+LIMIT $8;
+
+-- name: GetNotification :one
+SELECT
+    notifications.id,
+    notifications.type,
+    notifications.actor_id,
+    notifications.note_id
+FROM notifications
+LEFT JOIN notes ON notifications.note_id = notes.id
+WHERE
+    notifications.account_id = $1
+    AND notifications.id = $2
+    AND notes.deleted IS NULL;
+
+-- name: DeleteNotification :exec
+DELETE FROM notifications
+WHERE account_id = $1 AND id = $2;
+
+-- name: DeleteNoteNotifications :exec
+DELETE FROM notifications
+WHERE account_id = $1 AND type = $2 AND note_id = $3;
+
+-- name: ClearNotifications :exec
+DELETE FROM notifications
+WHERE account_id = $1;
+
+-- Notifications newer than the notifications marker, up to a limit
+-- name: CountUnreadNotifications :one
+SELECT COUNT(*) AS count
+FROM (
+    SELECT 1
+    FROM notifications
+    LEFT JOIN notes ON notifications.note_id = notes.id
+    LEFT JOIN markers
+        ON
+            notifications.account_id = markers.account_id
+            AND markers.timeline = 'notifications'
+    WHERE
+        notifications.account_id = $1
+        AND notes.deleted IS NULL
+        AND (markers.last_read_id IS NULL OR notifications.id > markers.last_read_id)
+    LIMIT $2
+) AS unread;
+
+-- name: GetMarkers :many
+SELECT
+    timeline,
+    last_read_id,
+    version,
+    updated_at
+FROM markers
+WHERE account_id = $1;
+
+-- name: UpsertMarker :one
+INSERT INTO markers (
+    account_id,
+    timeline,
+    last_read_id
+) VALUES ($1, $2, $3)
+ON CONFLICT (account_id, timeline) DO UPDATE
+SET
+    last_read_id = excluded.last_read_id,
+    version = markers.version + 1,
+    updated_at = CURRENT_TIMESTAMP
+RETURNING version, updated_at;
@@ -0,0 +1,47 @@
+-- name: SearchActors :many
+SELECT id
+FROM actors
+WHERE
+    search_tsv @@ websearch_to_tsquery('simple', $1::TEXT)
+    OR starts_with(lower(name), lower($1::TEXT))
+ORDER BY
+    lower(name) = lower($1::TEXT) DESC,
+    ts_rank(search_tsv, websearch_to_tsquery('simple', $1::TEXT)) DESC,
+    id ASC
+LIMIT $2 OFFSET $3;
+
+-- name: SearchNotes :many
+SELECT id
+FROM notes
+WHERE
+    deleted IS NULL
+    AND content_tsv @@ websearch_to_tsquery('simple', $1::TEXT)
+    AND ($2::UUID IS NULL OR actor_id = $2::UUID)
+ORDER BY id DESC
+LIMIT $3 OFFSET $4;
+
+-- name: SearchTags :many
+SELECT name
+FROM tags
+WHERE name % $1::TEXT OR starts_with(name, $1::TEXT)
+ORDER BY
+    starts_with(name, $1::TEXT) DESC,
+    similarity(name, $1::TEXT) DESC,
+    name ASC
+LIMIT $2 OFFSET $3;
+
+-- name: InsertTags :exec
+INSERT INTO tags (name)
+SELECT unnest($1::TEXT [])
+ON CONFLICT DO NOTHING;
+
+-- name: DeleteNoteTags :exec
+DELETE FROM note_tags
+WHERE note_id = $1;
+
+-- name: InsertNoteTags :exec
+INSERT INTO note_tags (note_id, tag_name)
+SELECT
+    $1,
+    unnest($2::TEXT [])
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,2 @@
+-- name: NotifyStream :exec
+SELECT pg_notify('mozu_stream', $1);
@@ -4,4 +4,7 @@ mod http;
 mod infrastructure;
 
 pub use http::{HttpServer, HttpServerConfig};
-pub use infrastructure::postgres::Postgres;
+pub use infrastructure::{
+    media_storage::{MediaCacheConfig, MediaStorageConfig},
+    postgres::Postgres,
+};
@@ -1,4 +1,4 @@
-use mozu::{HttpServer, HttpServerConfig, Postgres};
+use mozu::{HttpServer, HttpServerConfig, MediaCacheConfig, MediaStorageConfig, Postgres};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -6,10 +6,35 @@ async fn main() -> anyhow::Result<()> {
 
     let pg = Postgres::from_env().await?;
 
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None | Some("serve") => serve(pg).await,
+        // mozu rebuild-home-feed [NAME]
+        Some("rebuild-home-feed") => {
+            let name = args.next();
+            let count = pg.rebuild_home_feeds(name.as_deref()).await?;
+            tracing::info!(count, "Rebuilt home feeds");
+            Ok(())
+        }
+        Some(command) => anyhow::bail!("unknown command {command}"),
+    }
+}
+
+async fn serve(pg: Postgres) -> anyhow::Result<()> {
     tracing::info!("Starting HTTP server...");
     let server_config = HttpServerConfig::builder()
         .host_url(std::env::var("HOST_URL")?)
         .port(3000)
+        .admin_token(
+            std::env::var("ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty()),
+        )
+        .media_storage(MediaStorageConfig::from_env()?)
+        .media_cache(MediaCacheConfig::from_env()?)
+        .allow_private_addresses(
+            std::env::var("ALLOW_PRIVATE_ADDRESSES").is_ok_and(|value| value == "true"),
+        )
         .build();
     let server = HttpServer::new(server_config, pg);
     server.run().await?;